@@ -43,4 +43,11 @@ pub trait Allocator: IndexedMemoryAccess {
     /// # Parameters
     /// - `data_internal_index`: The index of the memory block to free.
     fn free(&mut self, data_internal_index: usize);
+
+    /// Returns the size of the memory block at the specified "data internal index".
+    ///
+    /// # Returns
+    /// The size in bytes, or `None` if the index does not refer to an allocated
+    /// memory block (e.g., a freed or a forged index).
+    fn get_size(&self, data_internal_index: usize) -> Option<usize>;
 }
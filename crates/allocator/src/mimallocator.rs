@@ -4,18 +4,25 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use std::ffi::c_void;
+use std::{collections::HashMap, ffi::c_void};
 
 use anc_memory::{indexed_memory_access::IndexedMemoryAccess, memory_access::MemoryAccess};
 use libmimalloc_sys::{mi_free, mi_malloc_aligned, mi_realloc_aligned};
 
 use crate::allocator::Allocator;
 
-pub struct MiMAllocator;
+pub struct MiMAllocator {
+    // The sizes of the allocated memory blocks, keyed by the "data internal index"
+    // (i.e., the memory address). It is used to check the index and the bounds
+    // of the memory access, since the index comes from the bytecode.
+    sizes: HashMap<usize, usize>,
+}
 
 impl MiMAllocator {
     pub fn new() -> Self {
-        MiMAllocator
+        Self {
+            sizes: HashMap::new(),
+        }
     }
 }
 
@@ -36,6 +43,9 @@ impl Allocator for MiMAllocator {
         // see:
         // https://github.com/purpleprotocol/mimalloc_rust/blob/master/libmimalloc-sys/src/lib.rs
         let ptr = unsafe { mi_malloc_aligned(size_in_bytes, alignment_in_bytes) };
+        if !ptr.is_null() {
+            self.sizes.insert(ptr as usize, size_in_bytes);
+        }
         ptr as usize
     }
 
@@ -57,6 +67,10 @@ impl Allocator for MiMAllocator {
         // `size` are uninitialized.
         let ptr = data_internal_index as *mut c_void;
         let new_ptr = unsafe { mi_realloc_aligned(ptr, new_size_in_bytes, alignment_in_bytes) };
+        if !new_ptr.is_null() {
+            self.sizes.remove(&data_internal_index);
+            self.sizes.insert(new_ptr as usize, new_size_in_bytes);
+        }
         new_ptr as usize
     }
 
@@ -64,9 +78,14 @@ impl Allocator for MiMAllocator {
     //
     // The pointer `p` must have been allocated before (or be null).
     fn free(&mut self, data_internal_index: usize) {
+        self.sizes.remove(&data_internal_index);
         let ptr = data_internal_index as *mut c_void;
         unsafe { mi_free(ptr) };
     }
+
+    fn get_size(&self, data_internal_index: usize) -> Option<usize> {
+        self.sizes.get(&data_internal_index).copied()
+    }
 }
 
 impl MemoryAccess for MiMAllocator {
@@ -144,6 +163,23 @@ mod tests {
         allocator.free(new_index1);
     }
 
+    #[test]
+    fn test_get_size() {
+        let mut allocator = MiMAllocator::new();
+
+        let index0 = allocator.allocate(4, 4);
+        assert_eq!(allocator.get_size(index0), Some(4));
+
+        let new_index0 = allocator.reallocate(index0, 16, 8);
+        assert_eq!(allocator.get_size(new_index0), Some(16));
+
+        // A forged index.
+        assert_eq!(allocator.get_size(new_index0 + 1), None);
+
+        allocator.free(new_index0);
+        assert_eq!(allocator.get_size(new_index0), None);
+    }
+
     #[test]
     fn test_access_out_of_bounds() {
         // No bounds checking in MiMAllocator,
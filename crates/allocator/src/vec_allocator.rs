@@ -105,6 +105,13 @@ impl Allocator for VecAllocator {
         // Mark the memory item at the specified index as freed.
         self.items[data_internal_index] = None;
     }
+
+    fn get_size(&self, data_internal_index: usize) -> Option<usize> {
+        self.items
+            .get(data_internal_index)
+            .and_then(|opt_item| opt_item.as_ref())
+            .map(|item| item.size)
+    }
 }
 
 impl MemoryAccess for VecAllocator {
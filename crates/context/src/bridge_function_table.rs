@@ -158,7 +158,8 @@ impl BridgeFunctionTable {
         target_module_index: usize,
         target_function_internal_index: usize,
     ) -> Option<*const u8> {
-        match self.functions_by_modules
+        match self
+            .functions_by_modules
             .iter()
             .find(|module_item| module_item.module_index == target_module_index)
         {
@@ -172,4 +173,30 @@ impl BridgeFunctionTable {
             None => None,
         }
     }
+
+    pub fn insert_bridge_function(
+        &mut self,
+        target_module_index: usize,
+        target_function_internal_index: usize,
+        bridge_function_ptr: *const u8,
+    ) {
+        let module_item_index = self
+            .functions_by_modules
+            .iter()
+            .position(|module_item| module_item.module_index == target_module_index)
+            .unwrap_or_else(|| {
+                self.functions_by_modules.push(BridgeFunctionsByModule {
+                    module_index: target_module_index,
+                    bridge_function_items: vec![],
+                });
+                self.functions_by_modules.len() - 1
+            });
+
+        self.functions_by_modules[module_item_index]
+            .bridge_function_items
+            .push(BridgeFunctionItem {
+                function_internal_index: target_function_internal_index,
+                bridge_function_ptr,
+            });
+    }
 }
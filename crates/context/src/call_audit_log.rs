@@ -0,0 +1,135 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Call Audit Log
+// --------------
+//
+// Records every `syscall` and `extcall` instruction of the process, both permitted
+// and denied by the capability (see `Capability::permits_syscall` and
+// `Capability::permits_extcall`), e.g.
+//
+// ```rust
+// let auditor = Arc::new(MemoryCallAuditor::default());
+//
+// let mut process_property = ProcessProperty::default();
+// process_property.call_audit_log = Some(CallAuditLog::new(auditor.clone()));
+//
+// // ... run the program ...
+//
+// for entry in auditor.get_entries() {
+//     println!("{}", entry);
+// }
+// ```
+
+use std::{
+    fmt::{Debug, Display},
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditedCall {
+    Syscall {
+        syscall_num: usize,
+    },
+    Extcall {
+        // The name of the external library entry, e.g. "libc".
+        library: String,
+        function: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallAuditEntry {
+    pub call: AuditedCall,
+    pub permitted: bool,
+}
+
+impl Display for CallAuditEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let decision = if self.permitted {
+            "permitted"
+        } else {
+            "denied"
+        };
+
+        match &self.call {
+            AuditedCall::Syscall { syscall_num } => {
+                write!(f, "syscall {} {}", syscall_num, decision)
+            }
+            AuditedCall::Extcall { library, function } => {
+                write!(f, "extcall {}::{} {}", library, function, decision)
+            }
+        }
+    }
+}
+
+/// Receives the audit entries, it may be called from any thread of the process.
+///
+/// It is called without holding the `process_property` lock of the process.
+pub trait CallAuditor: Send + Sync {
+    fn record(&self, entry: &CallAuditEntry);
+}
+
+/// Keeps the audit entries in memory.
+#[derive(Debug, Default)]
+pub struct MemoryCallAuditor {
+    entries: Mutex<Vec<CallAuditEntry>>,
+}
+
+impl MemoryCallAuditor {
+    pub fn get_entries(&self) -> Vec<CallAuditEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+}
+
+impl CallAuditor for MemoryCallAuditor {
+    fn record(&self, entry: &CallAuditEntry) {
+        self.entries.lock().unwrap().push(entry.clone());
+    }
+}
+
+/// Writes one line per audit entry, e.g. "syscall 39 permitted".
+pub struct WriterCallAuditor {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl WriterCallAuditor {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+}
+
+impl CallAuditor for WriterCallAuditor {
+    fn record(&self, entry: &CallAuditEntry) {
+        // Failing to write the log does not affect the program.
+        let _ = writeln!(self.writer.lock().unwrap(), "{}", entry);
+    }
+}
+
+/// The audit log of the process, see `ProcessProperty::call_audit_log`.
+#[derive(Clone)]
+pub struct CallAuditLog {
+    auditor: Arc<dyn CallAuditor>,
+}
+
+impl CallAuditLog {
+    pub fn new(auditor: Arc<dyn CallAuditor>) -> Self {
+        Self { auditor }
+    }
+
+    pub fn record(&self, call: AuditedCall, permitted: bool) {
+        self.auditor.record(&CallAuditEntry { call, permitted });
+    }
+}
+
+impl Debug for CallAuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallAuditLog").finish_non_exhaustive()
+    }
+}
@@ -120,4 +120,31 @@ impl CallbackDelegateFunctionTable {
             None => None,
         }
     }
+
+    pub fn insert_callback_delegate_function(
+        &mut self,
+        target_module_index: usize,
+        target_function_internal_index: usize,
+        callback_delegate_function_ptr: *const u8,
+    ) {
+        let module_item_index = self
+            .functions_by_modules
+            .iter()
+            .position(|module_item| module_item.module_index == target_module_index)
+            .unwrap_or_else(|| {
+                self.functions_by_modules
+                    .push(CallbackDelegateFunctionsByModule {
+                        module_index: target_module_index,
+                        callback_delegate_function_items: vec![],
+                    });
+                self.functions_by_modules.len() - 1
+            });
+
+        self.functions_by_modules[module_item_index]
+            .callback_delegate_function_items
+            .push(CallbackDelegateFunctionItem {
+                function_internal_index: target_function_internal_index,
+                callback_delegate_function_ptr,
+            });
+    }
 }
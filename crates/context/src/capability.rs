@@ -4,6 +4,12 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
+use std::{
+    fmt::Display,
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
 #[derive(Debug, Clone, Default)]
 pub struct Capability {
     pub syscall: bool,
@@ -14,9 +20,82 @@ pub struct Capability {
     pub file_execute_specified: Vec<String>,
     pub dir_access: Vec<FileAccess>,
     pub file_access: Vec<FileAccess>,
+
+    /// The access type to all files, `None` means only the files
+    /// and directories listed above are accessible.
+    pub file_access_all: Option<FileAccessType>,
+
+    /// The system call numbers which can be invoked when `syscall` is granted.
+    pub syscall_filter: CallFilter<usize>,
+
+    /// The external functions which can be called when `extcall` is granted.
+    pub extcall_filter: CallFilter<ExternalFunctionRule>,
 }
 
-#[derive(Debug, Clone, Default)]
+/// Restricts the items (e.g. the system call numbers) which a granted capability covers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CallFilter<T> {
+    /// All items are permitted.
+    #[default]
+    All,
+    /// Only the listed items are permitted.
+    Allow(Vec<T>),
+    /// All items except the listed ones are permitted.
+    Deny(Vec<T>),
+}
+
+impl<T> CallFilter<T> {
+    /// Checks whether the item is permitted, `matches` tells whether
+    /// a listed rule matches the item.
+    pub fn permits_by<F: Fn(&T) -> bool>(&self, matches: F) -> bool {
+        match self {
+            CallFilter::All => true,
+            CallFilter::Allow(rules) => rules.iter().any(matches),
+            CallFilter::Deny(rules) => !rules.iter().any(matches),
+        }
+    }
+}
+
+impl<T: PartialEq> CallFilter<T> {
+    pub fn permits(&self, item: &T) -> bool {
+        self.permits_by(|rule| rule == item)
+    }
+}
+
+/// Matches the external functions by the name of the external library
+/// (i.e. the name of the library entry in the module, e.g. "libc"), and optionally
+/// by the name of the function, `None` means all functions of the library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFunctionRule {
+    pub library: String,
+    pub function: Option<String>,
+}
+
+impl ExternalFunctionRule {
+    pub fn library(library: &str) -> Self {
+        Self {
+            library: library.to_owned(),
+            function: None,
+        }
+    }
+
+    pub fn function(library: &str, function: &str) -> Self {
+        Self {
+            library: library.to_owned(),
+            function: Some(function.to_owned()),
+        }
+    }
+
+    pub fn matches(&self, library: &str, function: &str) -> bool {
+        self.library == library
+            && self
+                .function
+                .as_ref()
+                .is_none_or(|function_rule| function_rule == function)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct FileAccess {
     pub path: String,
     pub type_: FileAccessType,
@@ -29,3 +108,311 @@ pub enum FileAccessType {
     Write,
     ReadWrite,
 }
+
+impl FileAccessType {
+    /// Returns `true` if this access type covers the `other`,
+    /// i.e. `ReadWrite` covers both `Read` and `Write`.
+    pub fn contains(&self, other: FileAccessType) -> bool {
+        *self == other || *self == FileAccessType::ReadWrite
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileAccessType::Read => "read",
+            FileAccessType::Write => "write",
+            FileAccessType::ReadWrite => "read-write",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read" => Some(FileAccessType::Read),
+            "write" => Some(FileAccessType::Write),
+            "read-write" => Some(FileAccessType::ReadWrite),
+            _ => None,
+        }
+    }
+}
+
+/// A capability which is requested by the program at runtime,
+/// see the `capable_*` envcalls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapabilityRequest {
+    Syscall,
+    Extcall,
+    ShellExecute(String),
+    ShellExecuteAll,
+    FileExecute(String),
+    FileExecuteAll,
+
+    // The personal and the application directories are resolved
+    // to the absolute paths before requesting.
+    DirAccess(FileAccess),
+    FileAccess(FileAccess),
+    FileAccessAll(FileAccessType),
+}
+
+impl Display for CapabilityRequest {
+    // The text is also used as the key of the remembered decisions,
+    // see `CapabilityDecisionStore`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapabilityRequest::Syscall => write!(f, "syscall"),
+            CapabilityRequest::Extcall => write!(f, "extcall"),
+            CapabilityRequest::ShellExecute(command) => write!(f, "shell_execute {}", command),
+            CapabilityRequest::ShellExecuteAll => write!(f, "shell_execute_all"),
+            CapabilityRequest::FileExecute(path) => write!(f, "file_execute {}", path),
+            CapabilityRequest::FileExecuteAll => write!(f, "file_execute_all"),
+            CapabilityRequest::DirAccess(access) => {
+                write!(f, "dir_access {} {}", access.type_.name(), access.path)
+            }
+            CapabilityRequest::FileAccess(access) => {
+                write!(f, "file_access {} {}", access.type_.name(), access.path)
+            }
+            CapabilityRequest::FileAccessAll(type_) => {
+                write!(f, "file_access_all {}", type_.name())
+            }
+        }
+    }
+}
+
+impl Capability {
+    /// Returns `true` if the requested capability is already granted.
+    pub fn has(&self, request: &CapabilityRequest) -> bool {
+        // The paths of the granted accesses are canonical, see `grant`.
+        let contains_access = |accesses: &[FileAccess], access: &FileAccess| {
+            canonicalize_access(access).is_some_and(|access| {
+                accesses
+                    .iter()
+                    .any(|item| item.path == access.path && item.type_.contains(access.type_))
+            })
+        };
+
+        match request {
+            CapabilityRequest::Syscall => self.syscall,
+            CapabilityRequest::Extcall => self.extcall,
+            CapabilityRequest::ShellExecute(command) => {
+                self.shell_execute || self.capable_shell_execute_specify.contains(command)
+            }
+            CapabilityRequest::ShellExecuteAll => self.shell_execute,
+            CapabilityRequest::FileExecute(path) => {
+                self.file_execute || self.file_execute_specified.contains(path)
+            }
+            CapabilityRequest::FileExecuteAll => self.file_execute,
+            CapabilityRequest::DirAccess(access) => contains_access(&self.dir_access, access),
+            CapabilityRequest::FileAccess(access) => contains_access(&self.file_access, access),
+            CapabilityRequest::FileAccessAll(type_) => self
+                .file_access_all
+                .is_some_and(|granted| granted.contains(*type_)),
+        }
+    }
+
+    /// Adds the requested capability to this capability.
+    ///
+    /// The paths of the directory and file accesses are stored in their canonical form,
+    /// the ones which can not be canonicalized are ignored.
+    pub fn grant(&mut self, request: &CapabilityRequest) {
+        if self.has(request) {
+            return;
+        }
+
+        match request {
+            CapabilityRequest::Syscall => self.syscall = true,
+            CapabilityRequest::Extcall => self.extcall = true,
+            CapabilityRequest::ShellExecute(command) => {
+                self.capable_shell_execute_specify.push(command.to_owned())
+            }
+            CapabilityRequest::ShellExecuteAll => self.shell_execute = true,
+            CapabilityRequest::FileExecute(path) => {
+                self.file_execute_specified.push(path.to_owned())
+            }
+            CapabilityRequest::FileExecuteAll => self.file_execute = true,
+            CapabilityRequest::DirAccess(access) => {
+                if let Some(access) = canonicalize_access(access) {
+                    grant_access(&mut self.dir_access, &access);
+                }
+            }
+            CapabilityRequest::FileAccess(access) => {
+                if let Some(access) = canonicalize_access(access) {
+                    grant_access(&mut self.file_access, &access);
+                }
+            }
+            CapabilityRequest::FileAccessAll(type_) => {
+                self.file_access_all = Some(merge_access_type(self.file_access_all, *type_));
+            }
+        }
+    }
+
+    /// Converts the paths of `dir_access` and `file_access` to their canonical form,
+    /// the items which can not be canonicalized are removed.
+    ///
+    /// It is called when the process context is created, so the rules are resolved
+    /// once instead of on every access, see `is_path_accessible`.
+    pub fn canonicalize_paths(&mut self) {
+        for accesses in [&mut self.dir_access, &mut self.file_access] {
+            let canonical_accesses = accesses.iter().filter_map(canonicalize_access).collect();
+            *accesses = canonical_accesses;
+        }
+    }
+
+    /// Checks whether the system call can be invoked, i.e. `syscall` is granted
+    /// and the number is permitted by `syscall_filter`.
+    pub fn permits_syscall(&self, syscall_num: usize) -> bool {
+        self.syscall && self.syscall_filter.permits(&syscall_num)
+    }
+
+    /// Checks whether the external function can be called, i.e. `extcall` is granted
+    /// and the function is permitted by `extcall_filter`.
+    pub fn permits_extcall(&self, library: &str, function: &str) -> bool {
+        self.extcall
+            && self
+                .extcall_filter
+                .permits_by(|rule| rule.matches(library, function))
+    }
+
+    /// Checks whether the path can be accessed with the specified access type,
+    /// the path must be canonicalized by `canonicalize_path` first.
+    ///
+    /// The path is accessible if one of the following rules matches:
+    ///
+    /// - `file_access_all` covers the access type.
+    /// - An item of `file_access` is the same file and covers the access type.
+    /// - An item of `dir_access` is the path itself or one of its ancestors,
+    ///   and covers the access type.
+    ///
+    /// The paths of the rules must be canonical too, see `canonicalize_paths`.
+    pub fn is_path_accessible(&self, canonical_path: &Path, type_: FileAccessType) -> bool {
+        if self
+            .file_access_all
+            .is_some_and(|granted| granted.contains(type_))
+        {
+            return true;
+        }
+
+        let matches = |accesses: &[FileAccess], is_dir: bool| {
+            accesses.iter().any(|access| {
+                let rule_path = Path::new(&access.path);
+                access.type_.contains(type_)
+                    && rule_path.is_absolute()
+                    && if is_dir {
+                        canonical_path.starts_with(rule_path)
+                    } else {
+                        canonical_path == rule_path
+                    }
+            })
+        };
+
+        matches(&self.file_access, false) || matches(&self.dir_access, true)
+    }
+}
+
+/// Returns the absolute form of the path with all `.`, `..` and symbolic links resolved.
+///
+/// Unlike `std::fs::canonicalize`, the path does not need to exist (e.g. a file
+/// which is going to be created), the existing part of the path is resolved
+/// by the file system, and the rest is appended as-is.
+///
+/// Returns `NotFound` if `..` follows a component which does not exist,
+/// because the file system can not resolve such path either, or if a component
+/// which does not exist is a (dangling) symbolic link, because the target of
+/// the link would be created or accessed instead.
+pub fn canonicalize_path(path: &Path) -> std::io::Result<PathBuf> {
+    let absolute_path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    // Find the longest existing ancestor.
+    let mut existing_path = absolute_path.as_path();
+    let mut missing_components = vec![];
+
+    let mut canonical_path = loop {
+        match existing_path.canonicalize() {
+            Ok(canonical_path) => break canonical_path,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                if existing_path.symlink_metadata().is_ok() {
+                    return Err(error);
+                }
+
+                let mut components = existing_path.components();
+                let Some(last) = components.next_back() else {
+                    return Err(error);
+                };
+                missing_components.push(last);
+                existing_path = components.as_path();
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    for component in missing_components.into_iter().rev() {
+        match component {
+            Component::Normal(name) => canonical_path.push(name),
+            Component::CurDir => {}
+            _ => return Err(ErrorKind::NotFound.into()),
+        }
+    }
+
+    Ok(canonical_path)
+}
+
+/// The sub-directories of the home directory, the index is the `dir_type` of the envcalls
+/// `capable_personal_dir_access`, `capable_application_dir_access` and `fs_get_application_dir`.
+pub const PERSONAL_DIRS: [&str; 9] = [
+    "Desktop",
+    "Documents",
+    "Downloads",
+    "Pictures",
+    "Music",
+    "Videos",
+    ".config",
+    ".cache",
+    ".local/share",
+];
+
+/// Returns the home directory of the current user, i.e. `$HOME`, or `%USERPROFILE%` on Windows.
+pub fn get_home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Returns the personal directory of the specified type, e.g. `$HOME/Documents`.
+///
+/// Returns `None` if the type is invalid or the home directory is not available.
+pub fn get_personal_dir(dir_type: usize) -> Option<PathBuf> {
+    let name = PERSONAL_DIRS.get(dir_type)?;
+    get_home_dir().map(|home| home.join(name))
+}
+
+// Merges the access type into the existing entry of the same path (e.g. `Read` + `Write` = `ReadWrite`),
+// or appends a new entry.
+fn grant_access(accesses: &mut Vec<FileAccess>, access: &FileAccess) {
+    match accesses.iter_mut().find(|item| item.path == access.path) {
+        Some(item) => item.type_ = merge_access_type(Some(item.type_), access.type_),
+        None => accesses.push(access.clone()),
+    }
+}
+
+// Returns the access with the canonical path, or `None` if the path is empty
+// or can not be canonicalized.
+fn canonicalize_access(access: &FileAccess) -> Option<FileAccess> {
+    if access.path.is_empty() {
+        return None;
+    }
+
+    let canonical_path = canonicalize_path(Path::new(&access.path)).ok()?;
+    Some(FileAccess {
+        path: canonical_path.to_str()?.to_owned(),
+        type_: access.type_,
+    })
+}
+
+fn merge_access_type(granted: Option<FileAccessType>, type_: FileAccessType) -> FileAccessType {
+    match granted {
+        Some(granted) if granted.contains(type_) => granted,
+        Some(_) => FileAccessType::ReadWrite,
+        None => type_,
+    }
+}
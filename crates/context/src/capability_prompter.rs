@@ -0,0 +1,522 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Capability Prompter
+// -------------------
+//
+// The program requests capabilities at runtime through the `capable_*` envcalls.
+// The request is granted immediately if the process already has the capability,
+// otherwise the decision is made by:
+//
+// 1. The remembered decision in the `CapabilityDecisionStore` (if any).
+// 2. The `CapabilityPrompter` supplied by the application which embeds the VM,
+//    e.g. a CLI prompt or a GUI dialog.
+//
+// If the user chooses "always" or "never", the decision is remembered, so
+// the prompt is skipped in the future runs of the same program.
+//
+// Example:
+//
+// ```rust
+// let mut process_property = ProcessProperty::default();
+// process_property.capability_grantor = CapabilityGrantor::new(Arc::new(TerminalPrompter::new()))
+//     .with_decision_store(CapabilityDecisionStore::for_program("/path/to/scripts/hello-world.anc"));
+// ```
+
+use std::{
+    fmt::Debug,
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::capability::{canonicalize_path, get_home_dir, CapabilityRequest};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityDecision {
+    // Grant this time only.
+    Grant,
+    // Grant and remember the decision.
+    GrantAlways,
+    // Deny this time only.
+    Deny,
+    // Deny and remember the decision.
+    DenyAlways,
+}
+
+impl CapabilityDecision {
+    pub fn is_granted(&self) -> bool {
+        matches!(
+            self,
+            CapabilityDecision::Grant | CapabilityDecision::GrantAlways
+        )
+    }
+
+    pub fn is_remembered(&self) -> bool {
+        matches!(
+            self,
+            CapabilityDecision::GrantAlways | CapabilityDecision::DenyAlways
+        )
+    }
+}
+
+/// Asks the user whether to grant the capability requested by the program.
+///
+/// The prompter may be called from any thread of the process concurrently, and it
+/// is called without holding the `process_property` mutex, so the other threads keep
+/// running while the user is answering. The prompter which can not answer multiple
+/// requests at the same time should serialize them itself (e.g. `TerminalPrompter`).
+pub trait CapabilityPrompter: Send + Sync {
+    fn prompt(&self, application_id: &str, request: &CapabilityRequest) -> CapabilityDecision;
+}
+
+/// Grants all requests without prompting, e.g. for trusted programs and unit tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysGrantPrompter;
+
+impl CapabilityPrompter for AlwaysGrantPrompter {
+    fn prompt(&self, _application_id: &str, _request: &CapabilityRequest) -> CapabilityDecision {
+        CapabilityDecision::Grant
+    }
+}
+
+/// Denies all requests without prompting, this is the default prompter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysDenyPrompter;
+
+impl CapabilityPrompter for AlwaysDenyPrompter {
+    fn prompt(&self, _application_id: &str, _request: &CapabilityRequest) -> CapabilityDecision {
+        CapabilityDecision::Deny
+    }
+}
+
+/// Prompts the user in the terminal, the answer is one of:
+///
+/// - `y`: grant this time.
+/// - `a`: always grant.
+/// - `v`: never grant.
+/// - others (including `n` and the end of input): deny this time.
+///
+/// The prompts are serialized, and the control characters of the application id
+/// and the request (e.g. the ANSI escape sequences and line breaks in a path
+/// supplied by the program) are escaped, so the program can not rewrite the prompt.
+pub struct TerminalPrompter {
+    streams: Mutex<(Box<dyn BufRead + Send>, Box<dyn Write + Send>)>,
+}
+
+impl TerminalPrompter {
+    /// Reads the answers from the standard input of the host process,
+    /// and writes the prompts to the standard error.
+    pub fn new() -> Self {
+        Self::with_streams(BufReader::new(std::io::stdin()), std::io::stderr())
+    }
+
+    pub fn with_streams<R, W>(reader: R, writer: W) -> Self
+    where
+        R: BufRead + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        Self {
+            streams: Mutex::new((Box::new(reader), Box::new(writer))),
+        }
+    }
+}
+
+impl Default for TerminalPrompter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CapabilityPrompter for TerminalPrompter {
+    fn prompt(&self, application_id: &str, request: &CapabilityRequest) -> CapabilityDecision {
+        let mut streams = self.streams.lock().unwrap();
+        let (reader, writer) = &mut *streams;
+
+        let _ = write!(
+            writer,
+            "The program \"{}\" requests the capability \"{}\".\nGrant? [y]es, [n]o, [a]lways, ne[v]er: ",
+            escape_control_characters(application_id),
+            escape_control_characters(&request.to_string())
+        );
+        let _ = writer.flush();
+
+        let mut answer = String::new();
+        if reader.read_line(&mut answer).is_err() {
+            return CapabilityDecision::Deny;
+        }
+
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => CapabilityDecision::Grant,
+            "a" | "always" => CapabilityDecision::GrantAlways,
+            "v" | "never" => CapabilityDecision::DenyAlways,
+            _ => CapabilityDecision::Deny,
+        }
+    }
+}
+
+// Replaces the control characters with their escaped forms, e.g. "\u{1b}" and "\n".
+fn escape_control_characters(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() {
+                c.escape_default().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Remembers the decisions of a program in a text file, one decision per line:
+///
+/// ```text
+/// grant syscall
+/// deny file_access read-write /etc/hosts
+/// ```
+///
+/// See `CapabilityRequest` for the text of the requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityDecisionStore {
+    path: PathBuf,
+}
+
+impl CapabilityDecisionStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The store of the specified program, i.e.
+    /// `$HOME/.config/anc/capabilities/$NAME-$HASH.txt`, where `$NAME` is the
+    /// file name of the program and `$HASH` is the hash of its canonical path,
+    /// so the programs with the same name in different places (or a program
+    /// which names itself after another one) do not share the decisions.
+    ///
+    /// Returns `None` if the home directory is not available or the path
+    /// can not be canonicalized.
+    pub fn for_program<P: AsRef<Path>>(program_path: P) -> Option<Self> {
+        let canonical_path = canonicalize_path(program_path.as_ref()).ok()?;
+
+        // Only the safe characters of the name are kept, the name is
+        // for reading only and the hash identifies the program.
+        let name = canonical_path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+
+        get_home_dir().map(|home| {
+            Self::new(home.join(".config/anc/capabilities").join(format!(
+                "{}-{:016x}.txt",
+                name,
+                hash_path(&canonical_path)
+            )))
+        })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    // A missing or unreadable file is regarded as empty.
+    fn load(&self) -> Vec<(bool, String)> {
+        let content = std::fs::read_to_string(&self.path).unwrap_or_default();
+        content
+            .lines()
+            .filter_map(|line| {
+                if let Some(key) = line.strip_prefix("grant ") {
+                    Some((true, key.to_owned()))
+                } else {
+                    line.strip_prefix("deny ")
+                        .map(|key| (false, key.to_owned()))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the remembered decision, `Some(true)` for granted,
+    /// `Some(false)` for denied, and `None` if there is no decision.
+    pub fn get(&self, request: &CapabilityRequest) -> Option<bool> {
+        let key = request.to_string();
+        self.load()
+            .into_iter()
+            .find(|(_, item)| *item == key)
+            .map(|(granted, _)| granted)
+    }
+
+    /// Remembers the decision, the previous decision of the same request is replaced.
+    pub fn set(&self, request: &CapabilityRequest, granted: bool) -> std::io::Result<()> {
+        let key = request.to_string();
+        if key.contains('\n') {
+            return Err(std::io::ErrorKind::InvalidInput.into());
+        }
+
+        let mut decisions = self.load();
+        decisions.retain(|(_, item)| *item != key);
+        decisions.push((granted, key));
+
+        let content = decisions
+            .iter()
+            .map(|(granted, key)| format!("{} {}\n", if *granted { "grant" } else { "deny" }, key))
+            .collect::<String>();
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, content)
+    }
+}
+
+// The FNV-1a hash of the path, the hash is a part of the file name of the store,
+// so it must be stable across the Rust versions (unlike `DefaultHasher`).
+fn hash_path(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Decides the capability requests of a process, see the module documentation.
+#[derive(Clone)]
+pub struct CapabilityGrantor {
+    pub prompter: Arc<dyn CapabilityPrompter>,
+    pub decision_store: Option<CapabilityDecisionStore>,
+}
+
+impl CapabilityGrantor {
+    pub fn new(prompter: Arc<dyn CapabilityPrompter>) -> Self {
+        Self {
+            prompter,
+            decision_store: None,
+        }
+    }
+
+    pub fn with_decision_store(mut self, decision_store: Option<CapabilityDecisionStore>) -> Self {
+        self.decision_store = decision_store;
+        self
+    }
+
+    /// Decides the request which is not granted to the process yet (see `Capability::has`),
+    /// returns `true` if the request is granted, the caller then grants it to the
+    /// capability (see `Capability::grant`).
+    ///
+    /// The prompter may block until the user answers, so it should not be
+    /// called while holding the `process_property` mutex.
+    pub fn decide(&self, application_id: &str, request: &CapabilityRequest) -> bool {
+        let remembered = self
+            .decision_store
+            .as_ref()
+            .and_then(|decision_store| decision_store.get(request));
+
+        if let Some(granted) = remembered {
+            return granted;
+        }
+
+        let decision = self.prompter.prompt(application_id, request);
+        if decision.is_remembered() {
+            if let Some(decision_store) = &self.decision_store {
+                // Failing to remember the decision does not affect this run.
+                let _ = decision_store.set(request, decision.is_granted());
+            }
+        }
+        decision.is_granted()
+    }
+}
+
+impl Default for CapabilityGrantor {
+    fn default() -> Self {
+        Self::new(Arc::new(AlwaysDenyPrompter))
+    }
+}
+
+impl Debug for CapabilityGrantor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapabilityGrantor")
+            .field("decision_store", &self.decision_store)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::capability::{Capability, CapabilityRequest, FileAccess, FileAccessType};
+
+    use super::{
+        CapabilityDecision, CapabilityDecisionStore, CapabilityPrompter, TerminalPrompter,
+    };
+
+    // Writes to the buffer which is shared with the test.
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_terminal_prompter() {
+        let prompter = TerminalPrompter::with_streams(
+            Cursor::new(b"y\nA\nnever\nn\nwhatever\n".to_vec()),
+            std::io::sink(),
+        );
+
+        let request = CapabilityRequest::Syscall;
+        assert_eq!(
+            prompter.prompt("hello", &request),
+            CapabilityDecision::Grant
+        );
+        assert_eq!(
+            prompter.prompt("hello", &request),
+            CapabilityDecision::GrantAlways
+        );
+        assert_eq!(
+            prompter.prompt("hello", &request),
+            CapabilityDecision::DenyAlways
+        );
+        assert_eq!(prompter.prompt("hello", &request), CapabilityDecision::Deny);
+        assert_eq!(prompter.prompt("hello", &request), CapabilityDecision::Deny);
+
+        // end of input
+        assert_eq!(prompter.prompt("hello", &request), CapabilityDecision::Deny);
+    }
+
+    #[test]
+    fn test_terminal_prompter_escape_control_characters() {
+        let output = Arc::new(Mutex::new(vec![]));
+        let prompter = TerminalPrompter::with_streams(
+            Cursor::new(b"n\n".to_vec()),
+            SharedWriter(output.clone()),
+        );
+
+        let request = CapabilityRequest::FileAccess(FileAccess {
+            path: "/tmp/a.txt\r\x1b[2K\x1b[1Adir_access read /tmp\n".to_owned(),
+            type_: FileAccessType::Read,
+        });
+        assert_eq!(prompter.prompt("hello", &request), CapabilityDecision::Deny);
+
+        let text = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            text,
+            "The program \"hello\" requests the capability \"file_access read /tmp/a.txt\\r\\u{1b}[2K\\u{1b}[1Adir_access read /tmp\\n\".\nGrant? [y]es, [n]o, [a]lways, ne[v]er: "
+        );
+    }
+
+    #[test]
+    fn test_capability_decision_store() {
+        let path = std::env::temp_dir().join(format!(
+            "anc_capability_decision_store_{}/hello.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let store = CapabilityDecisionStore::new(&path);
+        let request0 = CapabilityRequest::Extcall;
+        let request1 = CapabilityRequest::FileAccess(FileAccess {
+            path: "/tmp/a.txt".to_owned(),
+            type_: FileAccessType::Write,
+        });
+
+        assert_eq!(store.get(&request0), None);
+
+        store.set(&request0, true).unwrap();
+        store.set(&request1, false).unwrap();
+        assert_eq!(store.get(&request0), Some(true));
+        assert_eq!(store.get(&request1), Some(false));
+
+        // replace the decision
+        store.set(&request1, true).unwrap();
+        assert_eq!(store.get(&request1), Some(true));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "grant extcall\ngrant file_access write /tmp/a.txt\n"
+        );
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_capability_decision_store_for_program() {
+        let dir_path = std::env::temp_dir().join(format!(
+            "anc_capability_decision_store_for_program_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(dir_path.join("a")).unwrap();
+        std::fs::create_dir_all(dir_path.join("b")).unwrap();
+
+        let store_a = CapabilityDecisionStore::for_program(dir_path.join("a/hello.anc")).unwrap();
+        let store_b = CapabilityDecisionStore::for_program(dir_path.join("b/hello.anc")).unwrap();
+
+        // the programs with the same name do not share the store
+        assert_ne!(store_a, store_b);
+
+        // the path is canonicalized
+        assert_eq!(
+            CapabilityDecisionStore::for_program(dir_path.join("b/../a/./hello.anc")).unwrap(),
+            store_a
+        );
+
+        // the store is located in the directory of the stores
+        let file_name = store_a.get_path().file_name().unwrap().to_str().unwrap();
+        assert!(file_name.starts_with("hello_anc-"));
+        assert!(store_a
+            .get_path()
+            .parent()
+            .unwrap()
+            .ends_with(".config/anc/capabilities"));
+
+        std::fs::remove_dir_all(&dir_path).unwrap();
+    }
+
+    #[test]
+    fn test_capability_grant() {
+        let mut capability = Capability::default();
+        let read = CapabilityRequest::FileAccess(FileAccess {
+            path: "/tmp/a.txt".to_owned(),
+            type_: FileAccessType::Read,
+        });
+        let write = CapabilityRequest::FileAccess(FileAccess {
+            path: "/tmp/a.txt".to_owned(),
+            type_: FileAccessType::Write,
+        });
+
+        capability.grant(&read);
+        assert!(capability.has(&read));
+        assert!(!capability.has(&write));
+
+        // the access types are merged
+        capability.grant(&write);
+        assert_eq!(capability.file_access.len(), 1);
+        assert_eq!(capability.file_access[0].type_, FileAccessType::ReadWrite);
+        assert!(capability.has(&read));
+
+        capability.grant(&CapabilityRequest::ShellExecute("ls".to_owned()));
+        assert!(capability.has(&CapabilityRequest::ShellExecute("ls".to_owned())));
+        assert!(!capability.has(&CapabilityRequest::ShellExecuteAll));
+    }
+}
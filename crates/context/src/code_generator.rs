@@ -176,7 +176,6 @@ impl Generator<ObjectModule> {
     // Demo:
     //
     // https://github.com/bytecodealliance/wasmtime/blob/main/cranelift/object/tests/basic.rs
-    #[allow(dead_code)]
     pub fn new(module_name: &str, opt_platform: Option<&str>) -> Self {
         let mut flag_builder = settings::builder();
         flag_builder.set("use_colocated_libcalls", "false").unwrap();
@@ -247,7 +246,6 @@ where
     // 1. let gv = construct a GlobalValue object, e.g. module.declare_data_in_func(...)
     // 2. let target_address = ins().symbol_value(gv)
     // 3. let value = ins().load(target_address)
-    #[allow(dead_code)]
     pub fn define_read_only_data(
         &mut self,
         name: &str,
@@ -277,7 +275,6 @@ where
         Ok(data_id)
     }
 
-    #[allow(dead_code)]
     pub fn define_read_write_data(
         &mut self,
         name: &str,
@@ -306,7 +303,6 @@ where
         Ok(data_id)
     }
 
-    #[allow(dead_code)]
     pub fn define_uninitialized_data(
         &mut self,
         name: &str,
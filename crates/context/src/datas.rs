@@ -7,6 +7,8 @@
 use anc_image::common_sections::{
     read_only_data_section, read_write_data_section, uninit_data_section,
 };
+use std::ops::{Deref, DerefMut};
+
 use anc_memory::{indexed_memory_access::IndexedMemoryAccess, memory_access::MemoryAccess};
 
 // Represents a collection of read-only data items and their associated raw data.
@@ -18,7 +20,59 @@ pub struct ReadOnlyDatas<'a> {
 // Represents a collection of read-write data items and their associated raw data.
 pub struct ReadWriteDatas<'a> {
     data_items: &'a [read_write_data_section::DataItem], // Metadata for each data item.
-    datas: Vec<u8>,                                      // Mutable raw data buffer.
+    datas: DataBuffer,                                   // Mutable raw data buffer.
+}
+
+// The memory which holds the read-write data of a thread.
+//
+// It is usually a heap buffer copied from the module image, but it can also be
+// any other memory that owns its bytes, e.g. a private copy-on-write mapping of
+// the image file, so that the pages are only copied when they are written.
+pub struct DataBuffer {
+    ptr: *mut u8,
+    len: usize,
+    _owner: Box<dyn DerefMut<Target = [u8]>>,
+}
+
+impl DataBuffer {
+    pub fn new<T: DerefMut<Target = [u8]> + 'static>(owner: T) -> Self {
+        // Box the owner first so that the address of the bytes is stable,
+        // the address is cached to avoid the dynamic dispatch on each access.
+        let mut owner: Box<dyn DerefMut<Target = [u8]>> = Box::new(owner);
+        let bytes = owner.deref_mut();
+        Self {
+            ptr: bytes.as_mut_ptr(),
+            len: bytes.len(),
+            _owner: owner,
+        }
+    }
+}
+
+// Provides the read-write data of the modules for the new threads, instead of
+// copying the data from the module images.
+pub trait ReadWriteDataSource: Send + Sync {
+    // Returns `None` to fall back to copying the data from the module image.
+    fn create_read_write_data(&self, module_index: usize) -> Option<DataBuffer>;
+}
+
+impl From<Vec<u8>> for DataBuffer {
+    fn from(value: Vec<u8>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl Deref for DataBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl DerefMut for DataBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
 }
 
 // Represents a collection of uninitialized data items and their associated raw data.
@@ -32,13 +86,34 @@ impl<'a> ReadOnlyDatas<'a> {
     pub fn new(data_items: &'a [read_only_data_section::DataItem], datas: &'a [u8]) -> Self {
         Self { data_items, datas }
     }
+
+    // Returns the total length (in bytes) of the read-only data.
+    pub fn get_total_length(&self) -> usize {
+        self.datas.len()
+    }
 }
 
 impl<'a> ReadWriteDatas<'a> {
     // Creates a new instance of ReadWriteDatas with the given data items and raw data.
     pub fn new(data_items: &'a [read_write_data_section::DataItem], datas: Vec<u8>) -> Self {
+        Self {
+            data_items,
+            datas: datas.into(),
+        }
+    }
+
+    // Creates a new instance of ReadWriteDatas with the given data items and data buffer.
+    pub fn with_buffer(
+        data_items: &'a [read_write_data_section::DataItem],
+        datas: DataBuffer,
+    ) -> Self {
         Self { data_items, datas }
     }
+
+    // Returns the total length (in bytes) of the read-write data.
+    pub fn get_total_length(&self) -> usize {
+        self.datas.len()
+    }
 }
 
 impl<'a> UninitDatas<'a> {
@@ -46,6 +121,11 @@ impl<'a> UninitDatas<'a> {
     pub fn new(data_items: &'a [uninit_data_section::DataItem], datas: Vec<u8>) -> Self {
         Self { data_items, datas }
     }
+
+    // Returns the total length (in bytes) of the uninitialized data.
+    pub fn get_total_length(&self) -> usize {
+        self.datas.len()
+    }
 }
 
 impl MemoryAccess for ReadOnlyDatas<'_> {
@@ -0,0 +1,136 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Host Function
+// -------------
+//
+// The host functions are Rust closures registered by the application which embeds the VM,
+// they are used to expose the services of the application (e.g. database lookups,
+// configuration access) to the VM programs.
+//
+// The host functions are called through the instruction `envcall` with the
+// reserved envcall category, the item of the envcall number is the index
+// of the host function in this table.
+//
+// ```diagram
+//  XiaoXuan Core Application             Host Function Table
+// /------------------------\       /--------------------------------\
+// |                        |       | | idx | name      | closure  | |
+// | fn demo () -> ()       |       | |-----|-----------|----------| |
+// |   envcall 0x0100_0001  | ----> | | 0   | "db::get" | |a| {..} | |
+// | end                    | <---- | | 1   | "config"  | |a| {..} | |
+// |                        |       | | ... | ...       | ...      | |
+// \------------------------/       \--------------------------------/
+// ```
+//
+// The arguments are popped from the operand stack according to the declared parameters,
+// and the values returned by the closure are pushed onto the operand stack.
+//
+// The closure returns an error to stop the program, the envcall raises the trap
+// `HostFunctionFailed`, and the trap `HostFunctionResultsMismatch` is raised
+// if the returned values do not match the declared results.
+
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use anc_isa::{ForeignValue, OperandDataType};
+
+// The item of the envcall number is 16 bits.
+pub const MAX_HOST_FUNCTIONS: usize = 0x1_0000;
+
+pub type HostFunctionResult = Result<Vec<ForeignValue>, Box<dyn Error + Send + Sync>>;
+
+pub type HostFunction = Arc<dyn Fn(&[ForeignValue]) -> HostFunctionResult + Send + Sync>;
+
+#[derive(Clone)]
+pub struct HostFunctionItem {
+    pub name: String,
+    pub params: Vec<OperandDataType>,
+    pub results: Vec<OperandDataType>,
+    pub function: HostFunction,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HostFunctionTableError {
+    // A host function with the same name has already been registered.
+    DuplicateName(String),
+
+    // The number of host functions exceeds `MAX_HOST_FUNCTIONS`.
+    TooManyFunctions,
+}
+
+impl Display for HostFunctionTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostFunctionTableError::DuplicateName(name) => {
+                write!(f, "Duplicate host function \"{}\".", name)
+            }
+            HostFunctionTableError::TooManyFunctions => write!(
+                f,
+                "The number of host functions exceeds {}.",
+                MAX_HOST_FUNCTIONS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HostFunctionTableError {}
+
+#[derive(Clone)]
+pub struct HostFunctionTable {
+    pub items: Vec<HostFunctionItem>,
+}
+
+impl HostFunctionTable {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// Registers a host function, returns the index of the host function.
+    ///
+    /// Returns `DuplicateName` if a host function with the same name has already
+    /// been registered, or `TooManyFunctions` if the table is full.
+    pub fn register<F>(
+        &mut self,
+        name: &str,
+        params: &[OperandDataType],
+        results: &[OperandDataType],
+        function: F,
+    ) -> Result<usize, HostFunctionTableError>
+    where
+        F: Fn(&[ForeignValue]) -> HostFunctionResult + Send + Sync + 'static,
+    {
+        if self.find_host_function_index(name).is_some() {
+            return Err(HostFunctionTableError::DuplicateName(name.to_owned()));
+        }
+
+        if self.items.len() >= MAX_HOST_FUNCTIONS {
+            return Err(HostFunctionTableError::TooManyFunctions);
+        }
+
+        self.items.push(HostFunctionItem {
+            name: name.to_owned(),
+            params: params.to_vec(),
+            results: results.to_vec(),
+            function: Arc::new(function),
+        });
+
+        Ok(self.items.len() - 1)
+    }
+
+    pub fn find_host_function_index(&self, name: &str) -> Option<usize> {
+        self.items.iter().position(|item| item.name == name)
+    }
+
+    pub fn get_host_function(&self, index: usize) -> Option<&HostFunctionItem> {
+        self.items.get(index)
+    }
+}
+
+impl Default for HostFunctionTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A handle for stopping running VM threads from the host side,
+/// e.g. from a watchdog thread or a Ctrl-C handler.
+///
+/// The handle is cheap to clone, and all clones share the same flag.
+/// Once the handle is triggered, the interpreter loop exits at the
+/// next instruction boundary, and the flag remains set until `clear()`
+/// is called, i.e., subsequent calls on the same thread are also interrupted.
+///
+/// There are two kinds of handles:
+///
+/// - The handle obtained from `ThreadContext` interrupts only that thread.
+/// - The handle obtained from `ProcessContext` interrupts all threads
+///   created by the process context, including the child threads.
+///
+/// The flag is only checked between instructions, so a thread blocked in an
+/// envcall (e.g. reading from the stdin or a file, receiving a thread message,
+/// or waiting for a child thread) is stopped after the envcall returns.
+/// To stop such a thread promptly, the host should also unblock the call,
+/// e.g. close the redirected stdin or the pipe of the process.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the VM thread(s) to stop.
+    pub fn interrupt(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Clears the interrupt request so that the thread(s) can run again.
+    pub fn clear(&self) {
+        self.flag.store(false, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_interrupted(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// Returns the address of the flag, the byte at the address is non-zero
+    /// when the handle is triggered.
+    ///
+    /// It is used by the native code generated by the JIT compiler, which
+    /// polls the flag at the back edges of loops.
+    pub fn get_flag_address(&self) -> *const u8 {
+        self.flag.as_ptr() as *const u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterruptHandle;
+
+    #[test]
+    fn test_interrupt_handle() {
+        let handle0 = InterruptHandle::new();
+        let handle1 = handle0.clone();
+        assert!(!handle0.is_interrupted());
+
+        // trigger from another thread
+        std::thread::spawn(move || handle1.interrupt())
+            .join()
+            .unwrap();
+        assert!(handle0.is_interrupted());
+
+        handle0.clear();
+        assert!(!handle0.is_interrupted());
+    }
+}
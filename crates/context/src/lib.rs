@@ -5,15 +5,20 @@
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
 pub mod bridge_function_table;
+pub mod call_audit_log;
 pub mod callback_delegate_function_table;
 pub mod capability;
+pub mod capability_prompter;
 pub mod code_generator;
 pub mod datas;
 pub mod external_function_table;
+pub mod host_function_table;
+pub mod interrupt_handle;
 pub mod module_common_instance;
 pub mod module_linking_instance;
 pub mod process_context;
 pub mod process_property;
 pub mod program_source;
+pub mod standard_streams;
 pub mod thread_context;
 pub mod thread_resources;
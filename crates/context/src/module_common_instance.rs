@@ -4,6 +4,8 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
+use std::{any::Any, sync::Arc};
+
 use anc_image::{
     common_sections::{
         data_name_section::DataNameSection, function_name_section::FunctionNameSection,
@@ -14,7 +16,7 @@ use anc_image::{
 };
 use anc_memory::indexed_memory_access::IndexedMemoryAccess;
 
-use crate::datas::{ReadOnlyDatas, ReadWriteDatas, UninitDatas};
+use crate::datas::{DataBuffer, ReadOnlyDatas, ReadWriteDatas, UninitDatas};
 
 pub struct ModuleCommonInstance<'a> {
     // The name of the module or package.
@@ -34,15 +36,54 @@ pub struct ModuleCommonInstance<'a> {
     pub function_section: FunctionSection<'a>,
     pub datas: [Box<dyn IndexedMemoryAccess + 'a>; 3],
 
+    // The total length (in bytes) of the data of each section, indexed by
+    // the data section type, it is used for taking snapshots of the data.
+    pub datas_lengths: [usize; 3],
+
     // Used for the bridge function feature to manage function names.
     pub function_name_section: FunctionNameSection<'a>,
 
     // Used for the bridge function feature to manage data names.
     pub data_name_section: DataNameSection<'a>,
+
+    // The cache of pre-decoded functions, indexed by the function internal index.
+    //
+    // The items are created by the processor the first time a function is executed
+    // by the pre-decoded interpreter loop, and the content is opaque to this crate.
+    pub predecoded_functions: Vec<Option<Arc<dyn Any + Send + Sync>>>,
+
+    // The execution tier of each function, indexed by the function internal index.
+    //
+    // See `ThreadContext::jit_threshold` for details.
+    pub function_tiers: Vec<FunctionTier>,
+}
+
+/// The execution tier of a function.
+pub enum FunctionTier {
+    // The function is executed by the interpreter,
+    // the value is the number of times the function has been called.
+    Interpreted(u32),
+
+    // The function has been compiled into native code by the JIT compiler,
+    // the content is opaque to this crate.
+    Compiled(Arc<dyn Any + Send + Sync>),
+
+    // The function contains instructions that are not supported by the JIT compiler,
+    // it is always executed by the interpreter.
+    Unsupported,
 }
 
 impl<'a> ModuleCommonInstance<'a> {
     pub fn new(module_image: &'a ModuleImage<'a>) -> Self {
+        Self::with_read_write_data(module_image, None)
+    }
+
+    /// Creates the instance with the specified read-write data buffer,
+    /// the data is copied from the module image if the buffer is `None`.
+    pub fn with_read_write_data(
+        module_image: &'a ModuleImage<'a>,
+        read_write_data_buffer: Option<DataBuffer>,
+    ) -> Self {
         let property_section = module_image.get_property_section();
         let type_section = module_image.get_type_section();
         let local_variable_section = module_image.get_local_variable_section();
@@ -61,7 +102,10 @@ impl<'a> ModuleCommonInstance<'a> {
             .get_optional_read_write_data_section()
             .map_or_else(
                 || ReadWriteDatas::new(&[], Vec::<u8>::new()),
-                |section| ReadWriteDatas::new(section.items, section.datas_data.to_vec()),
+                |section| match read_write_data_buffer {
+                    Some(buffer) => ReadWriteDatas::with_buffer(section.items, buffer),
+                    None => ReadWriteDatas::new(section.items, section.datas_data.to_vec()),
+                },
             );
 
         // Initialize uninitialized data, allocating memory if the section is present.
@@ -92,6 +136,20 @@ impl<'a> ModuleCommonInstance<'a> {
         // Retrieve the module name from the property section.
         let name = property_section.get_module_name().to_owned();
 
+        // No function is pre-decoded until it is executed.
+        let predecoded_functions = vec![None; function_section.items.len()];
+
+        // All functions start in the interpreter tier.
+        let function_tiers = (0..function_section.items.len())
+            .map(|_| FunctionTier::Interpreted(0))
+            .collect::<Vec<_>>();
+
+        let datas_lengths = [
+            read_only_data.get_total_length(),
+            read_write_data.get_total_length(),
+            uninit_data.get_total_length(),
+        ];
+
         Self {
             name,
             type_section,
@@ -102,8 +160,11 @@ impl<'a> ModuleCommonInstance<'a> {
                 Box::new(read_write_data),
                 Box::new(uninit_data),
             ],
+            datas_lengths,
             function_name_section,
             data_name_section,
+            predecoded_functions,
+            function_tiers,
         }
     }
 }
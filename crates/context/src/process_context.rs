@@ -10,8 +10,9 @@ use anc_image::module_image::ModuleImage;
 use cranelift_jit::JITModule;
 
 use crate::{
-    capability::Capability, code_generator::Generator,
-    external_function_table::ExternalFunctionTable, process_property::ProcessProperty,
+    capability::Capability, code_generator::Generator, datas::ReadWriteDataSource,
+    external_function_table::ExternalFunctionTable, host_function_table::HostFunctionTable,
+    interrupt_handle::InterruptHandle, process_property::ProcessProperty,
     thread_context::ThreadContext,
 };
 
@@ -31,14 +32,31 @@ pub struct ProcessContext<'a> {
     /// the "external function table" must reside in `ProcessContext` instead of `ThreadContext`.
     pub external_function_table: Mutex<ExternalFunctionTable>,
 
+    /// The host functions registered by the application which embeds the VM.
+    ///
+    /// The host functions should be registered before creating the thread contexts.
+    pub host_function_table: HostFunctionTable,
+
     /// The code generator.
     pub jit_generator: Mutex<Generator<JITModule>>,
+
+    /// The process-wide interrupt handle.
+    ///
+    /// It is shared by all threads created by this process context,
+    /// including the child threads, triggering it stops all of them.
+    pub interrupt_handle: InterruptHandle,
+
+    /// Provides the read-write data of the modules for the new threads,
+    /// e.g. the private copy-on-write mappings of the image files.
+    ///
+    /// The data is copied from the module images if it is `None`.
+    pub read_write_data_source: Option<Box<dyn ReadWriteDataSource + 'a>>,
 }
 
 impl<'a> ProcessContext<'a> {
     /// Creates a new `ProcessContext` with the given process properties and module images.
     pub fn new(
-        loaded_process_property: ProcessProperty,
+        mut loaded_process_property: ProcessProperty,
         module_images: Vec<ModuleImage<'a>>,
     ) -> Self {
         // Determine the number of unified external libraries from the first module image.
@@ -60,23 +78,36 @@ impl<'a> ProcessContext<'a> {
         // create JIT generator without imported symbols
         let jit_generator = Mutex::new(Generator::<JITModule>::new(vec![]));
 
+        // Resolve the paths of the file access rules once, see `Capability::is_path_accessible`.
+        loaded_process_property.capability.canonicalize_paths();
         let process_property = Mutex::new(loaded_process_property);
 
         Self {
             module_images,
             process_property,
             external_function_table,
+            host_function_table: HostFunctionTable::new(),
             jit_generator,
+            interrupt_handle: InterruptHandle::new(),
+            read_write_data_source: None,
         }
     }
 
+    /// Returns a handle that interrupts all threads of this process.
+    pub fn get_interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt_handle.clone()
+    }
+
     /// Creates a new `ThreadContext` associated with this `ProcessContext`.
     pub fn create_thread_context(&'a self) -> ThreadContext<'a> {
         ThreadContext::new(
             &self.module_images,
             &self.process_property,
             &self.external_function_table,
+            &self.host_function_table,
             &self.jit_generator,
+            &self.interrupt_handle,
+            self.read_write_data_source.as_deref(),
         )
     }
 }
@@ -6,7 +6,10 @@
 
 use std::path::PathBuf;
 
-use crate::capability::Capability;
+use crate::{
+    call_audit_log::CallAuditLog, capability::Capability, capability_prompter::CapabilityGrantor,
+    standard_streams::StandardStreams,
+};
 
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -48,6 +51,39 @@ pub struct ProcessProperty {
 
     /// The capability of the process, which defines what operations it can perform.
     pub capability: Capability,
+
+    /// Decides the capabilities requested by the program at runtime.
+    ///
+    /// See `CapabilityGrantor` for details.
+    pub capability_grantor: CapabilityGrantor,
+
+    /// Records the `syscall` and `extcall` instructions which are permitted
+    /// or denied by the capability, `None` means no audit.
+    ///
+    /// See `CallAuditLog` for details.
+    pub call_audit_log: Option<CallAuditLog>,
+
+    /// The initial instruction budget ("fuel") of each thread, `None` means unlimited.
+    ///
+    /// See `ThreadContext::fuel` for details.
+    pub fuel: Option<u64>,
+
+    /// Whether to execute functions through the pre-decoded interpreter loop.
+    ///
+    /// See `ThreadContext::predecode` for details.
+    pub predecode: bool,
+
+    /// The number of calls after which a function is compiled into native code,
+    /// `None` means the JIT compiler is disabled.
+    ///
+    /// See `ThreadContext::jit_threshold` for details.
+    pub jit_threshold: Option<u32>,
+
+    /// The standard input, output and error of the process,
+    /// they are shared by all threads.
+    ///
+    /// See `StandardStreams` for details.
+    pub standard_streams: StandardStreams,
 }
 
 impl ProcessProperty {
@@ -64,8 +100,33 @@ impl ProcessProperty {
             arguments,
             environments,
             capability,
+            capability_grantor: CapabilityGrantor::default(),
+            call_audit_log: None,
+            fuel: None,
+            predecode: false,
+            jit_threshold: None,
+            standard_streams: StandardStreams::default(),
         }
     }
+
+    /// Returns the identity of the application which is derived from the program path,
+    /// i.e. the name of the module directory, or the file name (without the extension)
+    /// of the script file or package image.
+    ///
+    /// Returns `None` if the program is built in memory or the name is not available.
+    pub fn get_application_id(&self) -> Option<String> {
+        let name = match self.program_source_type {
+            ProgramSourceType::Module => self.program_path.file_name(),
+            ProgramSourceType::ScriptFile | ProgramSourceType::PackageImage => {
+                self.program_path.file_stem()
+            }
+            ProgramSourceType::Memory => None,
+        }?;
+
+        name.to_str()
+            .filter(|name| !name.is_empty())
+            .map(|name| name.to_owned())
+    }
 }
 
 impl Default for ProcessProperty {
@@ -81,6 +142,18 @@ impl Default for ProcessProperty {
             environments: Vec::new(),
             // Default capability is an empty capability.
             capability: Capability::default(),
+            // Default grantor denies all runtime requests.
+            capability_grantor: CapabilityGrantor::default(),
+            // The calls are not audited by default.
+            call_audit_log: None,
+            // Default instruction budget is unlimited.
+            fuel: None,
+            // Default interpreter loop decodes instructions on the fly.
+            predecode: false,
+            // The JIT compiler is disabled by default.
+            jit_threshold: None,
+            // Default standard streams are the ones of the host process.
+            standard_streams: StandardStreams::default(),
         }
     }
 }
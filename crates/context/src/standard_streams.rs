@@ -0,0 +1,123 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Standard Streams
+// ----------------
+//
+// By default, the standard input, output and error of the VM program are bound to
+// the streams of the host process. The application which embeds the VM can redirect
+// them to custom `Read`/`Write` implementations (e.g. in-memory buffers, pipes or log sinks)
+// through `ProcessProperty::standard_streams`.
+//
+// The streams are shared by all threads of the process, e.g.
+//
+// ```rust
+// let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+//
+// let mut process_property = ProcessProperty::default();
+// process_property.standard_streams = StandardStreams::new()
+//     .with_stdin(Cursor::new(b"hello".to_vec()))
+//     .with_stdout(output.clone());
+//
+// // ... run the program ...
+//
+// let text = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+// ```
+
+use std::{
+    fmt::Debug,
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+};
+
+pub type StandardInput = Arc<Mutex<dyn Read + Send>>;
+pub type StandardOutput = Arc<Mutex<dyn Write + Send>>;
+
+/// The standard streams of the process, `None` means the stream
+/// of the host process is used.
+#[derive(Clone, Default)]
+pub struct StandardStreams {
+    pub stdin: Option<StandardInput>,
+    pub stdout: Option<StandardOutput>,
+    pub stderr: Option<StandardOutput>,
+}
+
+impl StandardStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_stdin<R: Read + Send + 'static>(mut self, reader: R) -> Self {
+        self.stdin = Some(Arc::new(Mutex::new(reader)));
+        self
+    }
+
+    /// Redirects the standard output, the writer is shared with the caller
+    /// so that the output can be inspected after (or during) the execution.
+    pub fn with_stdout(mut self, writer: StandardOutput) -> Self {
+        self.stdout = Some(writer);
+        self
+    }
+
+    /// Redirects the standard error, see `with_stdout`.
+    pub fn with_stderr(mut self, writer: StandardOutput) -> Self {
+        self.stderr = Some(writer);
+        self
+    }
+
+    pub fn read_stdin(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &self.stdin {
+            Some(reader) => reader.lock().unwrap().read(buf),
+            None => std::io::stdin().read(buf),
+        }
+    }
+
+    pub fn write_stdout(&self, buf: &[u8]) -> std::io::Result<usize> {
+        match &self.stdout {
+            Some(writer) => writer.lock().unwrap().write(buf),
+            None => std::io::stdout().write(buf),
+        }
+    }
+
+    pub fn write_stderr(&self, buf: &[u8]) -> std::io::Result<usize> {
+        match &self.stderr {
+            Some(writer) => writer.lock().unwrap().write(buf),
+            None => std::io::stderr().write(buf),
+        }
+    }
+
+    pub fn flush_stdout(&self) -> std::io::Result<()> {
+        match &self.stdout {
+            Some(writer) => writer.lock().unwrap().flush(),
+            None => std::io::stdout().flush(),
+        }
+    }
+
+    pub fn flush_stderr(&self) -> std::io::Result<()> {
+        match &self.stderr {
+            Some(writer) => writer.lock().unwrap().flush(),
+            None => std::io::stderr().flush(),
+        }
+    }
+}
+
+impl Debug for StandardStreams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |redirected: bool| {
+            if redirected {
+                "redirected"
+            } else {
+                "inherited"
+            }
+        };
+
+        f.debug_struct("StandardStreams")
+            .field("stdin", &describe(self.stdin.is_some()))
+            .field("stdout", &describe(self.stdout.is_some()))
+            .field("stderr", &describe(self.stderr.is_some()))
+            .finish()
+    }
+}
@@ -4,7 +4,7 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use std::sync::Mutex;
+use std::{fmt::Display, sync::Mutex};
 
 use anc_allocator::{allocator::Allocator, mimallocator::MiMAllocator};
 use anc_image::module_image::{ModuleImage, Visibility};
@@ -16,9 +16,10 @@ use cranelift_jit::JITModule;
 use crate::{
     bridge_function_table::BridgeFunctionTable,
     callback_delegate_function_table::CallbackDelegateFunctionTable, code_generator::Generator,
-    external_function_table::ExternalFunctionTable, module_common_instance::ModuleCommonInstance,
-    module_linking_instance::ModuleLinkingInstance, process_property::ProcessProperty,
-    thread_resources::ThreadResources,
+    datas::ReadWriteDataSource, external_function_table::ExternalFunctionTable,
+    host_function_table::HostFunctionTable, interrupt_handle::InterruptHandle,
+    module_common_instance::ModuleCommonInstance, module_linking_instance::ModuleLinkingInstance,
+    process_property::ProcessProperty, thread_resources::ThreadResources,
 };
 
 // The index of the most significant bit for memory data access.
@@ -40,6 +41,9 @@ pub struct ThreadContext<'a> {
     // External function table, shared across threads and protected by a mutex.
     pub external_function_table: &'a Mutex<ExternalFunctionTable>,
 
+    // Host functions registered by the embedder, shared across threads.
+    pub host_function_table: &'a HostFunctionTable,
+
     // Table for callback delegate functions, used for callback function calls.
     pub callback_delegate_function_table: CallbackDelegateFunctionTable,
 
@@ -58,6 +62,44 @@ pub struct ThreadContext<'a> {
 
     // Properties of the process, such as configuration and runtime state.
     pub process_property: &'a Mutex<ProcessProperty>,
+
+    // The remaining instruction budget ("fuel") of this thread.
+    // Each executed instruction consumes one unit of fuel, and the interpreter
+    // stops with an "out of fuel" error when the budget is exhausted.
+    // `None` means unlimited.
+    pub fuel: Option<u64>,
+
+    // Whether the functions are executed through the pre-decoded interpreter loop.
+    // Each function is converted into a dense array of instruction handlers and
+    // decoded parameters the first time it is executed, which avoids decoding
+    // the opcode and the parameters of every instruction again and again.
+    pub predecode: bool,
+
+    // The number of calls after which a function is compiled into native code
+    // by the JIT compiler, `None` means the JIT compiler is disabled.
+    //
+    // Only the functions which consist of the supported instructions (e.g. the
+    // arithmetic, comparison, control flow, local variable and data instructions)
+    // are compiled, the others are always executed by the interpreter.
+    // The JIT compiler is bypassed when the fuel of the thread is limited,
+    // because the native code does not consume fuel.
+    pub jit_threshold: Option<u32>,
+
+    // The interrupt handle of this thread.
+    pub interrupt_handle: InterruptHandle,
+
+    // The interrupt handle shared by all threads of the process.
+    pub process_interrupt_handle: &'a InterruptHandle,
+}
+
+/// A snapshot of the data of a `ThreadContext`, see `ThreadContext::take_snapshot`.
+pub struct ThreadContextSnapshot {
+    // The data of each module, indexed by the data section type,
+    // the read-only data is not included.
+    datas: Vec<[Vec<u8>; 3]>,
+
+    // The instruction budget when the snapshot was taken.
+    fuel: Option<u64>,
 }
 
 /// Represents a target data object, including its module index, data section type,
@@ -69,6 +111,45 @@ pub struct TargetDataObject<'a> {
     pub accessor: &'a mut dyn IndexedMemoryAccess, // Accessor for memory operations.
 }
 
+/// The kind of a trap, i.e., a recoverable fault raised while executing an instruction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TrapKind {
+    InvalidOpcode,                  // The opcode is invalid.
+    InvalidEnvCallNumber,           // The number of the environment call is invalid.
+    ModuleIndexOutOfBounds,         // The module index is out of bounds.
+    FunctionIndexOutOfBounds,       // The function public index is out of bounds.
+    DataIndexOutOfBounds,           // The data public index is out of bounds.
+    DataAccessOutOfBounds,          // The access exceeds the length of the data.
+    LocalVariableIndexOutOfBounds,  // The local variable index is out of bounds.
+    LocalVariableAccessOutOfBounds, // The access exceeds the length of the local variable.
+    IntegerDivideByZero,            // Integer division or remainder by zero.
+    IntegerOverflow,                // Integer overflow, e.g., `i32::MIN / -1`.
+    HostFunctionFailed,             // The host function returned an error.
+    HostFunctionResultsMismatch,    // The results of the host function are mismatched.
+    NoParentThread,                 // The current thread is the main thread.
+}
+
+impl Display for TrapKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            TrapKind::InvalidOpcode => "invalid opcode",
+            TrapKind::InvalidEnvCallNumber => "invalid envcall number",
+            TrapKind::ModuleIndexOutOfBounds => "module index out of bounds",
+            TrapKind::FunctionIndexOutOfBounds => "function index out of bounds",
+            TrapKind::DataIndexOutOfBounds => "data index out of bounds",
+            TrapKind::DataAccessOutOfBounds => "data access out of bounds",
+            TrapKind::LocalVariableIndexOutOfBounds => "local variable index out of bounds",
+            TrapKind::LocalVariableAccessOutOfBounds => "local variable access out of bounds",
+            TrapKind::IntegerDivideByZero => "integer divide by zero",
+            TrapKind::IntegerOverflow => "integer overflow",
+            TrapKind::HostFunctionFailed => "host function failed",
+            TrapKind::HostFunctionResultsMismatch => "host function results mismatch",
+            TrapKind::NoParentThread => "no parent thread",
+        };
+        f.write_str(text)
+    }
+}
+
 /// Represents a target function object, including its module index and internal function index.
 pub struct TargetFunctionObject {
     pub module_index: usize, // Index of the module containing the function.
@@ -89,7 +170,10 @@ impl<'a> ThreadContext<'a> {
         module_images: &'a [ModuleImage<'a>],
         process_property: &'a Mutex<ProcessProperty>,
         external_function_table: &'a Mutex<ExternalFunctionTable>,
+        host_function_table: &'a HostFunctionTable,
         jit_generator: &'a Mutex<Generator<JITModule>>,
+        process_interrupt_handle: &'a InterruptHandle,
+        read_write_data_source: Option<&'a dyn ReadWriteDataSource>,
     ) -> Self {
         // Initialize the stack and allocator.
         let stack = NostdStack::new();
@@ -104,18 +188,37 @@ impl<'a> ThreadContext<'a> {
         let module_linking_instance = ModuleLinkingInstance::new(module_images);
         let module_common_instances = module_images
             .iter()
-            .map(ModuleCommonInstance::new)
+            .enumerate()
+            .map(|(module_index, module_image)| {
+                let read_write_data_buffer = read_write_data_source
+                    .and_then(|source| source.create_read_write_data(module_index));
+                ModuleCommonInstance::with_read_write_data(module_image, read_write_data_buffer)
+            })
             .collect::<Vec<ModuleCommonInstance>>();
 
         let callback_delegate_function_table = CallbackDelegateFunctionTable::new();
         let bridge_function_table = BridgeFunctionTable::new();
-        let resources = ThreadResources::new();
+
+        // Each thread starts with the initial budget and the interpreter loop
+        // defined in the process property, and shares the standard streams of the process.
+        let (fuel, predecode, jit_threshold, standard_streams) = {
+            let property = process_property.lock().unwrap();
+            (
+                property.fuel,
+                property.predecode,
+                property.jit_threshold,
+                property.standard_streams.clone(),
+            )
+        };
+
+        let resources = ThreadResources::new(standard_streams);
 
         Self {
             stack: Box::new(stack),
             allocator: Box::new(allocator),
             pc,
             external_function_table,
+            host_function_table,
             callback_delegate_function_table,
             bridge_function_table,
             thread_resources: resources,
@@ -123,75 +226,169 @@ impl<'a> ThreadContext<'a> {
             module_linking_instance,
             module_common_instances,
             process_property,
+            fuel,
+            predecode,
+            jit_threshold,
+            interrupt_handle: InterruptHandle::new(),
+            process_interrupt_handle,
+        }
+    }
+
+    /// Takes a snapshot of the read-write data and the uninit data of all modules.
+    ///
+    /// The snapshot is usually taken right after the thread context is created,
+    /// and restored between invocations to get a fresh instance without
+    /// rebuilding the module instances.
+    pub fn take_snapshot(&self) -> ThreadContextSnapshot {
+        let datas = self
+            .module_common_instances
+            .iter()
+            .map(|instance| {
+                std::array::from_fn(|data_section_index| {
+                    // The read-only data never changes.
+                    if data_section_index == DataSectionType::ReadOnly as usize {
+                        return vec![];
+                    }
+
+                    let length = instance.datas_lengths[data_section_index];
+                    let mut data = vec![0u8; length];
+                    instance.datas[data_section_index].read(0, 0, length, data.as_mut_ptr());
+                    data
+                })
+            })
+            .collect::<Vec<_>>();
+
+        ThreadContextSnapshot {
+            datas,
+            fuel: self.fuel,
+        }
+    }
+
+    /// Restores the data and the instruction budget from the snapshot,
+    /// and resets the stack, the allocator and the thread resources
+    /// (e.g. regexes and opened files).
+    ///
+    /// The memory allocated by the program and not freed is discarded
+    /// as if the thread context were dropped, so the data in the snapshot
+    /// should not refer to the allocated memory.
+    pub fn restore_snapshot(&mut self, snapshot: &ThreadContextSnapshot) {
+        // Only the pages which are changed since the snapshot was taken are written back,
+        // so the untouched pages are not written (e.g. the pages of a copy-on-write
+        // mapping are not copied), and a large data area is cheap to restore when
+        // the program changes only a small part of it.
+        const RESTORE_PAGE_SIZE_IN_BYTES: usize = 4096;
+
+        for (instance, datas) in self.module_common_instances.iter_mut().zip(&snapshot.datas) {
+            for (data_section_index, data) in datas.iter().enumerate() {
+                if data_section_index == DataSectionType::ReadOnly as usize {
+                    continue;
+                }
+
+                let accessor = instance.datas[data_section_index].as_mut();
+                for offset in (0..data.len()).step_by(RESTORE_PAGE_SIZE_IN_BYTES) {
+                    let snapshot_page =
+                        &data[offset..data.len().min(offset + RESTORE_PAGE_SIZE_IN_BYTES)];
+                    let current_page = unsafe {
+                        std::slice::from_raw_parts(accessor.get_ptr(0, offset), snapshot_page.len())
+                    };
+
+                    if current_page != snapshot_page {
+                        accessor.write(snapshot_page.as_ptr(), 0, offset, snapshot_page.len());
+                    }
+                }
+            }
+        }
+
+        self.stack.reset();
+        self.allocator = Box::new(MiMAllocator::new());
+        self.thread_resources.reset();
+        self.pc = ProgramCounter {
+            instruction_address: 0,
+            function_internal_index: 0,
+            module_index: 0,
+        };
+        self.fuel = snapshot.fuel;
+        self.interrupt_handle.clear();
+    }
+
+    /// Returns a handle that interrupts only this thread.
+    pub fn get_interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt_handle.clone()
+    }
+
+    /// Checks whether this thread or the whole process has been requested to stop.
+    #[inline]
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupt_handle.is_interrupted() || self.process_interrupt_handle.is_interrupted()
+    }
+
+    /// Returns the remaining instruction budget, `None` means unlimited.
+    pub fn get_fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Sets the remaining instruction budget, `None` means unlimited.
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// Tops up the instruction budget.
+    ///
+    /// This method has no effect if the budget is unlimited.
+    pub fn add_fuel(&mut self, amount: u64) {
+        if let Some(fuel) = self.fuel.as_mut() {
+            *fuel = fuel.saturating_add(amount);
         }
     }
 
-    /// Retrieves a target data object, performing bounds checks if enabled.
+    /// Retrieves a target data object, performing bounds checks.
+    ///
+    /// Returns a trap if the module index or the data index is out of bounds,
+    /// or the access exceeds the length of the data.
     pub fn get_target_data_object(
         &mut self,
         module_index: usize,
         data_access_index: usize,
         expect_offset_bytes: usize, // Expected offset in bytes for bounds checking.
         expect_data_length_in_bytes: usize, // Expected data length in bytes for bounds checking.
-    ) -> TargetDataObject {
+    ) -> Result<TargetDataObject, TrapKind> {
         if data_access_index & MEMORY_DATA_ACCESS_INDEX_MSB != 0 {
             // it is dynamically allocated memory
-            // boundary check is implemented in the allocator
-
             let data_internal_index = data_access_index & MEMORY_DATA_ACCESS_INDEX_MASK; // clear the MSB bit
 
-            //             let opt_size = self.allocator.get_size(data_internal_index);
-            //
-            //             let data_actual_length = if let Some(size) = opt_size {
-            //                 size
-            //             } else {
-            //                 panic!(
-            //                     "Out of bounds of the dynamically allocated data index, request data index: {}.",
-            //                     data_internal_index
-            //                 );
-            //             };
-            //
-            //             // bounds check
-            //             #[cfg(feature = "bounds_check")]
-            //             {
-            //                 if expect_data_length_in_bytes + expect_offset_bytes > data_actual_length {
-            //                     panic!(
-            //                         "Access exceeds the length of the dynamically allocated data.
-            // function internal index: {}, instruction address: 0x{:04x},
-            // data internal index: {},
-            // data actual length (in bytes): {}, access offset (in bytes): 0x{:02x}, expect length (in bytes): {}.",
-            //
-            //                         self.pc.function_internal_index,
-            //                         self.pc.instruction_address,
-            //                         data_internal_index,
-            //                         data_actual_length,
-            //                         expect_offset_bytes,
-            //                         expect_data_length_in_bytes,
-            //                     );
-            //                 }
-            //             }
-
-            TargetDataObject {
+            // The index comes from the operands, it is checked against the allocator
+            // to prevent the host from crashing.
+            let data_actual_length = self
+                .allocator
+                .get_size(data_internal_index)
+                .ok_or(TrapKind::DataIndexOutOfBounds)?;
+
+            // bounds check
+            let end_offset = expect_offset_bytes.checked_add(expect_data_length_in_bytes);
+            if !matches!(end_offset, Some(length) if length <= data_actual_length) {
+                return Err(TrapKind::DataAccessOutOfBounds);
+            }
+
+            Ok(TargetDataObject {
                 module_index: 0,
                 data_section_type: DataSectionType::ReadWrite,
                 data_internal_index_in_section: data_internal_index,
                 accessor: self.allocator.as_mut(),
-            }
+            })
         } else {
-            // data index bounds check for compilation error
-            #[cfg(debug_assertions)]
-            {
-                let count = self
-                    .module_linking_instance
-                    .data_index_section
-                    .get_items_count(module_index);
-
-                if data_access_index > count {
-                    panic!(
-                        "Out of bounds of the data public index, module index: {}, total data items: {}, request data index: {}.",
-                        module_index, count, data_access_index
-                    );
-                }
+            // The module index and data index come from the bytecode or the operands,
+            // they are checked here to prevent the host from crashing.
+            if module_index >= self.module_common_instances.len() {
+                return Err(TrapKind::ModuleIndexOutOfBounds);
+            }
+
+            let count = self
+                .module_linking_instance
+                .data_index_section
+                .get_items_count(module_index);
+
+            if data_access_index >= count {
+                return Err(TrapKind::DataIndexOutOfBounds);
             }
 
             let data_public_index = data_access_index;
@@ -208,59 +405,44 @@ impl<'a> ThreadContext<'a> {
             let accessor = target_module.datas[target_data_section_type as usize].as_mut();
 
             // bounds check
-            #[cfg(feature = "bounds_check")]
-            {
-                let data_actual_length = accessor.get_data_length(data_internal_index_in_section);
-
-                if expect_data_length_in_bytes + expect_offset_bytes > data_actual_length {
-                    panic!(
-                        "Access exceeds the length of the data.
-module index: {}, function internal index: {}, instruction address: 0x{:04x},
-data section type: {}, data public index: {}, data internal index: {},
-data actual length (in bytes): {}, access offset (in bytes): 0x{:02x}, expect length (in bytes): {}.",
-                        module_index,
-                        self.pc.function_internal_index,
-                        self.pc.instruction_address,
-                        target_data_section_type,
-                        data_access_index,
-                        data_internal_index_in_section,
-                        data_actual_length,
-                        expect_offset_bytes,
-                        expect_data_length_in_bytes,
-                    );
-                }
+            let data_actual_length = accessor.get_data_length(data_internal_index_in_section);
+
+            // the offset comes from the operand, so use `checked_add` to prevent overflow.
+            let end_offset = expect_offset_bytes.checked_add(expect_data_length_in_bytes);
+            if !matches!(end_offset, Some(length) if length <= data_actual_length) {
+                return Err(TrapKind::DataAccessOutOfBounds);
             }
 
-            TargetDataObject {
+            Ok(TargetDataObject {
                 module_index: target_module_index,
                 data_section_type: target_data_section_type,
                 data_internal_index_in_section,
                 accessor,
-            }
+            })
         }
     }
 
-    /// Retrieves a target function object, performing bounds checks if enabled.
+    /// Retrieves a target function object.
+    ///
+    /// Returns a trap if the module index or the function public index is out of bounds.
     pub fn get_target_function_object(
         &self,
         module_index: usize,
         function_public_index: usize,
-    ) -> TargetFunctionObject {
-        // function index bounds check for compilation error
-        #[cfg(debug_assertions)]
-        {
-            let count = self
-                .module_linking_instance
-                .function_index_section
-                .get_items_count(module_index);
+    ) -> Result<TargetFunctionObject, TrapKind> {
+        // The module index and function index may come from the operands
+        // (e.g. `call_dynamic`), they are checked here to prevent the host from crashing.
+        if module_index >= self.module_common_instances.len() {
+            return Err(TrapKind::ModuleIndexOutOfBounds);
+        }
 
-            if function_public_index > count {
-                panic!("Out of bounds of the function public index, module index: {}, total functions (includes imported): {}, request function public index: {}.",
-                    module_index,
-                    count,
-                    function_public_index
-                );
-            }
+        let count = self
+            .module_linking_instance
+            .function_index_section
+            .get_items_count(module_index);
+
+        if function_public_index >= count {
+            return Err(TrapKind::FunctionIndexOutOfBounds);
         }
 
         let (target_module_index, function_internal_index) = self
@@ -271,10 +453,10 @@ data actual length (in bytes): {}, access offset (in bytes): 0x{:02x}, expect le
                 function_public_index,
             );
 
-        TargetFunctionObject {
+        Ok(TargetFunctionObject {
             module_index: target_module_index,
             function_internal_index,
-        }
+        })
     }
 
     /// Retrieves metadata about a function, such as its type and local variable information.
@@ -306,52 +488,37 @@ data actual length (in bytes): {}, access offset (in bytes): 0x{:02x}, expect le
     }
 
     /// Calculates the start address of a local variable within the stack frame.
+    ///
+    /// Returns a trap if the local variable index is out of bounds,
+    /// or the access exceeds the length of the local variable.
     pub fn get_local_variable_start_address(
         &self,
         layers: u16,                 // The number of layers in the reverse stack frames.
         local_variable_index: usize, // Index of the local variable.
         // offset_bytes: usize,         // Offset in bytes for bounds checking.
         expect_data_length_in_bytes: usize, // Expected data length in bytes for bounds checking.
-    ) -> usize {
+    ) -> Result<usize, TrapKind> {
         // get the local variable info
-        let ProgramCounter {
-            instruction_address,
-            function_internal_index,
-            module_index,
-        } = self.pc;
+        let module_index = self.pc.module_index;
 
         let (local_variable_list_index, local_variables_start_address) = self
             .stack
             .get_local_variable_list_index_and_start_address_by_layers(layers);
 
-        let variable_item = &self.module_common_instances[module_index]
+        let variable_item = self.module_common_instances[module_index]
             .local_variable_section
-            .get_local_variable_list(local_variable_list_index)[local_variable_index];
+            .get_local_variable_list(local_variable_list_index)
+            .get(local_variable_index)
+            .ok_or(TrapKind::LocalVariableIndexOutOfBounds)?;
 
         // bounds check
-        #[cfg(feature = "bounds_check")]
+        if expect_data_length_in_bytes // + offset_bytes
+            > variable_item.variable_actual_size_in_bytes as usize
         {
-            if expect_data_length_in_bytes // + offset_bytes
-                > variable_item.variable_actual_size_in_bytes as usize
-            {
-                panic!(
-                    "Access exceeds the length of the local variable.
-module index: {}, function internal index: {}, instruction address: 0x{:04x},
-layers: {}, local variable index: {},
-variable actual length (in bytes): {}, expect length (in bytes): {}.",
-                    module_index,
-                    function_internal_index,
-                    instruction_address,
-                    layers,
-                    local_variable_index,
-                    variable_item.variable_actual_size_in_bytes,
-                    // offset_bytes,
-                    expect_data_length_in_bytes,
-                );
-            }
+            return Err(TrapKind::LocalVariableAccessOutOfBounds);
         }
 
-        local_variables_start_address + variable_item.variable_offset as usize
+        Ok(local_variables_start_address + variable_item.variable_offset as usize)
     }
 
     /// Finds a function by its fully qualified name, returning its module and internal indices.
@@ -4,10 +4,16 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use std::fs::File;
+use std::{
+    fs::{File, ReadDir},
+    io::{IsTerminal, Read, Seek, SeekFrom, Write},
+    iter::Peekable,
+};
 
 use regex_anre::{context::MatchRange, Regex};
 
+use crate::standard_streams::StandardStreams;
+
 pub struct ThreadResources {
     regexes: Vec<Option<Regex>>,
 
@@ -18,6 +24,12 @@ pub struct ThreadResources {
     // The first three files are standard input, output, and error.
     // Their indices are 0, 1, and 2 respectively.
     files: Vec<Option<FileObject>>,
+
+    // Directory streams that are opened by the thread, see the envcall `fs_list_dir`.
+    dirs: Vec<Option<Peekable<ReadDir>>>,
+
+    // The standard streams of the process, they are shared by all threads.
+    standard_streams: StandardStreams,
 }
 
 pub enum FileObject {
@@ -28,7 +40,7 @@ pub enum FileObject {
 }
 
 impl ThreadResources {
-    pub fn new() -> Self {
+    pub fn new(standard_streams: StandardStreams) -> Self {
         Self {
             regexes: Vec::new(),
             last_captures: Vec::new(),
@@ -37,6 +49,8 @@ impl ThreadResources {
                 Some(FileObject::StdOut),
                 Some(FileObject::StdErr),
             ],
+            dirs: Vec::new(),
+            standard_streams,
         }
     }
 
@@ -93,4 +107,111 @@ impl ThreadResources {
             self.files[index] = None;
         }
     }
+
+    /// Adds a new directory stream to the first `None` slot in the `dirs` vector.
+    /// Returns the index of the added directory stream.
+    pub fn add_dir(&mut self, dir: ReadDir) -> usize {
+        if let Some(index) = self.dirs.iter().position(Option::is_none) {
+            self.dirs[index] = Some(dir.peekable());
+            index
+        } else {
+            self.dirs.push(Some(dir.peekable()));
+            self.dirs.len() - 1
+        }
+    }
+
+    pub fn get_dir_mut(&mut self, index: usize) -> Option<&mut Peekable<ReadDir>> {
+        self.dirs.get_mut(index).and_then(Option::as_mut)
+    }
+
+    pub fn remove_dir(&mut self, index: usize) {
+        if index < self.dirs.len() {
+            self.dirs[index] = None;
+        }
+    }
+
+    /// Removes all regexes and closes all files and directory streams opened by the thread,
+    /// i.e. restores the resources to the initial state.
+    pub fn reset(&mut self) {
+        self.regexes.clear();
+        self.last_captures.clear();
+        self.files = vec![
+            Some(FileObject::StdIn),
+            Some(FileObject::StdOut),
+            Some(FileObject::StdErr),
+        ];
+        self.dirs.clear();
+    }
+
+    /// Reads from the specified file, the standard input is read
+    /// from the (possibly redirected) stream of the process.
+    pub fn read_file(&self, index: usize, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.get_file(index) {
+            Some(FileObject::StdIn) => self.standard_streams.read_stdin(buf),
+            Some(FileObject::User(file)) => {
+                let mut file: &File = file;
+                file.read(buf)
+            }
+            Some(_) => Err(std::io::ErrorKind::Unsupported.into()),
+            None => Err(std::io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    /// Writes to the specified file, the standard output and error are written
+    /// to the (possibly redirected) streams of the process.
+    pub fn write_file(&self, index: usize, buf: &[u8]) -> std::io::Result<usize> {
+        match self.get_file(index) {
+            Some(FileObject::StdOut) => self.standard_streams.write_stdout(buf),
+            Some(FileObject::StdErr) => self.standard_streams.write_stderr(buf),
+            Some(FileObject::User(file)) => {
+                let mut file: &File = file;
+                file.write(buf)
+            }
+            Some(_) => Err(std::io::ErrorKind::Unsupported.into()),
+            None => Err(std::io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    pub fn flush_file(&self, index: usize) -> std::io::Result<()> {
+        match self.get_file(index) {
+            Some(FileObject::StdIn) => Ok(()),
+            Some(FileObject::StdOut) => self.standard_streams.flush_stdout(),
+            Some(FileObject::StdErr) => self.standard_streams.flush_stderr(),
+            Some(FileObject::User(file)) => {
+                let mut file: &File = file;
+                file.flush()
+            }
+            None => Err(std::io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    /// Seeks the specified file, the standard streams are not seekable.
+    pub fn seek_file(&self, index: usize, pos: SeekFrom) -> std::io::Result<u64> {
+        match self.get_file(index) {
+            Some(FileObject::User(file)) => {
+                let mut file: &File = file;
+                file.seek(pos)
+            }
+            Some(_) => Err(std::io::ErrorKind::Unsupported.into()),
+            None => Err(std::io::ErrorKind::InvalidInput.into()),
+        }
+    }
+
+    /// Returns true if the specified file is a terminal, the redirected
+    /// standard streams are never terminals.
+    pub fn is_terminal_file(&self, index: usize) -> bool {
+        match self.get_file(index) {
+            Some(FileObject::StdIn) => {
+                self.standard_streams.stdin.is_none() && std::io::stdin().is_terminal()
+            }
+            Some(FileObject::StdOut) => {
+                self.standard_streams.stdout.is_none() && std::io::stdout().is_terminal()
+            }
+            Some(FileObject::StdErr) => {
+                self.standard_streams.stderr.is_none() && std::io::stderr().is_terminal()
+            }
+            Some(FileObject::User(file)) => file.is_terminal(),
+            None => false,
+        }
+    }
 }
@@ -0,0 +1,131 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Compares the default interpreter loop with the pre-decoded one.
+//
+// Run with:
+//
+// `$ cargo bench -p anc-processor --bench dispatch`
+
+use std::time::{Duration, Instant};
+
+use anc_context::program_source::ProgramSource;
+use anc_image::{
+    bytecode_writer::BytecodeWriterHelper,
+    utils::{helper_build_module_binary_with_single_function_and_blocks, HelperBlockEntry},
+};
+use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+use anc_processor::{in_memory_program_source::InMemoryProgramSource, process::process_function};
+
+const ROUNDS: usize = 20;
+const LOOP_COUNT: u32 = 1_000_000;
+
+fn build_binary() -> Vec<u8> {
+    // A compute-heavy loop which sums the squares:
+    //
+    // fn (sum/0:i64, n/1:i32) -> (i64)
+    //                              ;; sum = sum + n * n
+    //     local_load_i64(0, 0)
+    //     local_load_i32_u(0, 1)
+    //     extend_i32_u_to_i64
+    //     local_load_i32_u(0, 1)
+    //     extend_i32_u_to_i64
+    //     mul_i64
+    //     add_i64
+    //     local_store_i64(0, 0)
+    //                              ;; n = n - 1
+    //     local_load_i32_u(0, 1)
+    //     sub_imm_i32(1)
+    //     local_store_i32(0, 1)
+    //                              ;; if n > 0 recur (sum,n)
+    //     local_load_i32_u(0, 1)
+    //     imm_i32(0)
+    //     gt_i32_u
+    //     block_nez () -> ()
+    //         local_load_i64(0, 0)
+    //         local_load_i32_u(0, 1)
+    //         recur(1)
+    //     end
+    //     local_load_i64(0, 0)     ;; load sum
+    // end
+
+    let code0 = BytecodeWriterHelper::new()
+        .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+        .append_opcode(Opcode::extend_i32_u_to_i64)
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+        .append_opcode(Opcode::extend_i32_u_to_i64)
+        .append_opcode(Opcode::mul_i64)
+        .append_opcode(Opcode::add_i64)
+        .append_opcode_i16_i32(Opcode::local_store_i64, 0, 0)
+        //
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+        .append_opcode_i16(Opcode::sub_imm_i32, 1)
+        .append_opcode_i16_i32(Opcode::local_store_i32, 0, 1)
+        //
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+        .append_opcode_i32(Opcode::imm_i32, 0)
+        .append_opcode(Opcode::gt_i32_u)
+        .append_opcode_i32_i32(Opcode::block_nez, 1, 0x26)
+        .append_opcode_i16_i32(Opcode::local_load_i64, 1, 0)
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+        .append_opcode_i16_i32(Opcode::recur, 1, 0)
+        // block end
+        .append_opcode(Opcode::end)
+        //
+        .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+        .append_opcode(Opcode::end)
+        .to_bytes();
+
+    helper_build_module_binary_with_single_function_and_blocks(
+        vec![OperandDataType::I64, OperandDataType::I32], // params
+        vec![OperandDataType::I64],                       // results
+        vec![],                                           // local variables
+        code0,
+        vec![HelperBlockEntry {
+            params: vec![],
+            results: vec![],
+            local_variable_item_entries_without_args: vec![],
+        }], // blocks
+    )
+}
+
+fn measure(predecode: bool) -> Duration {
+    let resource0 = InMemoryProgramSource::new(vec![build_binary()]);
+    let process_context0 = resource0.create_process_context().unwrap();
+    let mut thread_context0 = process_context0.create_thread_context();
+    thread_context0.predecode = predecode;
+
+    let expected = (1..=LOOP_COUNT as u64).map(|n| n * n).sum::<u64>();
+    let arguments = [ForeignValue::U64(0), ForeignValue::U32(LOOP_COUNT)];
+
+    // Warm up, this also decodes the function for the pre-decoded loop.
+    process_function(&mut thread_context0, 0, 0, &arguments).unwrap();
+
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let results = process_function(&mut thread_context0, 0, 0, &arguments).unwrap();
+        best = best.min(start.elapsed());
+
+        assert_eq!(results, vec![ForeignValue::U64(expected)]);
+    }
+
+    best
+}
+
+fn main() {
+    let default_loop = measure(false);
+    let predecoded_loop = measure(true);
+
+    println!("loop count: {}, best of {} rounds", LOOP_COUNT, ROUNDS);
+    println!("default interpreter loop:    {:?}", default_loop);
+    println!("pre-decoded interpreter loop: {:?}", predecoded_loop);
+    println!(
+        "speedup: {:.2}x",
+        default_loop.as_secs_f64() / predecoded_loop.as_secs_f64()
+    );
+}
@@ -0,0 +1,152 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Compares the startup time and the resident memory (RSS) of loading a large
+// module image by reading the file (`FileProgramSource`) with memory-mapping
+// the file (`MmapProgramSource`).
+//
+// The module contains 32 MiB read-only data and 32 MiB read-write data, and the
+// function only touches the first item of the read-write data.
+//
+// Run with:
+//
+// `$ cargo bench -p anc-processor --bench mmap_loading`
+//
+// Note: the RSS is read from `/proc/self/statm`, so it is only available on Linux.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anc_context::program_source::ProgramSource;
+use anc_image::{
+    bytecode_writer::BytecodeWriterHelper,
+    entry::{ReadOnlyDataEntry, ReadWriteDataEntry},
+    utils::helper_build_module_binary_with_single_function_and_data,
+};
+use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+use anc_processor::{
+    file_program_source::FileProgramSource, mmap_program_source::MmapProgramSource,
+    process::process_function,
+};
+
+const ROUNDS: usize = 10;
+const THREADS: usize = 4;
+const DATA_SIZE_IN_BYTES: usize = 32 * 1024 * 1024;
+const PAGE_SIZE_IN_BYTES: usize = 4096;
+
+fn build_binary() -> Vec<u8> {
+    // fn (n:i32) -> (i32)
+    //     data_load_i32_u(0, 1)    ;; counter = counter + n
+    //     local_load_i32_u(0, 0)
+    //     add_i32
+    //     data_store_i32(0, 1)
+    //     data_load_i32_u(0, 1)
+    // end
+
+    let code0 = BytecodeWriterHelper::new()
+        .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+        .append_opcode(Opcode::add_i32)
+        .append_opcode_i16_i32(Opcode::data_store_i32, 0, 1)
+        .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+        .append_opcode(Opcode::end)
+        .to_bytes();
+
+    helper_build_module_binary_with_single_function_and_data(
+        &[OperandDataType::I32], // params
+        &[OperandDataType::I32], // results
+        &[],                     // local variables
+        code0,
+        &[ReadOnlyDataEntry::from_bytes(
+            vec![0x11u8; DATA_SIZE_IN_BYTES],
+            8,
+        )],
+        &[
+            ReadWriteDataEntry::from_i32(0),
+            ReadWriteDataEntry::from_bytes(vec![0x13u8; DATA_SIZE_IN_BYTES], 8),
+        ],
+        &[],
+    )
+}
+
+fn get_resident_memory_in_bytes() -> usize {
+    std::fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|content| content.split_whitespace().nth(1)?.parse::<usize>().ok())
+        .map_or(0, |pages| pages * PAGE_SIZE_IN_BYTES)
+}
+
+// Loads the program, creates the threads and calls the function once in each thread,
+// returns the elapsed time and the increment of RSS.
+fn measure<T: ProgramSource>(open: impl Fn() -> T) -> (Duration, usize) {
+    let arguments = [ForeignValue::U32(7)];
+    let expected = vec![ForeignValue::U32(7)];
+
+    let rss_before = get_resident_memory_in_bytes();
+    let start = Instant::now();
+
+    let program_source = open();
+    let process_context0 = program_source.create_process_context().unwrap();
+    let mut thread_contexts = (0..THREADS)
+        .map(|_| process_context0.create_thread_context())
+        .collect::<Vec<_>>();
+
+    for thread_context in &mut thread_contexts {
+        let results = process_function(thread_context, 0, 0, &arguments).unwrap();
+        assert_eq!(results, expected);
+    }
+
+    let elapsed = start.elapsed();
+    let rss_increment = get_resident_memory_in_bytes().saturating_sub(rss_before);
+
+    (elapsed, rss_increment)
+}
+
+fn measure_best<T: ProgramSource>(open: impl Fn() -> T) -> (Duration, usize) {
+    (0..ROUNDS)
+        .map(|_| measure(&open))
+        .fold((Duration::MAX, usize::MAX), |(time, rss), (t, r)| {
+            (time.min(t), rss.min(r))
+        })
+}
+
+fn main() {
+    let image_file_path = std::env::temp_dir().join(format!(
+        "anc_mmap_loading_bench_{}.ancm",
+        std::process::id()
+    ));
+    let binary = build_binary();
+    std::fs::write(&image_file_path, &binary).unwrap();
+
+    let path: &Path = &image_file_path;
+    let (read_time, read_rss) = measure_best(|| FileProgramSource::open(path).unwrap());
+    let (mmap_time, mmap_rss) = measure_best(|| MmapProgramSource::open(path).unwrap());
+
+    std::fs::remove_file(&image_file_path).unwrap();
+
+    println!(
+        "image size: {} bytes, threads: {}, best of {} rounds",
+        binary.len(),
+        THREADS,
+        ROUNDS
+    );
+    println!(
+        "read file:   startup {:?}, RSS +{} KiB",
+        read_time,
+        read_rss / 1024
+    );
+    println!(
+        "memory-map:  startup {:?}, RSS +{} KiB",
+        mmap_time,
+        mmap_rss / 1024
+    );
+    println!(
+        "speedup: {:.2}x",
+        read_time.as_secs_f64() / mmap_time.as_secs_f64()
+    );
+}
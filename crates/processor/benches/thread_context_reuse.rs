@@ -0,0 +1,110 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Compares creating a new thread context for each invocation with
+// restoring a snapshot of the initial thread context.
+//
+// Run with:
+//
+// `$ cargo bench -p anc-processor --bench thread_context_reuse`
+
+use std::time::{Duration, Instant};
+
+use anc_context::program_source::ProgramSource;
+use anc_image::{
+    bytecode_writer::BytecodeWriterHelper, entry::ReadWriteDataEntry,
+    utils::helper_build_module_binary_with_single_function_and_data,
+};
+use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+use anc_processor::{in_memory_program_source::InMemoryProgramSource, process::process_function};
+
+const ROUNDS: usize = 20;
+const INVOCATIONS: usize = 10_000;
+const DATA_SIZE_IN_BYTES: usize = 64 * 1024;
+
+fn build_binary() -> Vec<u8> {
+    // A small function which updates the read-write data:
+    //
+    // fn (n:i32) -> (i32)
+    //     data_load_i32_u(0, 0)    ;; counter = counter + n
+    //     local_load_i32_u(0, 0)
+    //     add_i32
+    //     data_store_i32(0, 0)
+    //     data_load_i32_u(0, 0)
+    // end
+
+    let code0 = BytecodeWriterHelper::new()
+        .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 0)
+        .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+        .append_opcode(Opcode::add_i32)
+        .append_opcode_i16_i32(Opcode::data_store_i32, 0, 0)
+        .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 0)
+        .append_opcode(Opcode::end)
+        .to_bytes();
+
+    helper_build_module_binary_with_single_function_and_data(
+        &[OperandDataType::I32], // params
+        &[OperandDataType::I32], // results
+        &[],                     // local variables
+        code0,
+        &[],
+        &[
+            ReadWriteDataEntry::from_i32(0),
+            ReadWriteDataEntry::from_bytes(vec![0x11u8; DATA_SIZE_IN_BYTES], 8),
+        ],
+        &[],
+    )
+}
+
+fn measure(reuse: bool) -> Duration {
+    let resource0 = InMemoryProgramSource::new(vec![build_binary()]);
+    let process_context0 = resource0.create_process_context().unwrap();
+
+    let arguments = [ForeignValue::U32(7)];
+    let expected = vec![ForeignValue::U32(7)];
+
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+
+        if reuse {
+            let mut thread_context0 = process_context0.create_thread_context();
+            let snapshot = thread_context0.take_snapshot();
+
+            for _ in 0..INVOCATIONS {
+                thread_context0.restore_snapshot(&snapshot);
+                let results = process_function(&mut thread_context0, 0, 0, &arguments).unwrap();
+                assert_eq!(results, expected);
+            }
+        } else {
+            for _ in 0..INVOCATIONS {
+                let mut thread_context0 = process_context0.create_thread_context();
+                let results = process_function(&mut thread_context0, 0, 0, &arguments).unwrap();
+                assert_eq!(results, expected);
+            }
+        }
+
+        best = best.min(start.elapsed());
+    }
+
+    best
+}
+
+fn main() {
+    let create_each_time = measure(false);
+    let restore_snapshot = measure(true);
+
+    println!(
+        "invocations: {}, data size: {} bytes, best of {} rounds",
+        INVOCATIONS, DATA_SIZE_IN_BYTES, ROUNDS
+    );
+    println!("create thread context each time: {:?}", create_each_time);
+    println!("restore snapshot:                {:?}", restore_snapshot);
+    println!(
+        "speedup: {:.2}x",
+        create_each_time.as_secs_f64() / restore_snapshot.as_secs_f64()
+    );
+}
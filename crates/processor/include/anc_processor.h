@@ -0,0 +1,155 @@
+/**
+ * Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+ *
+ * This Source Code Form is subject to the terms of
+ * the Mozilla Public License version 2.0 and additional exceptions.
+ * For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+ */
+
+#ifndef ANC_PROCESSOR_H
+#define ANC_PROCESSOR_H
+
+/* Warning, this file is autogenerated by cbindgen. Don't modify this manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define ANC_VALUE_TYPE_I32 0
+
+#define ANC_VALUE_TYPE_I64 1
+
+#define ANC_VALUE_TYPE_F32 2
+
+#define ANC_VALUE_TYPE_F64 3
+
+// Collects the module images of a process.
+typedef struct AncVmBuilder AncVmBuilder;
+
+// A process, i.e. the loaded module images and the process context.
+typedef struct AncProcess AncProcess;
+
+// A thread of a process.
+typedef struct AncThread AncThread;
+
+// The error returned by the API.
+typedef struct AncError AncError;
+
+// The payload of a tagged value.
+typedef union AncValuePayload {
+  int32_t i32;
+  int64_t i64;
+  float f32;
+  double f64;
+} AncValuePayload;
+
+// A tagged value, the `value_type` is one of the `ANC_VALUE_TYPE_*` constants.
+typedef struct AncValue {
+  uint32_t value_type;
+  union AncValuePayload payload;
+} AncValue;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns NULL if the builder can not be created.
+struct AncVmBuilder *anc_vm_builder_new(void);
+
+// Adds a module image, the bytes are copied. The first module is the main module.
+//
+// # Safety
+//
+// `builder` must be returned by `anc_vm_builder_new()`, and `data` must point
+// to `length` bytes.
+void anc_vm_builder_add_module_bytes(struct AncVmBuilder *builder,
+                                     const uint8_t *data,
+                                     size_t length);
+
+// Adds a module image file, it is read when the process is created.
+//
+// # Safety
+//
+// `builder` must be returned by `anc_vm_builder_new()`, and `path` must be
+// a NUL-terminated string, it is taken as the raw bytes of the path on Unix,
+// and as a UTF-8 string on the other platforms.
+void anc_vm_builder_add_module_file(struct AncVmBuilder *builder,
+                                    const char *path);
+
+// Frees a builder which is not passed to `anc_process_create()`.
+//
+// # Safety
+//
+// `builder` must be returned by `anc_vm_builder_new()` or be NULL.
+void anc_vm_builder_free(struct AncVmBuilder *builder);
+
+// Loads and verifies the module images and creates a process, the builder is consumed.
+//
+// Returns NULL and sets `error` (if it is not NULL) on failure.
+//
+// # Safety
+//
+// `builder` must be returned by `anc_vm_builder_new()`, and `error` must be
+// valid for writes or be NULL.
+struct AncProcess *anc_process_create(struct AncVmBuilder *builder,
+                                      struct AncError **error);
+
+// # Safety
+//
+// `process` must be returned by `anc_process_create()` or be NULL,
+// and all threads of the process must have been freed.
+void anc_process_free(struct AncProcess *process);
+
+// Creates a thread of the process, returns NULL if the thread can not be created.
+//
+// # Safety
+//
+// `process` must be returned by `anc_process_create()`, and it must outlive the thread.
+struct AncThread *anc_thread_create(struct AncProcess *process);
+
+// # Safety
+//
+// `thread` must be returned by `anc_thread_create()` or be NULL.
+void anc_thread_free(struct AncThread *thread);
+
+// Calls the specified public function, the function name should be
+// in the form of "module::name", e.g. "main::add".
+//
+// The results are written to `results` and the number of results is
+// written to `results_count`.
+//
+// Returns 0 on success, otherwise returns -1 and sets `error` (if it is not NULL).
+//
+// # Safety
+//
+// `thread` must be returned by `anc_thread_create()`, `function_name` must be
+// a NUL-terminated UTF-8 string, `arguments` must point to `arguments_count` values,
+// `results` must be valid for writing `results_capacity` values, and `results_count`
+// and `error` must be valid for writes or be NULL.
+int32_t anc_thread_call(struct AncThread *thread,
+                        const char *function_name,
+                        const struct AncValue *arguments,
+                        size_t arguments_count,
+                        struct AncValue *results,
+                        size_t results_capacity,
+                        size_t *results_count,
+                        struct AncError **error);
+
+// Returns the message of the error, the string is owned by the error.
+//
+// # Safety
+//
+// `error` must be returned by this API.
+const char *anc_error_message(const struct AncError *error);
+
+// # Safety
+//
+// `error` must be returned by this API or be NULL.
+void anc_error_free(struct AncError *error);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ANC_PROCESSOR_H */
@@ -0,0 +1,1261 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// AOT compiler
+// ------------
+//
+// Compiles the module images of a program into a native object file (ELF), which is
+// then linked with the AOT runtime (see `aot_runtime.rs`) into a native executable.
+//
+// The bytecode is translated by the native code translator (see `native_translator.rs`),
+// which is shared with the JIT compiler, so the same instructions are supported, and in
+// addition, the instructions `call`, `syscall`, `extcall` and the envcalls which only
+// take and return scalars (see `AOT_SUPPORTED_ENVCALLS`). Programs which contain other
+// instructions can not be compiled.
+//
+// The object file contains:
+//
+// - A data object for each data section of each module, i.e. `anc_data_{module index}_{section name}`,
+//   where the section name is one of `read_only`, `read_write` and `uninit`.
+// - A data object for each external function, i.e. `anc_extcall_names_{unified external function index}`,
+//   which contains the name of the external library followed by the name of the function.
+// - A local function for each function of each module, i.e. `anc_function_{module index}_{function internal index}`.
+// - The exported function `anc_aot_start`, which calls the entry function.
+//
+// The native functions
+// --------------------
+//
+// ```rust
+// extern "C" fn anc_function_M_N(
+//     args_ptr: *const u8,
+//     results_ptr: *mut u8,
+// ) -> u64 /* status */;
+//
+// extern "C" fn anc_aot_start(
+//     results_ptr: *mut u8,
+// ) -> u64 /* status */;
+// ```
+//
+// The arguments and the results are 8-byte operands. The local variables (includes the
+// arguments) are copied to the native stack when the function starts.
+//
+// The status is 0 when the function finishes, otherwise a trap has been raised (it is
+// recorded by the runtime function `anc_aot_raise_trap`), and the status is returned
+// to the caller all the way up to `anc_aot_start`. The runtime functions `anc_aot_envcall`,
+// `anc_aot_syscall` and `anc_aot_check_extcall` return the status in the same way.
+//
+// The instructions `syscall` and `extcall` are checked against the capability of the
+// program by the runtime functions `anc_aot_syscall` and `anc_aot_check_extcall`,
+// the latter is called before the external function is called.
+
+use std::fmt::Display;
+
+use anc_context::{
+    code_generator::{convert_vm_operand_data_type_to_jit_type, Generator},
+    module_linking_instance::ModuleLinkingInstance,
+    thread_context::TrapKind,
+};
+use anc_image::{
+    common_sections::{
+        function_section::FunctionSection, local_variable_section::LocalVariableSection,
+        type_section::TypeSection,
+    },
+    module_image::ModuleImage,
+};
+use anc_isa::{DataSectionType, OPERAND_SIZE_IN_BYTES};
+use cranelift_codegen::ir::{
+    types, AbiParam, Function, InstBuilder, InstructionData, MemFlags, Signature, StackSlot,
+    StackSlotData, StackSlotKind, Type, UserFuncName, Value, ValueDef,
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{DataId, FuncId, Linkage, Module, ModuleError};
+use cranelift_object::ObjectModule;
+
+use crate::{
+    aot_runtime::{
+        AOT_MAX_SYSCALL_ARGS, AOT_SUPPORTED_ENVCALLS, AOT_TRAP_CODE_INTEGER_DIVIDE_BY_ZERO,
+        AOT_TRAP_CODE_INTEGER_OVERFLOW, STATUS_TRAPPED,
+    },
+    native_translator::{CallTarget, ControlFrame, Translator, TranslatorEnvironment},
+};
+
+// The names of the data sections, the order is the same as `DataSectionType`.
+const DATA_SECTION_NAMES: [&str; 3] = ["read_only", "read_write", "uninit"];
+const DATA_ALIGN: u64 = 8;
+
+const MAIN_MODULE_INDEX: usize = 0;
+
+#[derive(Debug, PartialEq)]
+pub enum AotCompileError {
+    EntryPointNotFound(String),
+    EntryPointSignatureMismatch(String), // The signature of the entry function must be `fn () -> i32`.
+    UnsupportedFunction {
+        module_index: usize,
+        function_internal_index: usize,
+    },
+    ExternalFunctionMoreThanOneResult(String),
+    CodeGeneration(String),
+}
+
+impl Display for AotCompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AotCompileError::EntryPointNotFound(entry_point_name) => {
+                write!(f, "Entry point \"{entry_point_name}\" not found.")
+            }
+            AotCompileError::EntryPointSignatureMismatch(entry_point_name) => write!(
+                f,
+                "The signature of the entry point \"{entry_point_name}\" doesn't match."
+            ),
+            AotCompileError::UnsupportedFunction {
+                module_index,
+                function_internal_index,
+            } => write!(
+                f,
+                "The function contains unsupported instructions, module index: {}, function internal index: {}.",
+                module_index, function_internal_index
+            ),
+            AotCompileError::ExternalFunctionMoreThanOneResult(function_name) => write!(
+                f,
+                "The external function \"{function_name}\" has more than one return value."
+            ),
+            AotCompileError::CodeGeneration(message) => {
+                write!(f, "Code generation error: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AotCompileError {}
+
+impl From<ModuleError> for AotCompileError {
+    fn from(value: ModuleError) -> Self {
+        AotCompileError::CodeGeneration(value.to_string())
+    }
+}
+
+// The sections of a module which are used by the translation.
+struct ModuleSections<'a> {
+    type_section: TypeSection<'a>,
+    local_variable_section: LocalVariableSection<'a>,
+    function_section: FunctionSection<'a>,
+
+    // The data object of each data section, and the (offset, length)
+    // of each item in the data object.
+    data_objects: [Option<(DataId, Vec<(usize, usize)>)>; 3],
+}
+
+// The functions imported from the AOT runtime.
+struct RuntimeFunctions {
+    raise_trap: FuncId,
+    envcall: FuncId,
+    syscall: FuncId,
+    check_extcall: FuncId,
+}
+
+// The items shared by the translation of all functions.
+struct Program<'a> {
+    module_linking_instance: ModuleLinkingInstance<'a>,
+    modules: Vec<ModuleSections<'a>>,
+    function_ids: Vec<Vec<FuncId>>,
+    runtime_functions: RuntimeFunctions,
+
+    // The data object of the names of each unified external function, and
+    // the lengths of the library name and the function name.
+    extcall_names: Vec<(DataId, usize, usize)>,
+    signature: Signature,
+    pointer_type: Type,
+}
+
+/// Compiles the module images of a program into an object file, returns the content
+/// of the object file.
+///
+/// The `opt_platform` is the target triple, the default is "x86_64-unknown-linux-gnu".
+pub fn compile_program<'a>(
+    module_images: &'a [ModuleImage<'a>],
+    entry_point_name: &str,
+    opt_platform: Option<&str>,
+) -> Result<Vec<u8>, AotCompileError> {
+    let module_linking_instance = ModuleLinkingInstance::new(module_images);
+
+    let function_public_index = module_linking_instance
+        .entry_point_section
+        .get_function_public_index(entry_point_name)
+        .ok_or_else(|| AotCompileError::EntryPointNotFound(entry_point_name.to_owned()))?;
+
+    let (entry_module_index, entry_function_internal_index) = module_linking_instance
+        .function_index_section
+        .get_item_target_module_index_and_function_internal_index(
+            MAIN_MODULE_INDEX,
+            function_public_index,
+        );
+
+    let mut generator = Generator::<ObjectModule>::new("anc_aot_program", opt_platform);
+    let pointer_type = generator.module.isa().pointer_type();
+
+    let mut modules = vec![];
+    for (module_index, module_image) in module_images.iter().enumerate() {
+        let data_objects = define_data_objects(&mut generator, module_index, module_image)?;
+        modules.push(ModuleSections {
+            type_section: module_image.get_type_section(),
+            local_variable_section: module_image.get_local_variable_section(),
+            function_section: module_image.get_function_section(),
+            data_objects,
+        });
+    }
+
+    let mut signature = generator.module.make_signature();
+    signature.params.push(AbiParam::new(pointer_type)); // args_ptr
+    signature.params.push(AbiParam::new(pointer_type)); // results_ptr
+    signature.returns.push(AbiParam::new(types::I64)); // status
+
+    let mut function_ids = vec![];
+    for (module_index, module_sections) in modules.iter().enumerate() {
+        let mut ids = vec![];
+        for function_internal_index in 0..module_sections.function_section.items.len() {
+            let function_name =
+                format!("anc_function_{}_{}", module_index, function_internal_index);
+            ids.push(generator.module.declare_function(
+                &function_name,
+                Linkage::Local,
+                &signature,
+            )?);
+        }
+        function_ids.push(ids);
+    }
+
+    let runtime_functions = RuntimeFunctions {
+        raise_trap: declare_import_function(
+            &mut generator.module,
+            "anc_aot_raise_trap",
+            &[types::I32, types::I32, types::I32, types::I32],
+            &[],
+        )?,
+        envcall: declare_import_function(
+            &mut generator.module,
+            "anc_aot_envcall",
+            &[types::I32, types::I32, types::I32, types::I32, pointer_type],
+            &[types::I64],
+        )?,
+        syscall: declare_import_function(
+            &mut generator.module,
+            "anc_aot_syscall",
+            &[
+                types::I64,
+                types::I64,
+                types::I32,
+                types::I32,
+                types::I32,
+                pointer_type,
+                pointer_type,
+            ],
+            &[types::I64],
+        )?,
+        check_extcall: declare_import_function(
+            &mut generator.module,
+            "anc_aot_check_extcall",
+            &[
+                pointer_type,
+                types::I32,
+                types::I32,
+                types::I32,
+                types::I32,
+                types::I32,
+            ],
+            &[types::I64],
+        )?,
+    };
+
+    let extcall_names = define_extcall_names(&mut generator, &module_linking_instance)?;
+
+    let program = Program {
+        module_linking_instance,
+        modules,
+        function_ids,
+        runtime_functions,
+        extcall_names,
+        signature,
+        pointer_type,
+    };
+
+    for (module_index, module_sections) in program.modules.iter().enumerate() {
+        for function_internal_index in 0..module_sections.function_section.items.len() {
+            compile_function(
+                &mut generator,
+                &program,
+                module_index,
+                function_internal_index,
+            )?;
+        }
+    }
+
+    // The signature of the entry function must be exactly:
+    // 'fn () -> exit_code: i32'
+    let entry_module_sections = &program.modules[entry_module_index];
+    let entry_type_index = entry_module_sections.function_section.items
+        [entry_function_internal_index]
+        .type_index as usize;
+    let entry_type_item = &entry_module_sections.type_section.items[entry_type_index];
+    if entry_type_item.params_count != 0 || entry_type_item.results_count != 1 {
+        return Err(AotCompileError::EntryPointSignatureMismatch(
+            entry_point_name.to_owned(),
+        ));
+    }
+
+    compile_start_function(
+        &mut generator,
+        &program,
+        entry_module_index,
+        entry_function_internal_index,
+    )?;
+
+    generator
+        .module
+        .finish()
+        .emit()
+        .map_err(|e| AotCompileError::CodeGeneration(e.to_string()))
+}
+
+fn define_data_objects(
+    generator: &mut Generator<ObjectModule>,
+    module_index: usize,
+    module_image: &ModuleImage,
+) -> Result<[Option<(DataId, Vec<(usize, usize)>)>; 3], AotCompileError> {
+    let get_name = |section_index: usize| {
+        format!(
+            "anc_data_{}_{}",
+            module_index, DATA_SECTION_NAMES[section_index]
+        )
+    };
+
+    let mut data_objects = [None, None, None];
+
+    if let Some(section) = module_image.get_optional_read_only_data_section() {
+        let items = section
+            .items
+            .iter()
+            .map(|item| (item.data_offset as usize, item.data_length as usize))
+            .collect::<Vec<_>>();
+        let data_id = generator.define_read_only_data(
+            &get_name(0),
+            section.datas_data.to_vec(),
+            DATA_ALIGN,
+            false,
+            false,
+        )?;
+        data_objects[0] = Some((data_id, items));
+    }
+
+    if let Some(section) = module_image.get_optional_read_write_data_section() {
+        let items = section
+            .items
+            .iter()
+            .map(|item| (item.data_offset as usize, item.data_length as usize))
+            .collect::<Vec<_>>();
+        let data_id = generator.define_read_write_data(
+            &get_name(1),
+            section.datas_data.to_vec(),
+            DATA_ALIGN,
+            false,
+            false,
+        )?;
+        data_objects[1] = Some((data_id, items));
+    }
+
+    if let Some(section) = module_image.get_optional_uninit_data_section() {
+        let items = section
+            .items
+            .iter()
+            .map(|item| (item.data_offset as usize, item.data_length as usize))
+            .collect::<Vec<_>>();
+        let length = items.iter().map(|(_, data_length)| data_length).sum();
+        let data_id =
+            generator.define_uninitialized_data(&get_name(2), length, DATA_ALIGN, false, false)?;
+        data_objects[2] = Some((data_id, items));
+    }
+
+    Ok(data_objects)
+}
+
+fn define_extcall_names(
+    generator: &mut Generator<ObjectModule>,
+    module_linking_instance: &ModuleLinkingInstance,
+) -> Result<Vec<(DataId, usize, usize)>, AotCompileError> {
+    let count = module_linking_instance
+        .unified_external_function_section
+        .items
+        .len();
+
+    let mut extcall_names = vec![];
+    for unified_external_function_index in 0..count {
+        let (function_name, unified_external_library_index, _) = module_linking_instance
+            .unified_external_function_section
+            .get_item_name_and_external_library_index_and_type_index(
+                unified_external_function_index,
+            );
+
+        let (library_name, _, _) = module_linking_instance
+            .unified_external_library_section
+            .get_item_name_and_external_library_dependent_type_and_value(
+                unified_external_library_index,
+            );
+
+        let data_id = generator.define_read_only_data(
+            &format!("anc_extcall_names_{}", unified_external_function_index),
+            [library_name.as_bytes(), function_name.as_bytes()].concat(),
+            1,
+            false,
+            false,
+        )?;
+        extcall_names.push((data_id, library_name.len(), function_name.len()));
+    }
+
+    Ok(extcall_names)
+}
+
+fn declare_import_function(
+    module: &mut ObjectModule,
+    name: &str,
+    params: &[Type],
+    results: &[Type],
+) -> Result<FuncId, ModuleError> {
+    let mut signature = module.make_signature();
+    for param in params {
+        signature.params.push(AbiParam::new(*param));
+    }
+    for result in results {
+        signature.returns.push(AbiParam::new(*result));
+    }
+    module.declare_function(name, Linkage::Import, &signature)
+}
+
+fn compile_function(
+    generator: &mut Generator<ObjectModule>,
+    program: &Program,
+    module_index: usize,
+    function_internal_index: usize,
+) -> Result<(), AotCompileError> {
+    let unsupported_function_error = || AotCompileError::UnsupportedFunction {
+        module_index,
+        function_internal_index,
+    };
+
+    let module_sections = &program.modules[module_index];
+    let function_item = &module_sections.function_section.items[function_internal_index];
+    let type_item = module_sections
+        .type_section
+        .items
+        .get(function_item.type_index as usize)
+        .ok_or_else(unsupported_function_error)?;
+    let params_count = type_item.params_count as usize;
+    let results_count = type_item.results_count as usize;
+
+    let local_variable_list_index = function_item.local_variable_list_index as usize;
+    let local_variables_with_arguments_allocated_bytes = module_sections
+        .local_variable_section
+        .lists
+        .get(local_variable_list_index)
+        .ok_or_else(unsupported_function_error)?
+        .allocated_bytes as usize;
+
+    let code_offset = function_item.code_offset as usize;
+    let code_length = function_item.code_length as usize;
+    let codes =
+        &module_sections.function_section.codes_data[code_offset..(code_offset + code_length)];
+
+    let function_id = program.function_ids[module_index][function_internal_index];
+    let mut function = Function::with_name_signature(
+        UserFuncName::user(0, function_id.as_u32()),
+        program.signature.clone(),
+    );
+
+    // A dedicated builder context is used instead of the one in the generator, because
+    // the translation is abandoned halfway when an unsupported instruction is encountered,
+    // which leaves the builder context dirty.
+    let mut function_builder_context = FunctionBuilderContext::new();
+    let mut opt_error = None;
+
+    let opt_translated = {
+        let mut function_builder =
+            FunctionBuilder::new(&mut function, &mut function_builder_context);
+        let entry_block = function_builder.create_block();
+        function_builder.append_block_params_for_function_params(entry_block);
+        function_builder.switch_to_block(entry_block);
+
+        let params = function_builder.block_params(entry_block).to_vec();
+        let local_variables_ptr = build_local_variables(
+            &mut function_builder,
+            program.pointer_type,
+            params[0],
+            params_count,
+            local_variables_with_arguments_allocated_bytes,
+        );
+
+        let environment = AotEnvironment {
+            program,
+            module: &mut generator.module,
+            module_index,
+            function_internal_index,
+            opt_error: &mut opt_error,
+        };
+
+        let mut translator = Translator::new(
+            function_builder,
+            environment,
+            program.pointer_type,
+            local_variables_ptr,
+            params[1],
+        );
+
+        let opt_translated = translator.translate(
+            codes,
+            code_offset,
+            params_count,
+            results_count,
+            local_variable_list_index,
+            local_variables_with_arguments_allocated_bytes,
+        );
+
+        if opt_translated.is_some() {
+            translator.finish();
+        }
+
+        opt_translated
+    };
+
+    if let Some(error) = opt_error {
+        return Err(error);
+    }
+    opt_translated.ok_or_else(unsupported_function_error)?;
+
+    generator.context.func = function;
+    let define_result = generator
+        .module
+        .define_function(function_id, &mut generator.context);
+    generator.module.clear_context(&mut generator.context);
+    define_result?;
+
+    Ok(())
+}
+
+// Copies the arguments to the local variables on the native stack, and resets
+// the other local variables to 0, returns the address of the local variables.
+fn build_local_variables(
+    builder: &mut FunctionBuilder,
+    pointer_type: Type,
+    args_ptr: Value,
+    params_count: usize,
+    local_variables_with_arguments_allocated_bytes: usize,
+) -> Value {
+    if local_variables_with_arguments_allocated_bytes == 0 {
+        return args_ptr;
+    }
+
+    let stack_slot = create_operands_stack_slot(
+        builder,
+        local_variables_with_arguments_allocated_bytes / OPERAND_SIZE_IN_BYTES,
+    );
+
+    let zero = builder.ins().iconst(types::I64, 0);
+    let arguments_length = params_count * OPERAND_SIZE_IN_BYTES;
+
+    for offset in (0..local_variables_with_arguments_allocated_bytes).step_by(OPERAND_SIZE_IN_BYTES)
+    {
+        let value = if offset < arguments_length {
+            builder
+                .ins()
+                .load(types::I64, MemFlags::new(), args_ptr, offset as i32)
+        } else {
+            zero
+        };
+        builder.ins().stack_store(value, stack_slot, offset as i32);
+    }
+
+    builder.ins().stack_addr(pointer_type, stack_slot, 0)
+}
+
+// Generates the exported function `anc_aot_start`.
+fn compile_start_function(
+    generator: &mut Generator<ObjectModule>,
+    program: &Program,
+    entry_module_index: usize,
+    entry_function_internal_index: usize,
+) -> Result<(), AotCompileError> {
+    let mut signature = generator.module.make_signature();
+    signature.params.push(AbiParam::new(program.pointer_type)); // results_ptr
+    signature.returns.push(AbiParam::new(types::I64)); // status
+
+    let function_id =
+        generator
+            .module
+            .declare_function("anc_aot_start", Linkage::Export, &signature)?;
+
+    let mut function =
+        Function::with_name_signature(UserFuncName::user(0, function_id.as_u32()), signature);
+
+    {
+        let mut builder =
+            FunctionBuilder::new(&mut function, &mut generator.function_builder_context);
+        let entry_block = builder.create_block();
+        builder.append_block_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+
+        let results_ptr = builder.block_params(entry_block)[0];
+
+        // The entry function has no arguments.
+        let args_ptr = builder.ins().iconst(program.pointer_type, 0);
+
+        let entry_function_id =
+            program.function_ids[entry_module_index][entry_function_internal_index];
+        let func_ref = generator
+            .module
+            .declare_func_in_func(entry_function_id, builder.func);
+        let call = builder.ins().call(func_ref, &[args_ptr, results_ptr]);
+        let status = builder.inst_results(call)[0];
+        builder.ins().return_(&[status]);
+
+        builder.seal_all_blocks();
+        builder.finalize();
+    }
+
+    generator.context.func = function;
+    let define_result = generator
+        .module
+        .define_function(function_id, &mut generator.context);
+    generator.module.clear_context(&mut generator.context);
+    define_result?;
+
+    Ok(())
+}
+
+// Translates the instructions that depend on the environment into the references
+// of the symbols in the object file and the calls of the runtime functions.
+struct AotEnvironment<'a, 'b> {
+    program: &'b Program<'a>,
+    module: &'b mut ObjectModule,
+    module_index: usize,
+    function_internal_index: usize,
+
+    // The errors other than the unsupported instructions.
+    opt_error: &'b mut Option<AotCompileError>,
+}
+
+impl TranslatorEnvironment for AotEnvironment<'_, '_> {
+    fn get_sections(&self) -> (&TypeSection, &LocalVariableSection) {
+        let module_sections = &self.program.modules[self.module_index];
+        (
+            &module_sections.type_section,
+            &module_sections.local_variable_section,
+        )
+    }
+
+    fn translate_data_address(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        data_public_index: usize,
+        offset_bytes: usize,
+        data_length: usize,
+        for_writing: bool,
+    ) -> Option<Value> {
+        let data_index_section = &self.program.module_linking_instance.data_index_section;
+        if data_public_index >= data_index_section.get_items_count(self.module_index) {
+            return None;
+        }
+
+        let (target_module_index, target_data_section_type, data_internal_index_in_section) =
+            data_index_section
+                .get_item_target_module_index_and_data_section_type_and_data_internal_index_in_section(
+                    self.module_index,
+                    data_public_index,
+                );
+
+        if for_writing && matches!(target_data_section_type, DataSectionType::ReadOnly) {
+            return None;
+        }
+
+        let (data_id, items) = self.program.modules[target_module_index].data_objects
+            [target_data_section_type as usize]
+            .as_ref()?;
+        let (item_offset, item_length) = *items.get(data_internal_index_in_section)?;
+
+        // The bounds are checked statically, because the native code
+        // accesses the memory directly.
+        if offset_bytes + data_length > item_length {
+            return None;
+        }
+
+        let global_value = self.module.declare_data_in_func(*data_id, builder.func);
+        let start_address = builder
+            .ins()
+            .symbol_value(self.program.pointer_type, global_value);
+        Some(
+            builder
+                .ins()
+                .iadd_imm(start_address, (item_offset + offset_bytes) as i64),
+        )
+    }
+
+    fn translate_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        call_target: CallTarget,
+        instruction_address: usize,
+        operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        match call_target {
+            CallTarget::Function(function_public_index) => {
+                self.translate_function_call(builder, function_public_index, operands)
+            }
+            CallTarget::EnvCall(envcall_num) => {
+                self.translate_envcall(builder, envcall_num, instruction_address, operands)
+            }
+            CallTarget::SysCall => self.translate_syscall(builder, instruction_address, operands),
+            CallTarget::ExtCall(external_function_index) => self.translate_extcall(
+                builder,
+                external_function_index,
+                instruction_address,
+                operands,
+            ),
+        }
+    }
+
+    fn translate_trap(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        trap_kind: TrapKind,
+        instruction_address: usize,
+    ) {
+        let trap_code = match trap_kind {
+            TrapKind::IntegerDivideByZero => AOT_TRAP_CODE_INTEGER_DIVIDE_BY_ZERO,
+            TrapKind::IntegerOverflow => AOT_TRAP_CODE_INTEGER_OVERFLOW,
+            _ => unreachable!("the trap is not raised by the native code"),
+        };
+
+        let args = [
+            trap_code as i64,
+            self.module_index as i64,
+            self.function_internal_index as i64,
+            instruction_address as i64,
+        ]
+        .map(|value| builder.ins().iconst(types::I32, value));
+
+        let func_ref = self
+            .module
+            .declare_func_in_func(self.program.runtime_functions.raise_trap, builder.func);
+        builder.ins().call(func_ref, &args);
+
+        let status = builder.ins().iconst(types::I64, STATUS_TRAPPED as i64);
+        builder.ins().return_(&[status]);
+    }
+
+    fn translate_back_edge(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        frames: &[ControlFrame],
+        target_frame_index: usize,
+    ) {
+        builder
+            .ins()
+            .jump(frames[target_frame_index].body_block, &[]);
+    }
+}
+
+impl AotEnvironment<'_, '_> {
+    fn translate_function_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        function_public_index: usize,
+        operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        let function_index_section = &self.program.module_linking_instance.function_index_section;
+        if function_public_index >= function_index_section.get_items_count(self.module_index) {
+            return None;
+        }
+
+        let (target_module_index, target_function_internal_index) = function_index_section
+            .get_item_target_module_index_and_function_internal_index(
+                self.module_index,
+                function_public_index,
+            );
+
+        let target_module_sections = &self.program.modules[target_module_index];
+        let type_index = target_module_sections.function_section.items
+            [target_function_internal_index]
+            .type_index as usize;
+        let type_item = target_module_sections.type_section.items.get(type_index)?;
+        let params_count = type_item.params_count as usize;
+        let results_count = type_item.results_count as usize;
+
+        let arguments = pop_operands(operands, params_count)?;
+        let args_ptr = store_operands_to_stack(builder, self.program.pointer_type, &arguments);
+        let results_stack_slot = create_operands_stack_slot(builder, results_count);
+        let results_ptr =
+            builder
+                .ins()
+                .stack_addr(self.program.pointer_type, results_stack_slot, 0);
+
+        let function_id =
+            self.program.function_ids[target_module_index][target_function_internal_index];
+        let func_ref = self.module.declare_func_in_func(function_id, builder.func);
+        let call = builder.ins().call(func_ref, &[args_ptr, results_ptr]);
+        let status = builder.inst_results(call)[0];
+        return_if_trapped(builder, status);
+
+        load_operands_from_stack(builder, results_stack_slot, results_count, operands);
+
+        Some(())
+    }
+
+    // The location of the instruction, i.e. (module index, function internal index,
+    // instruction address), which is passed to the runtime functions for reporting traps.
+    fn build_location_arguments(
+        &self,
+        builder: &mut FunctionBuilder,
+        instruction_address: usize,
+    ) -> [Value; 3] {
+        [
+            self.module_index as i64,
+            self.function_internal_index as i64,
+            instruction_address as i64,
+        ]
+        .map(|value| builder.ins().iconst(types::I32, value))
+    }
+
+    fn translate_envcall(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        envcall_num: u32,
+        instruction_address: usize,
+        operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        let (_, results_count) = *AOT_SUPPORTED_ENVCALLS
+            .iter()
+            .find(|(num, _)| *num as u32 == envcall_num)?;
+
+        let results_stack_slot = create_operands_stack_slot(builder, results_count);
+        let results_ptr =
+            builder
+                .ins()
+                .stack_addr(self.program.pointer_type, results_stack_slot, 0);
+        let envcall_num_value = builder.ins().iconst(types::I32, envcall_num as i64);
+        let [module_index, function_internal_index, instruction_address] =
+            self.build_location_arguments(builder, instruction_address);
+
+        let func_ref = self
+            .module
+            .declare_func_in_func(self.program.runtime_functions.envcall, builder.func);
+        let call = builder.ins().call(
+            func_ref,
+            &[
+                envcall_num_value,
+                module_index,
+                function_internal_index,
+                instruction_address,
+                results_ptr,
+            ],
+        );
+        let status = builder.inst_results(call)[0];
+        return_if_trapped(builder, status);
+
+        load_operands_from_stack(builder, results_stack_slot, results_count, operands);
+        Some(())
+    }
+
+    fn translate_syscall(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        instruction_address: usize,
+        operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        // (operand args... params_count:i32 syscall_num:i32) -> (return_value:i64 error_number:i32)
+        const RESULTS_COUNT: usize = 2;
+
+        let syscall_num = operands.pop()?;
+        let params_count_value = operands.pop()?;
+
+        // The number of arguments must be known at compile time.
+        let params_count = get_constant_value(builder, params_count_value)? as usize;
+        if params_count > AOT_MAX_SYSCALL_ARGS {
+            return None;
+        }
+
+        let arguments = pop_operands(operands, params_count)?;
+        let args_ptr = store_operands_to_stack(builder, self.program.pointer_type, &arguments);
+        let results_stack_slot = create_operands_stack_slot(builder, RESULTS_COUNT);
+        let results_ptr =
+            builder
+                .ins()
+                .stack_addr(self.program.pointer_type, results_stack_slot, 0);
+
+        let [module_index, function_internal_index, instruction_address] =
+            self.build_location_arguments(builder, instruction_address);
+
+        let func_ref = self
+            .module
+            .declare_func_in_func(self.program.runtime_functions.syscall, builder.func);
+        let call = builder.ins().call(
+            func_ref,
+            &[
+                syscall_num,
+                params_count_value,
+                module_index,
+                function_internal_index,
+                instruction_address,
+                args_ptr,
+                results_ptr,
+            ],
+        );
+        let status = builder.inst_results(call)[0];
+        return_if_trapped(builder, status);
+
+        load_operands_from_stack(builder, results_stack_slot, RESULTS_COUNT, operands);
+        Some(())
+    }
+
+    // The external function is imported as a symbol, which is
+    // resolved by the linker, and is called directly after
+    // the runtime function `anc_aot_check_extcall` permits it.
+    fn translate_extcall(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        external_function_index: usize,
+        instruction_address: usize,
+        operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        let module_linking_instance = &self.program.module_linking_instance;
+        if external_function_index
+            >= module_linking_instance
+                .external_function_index_section
+                .get_items_count(self.module_index)
+        {
+            return None;
+        }
+
+        let unified_external_function_index = module_linking_instance
+            .external_function_index_section
+            .get_item_unified_external_function_index(self.module_index, external_function_index);
+
+        let (external_function_name, _, type_index) = module_linking_instance
+            .unified_external_function_section
+            .get_item_name_and_external_library_index_and_type_index(
+                unified_external_function_index,
+            );
+
+        let (param_datatypes, result_datatypes) = module_linking_instance
+            .unified_external_type_section
+            .get_item_params_and_results(type_index);
+
+        // For C ABI compatibility, only zero or one result is allowed.
+        if result_datatypes.len() > 1 {
+            *self.opt_error = Some(AotCompileError::ExternalFunctionMoreThanOneResult(
+                external_function_name.to_owned(),
+            ));
+            return None;
+        }
+
+        let mut signature = self.module.make_signature();
+        for dt in param_datatypes {
+            signature
+                .params
+                .push(AbiParam::new(convert_vm_operand_data_type_to_jit_type(*dt)));
+        }
+        for dt in result_datatypes {
+            signature
+                .returns
+                .push(AbiParam::new(convert_vm_operand_data_type_to_jit_type(*dt)));
+        }
+
+        let (names_data_id, library_name_length, function_name_length) =
+            self.program.extcall_names[unified_external_function_index];
+        let names_global_value = self
+            .module
+            .declare_data_in_func(names_data_id, builder.func);
+        let names_ptr = builder
+            .ins()
+            .symbol_value(self.program.pointer_type, names_global_value);
+        let [library_name_length, function_name_length] =
+            [library_name_length, function_name_length]
+                .map(|length| builder.ins().iconst(types::I32, length as i64));
+        let [module_index, function_internal_index, instruction_address] =
+            self.build_location_arguments(builder, instruction_address);
+
+        let check_func_ref = self
+            .module
+            .declare_func_in_func(self.program.runtime_functions.check_extcall, builder.func);
+        let check_call = builder.ins().call(
+            check_func_ref,
+            &[
+                names_ptr,
+                library_name_length,
+                function_name_length,
+                module_index,
+                function_internal_index,
+                instruction_address,
+            ],
+        );
+        let status = builder.inst_results(check_call)[0];
+        return_if_trapped(builder, status);
+
+        let function_id = self
+            .module
+            .declare_function(external_function_name, Linkage::Import, &signature)
+            .ok()?;
+        let func_ref = self.module.declare_func_in_func(function_id, builder.func);
+
+        let arguments = pop_operands(operands, param_datatypes.len())?
+            .iter()
+            .zip(param_datatypes)
+            .map(|(value, dt)| {
+                convert_operand_to_native_value(
+                    builder,
+                    *value,
+                    convert_vm_operand_data_type_to_jit_type(*dt),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let call = builder.ins().call(func_ref, &arguments);
+        if let Some(result) = builder.inst_results(call).first().copied() {
+            operands.push(convert_native_value_to_operand(builder, result));
+        }
+
+        Some(())
+    }
+}
+
+// Returns the status to the caller if a trap is raised by the callee,
+// and continues in a new block otherwise.
+fn return_if_trapped(builder: &mut FunctionBuilder, status: Value) {
+    let return_block = builder.create_block();
+    let continue_block = builder.create_block();
+    builder.set_cold_block(return_block);
+    builder
+        .ins()
+        .brif(status, return_block, &[], continue_block, &[]);
+
+    builder.switch_to_block(return_block);
+    builder.ins().return_(&[status]);
+
+    builder.switch_to_block(continue_block);
+}
+
+fn pop_operands(operands: &mut Vec<Value>, count: usize) -> Option<Vec<Value>> {
+    let start = operands.len().checked_sub(count)?;
+    Some(operands.split_off(start))
+}
+
+fn create_operands_stack_slot(builder: &mut FunctionBuilder, operands_count: usize) -> StackSlot {
+    // Zero-sized stack slots are avoided.
+    builder.create_sized_stack_slot(StackSlotData::new(
+        StackSlotKind::ExplicitSlot,
+        (operands_count.max(1) * OPERAND_SIZE_IN_BYTES) as u32,
+        3,
+    ))
+}
+
+// Stores the operands into a new stack slot, returns the address of the stack slot.
+fn store_operands_to_stack(
+    builder: &mut FunctionBuilder,
+    pointer_type: Type,
+    values: &[Value],
+) -> Value {
+    let stack_slot = create_operands_stack_slot(builder, values.len());
+    for (index, value) in values.iter().enumerate() {
+        builder
+            .ins()
+            .stack_store(*value, stack_slot, (index * OPERAND_SIZE_IN_BYTES) as i32);
+    }
+    builder.ins().stack_addr(pointer_type, stack_slot, 0)
+}
+
+fn load_operands_from_stack(
+    builder: &mut FunctionBuilder,
+    stack_slot: StackSlot,
+    count: usize,
+    operands: &mut Vec<Value>,
+) {
+    for index in 0..count {
+        let value = builder.ins().stack_load(
+            types::I64,
+            stack_slot,
+            (index * OPERAND_SIZE_IN_BYTES) as i32,
+        );
+        operands.push(value);
+    }
+}
+
+// Returns the value of the operand if it is a constant, e.g. it is pushed by `imm_i32`.
+fn get_constant_value(builder: &FunctionBuilder, value: Value) -> Option<i64> {
+    match builder.func.dfg.value_def(value) {
+        ValueDef::Result(inst, _) => match builder.func.dfg.insts[inst] {
+            InstructionData::UnaryImm { imm, .. } => Some(imm.bits()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn convert_operand_to_native_value(
+    builder: &mut FunctionBuilder,
+    value: Value,
+    native_type: Type,
+) -> Value {
+    match native_type {
+        types::I32 => builder.ins().ireduce(types::I32, value),
+        types::F32 => {
+            let value = builder.ins().ireduce(types::I32, value);
+            builder.ins().bitcast(types::F32, MemFlags::new(), value)
+        }
+        types::F64 => builder.ins().bitcast(types::F64, MemFlags::new(), value),
+        _ => value,
+    }
+}
+
+fn convert_native_value_to_operand(builder: &mut FunctionBuilder, value: Value) -> Value {
+    match builder.func.dfg.value_type(value) {
+        types::I32 => builder.ins().uextend(types::I64, value),
+        types::F32 => {
+            let value = builder.ins().bitcast(types::I32, MemFlags::new(), value);
+            builder.ins().uextend(types::I64, value)
+        }
+        types::F64 => builder.ins().bitcast(types::I64, MemFlags::new(), value),
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_context::program_source::ProgramSource;
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ExternalLibraryEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_data_and_external_functions,
+            helper_build_module_binary_with_single_function, HelperExternalFunctionEntry,
+            HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ExternalLibraryDependency, OperandDataType};
+
+    use crate::{envcall_num::EnvCallNum, in_memory_program_source::InMemoryProgramSource};
+
+    use super::{compile_program, AotCompileError};
+
+    fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_aot_compile_program() {
+        // `fn _start () -> i32 { host_memory_width() + 0x11 }`
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::envcall, EnvCallNum::host_memory_width as u32)
+            .append_opcode_i32(Opcode::imm_i32, 0x11)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+
+        let object_binary =
+            compile_program(&process_context0.module_images, "_start", None).unwrap();
+
+        assert_eq!(&object_binary[..4], b"\x7fELF");
+        assert!(contains_bytes(&object_binary, b"anc_aot_start"));
+        assert!(contains_bytes(&object_binary, b"anc_aot_envcall"));
+
+        assert_eq!(
+            compile_program(&process_context0.module_images, "_main", None),
+            Err(AotCompileError::EntryPointNotFound("_main".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_aot_compile_extcall() {
+        // `fn _start () -> i32 { libc::getuid() }`
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::extcall, 0) // 0 is the external function index
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[HelperFunctionEntry {
+                params: vec![],
+                results: vec![OperandDataType::I32],
+                local_variable_item_entries_without_args: vec![],
+                code: code0,
+            }],
+            &[],
+            &[],
+            &[],
+            &[ExternalLibraryEntry::new(
+                "libc".to_owned(),
+                Box::new(ExternalLibraryDependency::File(
+                    "system:libc.so.6".to_owned(),
+                )),
+            )],
+            &[HelperExternalFunctionEntry {
+                name: "getuid".to_string(),
+                params: vec![],
+                result: Some(OperandDataType::I32),
+                external_library_index: 0,
+            }],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+
+        let object_binary =
+            compile_program(&process_context0.module_images, "_start", None).unwrap();
+
+        // the external function is checked by the runtime before it is called
+        assert!(contains_bytes(&object_binary, b"anc_aot_check_extcall"));
+        assert!(contains_bytes(&object_binary, b"libcgetuid"));
+        assert!(contains_bytes(&object_binary, b"getuid"));
+    }
+
+    #[test]
+    fn test_aot_compile_unsupported_function() {
+        // the floating-point instructions are not supported.
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0x11)
+            .append_opcode_i32(Opcode::imm_i32, 0x13)
+            .append_opcode(Opcode::add_f32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+
+        assert_eq!(
+            compile_program(&process_context0.module_images, "_start", None),
+            Err(AotCompileError::UnsupportedFunction {
+                module_index: 0,
+                function_internal_index: 0
+            })
+        );
+    }
+}
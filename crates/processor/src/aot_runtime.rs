@@ -0,0 +1,708 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// AOT runtime
+// -----------
+//
+// The object file generated by the AOT compiler (see `aot_compiler.rs`) imports
+// the following functions, which are exported by this module:
+//
+// - `anc_aot_raise_trap`: records the trap raised by the native code.
+// - `anc_aot_envcall`: executes the envcalls which are supported by the AOT compiler.
+// - `anc_aot_syscall`: executes the instruction `syscall`.
+// - `anc_aot_check_extcall`: checks the instruction `extcall` before the external function is called.
+//
+// The object file is not trusted by the runtime, the unknown trap codes, envcall numbers
+// and the invalid number of syscall arguments are raised as traps instead of panicking,
+// since a panic can not unwind out of the `extern "C"` functions.
+//
+// The external functions (i.e., the instruction `extcall`) are called directly by the
+// native code, so the external libraries should be linked into the executable as well.
+//
+// As the interpreter does, the instructions `syscall` and `extcall` are checked against
+// the capability which is passed to `run_aot_program()`, and are recorded to the audit log.
+// The program is terminated with the code `TERMINATE_CODE_CAPABILITY_DENIED` if a call
+// is not permitted.
+//
+// To build a native executable, link the object file with a launcher which depends
+// on this crate, e.g.
+//
+// ```rust
+// use anc_context::capability::{CallFilter, Capability};
+// use anc_processor::aot_runtime::run_aot_program;
+//
+// extern "C" {
+//     fn anc_aot_start(results_ptr: *mut u8) -> u64;
+// }
+//
+// fn main() {
+//     // The program can only invoke the system call `getpid`.
+//     let capability = Capability {
+//         syscall: true,
+//         syscall_filter: CallFilter::Allow(vec![39]),
+//         ..Capability::default()
+//     };
+//
+//     let exit_code = run_aot_program(anc_aot_start, capability, None).unwrap();
+//     std::process::exit(exit_code as i32);
+// }
+// ```
+//
+// and pass the object file to the linker in the `build.rs` of the launcher:
+//
+// `println!("cargo:rustc-link-arg=/path/to/program.o");`
+
+use std::cell::{Cell, RefCell};
+
+use anc_context::{
+    call_audit_log::{AuditedCall, CallAuditLog},
+    capability::Capability,
+    thread_context::TrapKind,
+};
+use syscall_util::call::{
+    syscall_with_1_arg, syscall_with_2_args, syscall_with_3_args, syscall_with_4_args,
+    syscall_with_5_args, syscall_with_6_args, syscall_without_args,
+};
+
+use crate::{
+    envcall_handler::{runtime::get_runtime_version_number, time::get_time_now},
+    envcall_num::EnvCallNum,
+    native_translator::STATUS_FINISHED,
+    ProcessorError, ProcessorErrorType, TERMINATE_CODE_CAPABILITY_DENIED,
+};
+
+/// The status returned by the native functions and the runtime functions
+/// when a trap has been raised or the program has been terminated.
+pub(crate) const STATUS_TRAPPED: u64 = 1;
+
+/// The function `anc_aot_start` of the object file, it calls the entry function
+/// of the program and writes the exit code to the memory pointed by `results_ptr`.
+pub type AotStartFunction = unsafe extern "C" fn(results_ptr: *mut u8) -> u64;
+
+/// The envcalls which only take and return scalars, they are implemented
+/// by the AOT runtime natively. The second element is the number of results.
+pub(crate) const AOT_SUPPORTED_ENVCALLS: [(EnvCallNum, usize); 4] = [
+    (EnvCallNum::runtime_version, 1),
+    (EnvCallNum::host_endian, 1),
+    (EnvCallNum::host_memory_width, 1),
+    (EnvCallNum::time_now, 2),
+];
+
+/// The maximum number of arguments of the instruction `syscall`.
+pub(crate) const AOT_MAX_SYSCALL_ARGS: usize = 6;
+
+pub(crate) const AOT_TRAP_CODE_INTEGER_DIVIDE_BY_ZERO: u32 = 1;
+pub(crate) const AOT_TRAP_CODE_INTEGER_OVERFLOW: u32 = 2;
+
+thread_local! {
+    // The trap raised by the native code, or the termination of the program.
+    static LAST_ERROR: Cell<Option<ProcessorErrorType>> = const { Cell::new(None) };
+
+    // The capability and the audit log of the program which is running
+    // on this thread, see `run_aot_program()`.
+    static CALL_GATE: RefCell<Option<(Capability, Option<CallAuditLog>)>> = const { RefCell::new(None) };
+}
+
+/// Runs the program compiled by the AOT compiler, returns the exit code.
+///
+/// The instructions `syscall` and `extcall` are checked against the `capability`
+/// and recorded to the `call_audit_log`, see `Capability::permits_syscall` and
+/// `Capability::permits_extcall`.
+pub fn run_aot_program(
+    start_function: AotStartFunction,
+    capability: Capability,
+    call_audit_log: Option<CallAuditLog>,
+) -> Result<u32, ProcessorError> {
+    LAST_ERROR.with(|last_error| last_error.set(None));
+    CALL_GATE.with_borrow_mut(|call_gate| *call_gate = Some((capability, call_audit_log)));
+
+    let mut results = [0u64; 1];
+    let status = unsafe { start_function(results.as_mut_ptr() as *mut u8) };
+
+    CALL_GATE.with_borrow_mut(|call_gate| *call_gate = None);
+
+    if status == STATUS_FINISHED {
+        return Ok(results[0] as u32);
+    }
+
+    match LAST_ERROR.with(|last_error| last_error.take()) {
+        Some(error_type) => Err(ProcessorError::new(error_type)),
+        None => Err(ProcessorError::new(
+            ProcessorErrorType::UnexpectedNativeStatus(status),
+        )),
+    }
+}
+
+fn record_trap(
+    kind: TrapKind,
+    module_index: u32,
+    function_internal_index: u32,
+    instruction_address: u32,
+) {
+    LAST_ERROR.with(|last_error| {
+        last_error.set(Some(ProcessorErrorType::Trap {
+            kind,
+            module_index: module_index as usize,
+            function_internal_index: function_internal_index as usize,
+            instruction_address: instruction_address as usize,
+        }))
+    });
+}
+
+fn record_terminate(terminate_code: i32) {
+    LAST_ERROR
+        .with(|last_error| last_error.set(Some(ProcessorErrorType::Terminate(terminate_code))));
+}
+
+// Checks the call against the capability of the running program and records it
+// to the audit log, the call is denied if no program is running on this thread.
+fn check_call(call: AuditedCall, permits: impl FnOnce(&Capability) -> bool) -> bool {
+    CALL_GATE.with_borrow(|call_gate| {
+        let Some((capability, call_audit_log)) = call_gate else {
+            return false;
+        };
+
+        let permitted = permits(capability);
+
+        if let Some(call_audit_log) = call_audit_log {
+            call_audit_log.record(call, permitted);
+        }
+
+        permitted
+    })
+}
+
+/// Records the trap raised by the native code, the native code then
+/// returns to the caller until `run_aot_program()`.
+#[no_mangle]
+pub extern "C" fn anc_aot_raise_trap(
+    trap_code: u32,
+    module_index: u32,
+    function_internal_index: u32,
+    instruction_address: u32,
+) {
+    let kind = match trap_code {
+        AOT_TRAP_CODE_INTEGER_DIVIDE_BY_ZERO => TrapKind::IntegerDivideByZero,
+        AOT_TRAP_CODE_INTEGER_OVERFLOW => TrapKind::IntegerOverflow,
+        // The trap code is not generated by the AOT compiler,
+        // i.e., the object file is corrupted.
+        _ => TrapKind::InvalidOpcode,
+    };
+
+    record_trap(
+        kind,
+        module_index,
+        function_internal_index,
+        instruction_address,
+    );
+}
+
+/// Executes the envcall, the results (each of them occupies 8 bytes)
+/// are written to the memory pointed by `results_ptr`.
+///
+/// Returns `STATUS_TRAPPED` if the envcall is not supported, the location
+/// of the instruction is used for reporting the trap.
+///
+/// # Safety
+///
+/// The `results_ptr` must be valid for writing the results of the envcall,
+/// see `AOT_SUPPORTED_ENVCALLS`.
+#[no_mangle]
+pub unsafe extern "C" fn anc_aot_envcall(
+    envcall_num: u32,
+    module_index: u32,
+    function_internal_index: u32,
+    instruction_address: u32,
+    results_ptr: *mut u64,
+) -> u64 {
+    let Some((envcall_num, results_count)) = AOT_SUPPORTED_ENVCALLS
+        .iter()
+        .find(|(num, _)| *num as u32 == envcall_num)
+        .copied()
+    else {
+        record_trap(
+            TrapKind::InvalidEnvCallNumber,
+            module_index,
+            function_internal_index,
+            instruction_address,
+        );
+        return STATUS_TRAPPED;
+    };
+
+    let results = std::slice::from_raw_parts_mut(results_ptr, results_count);
+
+    match envcall_num {
+        EnvCallNum::runtime_version => {
+            results[0] = get_runtime_version_number();
+        }
+        EnvCallNum::host_endian => {
+            results[0] = if cfg!(target_endian = "little") { 0 } else { 1 };
+        }
+        EnvCallNum::host_memory_width => {
+            results[0] = size_of::<usize>() as u64;
+        }
+        EnvCallNum::time_now => {
+            let (secs, nanos) = get_time_now();
+            results[0] = secs;
+            results[1] = nanos;
+        }
+        // All items of `AOT_SUPPORTED_ENVCALLS` are handled above.
+        _ => {}
+    }
+
+    STATUS_FINISHED
+}
+
+/// Executes the system call, the return value and the error number
+/// are written to the memory pointed by `results_ptr`.
+///
+/// Returns `STATUS_TRAPPED` if the number of arguments exceeds `AOT_MAX_SYSCALL_ARGS`,
+/// the location of the instruction is used for reporting the trap, or if the system call
+/// is not permitted by the capability, the program is then terminated.
+///
+/// # Safety
+///
+/// The `args_ptr` must be valid for reading `params_count` arguments,
+/// and the `results_ptr` must be valid for writing 2 results.
+#[no_mangle]
+pub unsafe extern "C" fn anc_aot_syscall(
+    syscall_num: u64,
+    params_count: u64,
+    module_index: u32,
+    function_internal_index: u32,
+    instruction_address: u32,
+    args_ptr: *const u64,
+    results_ptr: *mut u64,
+) -> u64 {
+    if params_count as usize > AOT_MAX_SYSCALL_ARGS {
+        // The AOT compiler does not generate such syscall,
+        // i.e., the object file is corrupted.
+        record_trap(
+            TrapKind::InvalidOpcode,
+            module_index,
+            function_internal_index,
+            instruction_address,
+        );
+        return STATUS_TRAPPED;
+    }
+
+    let number = syscall_num as usize;
+
+    if !check_call(
+        AuditedCall::Syscall {
+            syscall_num: number,
+        },
+        |capability| capability.permits_syscall(number),
+    ) {
+        record_terminate(TERMINATE_CODE_CAPABILITY_DENIED);
+        return STATUS_TRAPPED;
+    }
+
+    let args = std::slice::from_raw_parts(args_ptr as *const usize, params_count as usize);
+
+    let result = match *args {
+        [] => syscall_without_args(number),
+        [a0] => syscall_with_1_arg(number, a0),
+        [a0, a1] => syscall_with_2_args(number, a0, a1),
+        [a0, a1, a2] => syscall_with_3_args(number, a0, a1, a2),
+        [a0, a1, a2, a3] => syscall_with_4_args(number, a0, a1, a2, a3),
+        [a0, a1, a2, a3, a4] => syscall_with_5_args(number, a0, a1, a2, a3, a4),
+        [a0, a1, a2, a3, a4, a5, ..] => syscall_with_6_args(number, a0, a1, a2, a3, a4, a5),
+    };
+
+    let results = std::slice::from_raw_parts_mut(results_ptr, 2);
+    match result {
+        Ok(return_value) => {
+            results[0] = return_value as u64;
+            results[1] = 0;
+        }
+        Err(error_number) => {
+            results[0] = 0;
+            results[1] = error_number as u64;
+        }
+    }
+
+    STATUS_FINISHED
+}
+
+/// Checks whether the external function can be called, the native code calls it
+/// before calling the external function. The name of the external library is
+/// followed by the name of the function in the memory pointed by `names_ptr`.
+///
+/// Returns `STATUS_TRAPPED` if the function is not permitted by the capability,
+/// the program is then terminated, or if the names are not valid UTF-8 strings,
+/// the location of the instruction is used for reporting the trap.
+///
+/// # Safety
+///
+/// The `names_ptr` must be valid for reading `library_name_length + function_name_length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn anc_aot_check_extcall(
+    names_ptr: *const u8,
+    library_name_length: u32,
+    function_name_length: u32,
+    module_index: u32,
+    function_internal_index: u32,
+    instruction_address: u32,
+) -> u64 {
+    let names = std::slice::from_raw_parts(
+        names_ptr,
+        library_name_length as usize + function_name_length as usize,
+    );
+    let (library_bytes, function_bytes) = names.split_at(library_name_length as usize);
+
+    let (Ok(library), Ok(function)) = (
+        std::str::from_utf8(library_bytes),
+        std::str::from_utf8(function_bytes),
+    ) else {
+        // The AOT compiler does not generate such names,
+        // i.e., the object file is corrupted.
+        record_trap(
+            TrapKind::InvalidOpcode,
+            module_index,
+            function_internal_index,
+            instruction_address,
+        );
+        return STATUS_TRAPPED;
+    };
+
+    let call = AuditedCall::Extcall {
+        library: library.to_owned(),
+        function: function.to_owned(),
+    };
+
+    if !check_call(call, |capability| {
+        capability.permits_extcall(library, function)
+    }) {
+        record_terminate(TERMINATE_CODE_CAPABILITY_DENIED);
+        return STATUS_TRAPPED;
+    }
+
+    STATUS_FINISHED
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anc_context::{
+        call_audit_log::{AuditedCall, CallAuditEntry, CallAuditLog, MemoryCallAuditor},
+        capability::{CallFilter, Capability, ExternalFunctionRule},
+        thread_context::TrapKind,
+    };
+    use syscall_util::number::SysCallNum;
+
+    use crate::{
+        envcall_num::EnvCallNum, native_translator::STATUS_FINISHED, ProcessorError,
+        ProcessorErrorType, TERMINATE_CODE_CAPABILITY_DENIED,
+    };
+
+    use super::{
+        anc_aot_check_extcall, anc_aot_envcall, anc_aot_raise_trap, anc_aot_syscall,
+        run_aot_program, AOT_TRAP_CODE_INTEGER_OVERFLOW, STATUS_TRAPPED,
+    };
+
+    unsafe extern "C" fn start_and_exit(results_ptr: *mut u8) -> u64 {
+        *(results_ptr as *mut u64) = 0x11;
+        0
+    }
+
+    unsafe extern "C" fn start_and_trap(_results_ptr: *mut u8) -> u64 {
+        anc_aot_raise_trap(AOT_TRAP_CODE_INTEGER_OVERFLOW, 0, 1, 0x20);
+        STATUS_TRAPPED
+    }
+
+    unsafe extern "C" fn start_and_trap_with_unknown_code(_results_ptr: *mut u8) -> u64 {
+        anc_aot_raise_trap(0xff, 0, 1, 0x20);
+        STATUS_TRAPPED
+    }
+
+    unsafe extern "C" fn start_and_return_bad_status(_results_ptr: *mut u8) -> u64 {
+        0x77
+    }
+
+    #[test]
+    fn test_aot_run_program() {
+        assert_eq!(
+            run_aot_program(start_and_exit, Capability::default(), None).unwrap(),
+            0x11
+        );
+
+        assert!(matches!(
+            run_aot_program(start_and_trap, Capability::default(), None),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::IntegerOverflow,
+                    module_index: 0,
+                    function_internal_index: 1,
+                    instruction_address: 0x20,
+                },
+                ..
+            })
+        ));
+
+        // the object file is not trusted
+        assert!(matches!(
+            run_aot_program(
+                start_and_trap_with_unknown_code,
+                Capability::default(),
+                None
+            ),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::InvalidOpcode,
+                    ..
+                },
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            run_aot_program(start_and_return_bad_status, Capability::default(), None),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::UnexpectedNativeStatus(0x77),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_aot_envcall() {
+        let mut results = [0u64; 2];
+
+        let status0 = unsafe {
+            anc_aot_envcall(
+                EnvCallNum::host_memory_width as u32,
+                0,
+                0,
+                0,
+                results.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status0, STATUS_FINISHED);
+        assert_eq!(results[0], size_of::<usize>() as u64);
+
+        let status1 =
+            unsafe { anc_aot_envcall(EnvCallNum::time_now as u32, 0, 0, 0, results.as_mut_ptr()) };
+        assert_eq!(status1, STATUS_FINISHED);
+        assert!(results[0] > 0);
+        assert!(results[1] < 1_000_000_000);
+
+        // unsupported envcall
+        unsafe extern "C" fn start_with_unsupported_envcall(results_ptr: *mut u8) -> u64 {
+            anc_aot_envcall(0xffff_ffff, 0, 2, 0x30, results_ptr as *mut u64)
+        }
+
+        assert!(matches!(
+            run_aot_program(start_with_unsupported_envcall, Capability::default(), None),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::InvalidEnvCallNumber,
+                    module_index: 0,
+                    function_internal_index: 2,
+                    instruction_address: 0x30,
+                },
+                ..
+            })
+        ));
+    }
+
+    // Invokes the system call `getpid` and exits with the process id.
+    unsafe extern "C" fn start_with_syscall_getpid(results_ptr: *mut u8) -> u64 {
+        let args: [u64; 0] = [];
+        let mut results = [0u64; 2];
+        let status = anc_aot_syscall(
+            SysCallNum::getpid as u64,
+            0,
+            0,
+            1,
+            0x10,
+            args.as_ptr(),
+            results.as_mut_ptr(),
+        );
+
+        *(results_ptr as *mut u64) = results[0];
+        status
+    }
+
+    // Calls the external function `libc::getuid` (it is not actually called here).
+    unsafe extern "C" fn start_with_extcall_libc_getuid(_results_ptr: *mut u8) -> u64 {
+        let names = b"libcgetuid";
+        anc_aot_check_extcall(names.as_ptr(), 4, 6, 0, 1, 0x20)
+    }
+
+    fn is_capability_denied(result: Result<u32, ProcessorError>) -> bool {
+        matches!(
+            result,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_CAPABILITY_DENIED),
+                ..
+            })
+        )
+    }
+
+    #[test]
+    fn test_aot_syscall() {
+        let capability = Capability {
+            syscall: true,
+            ..Capability::default()
+        };
+
+        assert_eq!(
+            run_aot_program(start_with_syscall_getpid, capability, None).unwrap(),
+            std::process::id()
+        );
+
+        // too many arguments
+        let args = [0u64; 7];
+        let mut results = [0u64; 2];
+        let status1 = unsafe {
+            anc_aot_syscall(
+                SysCallNum::getpid as u64,
+                7,
+                0,
+                0,
+                0,
+                args.as_ptr(),
+                results.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status1, STATUS_TRAPPED);
+    }
+
+    #[test]
+    fn test_aot_syscall_capability() {
+        let capable = |syscall_filter: CallFilter<usize>| Capability {
+            syscall: true,
+            syscall_filter,
+            ..Capability::default()
+        };
+
+        assert!(is_capability_denied(run_aot_program(
+            start_with_syscall_getpid,
+            Capability::default(),
+            None
+        )));
+        assert!(is_capability_denied(run_aot_program(
+            start_with_syscall_getpid,
+            capable(CallFilter::Deny(vec![SysCallNum::getpid as usize])),
+            None
+        )));
+        assert!(run_aot_program(
+            start_with_syscall_getpid,
+            capable(CallFilter::Allow(vec![SysCallNum::getpid as usize])),
+            None
+        )
+        .is_ok());
+
+        // no program is running
+        let args: [u64; 0] = [];
+        let mut results = [0u64; 2];
+        let status = unsafe {
+            anc_aot_syscall(
+                SysCallNum::getpid as u64,
+                0,
+                0,
+                0,
+                0,
+                args.as_ptr(),
+                results.as_mut_ptr(),
+            )
+        };
+        assert_eq!(status, STATUS_TRAPPED);
+    }
+
+    #[test]
+    fn test_aot_extcall_capability() {
+        let capable = |extcall_filter: CallFilter<ExternalFunctionRule>| Capability {
+            extcall: true,
+            extcall_filter,
+            ..Capability::default()
+        };
+
+        assert!(is_capability_denied(run_aot_program(
+            start_with_extcall_libc_getuid,
+            Capability::default(),
+            None
+        )));
+        assert!(is_capability_denied(run_aot_program(
+            start_with_extcall_libc_getuid,
+            capable(CallFilter::Allow(vec![ExternalFunctionRule::function(
+                "libc", "getpid"
+            )])),
+            None
+        )));
+        assert!(run_aot_program(
+            start_with_extcall_libc_getuid,
+            capable(CallFilter::Allow(vec![ExternalFunctionRule::library(
+                "libc"
+            )])),
+            None
+        )
+        .is_ok());
+
+        // the names are not valid UTF-8 strings
+        unsafe extern "C" fn start_with_invalid_names(_results_ptr: *mut u8) -> u64 {
+            let names = b"libc\xff";
+            anc_aot_check_extcall(names.as_ptr(), 4, 1, 0, 1, 0x20)
+        }
+
+        assert!(matches!(
+            run_aot_program(start_with_invalid_names, capable(CallFilter::All), None),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::InvalidOpcode,
+                    module_index: 0,
+                    function_internal_index: 1,
+                    instruction_address: 0x20,
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_aot_call_audit_log() {
+        let auditor = Arc::new(MemoryCallAuditor::default());
+        let call_audit_log = CallAuditLog::new(auditor.clone());
+
+        let capability = Capability {
+            syscall: true,
+            ..Capability::default()
+        };
+
+        assert!(run_aot_program(
+            start_with_syscall_getpid,
+            capability.clone(),
+            Some(call_audit_log.clone())
+        )
+        .is_ok());
+        assert!(is_capability_denied(run_aot_program(
+            start_with_extcall_libc_getuid,
+            capability,
+            Some(call_audit_log)
+        )));
+
+        assert_eq!(
+            auditor.get_entries(),
+            vec![
+                CallAuditEntry {
+                    call: AuditedCall::Syscall {
+                        syscall_num: SysCallNum::getpid as usize
+                    },
+                    permitted: true
+                },
+                CallAuditEntry {
+                    call: AuditedCall::Extcall {
+                        library: "libc".to_owned(),
+                        function: "getuid".to_owned()
+                    },
+                    permitted: false
+                },
+            ]
+        );
+    }
+}
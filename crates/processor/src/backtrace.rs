@@ -0,0 +1,288 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// VM-level backtrace
+// ------------------
+//
+// When a program terminates or traps, the frames are still on the stack,
+// so the calling chain can be recovered by walking the `FrameInfoData`
+// of function frames:
+//
+// ```diagram
+// |                  |
+// | func frame 2     | return PC -> (module 0, function 1, addr 0x0024) ---> backtrace frame 2
+// | block frame      |
+// | func frame 1     | return PC -> (module 0, function 0, addr 0x0010) ---> backtrace frame 1
+// | func frame 0     | return PC -> (EXIT_CURRENT_HANDLER_LOOP_BIT, 0, 0)
+// \------------------/
+//
+// current PC ----------------------> (module 0, function 2, addr 0x0008) ---> backtrace frame 0
+// ```
+//
+// The first backtrace frame is the current PC (i.e., the instruction which
+// raised the termination or trap), and the following frames are the
+// return PCs, i.e., the instructions next to the calling instructions.
+//
+// The return PC with the MSB of `module_index` set indicates the beginning
+// of a "function call path", it is skipped because it is not a real
+// location. Note that the location of the instruction calling the external
+// function (which in turn calls back into the VM) is not recorded in the stack,
+// so there is no frame for it in the backtrace.
+
+use std::fmt::Display;
+
+use anc_context::thread_context::ThreadContext;
+
+use crate::process::EXIT_CURRENT_HANDLER_LOOP_BIT;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BacktraceFrame {
+    pub module_index: usize,
+    pub function_internal_index: usize,
+    pub instruction_address: usize,
+
+    // The name of the module.
+    pub module_name: String,
+
+    // The full name of the function, it is only available
+    // for functions listed in the function name section.
+    pub function_name: Option<String>,
+}
+
+impl BacktraceFrame {
+    /// Returns the full name of the function, or the module name and the function
+    /// internal index if the name is not available.
+    pub fn get_name(&self) -> String {
+        match &self.function_name {
+            Some(function_name) => function_name.to_owned(),
+            None => format!(
+                "{}::<function {}>",
+                self.module_name, self.function_internal_index
+            ),
+        }
+    }
+}
+
+impl Display for BacktraceFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (module index: {}, function internal index: {}, instruction address: 0x{:04x})",
+            self.get_name(),
+            self.module_index,
+            self.function_internal_index,
+            self.instruction_address
+        )
+    }
+}
+
+/// Builds the backtrace of the current thread, the first item is
+/// the current (innermost) location.
+pub fn capture_backtrace(thread_context: &ThreadContext) -> Vec<BacktraceFrame> {
+    let current_location = (
+        thread_context.pc.module_index,
+        thread_context.pc.function_internal_index,
+        thread_context.pc.instruction_address,
+    );
+
+    let return_locations = thread_context
+        .stack
+        .get_return_program_counters()
+        .into_iter()
+        .filter(|pc| pc.module_index & EXIT_CURRENT_HANDLER_LOOP_BIT == 0)
+        .map(|pc| {
+            (
+                pc.module_index,
+                pc.function_internal_index,
+                pc.instruction_address,
+            )
+        });
+
+    std::iter::once(current_location)
+        .chain(return_locations)
+        .map(
+            |(module_index, function_internal_index, instruction_address)| {
+                let (module_name, function_name) =
+                    resolve_names(thread_context, module_index, function_internal_index);
+                BacktraceFrame {
+                    module_index,
+                    function_internal_index,
+                    instruction_address,
+                    module_name,
+                    function_name,
+                }
+            },
+        )
+        .collect()
+}
+
+fn resolve_names(
+    thread_context: &ThreadContext,
+    module_index: usize,
+    function_internal_index: usize,
+) -> (
+    /* module name */ String,
+    /* function full name */ Option<String>,
+) {
+    let module_common_instance = match thread_context.module_common_instances.get(module_index) {
+        Some(module_common_instance) => module_common_instance,
+        None => return (format!("<module {}>", module_index), None),
+    };
+
+    let function_name_section = &module_common_instance.function_name_section;
+    let function_name = function_name_section
+        .items
+        .iter()
+        .find(|item| item.function_internal_index as usize == function_internal_index)
+        .and_then(|item| {
+            let start = item.full_name_offset as usize;
+            let end = start + item.full_name_length as usize;
+            function_name_section.full_names_data.get(start..end)
+        })
+        .map(|data| String::from_utf8_lossy(data).to_string());
+
+    (module_common_instance.name.clone(), function_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::{helper_build_module_binary_with_functions_and_blocks, HelperFunctionEntry},
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
+        ProcessorErrorType,
+    };
+
+    use anc_context::{program_source::ProgramSource, thread_context::TrapKind};
+
+    #[test]
+    fn test_backtrace_of_trap() {
+        // fn0 () -> (i32)
+        //     imm_i32(11)
+        //     call(1)          ;; return address: 0x0010
+        //     end
+        //
+        // fn1 (a: i32) -> (i32)
+        //     local_load_i32_s(0, 0)
+        //     imm_i32(0)
+        //     call(2)          ;; return address: 0x0018
+        //     end
+        //
+        // fn2 (a: i32, b: i32) -> (i32)
+        //     local_load_i32_s(0, 0)
+        //     local_load_i32_s(0, 1)
+        //     div_i32_s        ;; address: 0x0010
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_s, 0, 0)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode_i32(Opcode::call, 2)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code2 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_s, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_s, 0, 1)
+            .append_opcode(Opcode::div_i32_s)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code2,
+                },
+            ],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        let error0 = result0.unwrap_err();
+
+        assert!(matches!(
+            error0,
+            ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::IntegerDivideByZero,
+                    ..
+                },
+                ..
+            }
+        ));
+
+        let locations = error0
+            .backtrace
+            .iter()
+            .map(|frame| {
+                (
+                    frame.module_index,
+                    frame.function_internal_index,
+                    frame.instruction_address,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // the instruction addresses are relative to the function
+        // section, rather than the start of each function.
+        let function_info1 = thread_context0.get_function_info(0, 1);
+        let function_info2 = thread_context0.get_function_info(0, 2);
+
+        assert_eq!(
+            locations,
+            vec![
+                (0, 2, function_info2.code_offset + 0x10),
+                (0, 1, function_info1.code_offset + 0x18),
+                (0, 0, 0x10),
+            ]
+        );
+
+        let text = error0.to_string();
+        assert!(text.contains("stack backtrace:"));
+        assert!(text.contains("0: "));
+        assert!(text.contains("2: "));
+
+        // the backtrace is only captured for termination and traps.
+        let result2 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(1)]);
+        assert!(matches!(
+            result2,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ParametersAmountMissmatch,
+                ..
+            })
+        ));
+        assert!(result2.unwrap_err().backtrace.is_empty());
+    }
+}
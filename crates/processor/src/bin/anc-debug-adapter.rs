@@ -0,0 +1,96 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Debug Adapter
+// -------------
+//
+// Runs a program under the control of the DAP server, the messages are
+// exchanged over the stdin and stdout, so the editors can launch it as
+// an "executable" debug adapter, e.g.
+//
+// ```text
+// anc-debug-adapter /path/to/projects/hello-world
+// anc-debug-adapter /path/to/scripts/hello-world.anc _start
+// ```
+//
+// The arguments are the program path (see `FileProgramSource`) and the
+// optional internal entry point name (see `start_program()`, default "_start").
+//
+// Since the stdin and stdout are occupied by the protocol, the standard input
+// of the program is empty and the standard output is redirected to the stderr.
+
+use std::{
+    io::BufReader,
+    process::ExitCode,
+    sync::{Arc, Mutex},
+};
+
+use anc_context::{
+    process_property::ProcessProperty, program_source::ProgramSource,
+    standard_streams::StandardStreams,
+};
+use anc_processor::{
+    debug_adapter::start_program_with_debug_adapter, file_program_source::FileProgramSource,
+    ProcessorError, ProcessorErrorType,
+};
+
+const DEFAULT_ENTRY_POINT_NAME: &str = "_start";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let (program_path, entry_point_name) = match args.as_slice() {
+        [program_path] => (program_path, DEFAULT_ENTRY_POINT_NAME),
+        [program_path, entry_point_name] => (program_path, entry_point_name.as_str()),
+        _ => {
+            eprintln!("Usage: anc-debug-adapter <program path> [entry point name]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let process_property = ProcessProperty {
+        standard_streams: StandardStreams::new()
+            .with_stdin(std::io::empty())
+            .with_stdout(Arc::new(Mutex::new(std::io::stderr()))),
+        ..ProcessProperty::default()
+    };
+
+    let program_source = match FileProgramSource::with_property(program_path, process_property) {
+        Ok(program_source) => program_source,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let process_context = match program_source.create_process_context() {
+        Ok(process_context) => process_context,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = start_program_with_debug_adapter(
+        &process_context,
+        entry_point_name,
+        vec![],
+        BufReader::new(std::io::stdin()),
+        std::io::stdout(),
+    );
+
+    match result {
+        Ok(exit_code) => ExitCode::from(exit_code as u8),
+        Err(ProcessorError {
+            error_type: ProcessorErrorType::Terminate(terminate_code),
+            ..
+        }) => ExitCode::from(terminate_code as u8),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
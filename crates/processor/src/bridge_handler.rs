@@ -6,8 +6,12 @@
 
 use std::sync::Mutex;
 
-use anc_context::thread_context::{ProgramCounter, ThreadContext};
+use anc_context::{
+    code_generator::{convert_vm_operand_data_type_to_jit_type, Generator},
+    thread_context::ThreadContext,
+};
 use anc_isa::{OperandDataType, OPERAND_SIZE_IN_BYTES};
+use anc_stack::ProgramCounter;
 use cranelift_codegen::ir::{
     types, AbiParam, Function, InstBuilder, StackSlotData, StackSlotKind, UserFuncName,
 };
@@ -16,120 +20,74 @@ use cranelift_jit::JITModule;
 use cranelift_module::{Linkage, Module};
 
 use crate::{
-    code_generator::Generator,
-    jit_context::convert_vm_operand_data_type_to_jit_type,
-    process::{process_continuous_instructions, EXIT_CURRENT_HANDLER_LOOP_BIT},
+    process::{
+        build_processor_error, process_continuous_instructions, EXIT_CURRENT_HANDLER_LOOP_BIT,
+    },
     ProcessorError, ProcessorErrorType,
 };
 
 static LAST_BRIDGE_FUNCTION_ID: Mutex<usize> = Mutex::new(0);
 
+// the signature of the delegate function which is called by the bridge function:
+//
+// extern "C" fn delegate_function (
+//     thread_context_ptr: *mut u8,
+//     target_module_index: u32,
+//     function_internal_index: u32,
+//     params_ptr: *const u8,
+//     results_ptr: *mut u8);
+pub(crate) type DelegateFunction = extern "C" fn(*mut u8, u32, u32, *const u8, *mut u8);
+
+/// Returns the bridge function of the specified VM function,
+/// the bridge function is created and stored in the bridge function table
+/// if it does not exist yet.
 pub fn get_or_create_bridge_function(
     thread_context: &mut ThreadContext,
-    module_index: usize,
-    function_public_index: usize,
+    target_module_index: usize,
+    function_internal_index: usize,
 ) -> Result<*const u8, ProcessorError> {
-    let (target_module_index, function_internal_index) = thread_context
-        .get_target_function_object(module_index, function_public_index);
-
     // check if the function specified (target_module_index, function_internal_index)
     // already exists in the "bridge function table"
-    let opt_bridge_function_ptr =
-        thread_context.find_bridge_function(target_module_index, function_internal_index);
-
-    // found it
-    if let Some(bridge_function_ptr) = opt_bridge_function_ptr {
-        return Ok(bridge_function_ptr /* as *const () */);
-    }
-
-    let type_index = thread_context.module_common_instances[target_module_index]
-        .function_section
-        .items[function_internal_index]
-        .type_index;
-    let (params, results) = thread_context.module_common_instances[target_module_index]
-        .type_section
-        .get_item_params_and_results(type_index as usize);
-
-    if results.len() > 1 {
-        // The specified function has more than 1 return value.
-        return Err(ProcessorError::new(
-            ProcessorErrorType::ResultsAmountMissmatch,
-        ));
+    if let Some(bridge_function_ptr) = thread_context
+        .bridge_function_table
+        .find_bridge_function(target_module_index, function_internal_index)
+    {
+        return Ok(bridge_function_ptr);
     }
 
-    let delegate_function_addr = delegate_bridge_function_call as *const u8 as usize;
-    let handler_addr = handler as *const Handler as *const u8 as usize;
-    let thread_context_addr = thread_context as *const ThreadContext as *const u8 as usize;
-
-    let mut jit_generator = handler.jit_generator.lock().unwrap();
-
     let bridge_function_ptr = build_bridge_function(
-        &mut jit_generator,
-        delegate_function_addr,
-        handler_addr,
-        thread_context_addr,
+        thread_context,
+        delegate_bridge_function_call,
         target_module_index,
         function_internal_index,
-        params,
-        results,
-    );
+    )?;
 
     // store the function pointer into table
-    thread_context.insert_bridge_function(
+    thread_context.bridge_function_table.insert_bridge_function(
         target_module_index,
         function_internal_index,
         bridge_function_ptr,
     );
 
-    Ok(bridge_function_ptr /*as *const ()*/)
-}
-
-pub fn get_or_create_bridge_data(
-    thread_context: &mut ThreadContext,
-    module_index: usize,
-    data_public_index: usize,
-    offset_bytes: usize,
-    data_length_in_bytes: usize,
-) -> Result<*const u8, ProcessorError> {
-    let (_target_module_index, data_internal_index, data_object) = thread_context
-        .get_target_data_object(
-            module_index,
-            data_public_index,
-            offset_bytes,
-            data_length_in_bytes,
-        );
-
-    let total_offset =
-        data_object.get_data_address_by_index_and_offset(data_internal_index, offset_bytes);
-    let ptr = data_object.get_ptr(total_offset);
-    Ok(ptr /*as *const ()*/)
+    Ok(bridge_function_ptr)
 }
 
-pub fn get_or_create_bridge_callback_function(
+/// Generates a native function which has the same signature as the specified VM function,
+/// the native function calls the delegate function with the address of the thread context.
+///
+/// Returns `ResultsAmountMissmatch` if the VM function has more than 1 return value,
+/// since a C function can only return 0 or 1 value.
+pub(crate) fn build_bridge_function(
     thread_context: &mut ThreadContext,
-    module_index: usize,
-    function_public_index: usize,
+    delegate_function: DelegateFunction,
+    target_module_index: usize,
+    function_internal_index: usize,
 ) -> Result<*const u8, ProcessorError> {
-    // get the internal index of function
-    let (target_module_index, function_internal_index) = thread_context
-        .get_target_function_object(module_index, function_public_index);
-
-    // check if the specified (target_module_index, function_internal_index) already
-    // exists in the callback function table
-    let opt_callback_function_ptr =
-        thread_context.find_bridge_callback_function(target_module_index, function_internal_index);
-
-    if let Some(callback_function_ptr) = opt_callback_function_ptr {
-        return Ok(callback_function_ptr);
-    }
-
-    let type_index = thread_context.module_common_instances[target_module_index]
-        .function_section
-        .items[function_internal_index]
-        .type_index;
+    let function_info =
+        thread_context.get_function_info(target_module_index, function_internal_index);
     let (params, results) = thread_context.module_common_instances[target_module_index]
         .type_section
-        .get_item_params_and_results(type_index as usize);
+        .get_item_params_and_results(function_info.type_index);
 
     if results.len() > 1 {
         // The specified function has more than 1 return value.
@@ -138,31 +96,20 @@ pub fn get_or_create_bridge_callback_function(
         ));
     }
 
-    let delegate_function_addr = delegate_callback_function_call as *const u8 as usize;
-    let handler_addr = handler as *const Handler as *const u8 as usize;
+    let delegate_function_addr = delegate_function as *const u8 as usize;
     let thread_context_addr = thread_context as *const ThreadContext as *const u8 as usize;
 
-    let mut jit_generator = handler.jit_generator.lock().unwrap();
+    let mut jit_generator = thread_context.jit_generator.lock().unwrap();
 
-    let callback_function_ptr = build_bridge_function(
+    Ok(generate_bridge_function(
         &mut jit_generator,
         delegate_function_addr,
-        handler_addr,
         thread_context_addr,
         target_module_index,
         function_internal_index,
         params,
         results,
-    );
-
-    // store the function pointer into table
-    thread_context.insert_callback_function(
-        target_module_index,
-        function_internal_index,
-        callback_function_ptr,
-    );
-
-    Ok(callback_function_ptr)
+    ))
 }
 
 //     Rust/C application
@@ -194,34 +141,19 @@ pub fn get_or_create_bridge_callback_function(
 //     3. call delegate_function (...stack slots...)
 //     4. return value
 // }
-#[allow(clippy::too_many_arguments)]
-fn build_bridge_function(
+fn generate_bridge_function(
     jit_generator: &mut Generator<JITModule>,
     delegate_function_addr: usize,
-    handler_addr: usize,
     thread_context_addr: usize,
     target_module_index: usize,
     function_internal_index: usize,
     params: &[OperandDataType],
     results: &[OperandDataType],
 ) -> *const u8 {
-    // let mut mutex_jit_generator = get_jit_generator_without_imported_symbols();
-    // let jit_generator = mutex_jit_generator.as_mut().unwrap();
-
     let pointer_type = jit_generator.module.isa().pointer_type();
 
-    // the signature of the delegate function:
-    //
-    // extern "C" fn delegate_function (
-    //     handler_ptr: *const u8,
-    //     thread_context_ptr: *mut u8,
-    //     target_module_index: usize,
-    //     function_internal_index: usize,
-    //     params_ptr: *const u8,
-    //     results_ptr: *mut u8);
-
+    // the signature of the delegate function, see `DelegateFunction`.
     let mut func_delegate_sig = jit_generator.module.make_signature();
-    func_delegate_sig.params.push(AbiParam::new(pointer_type)); // handler_ptr
     func_delegate_sig.params.push(AbiParam::new(pointer_type)); // thread_context_ptr
     func_delegate_sig.params.push(AbiParam::new(types::I32)); // target_module_index
     func_delegate_sig.params.push(AbiParam::new(types::I32)); // function_internal_index
@@ -306,26 +238,23 @@ fn build_bridge_function(
             .ins()
             .iconst(pointer_type, delegate_function_addr as i64);
         let param_0 = function_builder
-            .ins()
-            .iconst(pointer_type, handler_addr as i64);
-        let param_1 = function_builder
             .ins()
             .iconst(pointer_type, thread_context_addr as i64);
-        let param_2 = function_builder
+        let param_1 = function_builder
             .ins()
             .iconst(types::I32, target_module_index as i64);
-        let param_3 = function_builder
+        let param_2 = function_builder
             .ins()
             .iconst(types::I32, function_internal_index as i64);
-        let param_4 = function_builder.ins().stack_addr(pointer_type, ss0, 0);
-        let param_5 = function_builder.ins().stack_addr(pointer_type, ss1, 0);
+        let param_3 = function_builder.ins().stack_addr(pointer_type, ss0, 0);
+        let param_4 = function_builder.ins().stack_addr(pointer_type, ss1, 0);
 
         // call delegate function
         let sig_ref0 = function_builder.import_signature(func_delegate_sig);
         function_builder.ins().call_indirect(
             sig_ref0,
             callee_0,
-            &[param_0, param_1, param_2, param_3, param_4, param_5],
+            &[param_0, param_1, param_2, param_3, param_4],
         );
 
         if !results.is_empty() {
@@ -342,9 +271,7 @@ fn build_bridge_function(
         function_builder.seal_all_blocks();
         function_builder.finalize();
 
-        // println!("{}", func_exported.display());
-
-        // generate the (machine/native) code of func_bridge
+        // generate the (machine/native) code of func_exported
         jit_generator.context.func = func_exported;
 
         jit_generator
@@ -360,7 +287,7 @@ fn build_bridge_function(
     // link
     jit_generator.module.finalize_definitions().unwrap();
 
-    // get func_bridge ptr
+    // get func_exported ptr
     jit_generator
         .module
         .get_finalized_function(func_exported_declare)
@@ -368,12 +295,10 @@ fn build_bridge_function(
 
 // function calling from outside of VM (such as a Rust program
 // embeds this VM and call a function of VM)
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
 extern "C" fn delegate_bridge_function_call(
-    handler_ptr: *const u8,
     thread_context_ptr: *mut u8,
-    target_module_index: usize,
-    function_internal_index: usize,
+    target_module_index: u32,
+    function_internal_index: u32,
     params_ptr: *const u8,
     results_ptr: *mut u8,
 ) {
@@ -383,41 +308,40 @@ extern "C" fn delegate_bridge_function_call(
     // results:
     // | 8 bytes |
 
-    let handler = unsafe { &*(handler_ptr as *const Handler) };
     let thread_context = unsafe { &mut *(thread_context_ptr as *mut ThreadContext) };
+    let target_module_index = target_module_index as usize;
+    let function_internal_index = function_internal_index as usize;
 
-    let (type_index, local_variable_list_index, code_offset, local_variables_with_arguments_allocated_bytes) =
-        thread_context
-            .get_function_info(
-                target_module_index,
-                function_internal_index,
-            );
+    let function_info =
+        thread_context.get_function_info(target_module_index, function_internal_index);
     let type_item = &thread_context.module_common_instances[target_module_index]
         .type_section
-        .items[type_index];
+        .items[function_info.type_index];
 
-    let params_count = type_item.params_count as usize;
-    let results_count = type_item.results_count as usize;
+    let params_count = type_item.params_count;
+    let results_count = type_item.results_count;
 
     // reset the statck
     thread_context.stack.reset();
 
     // push arguments
-    let stack_push_ptr = thread_context.stack.push_operands_from_memory(params_count);
+    let stack_push_ptr = thread_context
+        .stack
+        .push_operands_from_memory(params_count as usize);
     unsafe {
         std::ptr::copy(
             params_ptr,
             stack_push_ptr,
-            OPERAND_SIZE_IN_BYTES * params_count,
+            OPERAND_SIZE_IN_BYTES * params_count as usize,
         )
     };
 
     // create function statck frame
-    thread_context.stack.create_frame(
-        type_item.params_count,
-        type_item.results_count,
-        local_variable_list_index as u32,
-        local_variables_with_arguments_allocated_bytes,
+    let create_frame_result = thread_context.stack.create_frame(
+        params_count,
+        results_count,
+        function_info.local_variable_list_index as u32,
+        function_info.local_variables_with_arguments_allocated_bytes as u32,
         Some(ProgramCounter {
             instruction_address: 0,
             function_internal_index: 0,
@@ -428,110 +352,20 @@ extern "C" fn delegate_bridge_function_call(
         }),
     );
 
-    // set new PC
-    thread_context.pc.module_index = target_module_index;
-    thread_context.pc.function_internal_index = function_internal_index;
-    thread_context.pc.instruction_address = code_offset;
-
-    // start processing instructions
-    if let Some(terminate_code) = process_continuous_instructions(handler, thread_context) {
-        // there is no way to return the details of ProcessorError in the
-        // callback function processing, so only the panic can be thrown.
-        panic!("Program terminated, code: {}", terminate_code);
+    // there is no way to return the details of ProcessorError to the
+    // caller of the bridge function, so only the panic can be thrown.
+    if create_frame_result.is_err() {
+        panic!("{}", ProcessorError::new(ProcessorErrorType::StackOverflow));
     }
 
-    // pop the results from the stack
-    // note:
-    //
-    // only 0 or 1 return value is allowed for C function.
-    if results_count > 0 {
-        let result_operands = thread_context.stack.pop_last_operands(1);
-        unsafe { std::ptr::copy(result_operands.as_ptr(), results_ptr, OPERAND_SIZE_IN_BYTES) };
-    }
-}
-
-// it's similar to the function 'process_bridge_function_call' except
-// that 'process_callback_function_call' will not reset the calling stack.
-//
-// in the other word, the 'process_bridge_function_call' starts a new 'calling-thread',
-// and the 'process_callback_function_call' is 'insert' a 'sub-calling-thread' into
-// the current 'calling-thread'.
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-extern "C" fn delegate_callback_function_call(
-    handler_ptr: *const u8,
-    thread_context_ptr: *mut u8,
-    target_module_index: usize,
-    function_internal_index: usize,
-    params_ptr: *const u8,
-    results_ptr: *mut u8,
-) {
-    // params:
-    // | 8 bytes | 8 bytes | ... |
-    //
-    // results:
-    // | 8 bytes |
-
-    let handler = unsafe { &*(handler_ptr as *const Handler) };
-    let thread_context = unsafe { &mut *(thread_context_ptr as *mut ThreadContext) };
-
-    let (type_index, local_variable_list_index, code_offset, local_variables_with_arguments_allocated_bytes) =
-        thread_context
-            .get_function_info(
-                target_module_index,
-                function_internal_index,
-            );
-    let type_item = &thread_context.module_common_instances[target_module_index]
-        .type_section
-        .items[type_index];
-
-    let params_count = type_item.params_count as usize;
-    let results_count = type_item.results_count as usize;
-
-    // push arguments
-    let stack_push_ptr = thread_context.stack.push_operands_from_memory(params_count);
-    unsafe {
-        std::ptr::copy(
-            params_ptr,
-            stack_push_ptr,
-            OPERAND_SIZE_IN_BYTES * params_count,
-        )
-    };
-
-    // store the current PC as return PC
-    let ProgramCounter {
-        instruction_address: return_instruction_address,
-        function_internal_index: return_function_internal_index,
-        module_index: return_module_index,
-    } = thread_context.pc;
-
-    let return_pc = ProgramCounter {
-        instruction_address: return_instruction_address,
-        function_internal_index: return_function_internal_index,
-
-        // set MSB of 'return module index' to '1' to indicate that it's the END of the
-        // current function call.
-        module_index: return_module_index | EXIT_CURRENT_HANDLER_LOOP_BIT,
-    };
-
-    // create function statck frame
-    thread_context.stack.create_frame(
-        type_item.params_count,
-        type_item.results_count,
-        local_variable_list_index as u32,
-        local_variables_with_arguments_allocated_bytes,
-        Some(return_pc),
-    );
-
     // set new PC
     thread_context.pc.module_index = target_module_index;
     thread_context.pc.function_internal_index = function_internal_index;
-    thread_context.pc.instruction_address = code_offset;
+    thread_context.pc.instruction_address = function_info.code_offset;
 
     // start processing instructions
-    if let Some(terminate_code) = process_continuous_instructions(handler, thread_context) {
-        // there is no way to return the details of ProcessorError in the
-        // callback function processing, so only the panic can be thrown.
-        panic!("Program terminated, code: {}", terminate_code);
+    if let Err(error_type) = process_continuous_instructions(thread_context) {
+        panic!("{}", build_processor_error(thread_context, error_type));
     }
 
     // pop the results from the stack
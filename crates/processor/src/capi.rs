@@ -0,0 +1,680 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// C API
+// -----
+//
+// The `extern "C"` API for the C and C++ applications which embed the VM,
+// it wraps the `Vm` facade (see `vm.rs`) with opaque handles:
+//
+// - `AncVmBuilder`: collects the module images, it is consumed by `anc_process_create()`.
+// - `AncProcess`: a process, i.e. the loaded module images and the process context.
+// - `AncThread`: a thread of the process, the data of the program is kept between calls.
+// - `AncError`: the error returned by the API.
+//
+// Example:
+//
+// ```c
+// AncVmBuilder *builder = anc_vm_builder_new();
+// anc_vm_builder_add_module_file(builder, "/path/to/main.ancm");
+//
+// AncError *error = NULL;
+// AncProcess *process = anc_process_create(builder, &error);
+// AncThread *thread = anc_thread_create(process);
+//
+// AncValue args[2] = {
+//     {.value_type = ANC_VALUE_TYPE_I32, .payload = {.i32 = 11}},
+//     {.value_type = ANC_VALUE_TYPE_I32, .payload = {.i32 = 13}},
+// };
+// AncValue results[1];
+// size_t results_count = 0;
+//
+// if (anc_thread_call(thread, "main::add", args, 2, results, 1, &results_count, &error) != 0) {
+//     printf("%s\n", anc_error_message(error));
+//     anc_error_free(error);
+// }
+//
+// anc_thread_free(thread);
+// anc_process_free(process);
+// ```
+//
+// The threads must be freed before the process they belong to.
+//
+// The C header `include/anc_processor.h` is generated by cbindgen:
+//
+// `$ cbindgen --config cbindgen.toml --output include/anc_processor.h`
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+};
+
+use anc_context::process_context::ProcessContext;
+use anc_isa::ForeignValue;
+
+use crate::vm::{Instance, Vm, VmBuilder, VmError};
+
+pub const ANC_VALUE_TYPE_I32: u32 = 0;
+pub const ANC_VALUE_TYPE_I64: u32 = 1;
+pub const ANC_VALUE_TYPE_F32: u32 = 2;
+pub const ANC_VALUE_TYPE_F64: u32 = 3;
+
+/// The payload of a tagged value.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union AncValuePayload {
+    pub i32: i32,
+    pub i64: i64,
+    pub f32: f32,
+    pub f64: f64,
+}
+
+/// A tagged value, the `value_type` is one of the `ANC_VALUE_TYPE_*` constants.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AncValue {
+    pub value_type: u32,
+    pub payload: AncValuePayload,
+}
+
+/// Collects the module images of a process.
+pub struct AncVmBuilder {
+    builder: VmBuilder,
+}
+
+/// A process, i.e. the loaded module images and the process context.
+pub struct AncProcess {
+    // The process context borrows the module images owned by the `Vm`,
+    // so it must be dropped first (the fields are dropped in declaration order).
+    process_context: ProcessContext<'static>,
+    _vm: Box<Vm>,
+}
+
+/// A thread of a process.
+pub struct AncThread {
+    instance: Instance<'static>,
+}
+
+/// The error returned by the API.
+pub struct AncError {
+    message: CString,
+}
+
+impl AncError {
+    fn new(message: String) -> Self {
+        // The message never contains '\0' in practice, replace it just in case.
+        let message = CString::new(message.replace('\0', " ")).unwrap();
+        Self { message }
+    }
+}
+
+fn set_error(error: *mut *mut AncError, message: String) {
+    if !error.is_null() {
+        unsafe { *error = Box::into_raw(Box::new(AncError::new(message))) };
+    }
+}
+
+// The panics must not unwind across the FFI boundary, so the body of
+// every exported function is executed through this function, and
+// the result of `on_panic` is returned if the body panics.
+fn call_without_unwinding<R>(on_panic: impl FnOnce() -> R, body: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| on_panic())
+}
+
+/// Returns NULL if the builder can not be created.
+#[no_mangle]
+pub extern "C" fn anc_vm_builder_new() -> *mut AncVmBuilder {
+    call_without_unwinding(std::ptr::null_mut, || {
+        Box::into_raw(Box::new(AncVmBuilder {
+            builder: VmBuilder::new(),
+        }))
+    })
+}
+
+/// Adds a module image, the bytes are copied. The first module is the main module.
+///
+/// # Safety
+///
+/// `builder` must be returned by `anc_vm_builder_new()`, and `data` must point
+/// to `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn anc_vm_builder_add_module_bytes(
+    builder: *mut AncVmBuilder,
+    data: *const u8,
+    length: usize,
+) {
+    call_without_unwinding(
+        || (),
+        || {
+            let builder = &mut *builder;
+            let module_binary = std::slice::from_raw_parts(data, length).to_vec();
+            builder.builder = std::mem::take(&mut builder.builder).module_binary(module_binary);
+        },
+    )
+}
+
+/// Adds a module image file, it is read when the process is created.
+///
+/// # Safety
+///
+/// `builder` must be returned by `anc_vm_builder_new()`, and `path` must be
+/// a NUL-terminated string, it is taken as the raw bytes of the path on Unix,
+/// and as a UTF-8 string on the other platforms.
+#[no_mangle]
+pub unsafe extern "C" fn anc_vm_builder_add_module_file(
+    builder: *mut AncVmBuilder,
+    path: *const c_char,
+) {
+    call_without_unwinding(
+        || (),
+        || {
+            let builder = &mut *builder;
+            let path = convert_c_str_to_path(CStr::from_ptr(path));
+            builder.builder = std::mem::take(&mut builder.builder).module_file(path);
+        },
+    )
+}
+
+#[cfg(unix)]
+fn convert_c_str_to_path(path: &CStr) -> PathBuf {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+    PathBuf::from(OsStr::from_bytes(path.to_bytes()))
+}
+
+#[cfg(not(unix))]
+fn convert_c_str_to_path(path: &CStr) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().into_owned())
+}
+
+/// Frees a builder which is not passed to `anc_process_create()`.
+///
+/// # Safety
+///
+/// `builder` must be returned by `anc_vm_builder_new()` or be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn anc_vm_builder_free(builder: *mut AncVmBuilder) {
+    call_without_unwinding(
+        || (),
+        || {
+            if !builder.is_null() {
+                drop(Box::from_raw(builder));
+            }
+        },
+    )
+}
+
+/// Loads and verifies the module images and creates a process, the builder is consumed.
+///
+/// Returns NULL and sets `error` (if it is not NULL) on failure.
+///
+/// # Safety
+///
+/// `builder` must be returned by `anc_vm_builder_new()`, and `error` must be
+/// valid for writes or be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn anc_process_create(
+    builder: *mut AncVmBuilder,
+    error: *mut *mut AncError,
+) -> *mut AncProcess {
+    call_without_unwinding(
+        || {
+            set_error(
+                error,
+                "The VM panicked while creating the process.".to_owned(),
+            );
+            std::ptr::null_mut()
+        },
+        || {
+            let builder = Box::from_raw(builder).builder;
+
+            let vm = match builder.build() {
+                Ok(vm) => Box::new(vm),
+                Err(e) => {
+                    set_error(error, e.to_string());
+                    return std::ptr::null_mut();
+                }
+            };
+
+            // The `Vm` is boxed and owned by the process, so it outlives the process context.
+            let vm_ref: &'static Vm = &*(vm.as_ref() as *const Vm);
+
+            match vm_ref.create_process_context() {
+                Ok(process_context) => Box::into_raw(Box::new(AncProcess {
+                    process_context,
+                    _vm: vm,
+                })),
+                Err(e) => {
+                    set_error(error, e.to_string());
+                    std::ptr::null_mut()
+                }
+            }
+        },
+    )
+}
+
+/// # Safety
+///
+/// `process` must be returned by `anc_process_create()` or be NULL,
+/// and all threads of the process must have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn anc_process_free(process: *mut AncProcess) {
+    call_without_unwinding(
+        || (),
+        || {
+            if !process.is_null() {
+                drop(Box::from_raw(process));
+            }
+        },
+    )
+}
+
+/// Creates a thread of the process, returns NULL if the thread can not be created.
+///
+/// # Safety
+///
+/// `process` must be returned by `anc_process_create()`, and it must outlive the thread.
+#[no_mangle]
+pub unsafe extern "C" fn anc_thread_create(process: *mut AncProcess) -> *mut AncThread {
+    call_without_unwinding(std::ptr::null_mut, || {
+        let process_context: &'static ProcessContext<'static> = &(*process).process_context;
+        Box::into_raw(Box::new(AncThread {
+            instance: Instance::new(process_context),
+        }))
+    })
+}
+
+/// # Safety
+///
+/// `thread` must be returned by `anc_thread_create()` or be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn anc_thread_free(thread: *mut AncThread) {
+    call_without_unwinding(
+        || (),
+        || {
+            if !thread.is_null() {
+                drop(Box::from_raw(thread));
+            }
+        },
+    )
+}
+
+/// Calls the specified public function, the function name should be
+/// in the form of "module::name", e.g. "main::add".
+///
+/// The results are written to `results` and the number of results is
+/// written to `results_count`.
+///
+/// Returns 0 on success, otherwise returns -1 and sets `error` (if it is not NULL).
+///
+/// # Safety
+///
+/// `thread` must be returned by `anc_thread_create()`, `function_name` must be
+/// a NUL-terminated UTF-8 string, `arguments` must point to `arguments_count` values,
+/// `results` must be valid for writing `results_capacity` values, and `results_count`
+/// and `error` must be valid for writes or be NULL.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn anc_thread_call(
+    thread: *mut AncThread,
+    function_name: *const c_char,
+    arguments: *const AncValue,
+    arguments_count: usize,
+    results: *mut AncValue,
+    results_capacity: usize,
+    results_count: *mut usize,
+    error: *mut *mut AncError,
+) -> i32 {
+    call_without_unwinding(
+        || {
+            set_error(
+                error,
+                format!(
+                    "The VM panicked while calling function \"{}\".",
+                    CStr::from_ptr(function_name).to_string_lossy()
+                ),
+            );
+            -1
+        },
+        || {
+            let thread = &mut *thread;
+
+            let Ok(function_name) = CStr::from_ptr(function_name).to_str() else {
+                set_error(error, "The function name is not valid UTF-8.".to_owned());
+                return -1;
+            };
+
+            let arguments: &[AncValue] = if arguments_count == 0 {
+                &[]
+            } else {
+                std::slice::from_raw_parts(arguments, arguments_count)
+            };
+
+            let foreign_values = match arguments
+                .iter()
+                .map(convert_value_to_foreign_value)
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(values) => values,
+                None => {
+                    set_error(error, "Invalid argument value type.".to_owned());
+                    return -1;
+                }
+            };
+
+            let values = match thread.instance.call(function_name, &foreign_values) {
+                Ok(values) => values,
+                Err(e) => {
+                    set_error(error, e.to_string());
+                    return -1;
+                }
+            };
+
+            if values.len() > results_capacity {
+                set_error(
+                    error,
+                    VmError::ResultsMismatch {
+                        function_name: function_name.to_owned(),
+                        results: values,
+                    }
+                    .to_string(),
+                );
+                return -1;
+            }
+
+            for (idx, value) in values.iter().enumerate() {
+                *results.add(idx) = convert_foreign_value_to_value(value);
+            }
+
+            if !results_count.is_null() {
+                *results_count = values.len();
+            }
+
+            0
+        },
+    )
+}
+
+/// Returns the message of the error, the string is owned by the error.
+///
+/// # Safety
+///
+/// `error` must be returned by this API.
+#[no_mangle]
+pub unsafe extern "C" fn anc_error_message(error: *const AncError) -> *const c_char {
+    call_without_unwinding(std::ptr::null, || (*error).message.as_ptr())
+}
+
+/// # Safety
+///
+/// `error` must be returned by this API or be NULL.
+#[no_mangle]
+pub unsafe extern "C" fn anc_error_free(error: *mut AncError) {
+    call_without_unwinding(
+        || (),
+        || {
+            if !error.is_null() {
+                drop(Box::from_raw(error));
+            }
+        },
+    )
+}
+
+fn convert_value_to_foreign_value(value: &AncValue) -> Option<ForeignValue> {
+    let foreign_value = unsafe {
+        match value.value_type {
+            ANC_VALUE_TYPE_I32 => ForeignValue::U32(value.payload.i32 as u32),
+            ANC_VALUE_TYPE_I64 => ForeignValue::U64(value.payload.i64 as u64),
+            ANC_VALUE_TYPE_F32 => ForeignValue::F32(value.payload.f32),
+            ANC_VALUE_TYPE_F64 => ForeignValue::F64(value.payload.f64),
+            _ => return None,
+        }
+    };
+    Some(foreign_value)
+}
+
+fn convert_foreign_value_to_value(value: &ForeignValue) -> AncValue {
+    match value {
+        ForeignValue::U32(v) => AncValue {
+            value_type: ANC_VALUE_TYPE_I32,
+            payload: AncValuePayload { i32: *v as i32 },
+        },
+        ForeignValue::U64(v) => AncValue {
+            value_type: ANC_VALUE_TYPE_I64,
+            payload: AncValuePayload { i64: *v as i64 },
+        },
+        ForeignValue::F32(v) => AncValue {
+            value_type: ANC_VALUE_TYPE_F32,
+            payload: AncValuePayload { f32: *v },
+        },
+        ForeignValue::F64(v) => AncValue {
+            value_type: ANC_VALUE_TYPE_F64,
+            payload: AncValuePayload { f64: *v },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString},
+        path::Path,
+    };
+
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::helper_build_module_binary_with_single_function,
+    };
+    use anc_isa::{opcode::Opcode, OperandDataType};
+
+    use super::{
+        anc_error_free, anc_error_message, anc_process_create, anc_process_free, anc_thread_call,
+        anc_thread_create, anc_thread_free, anc_vm_builder_add_module_bytes,
+        anc_vm_builder_add_module_file, anc_vm_builder_new, AncError, AncValue, AncValuePayload,
+        ANC_VALUE_TYPE_I32, ANC_VALUE_TYPE_I64,
+    };
+
+    fn build_binary() -> Vec<u8> {
+        // fn (a: i32, b: i32) -> (i32)
+        //     local_load_i32_u(0, 0)
+        //     local_load_i32_u(0, 1)
+        //     add_i32
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function(
+            &[OperandDataType::I32, OperandDataType::I32], // params
+            &[OperandDataType::I32],                       // results
+            &[],                                           // local variables
+            code0,
+        )
+    }
+
+    fn i32_value(value: i32) -> AncValue {
+        AncValue {
+            value_type: ANC_VALUE_TYPE_I32,
+            payload: AncValuePayload { i32: value },
+        }
+    }
+
+    #[test]
+    fn test_capi_call_function() {
+        let binary0 = build_binary();
+
+        unsafe {
+            let builder = anc_vm_builder_new();
+            anc_vm_builder_add_module_bytes(builder, binary0.as_ptr(), binary0.len());
+
+            let mut error: *mut AncError = std::ptr::null_mut();
+            let process = anc_process_create(builder, &mut error);
+            assert!(!process.is_null());
+
+            let thread = anc_thread_create(process);
+
+            let function_name = CString::new("main::func0").unwrap();
+            let arguments = [i32_value(11), i32_value(-13)];
+            let mut results = [i32_value(0)];
+            let mut results_count = 0;
+
+            assert_eq!(
+                anc_thread_call(
+                    thread,
+                    function_name.as_ptr(),
+                    arguments.as_ptr(),
+                    arguments.len(),
+                    results.as_mut_ptr(),
+                    results.len(),
+                    &mut results_count,
+                    &mut error,
+                ),
+                0
+            );
+            assert_eq!(results_count, 1);
+            assert_eq!(results[0].value_type, ANC_VALUE_TYPE_I32);
+            assert_eq!(results[0].payload.i32, -2);
+
+            // argument type mismatch
+            let arguments = [
+                i32_value(11),
+                AncValue {
+                    value_type: ANC_VALUE_TYPE_I64,
+                    payload: AncValuePayload { i64: 13 },
+                },
+            ];
+            assert_eq!(
+                anc_thread_call(
+                    thread,
+                    function_name.as_ptr(),
+                    arguments.as_ptr(),
+                    arguments.len(),
+                    results.as_mut_ptr(),
+                    results.len(),
+                    &mut results_count,
+                    &mut error,
+                ),
+                -1
+            );
+            assert!(!error.is_null());
+            assert!(CStr::from_ptr(anc_error_message(error))
+                .to_str()
+                .unwrap()
+                .contains("func0"));
+            anc_error_free(error);
+
+            // invalid value type
+            let mut error: *mut AncError = std::ptr::null_mut();
+            let arguments = [
+                i32_value(11),
+                AncValue {
+                    value_type: 0xff,
+                    payload: AncValuePayload { i64: 13 },
+                },
+            ];
+            assert_eq!(
+                anc_thread_call(
+                    thread,
+                    function_name.as_ptr(),
+                    arguments.as_ptr(),
+                    arguments.len(),
+                    results.as_mut_ptr(),
+                    results.len(),
+                    &mut results_count,
+                    &mut error,
+                ),
+                -1
+            );
+            anc_error_free(error);
+
+            anc_thread_free(thread);
+            anc_process_free(process);
+        }
+    }
+
+    #[test]
+    fn test_capi_create_process_error() {
+        let path = CString::new("/path/to/nonexistent.ancm").unwrap();
+
+        unsafe {
+            let builder = anc_vm_builder_new();
+            anc_vm_builder_add_module_file(builder, path.as_ptr());
+
+            let mut error: *mut AncError = std::ptr::null_mut();
+            let process = anc_process_create(builder, &mut error);
+            assert!(process.is_null());
+            assert!(CStr::from_ptr(anc_error_message(error))
+                .to_str()
+                .unwrap()
+                .contains("nonexistent.ancm"));
+            anc_error_free(error);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_capi_non_utf8_module_file_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        // the file name contains the byte 0xff which is not valid UTF-8
+        let mut file_name = format!("anc_capi_test_{}_", std::process::id()).into_bytes();
+        file_name.push(0xff);
+        file_name.extend_from_slice(b".ancm");
+
+        let module_file_path = std::env::temp_dir().join(std::ffi::OsStr::from_bytes(&file_name));
+        std::fs::write(&module_file_path, build_binary()).unwrap();
+
+        let path = CString::new(module_file_path.as_os_str().as_bytes()).unwrap();
+
+        unsafe {
+            let builder = anc_vm_builder_new();
+            anc_vm_builder_add_module_file(builder, path.as_ptr());
+
+            let mut error: *mut AncError = std::ptr::null_mut();
+            let process = anc_process_create(builder, &mut error);
+            std::fs::remove_file(&module_file_path).unwrap();
+
+            assert!(!process.is_null());
+            assert!(error.is_null());
+            anc_process_free(process);
+        }
+    }
+
+    #[test]
+    fn test_capi_c_program() {
+        // The C test program "tests/resources/capi/capi_test" is built
+        // by the script "tests/resources/capi/compile.sh".
+        let program_path = Path::new("tests/resources/capi/capi_test");
+        if !program_path.exists() {
+            eprintln!(
+                "skipped: the C test program \"{}\" does not exist, run \"compile.sh\" to build it.",
+                program_path.display()
+            );
+            return;
+        }
+
+        let module_file_path =
+            std::env::temp_dir().join(format!("anc_capi_test_{}.ancm", std::process::id()));
+        std::fs::write(&module_file_path, build_binary()).unwrap();
+
+        let output = std::process::Command::new(program_path)
+            .arg(&module_file_path)
+            .output()
+            .unwrap();
+        std::fs::remove_file(&module_file_path).unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(output.stdout, b"ok\n");
+    }
+}
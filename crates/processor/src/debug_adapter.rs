@@ -0,0 +1,1321 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Debug Adapter Protocol (DAP) server
+// -----------------------------------
+//
+// The server speaks DAP (JSON messages with the "Content-Length" header) over
+// a pair of reader and writer (usually the stdin and stdout), so that the editors
+// can debug the bytecode of a program started by `start_program()`.
+// The executable `anc-debug-adapter` (see `src/bin/anc-debug-adapter.rs`) serves
+// a program over the stdin and stdout.
+//
+// ```diagram
+//             requests  /--------------\  commands   /-----------\
+// /--------\ ---------> | server       | ----------> | VM thread | (main thread)
+// | editor |            | thread       |   replies   \-----------/
+// \--------/ <--------- |              | <---------- /-----------\
+//     responses/events  \--------------/             | VM thread | (child threads)
+//                              ^                     \-----------/
+//                              |        events             |
+//                              \---------------------------/
+// ```
+//
+// Each VM thread (the main thread and the threads created by `multithread_handler`)
+// is executed by a `Debugger` and registered as a DAP thread. Since the thread context
+// can only be accessed by its own thread, the server sends the inspecting requests
+// (e.g. "stackTrace", "variables") to the paused VM thread and waits for the replies.
+//
+// The mapping between DAP and VM:
+//
+// - thread: a VM thread, the thread ID is allocated by the session (starts from 1).
+// - stack frame: a function frame of the `NostdStack`, the first frame is the current one.
+// - scope "Locals": the local variables (includes arguments) of a function frame,
+//   according to the local variable list in the `LocalVariableSection`.
+// - scope "Operands": the operands of the current frame.
+// - instruction breakpoint / memory reference: a location in the format of
+//   "{module index}:{function internal index}:0x{instruction address}", e.g. "0:1:0x0010".
+//
+// There is no source mapping in the module image, so only the instruction breakpoints
+// are supported, and the disassembly is produced by `format_bytecode_as_text()`.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    io::{BufRead, Write},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+use anc_context::{
+    interrupt_handle::InterruptHandle, process_context::ProcessContext,
+    thread_context::ThreadContext,
+};
+use anc_image::bytecode_reader::format_bytecode_as_text;
+use anc_isa::ForeignValue;
+use serde_json::{json, Value};
+
+use crate::{
+    backtrace::capture_backtrace,
+    debugger::{get_operands, read_local_variables, Breakpoint, DebugStatus, Debugger},
+    process::process_function,
+    program::start_program,
+    ProcessorError, ProcessorErrorType,
+};
+
+thread_local! {
+    // The debug session of the current process, it is `None` if the program is not being debugged.
+    //
+    // Similar to `PROCESS_CONTEXT_ADDRESS`, it is thread-local to avoid conflicts between
+    // unit tests, and the child threads inherit it from the parent thread.
+    pub static DEBUG_SESSION: RefCell<Option<Arc<DebugSession>>> = const { RefCell::new(None) };
+}
+
+// The DAP frame ID and variables reference are integers, they are composed of
+// the thread ID and the index of the frame:
+//
+// - frame ID = thread ID * FRAME_ID_STRIDE + frame index
+// - variables reference = frame ID * 2 + 1 (the local variables)
+// - variables reference = frame ID * 2 + 2 (the operands)
+const FRAME_ID_STRIDE: i64 = 0x1_0000;
+
+// The maximum length of the content of a DAP message, the messages
+// sent by the editors are far smaller than this.
+pub const MAX_MESSAGE_CONTENT_LENGTH: usize = 1024 * 1024;
+
+// The content is read in chunks, so the memory is allocated as the data arrives
+// rather than according to the (untrusted) content length.
+const MESSAGE_CONTENT_CHUNK_LENGTH: usize = 64 * 1024;
+
+/// Reads a DAP message from the reader.
+///
+/// Returns `None` if the reader reaches the end, and returns the error `InvalidData`
+/// if the content length exceeds `MAX_MESSAGE_CONTENT_LENGTH`.
+pub fn read_message<R: BufRead>(reader: &mut R) -> std::io::Result<Option<Value>> {
+    let mut content_length: Option<usize> = None;
+
+    // read the header
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            } else {
+                continue;
+            }
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length.unwrap();
+    if content_length > MAX_MESSAGE_CONTENT_LENGTH {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("The content length {} is too large.", content_length),
+        ));
+    }
+
+    // read the content
+    let mut content = Vec::with_capacity(content_length.min(MESSAGE_CONTENT_CHUNK_LENGTH));
+    let mut chunk = [0u8; MESSAGE_CONTENT_CHUNK_LENGTH];
+    while content.len() < content_length {
+        let length = (content_length - content.len()).min(MESSAGE_CONTENT_CHUNK_LENGTH);
+        reader.read_exact(&mut chunk[..length])?;
+        content.extend_from_slice(&chunk[..length]);
+    }
+
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Writes a DAP message to the writer.
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> std::io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+/// Starts the program under the control of the DAP server.
+///
+/// The program is started after the client sends the "configurationDone" request
+/// (or the client is disconnected), and the "exited" and "terminated" events are
+/// sent when the program is finished.
+///
+/// This function returns after the server thread is finished, i.e., the client
+/// sends the "disconnect" request or the reader reaches the end.
+pub fn start_program_with_debug_adapter<R, W>(
+    process_context: &ProcessContext,
+    internal_entry_point_name: &str,
+    thread_start_data: Vec<u8>,
+    reader: R,
+    writer: W,
+) -> Result<u32, ProcessorError>
+where
+    R: BufRead + Send + 'static,
+    W: Write + Send + 'static,
+{
+    let session = Arc::new(DebugSession::new(Box::new(writer)));
+    let (configuration_done_tx, configuration_done_rx) = mpsc::channel::<()>();
+
+    let server_session = session.clone();
+    let server_thread =
+        std::thread::spawn(move || serve(&server_session, reader, configuration_done_tx));
+
+    // Wait until the client finishes the configuration (e.g. setting breakpoints).
+    let _ = configuration_done_rx.recv();
+
+    DEBUG_SESSION.with(|data| {
+        data.replace(Some(session.clone()));
+    });
+
+    let result = start_program(
+        process_context,
+        internal_entry_point_name,
+        thread_start_data,
+    );
+
+    DEBUG_SESSION.with(|data| {
+        data.replace(None);
+    });
+
+    let exit_code = match &result {
+        Ok(exit_code) => *exit_code as i64,
+        Err(ProcessorError {
+            error_type: ProcessorErrorType::Terminate(terminate_code),
+            ..
+        }) => *terminate_code as i64,
+        Err(_) => -1,
+    };
+
+    session.send_event("exited", json!({ "exitCode": exit_code }));
+    session.send_event("terminated", json!({}));
+
+    // The client disconnects after receiving the "terminated" event.
+    let _ = server_thread.join();
+
+    result
+}
+
+/// Calls the function, the function is executed by the debugger
+/// if the program is being debugged.
+pub(crate) fn process_function_or_debug(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_public_index: usize,
+    arguments: &[ForeignValue],
+    thread_name: &str,
+) -> Result<Vec<ForeignValue>, ProcessorError> {
+    let opt_session = DEBUG_SESSION.with(|data| data.borrow().clone());
+
+    match opt_session {
+        Some(session) => debug_function(
+            &session,
+            thread_context,
+            module_index,
+            function_public_index,
+            arguments,
+            thread_name,
+        ),
+        None => process_function(
+            thread_context,
+            module_index,
+            function_public_index,
+            arguments,
+        ),
+    }
+}
+
+pub struct DebugSession {
+    output: Mutex<MessageWriter>,
+    state: Mutex<SessionState>,
+}
+
+struct MessageWriter {
+    writer: Box<dyn Write + Send>,
+    next_seq: i64,
+}
+
+#[derive(Default)]
+struct SessionState {
+    // The instruction breakpoints, they are shared by all threads.
+    breakpoints: HashSet<Breakpoint>,
+    stop_on_entry: bool,
+    is_disconnected: bool,
+    threads: BTreeMap</* thread id */ i64, DebugThread>,
+    next_thread_id: i64,
+}
+
+struct DebugThread {
+    name: String,
+    command_tx: Sender<ThreadCommand>,
+    interrupt_handle: InterruptHandle,
+    is_paused: bool,
+    is_pause_requested: bool,
+}
+
+enum ThreadCommand {
+    Resume(ResumeKind),
+    Inspect(InspectRequest, Sender<Result<Value, String>>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ResumeKind {
+    Continue,
+    StepOver,
+    StepIn,
+    StepOut,
+}
+
+enum InspectRequest {
+    StackTrace,
+    Scopes {
+        frame_index: usize,
+    },
+    Variables {
+        frame_index: usize,
+        is_operands: bool,
+    },
+    Disassemble {
+        location: Breakpoint,
+        instruction_offset: i64,
+        instruction_count: usize,
+    },
+}
+
+impl DebugSession {
+    fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            output: Mutex::new(MessageWriter {
+                writer,
+                next_seq: 1,
+            }),
+            state: Mutex::new(SessionState {
+                next_thread_id: 1,
+                ..SessionState::default()
+            }),
+        }
+    }
+
+    fn send(&self, mut message: Value) {
+        let mut output = self.output.lock().unwrap();
+        message["seq"] = json!(output.next_seq);
+        output.next_seq += 1;
+
+        // The client may have been disconnected, the error is ignored.
+        let _ = write_message(&mut output.writer, &message);
+    }
+
+    fn send_event(&self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    fn send_response(&self, request: &Value, result: Result<Value, String>) {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+
+        self.send(response);
+    }
+
+    fn register_thread(
+        &self,
+        name: &str,
+        command_tx: Sender<ThreadCommand>,
+        interrupt_handle: InterruptHandle,
+    ) -> (/* thread id */ i64, /* stop on entry */ bool) {
+        let mut state = self.state.lock().unwrap();
+        let thread_id = state.next_thread_id;
+        state.next_thread_id += 1;
+        state.threads.insert(
+            thread_id,
+            DebugThread {
+                name: name.to_owned(),
+                command_tx,
+                interrupt_handle,
+                is_paused: false,
+                is_pause_requested: false,
+            },
+        );
+        (thread_id, state.stop_on_entry)
+    }
+
+    fn unregister_thread(&self, thread_id: i64) {
+        self.state.lock().unwrap().threads.remove(&thread_id);
+    }
+
+    /// Marks the thread as paused, returns `false` if the client has been
+    /// disconnected, in which case the thread should continue running.
+    fn mark_thread_paused(&self, thread_id: i64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.is_disconnected {
+            return false;
+        }
+
+        if let Some(thread) = state.threads.get_mut(&thread_id) {
+            thread.is_paused = true;
+        }
+        true
+    }
+
+    fn take_pause_request(&self, thread_id: i64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.threads.get_mut(&thread_id) {
+            Some(thread) => std::mem::take(&mut thread.is_pause_requested),
+            None => false,
+        }
+    }
+
+    fn get_breakpoints(&self) -> Vec<Breakpoint> {
+        self.state
+            .lock()
+            .unwrap()
+            .breakpoints
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    fn set_instruction_breakpoints(&self, arguments: &Value) -> Result<Value, String> {
+        let mut state = self.state.lock().unwrap();
+
+        // The request replaces all existing breakpoints.
+        state.breakpoints.clear();
+
+        let items = arguments["breakpoints"]
+            .as_array()
+            .map(|items| items.as_slice())
+            .unwrap_or_default();
+
+        let breakpoints = items
+            .iter()
+            .map(|item| {
+                let reference = item["instructionReference"].as_str().unwrap_or_default();
+                let offset = item["offset"].as_i64().unwrap_or(0);
+
+                let opt_location = parse_location(reference).and_then(|location| {
+                    let instruction_address =
+                        (location.instruction_address as i64).checked_add(offset)?;
+                    Some(Breakpoint {
+                        instruction_address: usize::try_from(instruction_address).ok()?,
+                        ..location
+                    })
+                });
+
+                match opt_location {
+                    Some(location) => {
+                        state.breakpoints.insert(location);
+                        json!({
+                            "verified": true,
+                            "instructionReference": format_location(&location),
+                        })
+                    }
+                    None => json!({
+                        "verified": false,
+                        "message": format!("Invalid instruction reference \"{}\".", reference),
+                    }),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn get_threads(&self) -> Value {
+        let state = self.state.lock().unwrap();
+        let threads = state
+            .threads
+            .iter()
+            .map(|(thread_id, thread)| json!({ "id": thread_id, "name": thread.name }))
+            .collect::<Vec<_>>();
+        json!({ "threads": threads })
+    }
+
+    fn pause_thread(&self, thread_id: i64) -> Result<Value, String> {
+        let mut state = self.state.lock().unwrap();
+        let thread = state
+            .threads
+            .get_mut(&thread_id)
+            .ok_or_else(|| format!("Thread {} not found.", thread_id))?;
+
+        if !thread.is_paused {
+            thread.is_pause_requested = true;
+            thread.interrupt_handle.interrupt();
+        }
+
+        Ok(json!({}))
+    }
+
+    fn resume_thread(&self, thread_id: i64, resume_kind: ResumeKind) -> Result<Value, String> {
+        let mut state = self.state.lock().unwrap();
+        let thread = state
+            .threads
+            .get_mut(&thread_id)
+            .ok_or_else(|| format!("Thread {} not found.", thread_id))?;
+
+        if !thread.is_paused {
+            return Err(format!("Thread {} is not paused.", thread_id));
+        }
+
+        thread.is_paused = false;
+        thread
+            .command_tx
+            .send(ThreadCommand::Resume(resume_kind))
+            .map_err(|_| format!("Thread {} has exited.", thread_id))?;
+
+        Ok(json!({ "allThreadsContinued": false }))
+    }
+
+    /// Sends the inspecting request to the paused thread and waits for the reply.
+    ///
+    /// The request is sent to an arbitrary paused thread if the `thread_id` is `None`.
+    fn inspect(
+        &self,
+        opt_thread_id: Option<i64>,
+        request: InspectRequest,
+    ) -> Result<Value, String> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+
+        {
+            let state = self.state.lock().unwrap();
+            let thread = match opt_thread_id {
+                Some(thread_id) => state
+                    .threads
+                    .get(&thread_id)
+                    .filter(|thread| thread.is_paused)
+                    .ok_or_else(|| format!("Thread {} is not paused.", thread_id))?,
+                None => state
+                    .threads
+                    .values()
+                    .find(|thread| thread.is_paused)
+                    .ok_or_else(|| "No paused thread.".to_owned())?,
+            };
+
+            thread
+                .command_tx
+                .send(ThreadCommand::Inspect(request, reply_tx))
+                .map_err(|_| "The thread has exited.".to_owned())?;
+        }
+
+        reply_rx
+            .recv()
+            .map_err(|_| "The thread has exited.".to_owned())?
+    }
+
+    /// Removes all breakpoints and resumes all paused threads,
+    /// so that the program runs to the end.
+    fn disconnect(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.is_disconnected = true;
+        state.breakpoints.clear();
+
+        for thread in state.threads.values_mut() {
+            if thread.is_paused {
+                thread.is_paused = false;
+                let _ = thread
+                    .command_tx
+                    .send(ThreadCommand::Resume(ResumeKind::Continue));
+            }
+        }
+    }
+}
+
+/// Reads and handles the requests from the client until the client is disconnected.
+fn serve<R: BufRead>(session: &DebugSession, mut reader: R, configuration_done_tx: Sender<()>) {
+    while let Ok(Some(message)) = read_message(&mut reader) {
+        if message["type"] != "request" {
+            continue;
+        }
+
+        let command = message["command"].as_str().unwrap_or_default();
+        let arguments = &message["arguments"];
+        let thread_id = arguments["threadId"].as_i64().unwrap_or(0);
+
+        let result = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsInstructionBreakpoints": true,
+                "supportsDisassembleRequest": true,
+                "supportsSteppingGranularity": true,
+            })),
+            "launch" | "attach" => {
+                session.state.lock().unwrap().stop_on_entry =
+                    arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(json!({}))
+            }
+            "setBreakpoints" => {
+                // There is no source mapping in the module image.
+                let count = arguments["breakpoints"]
+                    .as_array()
+                    .map(|items| items.len())
+                    .unwrap_or(0);
+                let breakpoints = (0..count)
+                    .map(|_| {
+                        json!({
+                            "verified": false,
+                            "message": "Source breakpoints are not supported, use instruction breakpoints instead.",
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Ok(json!({ "breakpoints": breakpoints }))
+            }
+            "setInstructionBreakpoints" => session.set_instruction_breakpoints(arguments),
+            "setExceptionBreakpoints" => Ok(json!({})),
+            "configurationDone" => {
+                let _ = configuration_done_tx.send(());
+                Ok(json!({}))
+            }
+            "threads" => Ok(session.get_threads()),
+            "pause" => session.pause_thread(thread_id),
+            "continue" => session.resume_thread(thread_id, ResumeKind::Continue),
+            "next" => session.resume_thread(thread_id, ResumeKind::StepOver),
+            "stepIn" => session.resume_thread(thread_id, ResumeKind::StepIn),
+            "stepOut" => session.resume_thread(thread_id, ResumeKind::StepOut),
+            "stackTrace" => session.inspect(Some(thread_id), InspectRequest::StackTrace),
+            "scopes" => {
+                let frame_id = arguments["frameId"].as_i64().unwrap_or(0);
+                session.inspect(
+                    Some(frame_id / FRAME_ID_STRIDE),
+                    InspectRequest::Scopes {
+                        frame_index: (frame_id % FRAME_ID_STRIDE) as usize,
+                    },
+                )
+            }
+            "variables" => {
+                let variables_reference = arguments["variablesReference"].as_i64().unwrap_or(0);
+                let frame_id = (variables_reference - 1) / 2;
+                session.inspect(
+                    Some(frame_id / FRAME_ID_STRIDE),
+                    InspectRequest::Variables {
+                        frame_index: (frame_id % FRAME_ID_STRIDE) as usize,
+                        is_operands: variables_reference % 2 == 0,
+                    },
+                )
+            }
+            "disassemble" => {
+                let reference = arguments["memoryReference"].as_str().unwrap_or_default();
+                match parse_location(reference) {
+                    Some(location) => session.inspect(
+                        None,
+                        InspectRequest::Disassemble {
+                            location,
+                            instruction_offset: arguments["instructionOffset"]
+                                .as_i64()
+                                .unwrap_or(0),
+                            instruction_count: arguments["instructionCount"].as_u64().unwrap_or(0)
+                                as usize,
+                        },
+                    ),
+                    None => Err(format!("Invalid memory reference \"{}\".", reference)),
+                }
+            }
+            "disconnect" => {
+                session.disconnect();
+                Ok(json!({}))
+            }
+            _ => Err(format!("Unsupported request \"{}\".", command)),
+        };
+
+        session.send_response(&message, result);
+
+        match command {
+            "initialize" => session.send_event("initialized", json!({})),
+            "disconnect" => break,
+            _ => {}
+        }
+    }
+
+    // The client is gone, let the program run to the end.
+    session.disconnect();
+    let _ = configuration_done_tx.send(());
+}
+
+/// Executes the function by the debugger, and handles the commands from
+/// the server while the thread is paused.
+fn debug_function(
+    session: &DebugSession,
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_public_index: usize,
+    arguments: &[ForeignValue],
+    thread_name: &str,
+) -> Result<Vec<ForeignValue>, ProcessorError> {
+    let mut debugger = Debugger::new();
+    debugger.start_function(
+        thread_context,
+        module_index,
+        function_public_index,
+        arguments,
+    )?;
+
+    let (command_tx, command_rx) = mpsc::channel();
+    let (thread_id, stop_on_entry) = session.register_thread(
+        thread_name,
+        command_tx,
+        thread_context.get_interrupt_handle(),
+    );
+    session.send_event(
+        "thread",
+        json!({ "reason": "started", "threadId": thread_id }),
+    );
+
+    // The reason and the description of the stopping.
+    let mut opt_stop: Option<(&str, Option<String>)> = if stop_on_entry {
+        Some(("entry", None))
+    } else {
+        None
+    };
+
+    // The error (termination or trap) which is returned after the
+    // user inspects the state of the thread and resumes the thread.
+    let mut opt_pending_error: Option<ProcessorError> = None;
+
+    let outcome = loop {
+        let mut resume_kind = ResumeKind::Continue;
+
+        if let Some((reason, description)) = opt_stop.take() {
+            if session.mark_thread_paused(thread_id) {
+                session.send_event(
+                    "stopped",
+                    json!({
+                        "reason": reason,
+                        "description": description,
+                        "threadId": thread_id,
+                        "allThreadsStopped": false,
+                    }),
+                );
+
+                resume_kind = wait_for_resuming(&command_rx, thread_context, thread_id);
+            }
+        }
+
+        if let Some(error) = opt_pending_error.take() {
+            break Err(error);
+        }
+
+        // Apply the latest breakpoints.
+        debugger.clear_all_breakpoints();
+        for breakpoint in session.get_breakpoints() {
+            debugger.set_breakpoint(
+                breakpoint.module_index,
+                breakpoint.function_internal_index,
+                breakpoint.instruction_address,
+            );
+        }
+
+        let result = match resume_kind {
+            ResumeKind::Continue => debugger.run(thread_context),
+            ResumeKind::StepOver => debugger.step_over(thread_context),
+            ResumeKind::StepIn => debugger.step_instruction(thread_context),
+            ResumeKind::StepOut => debugger.step_out(thread_context),
+        };
+
+        match result {
+            Ok(DebugStatus::Finished(values)) => break Ok(values),
+            Ok(DebugStatus::BreakpointHit(_)) => opt_stop = Some(("breakpoint", None)),
+            Ok(DebugStatus::Paused) => opt_stop = Some(("step", None)),
+            Err(error) => match error.error_type {
+                ProcessorErrorType::Interrupted if session.take_pause_request(thread_id) => {
+                    thread_context.get_interrupt_handle().clear();
+                    opt_stop = Some(("pause", None));
+                }
+                ProcessorErrorType::Terminate(_) | ProcessorErrorType::Trap { .. } => {
+                    // Pause at the faulting instruction so that the state can be inspected.
+                    opt_stop = Some(("exception", Some(error.to_string())));
+                    opt_pending_error = Some(error);
+                }
+                _ => break Err(error),
+            },
+        }
+    };
+
+    debugger.finish(thread_context);
+
+    session.unregister_thread(thread_id);
+    session.send_event(
+        "thread",
+        json!({ "reason": "exited", "threadId": thread_id }),
+    );
+
+    outcome
+}
+
+fn wait_for_resuming(
+    command_rx: &Receiver<ThreadCommand>,
+    thread_context: &mut ThreadContext,
+    thread_id: i64,
+) -> ResumeKind {
+    while let Ok(command) = command_rx.recv() {
+        match command {
+            ThreadCommand::Resume(resume_kind) => return resume_kind,
+            ThreadCommand::Inspect(request, reply_tx) => {
+                let _ = reply_tx.send(inspect(thread_context, thread_id, request));
+            }
+        }
+    }
+
+    // The session is gone.
+    ResumeKind::Continue
+}
+
+fn inspect(
+    thread_context: &mut ThreadContext,
+    thread_id: i64,
+    request: InspectRequest,
+) -> Result<Value, String> {
+    let backtrace = capture_backtrace(thread_context);
+
+    match request {
+        InspectRequest::StackTrace => {
+            let stack_frames = backtrace
+                .iter()
+                .enumerate()
+                .map(|(frame_index, frame)| {
+                    json!({
+                        "id": thread_id * FRAME_ID_STRIDE + frame_index as i64,
+                        "name": frame.get_name(),
+                        "line": 0,
+                        "column": 0,
+                        "instructionPointerReference": format_location(&Breakpoint {
+                            module_index: frame.module_index,
+                            function_internal_index: frame.function_internal_index,
+                            instruction_address: frame.instruction_address,
+                        }),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            Ok(json!({
+                "stackFrames": stack_frames,
+                "totalFrames": backtrace.len(),
+            }))
+        }
+        InspectRequest::Scopes { frame_index } => {
+            if frame_index >= backtrace.len() {
+                return Err(format!("Frame {} not found.", frame_index));
+            }
+
+            let frame_id = thread_id * FRAME_ID_STRIDE + frame_index as i64;
+            let mut scopes = vec![json!({
+                "name": "Locals",
+                "variablesReference": frame_id * 2 + 1,
+                "expensive": false,
+            })];
+
+            // Only the operands of the current frame are accessible.
+            if frame_index == 0 {
+                scopes.push(json!({
+                    "name": "Operands",
+                    "variablesReference": frame_id * 2 + 2,
+                    "expensive": false,
+                }));
+            }
+
+            Ok(json!({ "scopes": scopes }))
+        }
+        InspectRequest::Variables {
+            frame_index,
+            is_operands,
+        } => {
+            let variables = if is_operands {
+                get_operands(thread_context)
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| {
+                        json!({
+                            "name": format!("#{}", idx),
+                            "value": format!("{} (0x{:016x})", *value as i64, value),
+                            "variablesReference": 0,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                let frame = backtrace
+                    .get(frame_index)
+                    .ok_or_else(|| format!("Frame {} not found.", frame_index))?;
+                let (local_variable_list_index, local_variables_start_address) = thread_context
+                    .stack
+                    .get_function_frames_local_variable_list_index_and_start_address()
+                    .get(frame_index)
+                    .copied()
+                    .ok_or_else(|| format!("Frame {} not found.", frame_index))?;
+
+                read_local_variables(
+                    thread_context,
+                    frame.module_index,
+                    local_variable_list_index,
+                    local_variables_start_address,
+                )
+                .iter()
+                .enumerate()
+                .map(|(idx, data)| {
+                    json!({
+                        "name": format!("local {}", idx),
+                        "value": format_local_variable_value(data),
+                        "variablesReference": 0,
+                    })
+                })
+                .collect::<Vec<_>>()
+            };
+
+            Ok(json!({ "variables": variables }))
+        }
+        InspectRequest::Disassemble {
+            location,
+            instruction_offset,
+            instruction_count,
+        } => {
+            let module_common_instance = thread_context
+                .module_common_instances
+                .get(location.module_index)
+                .ok_or_else(|| format!("Module {} not found.", location.module_index))?;
+            let function_section = &module_common_instance.function_section;
+            let function_item = function_section
+                .items
+                .get(location.function_internal_index)
+                .ok_or_else(|| {
+                    format!("Function {} not found.", location.function_internal_index)
+                })?;
+
+            let code_offset = function_item.code_offset as usize;
+            let code_length = function_item.code_length as usize;
+            let text = format_bytecode_as_text(
+                &function_section.codes_data[code_offset..(code_offset + code_length)],
+            );
+
+            // Each line of the text starts with the address (relative to the
+            // start of the function) of the instruction, e.g. "0x0008  ...".
+            let lines = text
+                .lines()
+                .filter_map(|line| {
+                    let (address, instruction) = line.trim().split_once(char::is_whitespace)?;
+                    let address = usize::from_str_radix(
+                        address.trim_end_matches(':').strip_prefix("0x")?,
+                        16,
+                    )
+                    .ok()?;
+                    Some((code_offset + address, instruction.trim().to_owned()))
+                })
+                .collect::<Vec<_>>();
+
+            let position = lines
+                .iter()
+                .position(|(address, _)| *address == location.instruction_address)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid instruction address 0x{:04x}.",
+                        location.instruction_address
+                    )
+                })? as i64;
+
+            let instructions = (0..instruction_count as i64)
+                .map(|idx| {
+                    let opt_line = usize::try_from(position + instruction_offset + idx)
+                        .ok()
+                        .and_then(|line_index| lines.get(line_index));
+
+                    match opt_line {
+                        Some((address, instruction)) => json!({
+                            "address": format_location(&Breakpoint {
+                                instruction_address: *address,
+                                ..location
+                            }),
+                            "instruction": instruction,
+                        }),
+                        None => json!({
+                            "address": "",
+                            "instruction": "",
+                            "presentationHint": "invalid",
+                        }),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            Ok(json!({ "instructions": instructions }))
+        }
+    }
+}
+
+fn format_local_variable_value(data: &[u8]) -> String {
+    match data.len() {
+        4 => {
+            let value = u32::from_le_bytes(data.try_into().unwrap());
+            format!("{} (0x{:08x})", value as i32, value)
+        }
+        8 => {
+            let value = u64::from_le_bytes(data.try_into().unwrap());
+            format!("{} (0x{:016x})", value as i64, value)
+        }
+        _ => data
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Formats the location as "{module index}:{function internal index}:0x{instruction address}".
+fn format_location(location: &Breakpoint) -> String {
+    format!(
+        "{}:{}:0x{:04x}",
+        location.module_index, location.function_internal_index, location.instruction_address
+    )
+}
+
+fn parse_location(text: &str) -> Option<Breakpoint> {
+    let mut parts = text.trim().split(':');
+    let module_index = parts.next()?.parse::<usize>().ok()?;
+    let function_internal_index = parts.next()?.parse::<usize>().ok()?;
+    let instruction_address = usize::from_str_radix(parts.next()?.strip_prefix("0x")?, 16).ok()?;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(Breakpoint {
+        module_index,
+        function_internal_index,
+        instruction_address,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        io::{BufReader, Cursor, Read, Write},
+        sync::{
+            mpsc::{self, Receiver, Sender},
+            Arc, Mutex,
+        },
+        time::{Duration, Instant},
+    };
+
+    use anc_context::program_source::ProgramSource;
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::helper_build_module_binary_with_single_function,
+    };
+    use anc_isa::{opcode::Opcode, OperandDataType};
+    use serde_json::{json, Value};
+
+    use crate::in_memory_program_source::InMemoryProgramSource;
+
+    use super::{
+        format_location, parse_location, read_message, start_program_with_debug_adapter,
+        write_message,
+    };
+
+    // A reader which receives the data from a channel, it simulates the stdin.
+    struct ChannelReader {
+        rx: Receiver<Vec<u8>>,
+        buffer: Vec<u8>,
+        position: usize,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.position == self.buffer.len() {
+                match self.rx.recv() {
+                    Ok(data) => {
+                        self.buffer = data;
+                        self.position = 0;
+                    }
+                    Err(_) => return Ok(0),
+                }
+            }
+
+            let length = buf.len().min(self.buffer.len() - self.position);
+            buf[..length].copy_from_slice(&self.buffer[self.position..(self.position + length)]);
+            self.position += length;
+            Ok(length)
+        }
+    }
+
+    // A writer which writes the data to a shared buffer, it simulates the stdout.
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct TestClient {
+        tx: Sender<Vec<u8>>,
+        output: Arc<Mutex<Vec<u8>>>,
+        next_seq: i64,
+        consumed_message_indices: HashSet<usize>,
+    }
+
+    impl TestClient {
+        fn send_request(&mut self, command: &str, arguments: Value) -> i64 {
+            let seq = self.next_seq;
+            self.next_seq += 1;
+
+            let mut data = vec![];
+            write_message(
+                &mut data,
+                &json!({
+                    "seq": seq,
+                    "type": "request",
+                    "command": command,
+                    "arguments": arguments,
+                }),
+            )
+            .unwrap();
+            self.tx.send(data).unwrap();
+            seq
+        }
+
+        /// Waits for the first unconsumed message which matches the predicate.
+        fn wait_for(&mut self, predicate: impl Fn(&Value) -> bool) -> Value {
+            let start = Instant::now();
+            loop {
+                let data = self.output.lock().unwrap().clone();
+                let mut reader = Cursor::new(data);
+                let mut messages = vec![];
+                while let Some(message) = read_message(&mut reader).unwrap() {
+                    messages.push(message);
+                }
+
+                // The responses and events may arrive in any order, e.g. the "stopped"
+                // event may arrive before the response of "configurationDone".
+                if let Some(idx) = (0..messages.len()).find(|idx| {
+                    !self.consumed_message_indices.contains(idx) && predicate(&messages[*idx])
+                }) {
+                    self.consumed_message_indices.insert(idx);
+                    return messages.remove(idx);
+                }
+
+                if start.elapsed() > Duration::from_secs(10) {
+                    panic!("Timeout while waiting for the message.");
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        fn request(&mut self, command: &str, arguments: Value) -> Value {
+            let seq = self.send_request(command, arguments);
+            let response = self
+                .wait_for(|message| message["type"] == "response" && message["request_seq"] == seq);
+            assert_eq!(response["success"], true, "{}", response);
+            response["body"].clone()
+        }
+
+        fn wait_for_event(&mut self, event: &str) -> Value {
+            self.wait_for(|message| message["type"] == "event" && message["event"] == event)["body"]
+                .clone()
+        }
+    }
+
+    #[test]
+    fn test_read_and_write_message() {
+        let mut data = vec![];
+        write_message(&mut data, &json!({"seq": 1, "type": "request"})).unwrap();
+        write_message(&mut data, &json!({"seq": 2, "type": "event"})).unwrap();
+
+        assert!(data.starts_with(b"Content-Length: 26\r\n\r\n{"));
+
+        let mut reader = Cursor::new(data);
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(json!({"seq": 1, "type": "request"}))
+        );
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(json!({"seq": 2, "type": "event"}))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+
+        // the content length is not trusted
+        let mut reader = Cursor::new(b"Content-Length: 18446744073709551615\r\n\r\n{}".to_vec());
+        assert_eq!(
+            read_message(&mut reader).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+
+        // the content is shorter than the content length
+        let mut reader = Cursor::new(b"Content-Length: 100000\r\n\r\n{}".to_vec());
+        assert_eq!(
+            read_message(&mut reader).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_location_reference() {
+        let location = parse_location("1:2:0x0010").unwrap();
+        assert_eq!(location.module_index, 1);
+        assert_eq!(location.function_internal_index, 2);
+        assert_eq!(location.instruction_address, 0x10);
+        assert_eq!(format_location(&location), "1:2:0x0010");
+
+        assert!(parse_location("1:2").is_none());
+        assert!(parse_location("1:2:16").is_none());
+        assert!(parse_location("1:2:0x10:3").is_none());
+    }
+
+    #[test]
+    fn test_debug_adapter_session() {
+        // fn () -> (i32)
+        //     imm_i32(11)      ;; 0x0000
+        //     imm_i32(13)      ;; 0x0008
+        //     add_i32          ;; 0x0010
+        //     end              ;; 0x0012
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::imm_i32, 13)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let (tx, rx) = mpsc::channel();
+        let output = Arc::new(Mutex::new(vec![]));
+
+        let reader = BufReader::new(ChannelReader {
+            rx,
+            buffer: vec![],
+            position: 0,
+        });
+        let writer = SharedWriter(output.clone());
+
+        let program_thread = std::thread::spawn(move || {
+            let resource0 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context0 = resource0.create_process_context().unwrap();
+            start_program_with_debug_adapter(&process_context0, "_start", vec![], reader, writer)
+        });
+
+        let mut client = TestClient {
+            tx,
+            output,
+            next_seq: 1,
+            consumed_message_indices: HashSet::new(),
+        };
+
+        let capabilities = client.request("initialize", json!({"adapterID": "anc"}));
+        assert_eq!(capabilities["supportsInstructionBreakpoints"], true);
+        client.wait_for_event("initialized");
+
+        client.request("launch", json!({}));
+
+        let breakpoints = client.request(
+            "setInstructionBreakpoints",
+            json!({"breakpoints": [{"instructionReference": "0:0:0x0010"}]}),
+        );
+        assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+
+        client.request("configurationDone", json!({}));
+
+        // hit the breakpoint
+        let stopped = client.wait_for_event("stopped");
+        assert_eq!(stopped["reason"], "breakpoint");
+        let thread_id = stopped["threadId"].clone();
+
+        let threads = client.request("threads", json!({}));
+        assert_eq!(threads["threads"].as_array().unwrap().len(), 1);
+        assert_eq!(threads["threads"][0]["id"], thread_id);
+
+        let stack_trace = client.request("stackTrace", json!({"threadId": thread_id}));
+        assert_eq!(stack_trace["totalFrames"], 1);
+        let frame = &stack_trace["stackFrames"][0];
+        assert_eq!(frame["instructionPointerReference"], "0:0:0x0010");
+
+        let scopes = client.request("scopes", json!({"frameId": frame["id"]}));
+        let operands_scope = scopes["scopes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|scope| scope["name"] == "Operands")
+            .unwrap()
+            .clone();
+
+        let variables = client.request(
+            "variables",
+            json!({"variablesReference": operands_scope["variablesReference"]}),
+        );
+        let values = variables["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variable| variable["value"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec!["11 (0x000000000000000b)", "13 (0x000000000000000d)"]
+        );
+
+        let disassembly = client.request(
+            "disassemble",
+            json!({"memoryReference": "0:0:0x0010", "instructionOffset": 0, "instructionCount": 2}),
+        );
+        assert_eq!(disassembly["instructions"][0]["address"], "0:0:0x0010");
+        assert_eq!(disassembly["instructions"][1]["address"], "0:0:0x0012");
+
+        // step over the `add_i32`
+        client.request("next", json!({"threadId": thread_id}));
+        let stopped = client.wait_for_event("stopped");
+        assert_eq!(stopped["reason"], "step");
+
+        let stack_trace = client.request("stackTrace", json!({"threadId": thread_id}));
+        assert_eq!(
+            stack_trace["stackFrames"][0]["instructionPointerReference"],
+            "0:0:0x0012"
+        );
+
+        client.request("continue", json!({"threadId": thread_id}));
+
+        let exited = client.wait_for_event("exited");
+        assert_eq!(exited["exitCode"], 24);
+        client.wait_for_event("terminated");
+
+        // the server thread is joined after the client is disconnected
+        client.request("disconnect", json!({}));
+        assert_eq!(program_thread.join().unwrap().unwrap(), 24);
+    }
+}
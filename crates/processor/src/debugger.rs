@@ -0,0 +1,643 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Step debugger
+// -------------
+//
+// The debugger executes the bytecode of a thread instruction by instruction,
+// and pauses when:
+//
+// - a breakpoint is hit, i.e., the program counter reaches the location of a breakpoint.
+//   The instruction at the breakpoint has not been executed yet.
+// - a stepping is completed:
+//   - "step instruction": one instruction is executed.
+//   - "step over": one instruction is executed, if it is a calling instruction
+//     (e.g. `call` and `call_dynamic`), the execution continues until the callee returns.
+//   - "step out": the execution continues until the current function returns.
+//
+// The stepping is determined by the number of function frames on the stack
+// (i.e., the "depth" of the calling chain), rather than the instruction address,
+// so the recursive functions are handled correctly.
+//
+// While the debugger is paused, the operand stack, the local variables and
+// the data of the thread can be inspected.
+//
+// Note that the callback functions (which are called by the external functions)
+// are executed by their own instruction processing loop, they can not be stepped into.
+
+use std::collections::HashSet;
+
+use anc_context::thread_context::{ThreadContext, TrapKind};
+use anc_isa::{ForeignValue, OperandDataType, OPERAND_SIZE_IN_BYTES};
+
+use crate::{
+    backtrace::{capture_backtrace, BacktraceFrame},
+    process::{
+        build_processor_error, pop_results, prepare_function, process_next_metered_instruction,
+    },
+    ProcessorError, ProcessorErrorType,
+};
+
+/// The location of a breakpoint, it is the same as the `ProgramCounter`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Breakpoint {
+    pub module_index: usize,
+    pub function_internal_index: usize,
+
+    // The address of the instruction, which is relative to the start of the
+    // code of the function section, rather than the start of the function.
+    pub instruction_address: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DebugStatus {
+    // The stepping is completed.
+    Paused,
+
+    // The program counter reaches a breakpoint,
+    // the instruction at the breakpoint is not executed yet.
+    BreakpointHit(Breakpoint),
+
+    // The entry function is finished, with the results.
+    Finished(Vec<ForeignValue>),
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    breakpoints: HashSet<Breakpoint>,
+
+    // The data types of the results of the entry function,
+    // it is `None` if the debugging is not started or it has been finished.
+    entry_function_results: Option<Vec<OperandDataType>>,
+
+    // The JIT threshold of the thread before the debugging is started,
+    // it is restored when the debugging is finished.
+    saved_jit_threshold: Option<u32>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a breakpoint, returns `false` if the breakpoint already exists.
+    pub fn set_breakpoint(
+        &mut self,
+        module_index: usize,
+        function_internal_index: usize,
+        instruction_address: usize,
+    ) -> bool {
+        self.breakpoints.insert(Breakpoint {
+            module_index,
+            function_internal_index,
+            instruction_address,
+        })
+    }
+
+    /// Removes a breakpoint, returns `false` if the breakpoint does not exist.
+    pub fn clear_breakpoint(
+        &mut self,
+        module_index: usize,
+        function_internal_index: usize,
+        instruction_address: usize,
+    ) -> bool {
+        self.breakpoints.remove(&Breakpoint {
+            module_index,
+            function_internal_index,
+            instruction_address,
+        })
+    }
+
+    pub fn clear_all_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Returns all breakpoints, ordered by their locations.
+    pub fn get_breakpoints(&self) -> Vec<Breakpoint> {
+        let mut breakpoints = self.breakpoints.iter().copied().collect::<Vec<_>>();
+        breakpoints.sort();
+        breakpoints
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.entry_function_results.is_some()
+    }
+
+    /// Prepares the calling of the entry function and pauses at its first instruction.
+    pub fn start_function(
+        &mut self,
+        thread_context: &mut ThreadContext,
+        module_index: usize,
+        function_public_index: usize,
+        arguments: &[ForeignValue],
+    ) -> Result<(), ProcessorError> {
+        let results = prepare_function(
+            thread_context,
+            module_index,
+            function_public_index,
+            arguments,
+        )?;
+
+        // The native code generated by the JIT compiler can not be stepped,
+        // so the functions are always executed by the interpreter while debugging.
+        self.saved_jit_threshold = thread_context.jit_threshold.take();
+        self.entry_function_results = Some(results);
+        Ok(())
+    }
+
+    /// Finishes the debugging and restores the JIT threshold of the thread.
+    ///
+    /// It is called automatically when the entry function is finished, and it should
+    /// be called if the debugging is abandoned, e.g. after the program is terminated.
+    pub fn finish(&mut self, thread_context: &mut ThreadContext) {
+        if self.entry_function_results.take().is_some() {
+            thread_context.jit_threshold = self.saved_jit_threshold.take();
+        }
+    }
+
+    /// Continues the execution until a breakpoint is hit or the entry function is finished.
+    pub fn run(
+        &mut self,
+        thread_context: &mut ThreadContext,
+    ) -> Result<DebugStatus, ProcessorError> {
+        self.execute(thread_context, |_| false)
+    }
+
+    /// Executes one instruction.
+    pub fn step_instruction(
+        &mut self,
+        thread_context: &mut ThreadContext,
+    ) -> Result<DebugStatus, ProcessorError> {
+        self.execute(thread_context, |_| true)
+    }
+
+    /// Executes one instruction, if the instruction calls a function,
+    /// continues the execution until the callee returns.
+    pub fn step_over(
+        &mut self,
+        thread_context: &mut ThreadContext,
+    ) -> Result<DebugStatus, ProcessorError> {
+        let depth = get_calling_depth(thread_context);
+        self.execute(thread_context, |thread_context| {
+            get_calling_depth(thread_context) <= depth
+        })
+    }
+
+    /// Continues the execution until the current function returns.
+    pub fn step_out(
+        &mut self,
+        thread_context: &mut ThreadContext,
+    ) -> Result<DebugStatus, ProcessorError> {
+        let depth = get_calling_depth(thread_context);
+        self.execute(thread_context, |thread_context| {
+            get_calling_depth(thread_context) < depth
+        })
+    }
+
+    /// Executes at least one instruction, and then pauses when the predicate `should_pause`
+    /// returns `true` or a breakpoint is hit.
+    ///
+    /// Returns `DebuggingNotStarted` if the debugging is not started or it has been finished.
+    fn execute(
+        &mut self,
+        thread_context: &mut ThreadContext,
+        should_pause: impl Fn(&ThreadContext) -> bool,
+    ) -> Result<DebugStatus, ProcessorError> {
+        if self.entry_function_results.is_none() {
+            return Err(ProcessorError::new(ProcessorErrorType::DebuggingNotStarted));
+        }
+
+        loop {
+            // The interrupt handle and the fuel are checked as the interpreter does.
+            match process_next_metered_instruction(thread_context) {
+                None => {
+                    // continue
+                }
+                Some(Ok(())) => {
+                    let results = self.entry_function_results.clone().unwrap();
+                    self.finish(thread_context);
+                    return Ok(DebugStatus::Finished(pop_results(thread_context, &results)));
+                }
+                Some(Err(error_type)) => {
+                    // The program counter and the stack remain unchanged, so
+                    // the state of the thread can still be inspected.
+                    return Err(build_processor_error(thread_context, error_type));
+                }
+            }
+
+            if should_pause(thread_context) {
+                return Ok(DebugStatus::Paused);
+            }
+
+            let location = Breakpoint {
+                module_index: thread_context.pc.module_index,
+                function_internal_index: thread_context.pc.function_internal_index,
+                instruction_address: thread_context.pc.instruction_address,
+            };
+
+            if self.breakpoints.contains(&location) {
+                return Ok(DebugStatus::BreakpointHit(location));
+            }
+        }
+    }
+}
+
+/// Returns the number of function frames on the stack.
+fn get_calling_depth(thread_context: &ThreadContext) -> usize {
+    thread_context.stack.get_return_program_counters().len()
+}
+
+/// Returns the operands of the current frame, the first item is the bottom operand.
+///
+/// Each operand is 64-bit, the `i32` and `f32` values occupy the low 32 bits.
+pub fn get_operands(thread_context: &ThreadContext) -> Vec<u64> {
+    thread_context
+        .stack
+        .get_operands()
+        .chunks_exact(OPERAND_SIZE_IN_BYTES)
+        .map(|data| u64::from_le_bytes(data.try_into().unwrap()))
+        .collect()
+}
+
+/// Returns the data of all local variables (includes arguments) of the specified frame.
+///
+/// The `layers` is the depth of the frame relative to the current frame,
+/// it can not cross the current function, i.e., only the block frames
+/// within the current function can be specified.
+///
+/// Returns `ItemNotFound` if the `layers` is out of bounds.
+pub fn get_local_variables(
+    thread_context: &ThreadContext,
+    layers: u16,
+) -> Result<Vec<Vec<u8>>, ProcessorError> {
+    if layers as usize >= thread_context.stack.get_current_function_frames_count() {
+        return Err(ProcessorError::new(ProcessorErrorType::ItemNotFound));
+    }
+
+    let (local_variable_list_index, local_variables_start_address) = thread_context
+        .stack
+        .get_local_variable_list_index_and_start_address_by_layers(layers);
+
+    Ok(read_local_variables(
+        thread_context,
+        thread_context.pc.module_index,
+        local_variable_list_index,
+        local_variables_start_address,
+    ))
+}
+
+/// Reads the data of all local variables of a frame, the frame can be located
+/// by `get_local_variable_list_index_and_start_address_by_layers()` or
+/// `get_function_frames_local_variable_list_index_and_start_address()`.
+pub(crate) fn read_local_variables(
+    thread_context: &ThreadContext,
+    module_index: usize,
+    local_variable_list_index: usize,
+    local_variables_start_address: usize,
+) -> Vec<Vec<u8>> {
+    thread_context.module_common_instances[module_index]
+        .local_variable_section
+        .get_local_variable_list(local_variable_list_index)
+        .iter()
+        .map(|variable_item| {
+            let length = variable_item.variable_actual_size_in_bytes as usize;
+            let mut data = vec![0u8; length];
+            thread_context.stack.read(
+                local_variables_start_address,
+                variable_item.variable_offset as usize,
+                length,
+                data.as_mut_ptr(),
+            );
+            data
+        })
+        .collect()
+}
+
+/// Returns the data of a local variable of the specified frame.
+///
+/// Returns `ItemNotFound` if the `layers` or the `local_variable_index` is out of bounds.
+pub fn get_local_variable(
+    thread_context: &ThreadContext,
+    layers: u16,
+    local_variable_index: usize,
+) -> Result<Vec<u8>, ProcessorError> {
+    get_local_variables(thread_context, layers)?
+        .into_iter()
+        .nth(local_variable_index)
+        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))
+}
+
+/// Returns the content of the specified data.
+pub fn read_data(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    data_public_index: usize,
+) -> Result<Vec<u8>, TrapKind> {
+    let target_data_object =
+        thread_context.get_target_data_object(module_index, data_public_index, 0, 0)?;
+
+    let data_internal_index = target_data_object.data_internal_index_in_section;
+    let length = target_data_object
+        .accessor
+        .get_data_length(data_internal_index);
+
+    let mut data = vec![0u8; length];
+    target_data_object
+        .accessor
+        .read_idx(data_internal_index, 0, length, data.as_mut_ptr());
+    Ok(data)
+}
+
+/// Returns the calling chain of the current location.
+pub fn get_backtrace(thread_context: &ThreadContext) -> Vec<BacktraceFrame> {
+    capture_backtrace(thread_context)
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_context::program_source::ProgramSource;
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadWriteDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_blocks,
+            helper_build_module_binary_with_single_function_and_data, HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource, ProcessorError, ProcessorErrorType,
+    };
+
+    use super::{
+        get_backtrace, get_local_variables, get_operands, read_data, DebugStatus, Debugger,
+    };
+
+    fn build_binary_with_two_functions() -> Vec<u8> {
+        // fn0 () -> (i32)
+        //     imm_i32(11)      ;; 0x0000
+        //     imm_i32(13)      ;; 0x0008
+        //     call(1)          ;; 0x0010
+        //     end              ;; 0x0018
+        //
+        // fn1 (a: i32, b: i32) -> (i32)
+        //     local_load_i32_u(0, 0)   ;; 0x0000
+        //     local_load_i32_u(0, 1)   ;; 0x0008
+        //     add_i32                  ;; 0x0010
+        //     end                      ;; 0x0012
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::imm_i32, 13)
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+            ],
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_debugger_step_instruction_and_step_over() {
+        let binary0 = build_binary_with_two_functions();
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        thread_context0.jit_threshold = Some(3);
+
+        let mut debugger = Debugger::new();
+        assert!(matches!(
+            debugger.step_instruction(&mut thread_context0),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DebuggingNotStarted,
+                ..
+            })
+        ));
+
+        debugger
+            .start_function(&mut thread_context0, 0, 0, &[])
+            .unwrap();
+        assert!(debugger.is_running());
+        assert_eq!(thread_context0.jit_threshold, None);
+        assert_eq!(thread_context0.pc.instruction_address, 0);
+        assert!(get_operands(&thread_context0).is_empty());
+
+        assert_eq!(
+            debugger.step_instruction(&mut thread_context0).unwrap(),
+            DebugStatus::Paused
+        );
+        assert_eq!(thread_context0.pc.instruction_address, 0x08);
+        assert_eq!(get_operands(&thread_context0), vec![11]);
+
+        assert_eq!(
+            debugger.step_instruction(&mut thread_context0).unwrap(),
+            DebugStatus::Paused
+        );
+        assert_eq!(thread_context0.pc.instruction_address, 0x10);
+        assert_eq!(get_operands(&thread_context0), vec![11, 13]);
+
+        // step over the `call` instruction
+        assert_eq!(
+            debugger.step_over(&mut thread_context0).unwrap(),
+            DebugStatus::Paused
+        );
+        assert_eq!(thread_context0.pc.function_internal_index, 0);
+        assert_eq!(thread_context0.pc.instruction_address, 0x18);
+        assert_eq!(get_operands(&thread_context0), vec![24]);
+
+        assert_eq!(
+            debugger.run(&mut thread_context0).unwrap(),
+            DebugStatus::Finished(vec![ForeignValue::U32(24)])
+        );
+        assert!(!debugger.is_running());
+        assert_eq!(thread_context0.jit_threshold, Some(3));
+
+        assert!(matches!(
+            debugger.run(&mut thread_context0),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DebuggingNotStarted,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_debugger_breakpoint_and_step_out() {
+        let binary0 = build_binary_with_two_functions();
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let code_offset1 = thread_context0.get_function_info(0, 1).code_offset;
+
+        let mut debugger = Debugger::new();
+        assert!(debugger.set_breakpoint(0, 1, code_offset1 + 0x08));
+        assert!(!debugger.set_breakpoint(0, 1, code_offset1 + 0x08));
+        assert!(debugger.set_breakpoint(0, 0, 0x08));
+        assert!(debugger.clear_breakpoint(0, 0, 0x08));
+        assert!(!debugger.clear_breakpoint(0, 0, 0x08));
+        assert_eq!(debugger.get_breakpoints().len(), 1);
+
+        debugger
+            .start_function(&mut thread_context0, 0, 0, &[])
+            .unwrap();
+
+        let status0 = debugger.run(&mut thread_context0).unwrap();
+        assert!(matches!(status0, DebugStatus::BreakpointHit(_)));
+        assert_eq!(thread_context0.pc.function_internal_index, 1);
+        assert_eq!(thread_context0.pc.instruction_address, code_offset1 + 0x08);
+
+        // inspect the current frame
+        assert_eq!(get_operands(&thread_context0), vec![11]);
+
+        let local_variables = get_local_variables(&thread_context0, 0).unwrap();
+        assert_eq!(local_variables.len(), 2);
+        assert_eq!(local_variables[0][0..4], 11u32.to_le_bytes());
+        assert_eq!(local_variables[1][0..4], 13u32.to_le_bytes());
+
+        // the function has no block frame
+        assert!(matches!(
+            get_local_variables(&thread_context0, 1),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ItemNotFound,
+                ..
+            })
+        ));
+
+        let backtrace = get_backtrace(&thread_context0);
+        assert_eq!(backtrace.len(), 2);
+        assert_eq!(backtrace[0].function_internal_index, 1);
+        assert_eq!(backtrace[1].function_internal_index, 0);
+        assert_eq!(backtrace[1].instruction_address, 0x18);
+
+        // return to the caller
+        assert_eq!(
+            debugger.step_out(&mut thread_context0).unwrap(),
+            DebugStatus::Paused
+        );
+        assert_eq!(thread_context0.pc.function_internal_index, 0);
+        assert_eq!(thread_context0.pc.instruction_address, 0x18);
+        assert_eq!(get_operands(&thread_context0), vec![24]);
+
+        assert_eq!(
+            debugger.run(&mut thread_context0).unwrap(),
+            DebugStatus::Finished(vec![ForeignValue::U32(24)])
+        );
+    }
+
+    #[test]
+    fn test_debugger_fuel() {
+        let binary0 = build_binary_with_two_functions();
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let mut debugger = Debugger::new();
+        debugger
+            .start_function(&mut thread_context0, 0, 0, &[])
+            .unwrap();
+
+        thread_context0.set_fuel(Some(1));
+        assert_eq!(
+            debugger.step_instruction(&mut thread_context0).unwrap(),
+            DebugStatus::Paused
+        );
+
+        // the instruction is not executed when the fuel is exhausted
+        assert!(matches!(
+            debugger.run(&mut thread_context0),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::OutOfFuel,
+                ..
+            })
+        ));
+        assert_eq!(thread_context0.pc.instruction_address, 0x08);
+        assert!(debugger.is_running());
+
+        thread_context0.add_fuel(100);
+        assert_eq!(
+            debugger.run(&mut thread_context0).unwrap(),
+            DebugStatus::Finished(vec![ForeignValue::U32(24)])
+        );
+    }
+
+    #[test]
+    fn test_debugger_read_data() {
+        // fn () -> ()
+        //     imm_i32(0x22)
+        //     data_store_i32(0, 0)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0x22)
+            .append_opcode_i16_i32(Opcode::data_store_i32, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_data(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            code0,
+            &[],
+            &[ReadWriteDataEntry::from_i64(0x11)],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let mut debugger = Debugger::new();
+        debugger
+            .start_function(&mut thread_context0, 0, 0, &[])
+            .unwrap();
+
+        assert_eq!(
+            read_data(&mut thread_context0, 0, 0).unwrap(),
+            0x11u64.to_le_bytes()
+        );
+
+        debugger.step_instruction(&mut thread_context0).unwrap();
+        debugger.step_instruction(&mut thread_context0).unwrap();
+
+        assert_eq!(
+            read_data(&mut thread_context0, 0, 0).unwrap(),
+            0x22u64.to_le_bytes()
+        );
+
+        assert!(read_data(&mut thread_context0, 0, 1).is_err());
+
+        assert_eq!(
+            debugger.run(&mut thread_context0).unwrap(),
+            DebugStatus::Finished(vec![])
+        );
+    }
+}
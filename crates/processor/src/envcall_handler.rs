@@ -4,53 +4,41 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
+mod capability;
 mod environment;
+mod file;
+mod fs;
 mod host;
+mod host_function;
 mod multithread;
 mod random;
 mod regex;
-mod runtime;
-mod time;
+pub mod runtime;
+pub mod time;
 
-use anc_context::thread_context::ThreadContext;
-use anc_image::bytecode_reader::format_bytecode_as_text;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 
-use crate::envcall_num::EnvCallNum;
+use crate::envcall_num::{EnvCallNum, ENVCALL_CATEGORY_HOST_FUNCTION};
 
-pub type EnvCallHandlerFunc = fn(&mut ThreadContext);
+pub type EnvCallHandlerFunc = fn(&mut ThreadContext) -> Result<(), TrapKind>;
 
-fn envcall_unreachable_handler(thread_context: &mut ThreadContext) {
-    let pc = &thread_context.pc;
-    let func_item = &thread_context.module_common_instances[pc.module_index]
-        .function_section
-        .items[pc.function_internal_index];
-    let codes = &thread_context.module_common_instances[pc.module_index]
-        .function_section
-        .codes_data
-        [func_item.code_offset as usize..(func_item.code_offset + func_item.code_length) as usize];
-    let code_text = format_bytecode_as_text(codes);
-
-    unreachable!(
-        "\
-Invalid EnvCall number: 0x{:04x}
-Module index: {}
-Function internal index: {}
-Instruction address: 0x{:04x}
-Bytecode:
-{}",
-        thread_context.get_param_i32(),
-        pc.module_index,
-        pc.function_internal_index,
-        pc.instruction_address,
-        code_text
-    );
+fn envcall_unreachable_handler(_thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // The envcall number is invalid, raise a trap instead of panicking.
+    Err(TrapKind::InvalidEnvCallNumber)
 }
 
 #[inline]
 pub fn get_envcall_handlers(envcall_num_integer: u32) -> EnvCallHandlerFunc {
-    let envcall_num = unsafe { std::mem::transmute::<u32, EnvCallNum>(envcall_num_integer) };
     let category = envcall_num_integer >> 16;
 
+    // The host function numbers are not members of `EnvCallNum`,
+    // so they must be dispatched before the conversion.
+    if category == ENVCALL_CATEGORY_HOST_FUNCTION {
+        return host_function::host_function_call;
+    }
+
+    let envcall_num = unsafe { std::mem::transmute::<u32, EnvCallNum>(envcall_num_integer) };
+
     match category {
         0x0001 => {
             // Category: Runtime information
@@ -157,45 +145,47 @@ pub fn get_envcall_handlers(envcall_num_integer: u32) -> EnvCallHandlerFunc {
         0x0008 => {
             // Category: Capabilities
             match envcall_num {
-                EnvCallNum::capable_syscall => envcall_unreachable_handler,
-                EnvCallNum::capable_extcall => envcall_unreachable_handler,
-                EnvCallNum::capable_shell_execute => envcall_unreachable_handler,
-                EnvCallNum::capable_shell_execute_all => envcall_unreachable_handler,
-                EnvCallNum::capable_file_execute => envcall_unreachable_handler,
-                EnvCallNum::capable_file_execute_all => envcall_unreachable_handler,
-                EnvCallNum::capable_personal_dir_access => envcall_unreachable_handler,
-                EnvCallNum::capable_application_dir_access => envcall_unreachable_handler,
-                EnvCallNum::capable_file_access => envcall_unreachable_handler,
-                EnvCallNum::capable_file_access_all => envcall_unreachable_handler,
+                EnvCallNum::capable_syscall => capability::capable_syscall,
+                EnvCallNum::capable_extcall => capability::capable_extcall,
+                EnvCallNum::capable_shell_execute => capability::capable_shell_execute,
+                EnvCallNum::capable_shell_execute_all => capability::capable_shell_execute_all,
+                EnvCallNum::capable_file_execute => capability::capable_file_execute,
+                EnvCallNum::capable_file_execute_all => capability::capable_file_execute_all,
+                EnvCallNum::capable_personal_dir_access => capability::capable_personal_dir_access,
+                EnvCallNum::capable_application_dir_access => {
+                    capability::capable_application_dir_access
+                }
+                EnvCallNum::capable_file_access => capability::capable_file_access,
+                EnvCallNum::capable_file_access_all => capability::capable_file_access_all,
                 _ => envcall_unreachable_handler,
             }
         }
         0x0009 => {
             // Category: I/O
             match envcall_num {
-                EnvCallNum::file_open => envcall_unreachable_handler,
-                EnvCallNum::file_read => envcall_unreachable_handler,
-                EnvCallNum::file_write => envcall_unreachable_handler,
-                EnvCallNum::file_seek => envcall_unreachable_handler,
-                EnvCallNum::file_flush => envcall_unreachable_handler,
-                EnvCallNum::file_close => envcall_unreachable_handler,
-                EnvCallNum::file_is_terminal => envcall_unreachable_handler,
+                EnvCallNum::file_open => file::file_open,
+                EnvCallNum::file_read => file::file_read,
+                EnvCallNum::file_write => file::file_write,
+                EnvCallNum::file_seek => file::file_seek,
+                EnvCallNum::file_flush => file::file_flush,
+                EnvCallNum::file_close => file::file_close,
+                EnvCallNum::file_is_terminal => file::file_is_terminal,
                 _ => envcall_unreachable_handler,
             }
         }
         0x000A => {
             // Category: File system
             match envcall_num {
-                EnvCallNum::fs_get_current_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_get_temporary_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_get_application_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_list_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_list_dir_read => envcall_unreachable_handler,
-                EnvCallNum::fs_create_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_remove_dir => envcall_unreachable_handler,
-                EnvCallNum::fs_remove_file => envcall_unreachable_handler,
-                EnvCallNum::fs_rename => envcall_unreachable_handler,
-                EnvCallNum::fs_exists => envcall_unreachable_handler,
+                EnvCallNum::fs_get_current_dir => fs::fs_get_current_dir,
+                EnvCallNum::fs_get_temporary_dir => fs::fs_get_temporary_dir,
+                EnvCallNum::fs_get_application_dir => fs::fs_get_application_dir,
+                EnvCallNum::fs_list_dir => fs::fs_list_dir,
+                EnvCallNum::fs_list_dir_read => fs::fs_list_dir_read,
+                EnvCallNum::fs_create_dir => fs::fs_create_dir,
+                EnvCallNum::fs_remove_dir => fs::fs_remove_dir,
+                EnvCallNum::fs_remove_file => fs::fs_remove_file,
+                EnvCallNum::fs_rename => fs::fs_rename,
+                EnvCallNum::fs_exists => fs::fs_exists,
                 _ => envcall_unreachable_handler,
             }
         }
@@ -0,0 +1,673 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+use std::path::Path;
+
+use anc_context::{
+    capability::{
+        canonicalize_path, get_personal_dir, CapabilityRequest, FileAccess, FileAccessType,
+    },
+    process_property::ProcessProperty,
+    thread_context::{ThreadContext, TrapKind},
+};
+
+use super::file::read_data_bytes;
+
+fn get_file_access_type(access_type: u32) -> Option<FileAccessType> {
+    match access_type {
+        0 => Some(FileAccessType::Read),
+        1 => Some(FileAccessType::Write),
+        2 => Some(FileAccessType::ReadWrite),
+        _ => None,
+    }
+}
+
+// The paths of the requests are canonicalized, so that the prompt shows
+// and the decision store remembers the path which is actually accessed,
+// rather than the one with `..` or symbolic links.
+fn get_file_access_request(
+    path: &Path,
+    access_type: u32,
+    is_dir: bool,
+) -> Option<CapabilityRequest> {
+    let path = canonicalize_path(path).ok()?;
+    let access = FileAccess {
+        path: path.to_string_lossy().into_owned(),
+        type_: get_file_access_type(access_type)?,
+    };
+
+    if is_dir {
+        Some(CapabilityRequest::DirAccess(access))
+    } else {
+        Some(CapabilityRequest::FileAccess(access))
+    }
+}
+
+// Programs built in memory are identified by the name of the main module.
+pub(crate) fn get_application_id(
+    thread_context: &ThreadContext,
+    process_property: &ProcessProperty,
+) -> String {
+    process_property
+        .get_application_id()
+        .unwrap_or_else(|| thread_context.module_common_instances[0].name.clone())
+}
+
+// Decides the request and pushes 1 if granted or 0 if denied.
+//
+// The `process_property` mutex is released while the grantor is deciding (it may
+// prompt the user and block), so the other threads are not blocked by the prompt.
+fn request_capability(thread_context: &mut ThreadContext, request: Option<CapabilityRequest>) {
+    let granted = request.is_some_and(|request| {
+        let (application_id, capability_grantor) = {
+            let process_property = thread_context.process_property.lock().unwrap();
+            if process_property.capability.has(&request) {
+                return true;
+            }
+
+            (
+                get_application_id(thread_context, &process_property),
+                process_property.capability_grantor.clone(),
+            )
+        };
+
+        let granted = capability_grantor.decide(&application_id, &request);
+        if granted {
+            thread_context
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .grant(&request);
+        }
+        granted
+    });
+
+    thread_context.stack.push_i32_u(granted as u32);
+}
+
+// Reads the UTF-8 string from the data, `None` if the content is invalid.
+fn read_string(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    data_access_index: usize,
+    content_length_in_bytes: usize,
+) -> Result<Option<String>, TrapKind> {
+    let bytes = read_data_bytes(
+        thread_context,
+        module_index,
+        data_access_index,
+        0,
+        content_length_in_bytes,
+    )?;
+    Ok(String::from_utf8(bytes).ok())
+}
+
+pub fn capable_syscall(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn () -> i32`
+    request_capability(thread_context, Some(CapabilityRequest::Syscall));
+    Ok(())
+}
+
+pub fn capable_extcall(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn () -> i32`
+    request_capability(thread_context, Some(CapabilityRequest::Extcall));
+    Ok(())
+}
+
+pub fn capable_shell_execute(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> i32`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let command = read_string(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+    )?;
+
+    request_capability(
+        thread_context,
+        command
+            .filter(|command| !command.is_empty())
+            .map(CapabilityRequest::ShellExecute),
+    );
+    Ok(())
+}
+
+pub fn capable_shell_execute_all(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn () -> i32`
+    request_capability(thread_context, Some(CapabilityRequest::ShellExecuteAll));
+    Ok(())
+}
+
+pub fn capable_file_execute(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> i32`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let path = read_string(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+    )?;
+
+    request_capability(
+        thread_context,
+        path.filter(|path| !path.is_empty())
+            .map(CapabilityRequest::FileExecute),
+    );
+    Ok(())
+}
+
+pub fn capable_file_execute_all(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn () -> i32`
+    request_capability(thread_context, Some(CapabilityRequest::FileExecuteAll));
+    Ok(())
+}
+
+pub fn capable_personal_dir_access(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (dir_type: i32, access_type: i32) -> i32`
+
+    let access_type = thread_context.stack.pop_i32_u();
+    let dir_type = thread_context.stack.pop_i32_u() as usize;
+
+    let request = get_personal_dir(dir_type)
+        .and_then(|path| get_file_access_request(&path, access_type, true));
+
+    request_capability(thread_context, request);
+    Ok(())
+}
+
+pub fn capable_application_dir_access(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (dir_type: i32, access_type: i32) -> i32`
+
+    let access_type = thread_context.stack.pop_i32_u();
+    let dir_type = thread_context.stack.pop_i32_u() as usize;
+
+    let application_id = {
+        let process_property = thread_context.process_property.lock().unwrap();
+        get_application_id(thread_context, &process_property)
+    };
+
+    let request = get_personal_dir(dir_type)
+        .and_then(|path| get_file_access_request(&path.join(application_id), access_type, true));
+
+    request_capability(thread_context, request);
+    Ok(())
+}
+
+pub fn capable_file_access(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32, access_type: i32) -> i32`
+
+    let access_type = thread_context.stack.pop_i32_u();
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let path = read_string(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+    )?;
+
+    // Relative paths are resolved against the current directory,
+    // so that the remembered decision does not depend on it.
+    let request = path
+        .filter(|path| !path.is_empty())
+        .and_then(|path| get_file_access_request(Path::new(&path), access_type, false));
+
+    request_capability(thread_context, request);
+    Ok(())
+}
+
+pub fn capable_file_access_all(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (access_type: i32) -> i32`
+
+    let access_type = thread_context.stack.pop_i32_u();
+
+    request_capability(
+        thread_context,
+        get_file_access_type(access_type).map(CapabilityRequest::FileAccessAll),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, OnceLock,
+        },
+    };
+
+    use anc_context::{
+        capability::{canonicalize_path, CapabilityRequest, FileAccessType},
+        capability_prompter::{
+            AlwaysDenyPrompter, AlwaysGrantPrompter, CapabilityDecision, CapabilityDecisionStore,
+            CapabilityGrantor, CapabilityPrompter,
+        },
+        process_context::ProcessContext,
+        process_property::ProcessProperty,
+        program_source::ProgramSource,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper, entry::ReadWriteDataEntry,
+        utils::helper_build_module_binary_with_single_function_and_data,
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::EnvCallNum, in_memory_program_source::InMemoryProgramSource,
+        process::process_function,
+    };
+
+    // Builds a function which passes all its arguments to the envcall.
+    fn build_binary(
+        envcall_num: EnvCallNum,
+        params: &[OperandDataType],
+        read_write_datas: &[ReadWriteDataEntry],
+    ) -> Vec<u8> {
+        let mut code0 = BytecodeWriterHelper::new();
+        for (index, data_type) in params.iter().enumerate() {
+            let opcode = match data_type {
+                OperandDataType::I64 => Opcode::local_load_i64,
+                _ => Opcode::local_load_i32_u,
+            };
+            code0 = code0.append_opcode_i16_i32(opcode, 0, index as u32);
+        }
+        let code0 = code0
+            .append_opcode_i32(Opcode::envcall, envcall_num as u32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function_and_data(
+            params,
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+            &[],
+            read_write_datas,
+            &[],
+        )
+    }
+
+    fn create_process_property(capability_grantor: CapabilityGrantor) -> ProcessProperty {
+        let mut process_property = ProcessProperty::default();
+        process_property.capability_grantor = capability_grantor;
+        process_property
+    }
+
+    // Counts the prompts and grants (and remembers) all requests.
+    struct CountingPrompter {
+        count: AtomicUsize,
+    }
+
+    impl CapabilityPrompter for CountingPrompter {
+        fn prompt(&self, application_id: &str, _request: &CapabilityRequest) -> CapabilityDecision {
+            assert_eq!(application_id, "main");
+            self.count.fetch_add(1, Ordering::SeqCst);
+            CapabilityDecision::GrantAlways
+        }
+    }
+
+    // Grants the request only if the `process_property` mutex is not held while prompting.
+    struct LockCheckingPrompter {
+        process_property: OnceLock<&'static Mutex<ProcessProperty>>,
+    }
+
+    impl CapabilityPrompter for LockCheckingPrompter {
+        fn prompt(
+            &self,
+            _application_id: &str,
+            _request: &CapabilityRequest,
+        ) -> CapabilityDecision {
+            if self.process_property.get().unwrap().try_lock().is_ok() {
+                CapabilityDecision::Grant
+            } else {
+                CapabilityDecision::Deny
+            }
+        }
+    }
+
+    #[test]
+    fn test_envcall_capable_prompt_without_lock() {
+        let binary0 = build_binary(EnvCallNum::capable_syscall, &[], &[]);
+        let prompter = Arc::new(LockCheckingPrompter {
+            process_property: OnceLock::new(),
+        });
+        let process_property = create_process_property(CapabilityGrantor::new(prompter.clone()));
+
+        // The prompter refers to the process property, so the process
+        // context is leaked to live as long as the prompter.
+        let resource0: &'static InMemoryProgramSource = Box::leak(Box::new(
+            InMemoryProgramSource::with_property(vec![binary0], process_property),
+        ));
+        let process_context0: &'static ProcessContext =
+            Box::leak(Box::new(resource0.create_process_context().unwrap()));
+        let _ = prompter
+            .process_property
+            .set(&process_context0.process_property);
+
+        let mut thread_context0 = process_context0.create_thread_context();
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+        assert!(
+            thread_context0
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .syscall
+        );
+    }
+
+    #[test]
+    fn test_envcall_capable_always_grant() {
+        let binary0 = build_binary(EnvCallNum::capable_syscall, &[], &[]);
+        let process_property =
+            create_process_property(CapabilityGrantor::new(Arc::new(AlwaysGrantPrompter)));
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        assert!(
+            !thread_context0
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .syscall
+        );
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+
+        // the capability is added to the process
+        assert!(
+            thread_context0
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .syscall
+        );
+
+        // other threads have the capability
+        let thread_context1 = process_context0.create_thread_context();
+        assert!(
+            thread_context1
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .syscall
+        );
+    }
+
+    #[test]
+    fn test_envcall_capable_always_deny() {
+        let path = "/tmp/hello.txt";
+        let binary0 = build_binary(
+            EnvCallNum::capable_file_access,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+                OperandDataType::I32, // access type
+            ],
+            &[ReadWriteDataEntry::from_bytes(path.as_bytes().to_vec(), 1)],
+        );
+        let process_property =
+            create_process_property(CapabilityGrantor::new(Arc::new(AlwaysDenyPrompter)));
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(path.len() as u32),
+                ForeignValue::U32(1),
+            ],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+        assert!(thread_context0
+            .process_property
+            .lock()
+            .unwrap()
+            .capability
+            .file_access
+            .is_empty());
+    }
+
+    // Records the requests and grants them.
+    struct RecordingPrompter {
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl CapabilityPrompter for RecordingPrompter {
+        fn prompt(&self, _application_id: &str, request: &CapabilityRequest) -> CapabilityDecision {
+            self.requests.lock().unwrap().push(request.to_string());
+            CapabilityDecision::Grant
+        }
+    }
+
+    #[test]
+    fn test_envcall_capable_file_access_canonical_path() {
+        let dir_path = std::env::temp_dir().join(format!(
+            "anc_envcall_capability_canonical_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(dir_path.join("sub")).unwrap();
+
+        // the path contains `..`
+        let path = dir_path.join("sub/../hello.txt");
+        let path = path.to_str().unwrap();
+        let expected = canonicalize_path(&dir_path.join("hello.txt")).unwrap();
+        let expected = expected.to_str().unwrap();
+
+        let binary0 = build_binary(
+            EnvCallNum::capable_file_access,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+                OperandDataType::I32, // access type
+            ],
+            &[ReadWriteDataEntry::from_bytes(path.as_bytes().to_vec(), 1)],
+        );
+        let prompter = Arc::new(RecordingPrompter {
+            requests: Mutex::new(vec![]),
+        });
+        let process_property = create_process_property(CapabilityGrantor::new(prompter.clone()));
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(path.len() as u32),
+                ForeignValue::U32(0),
+            ],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+
+        // the prompt and the granted capability use the canonical path
+        assert_eq!(
+            prompter.requests.lock().unwrap().as_slice(),
+            &[format!("file_access read {}", expected)]
+        );
+        {
+            let process_property = thread_context0.process_property.lock().unwrap();
+            let file_access = &process_property.capability.file_access;
+            assert_eq!(file_access.len(), 1);
+            assert_eq!(file_access[0].path, expected);
+        }
+
+        std::fs::remove_dir_all(&dir_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_capable_file_access_all() {
+        let binary0 = build_binary(
+            EnvCallNum::capable_file_access_all,
+            &[OperandDataType::I32],
+            &[],
+        );
+        let prompter = Arc::new(CountingPrompter {
+            count: AtomicUsize::new(0),
+        });
+        let process_property = create_process_property(CapabilityGrantor::new(prompter.clone()));
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // read-write
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(2)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+        assert_eq!(
+            thread_context0
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .file_access_all,
+            Some(FileAccessType::ReadWrite)
+        );
+
+        // read is covered by read-write, no prompt
+        let result1 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(0)]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(1)]);
+        assert_eq!(prompter.count.load(Ordering::SeqCst), 1);
+
+        // invalid access type
+        let result2 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(3)]);
+        assert_eq!(result2.unwrap(), vec![ForeignValue::U32(0)]);
+        assert_eq!(prompter.count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_envcall_capable_remembered_decision() {
+        let binary0 = build_binary(EnvCallNum::capable_extcall, &[], &[]);
+        let store_path = std::env::temp_dir().join(format!(
+            "anc_envcall_capability_{}/main.txt",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&store_path);
+
+        let prompter = Arc::new(CountingPrompter {
+            count: AtomicUsize::new(0),
+        });
+        let capability_grantor = CapabilityGrantor::new(prompter.clone())
+            .with_decision_store(Some(CapabilityDecisionStore::new(&store_path)));
+
+        // the first run prompts
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0.clone()],
+            create_process_property(capability_grantor.clone()),
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+        assert_eq!(prompter.count.load(Ordering::SeqCst), 1);
+
+        // the second run uses the remembered decision
+        let resource1 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            create_process_property(capability_grantor),
+        );
+        let process_context1 = resource1.create_process_context().unwrap();
+        let mut thread_context1 = process_context1.create_thread_context();
+        assert!(
+            !thread_context1
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .extcall
+        );
+
+        let result1 = process_function(&mut thread_context1, 0, 0, &[]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(1)]);
+        assert_eq!(prompter.count.load(Ordering::SeqCst), 1);
+        assert!(
+            thread_context1
+                .process_property
+                .lock()
+                .unwrap()
+                .capability
+                .extcall
+        );
+
+        std::fs::remove_dir_all(store_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_capable_application_dir_access() {
+        let binary0 = build_binary(
+            EnvCallNum::capable_application_dir_access,
+            &[OperandDataType::I32, OperandDataType::I32],
+            &[],
+        );
+        let process_property =
+            create_process_property(CapabilityGrantor::new(Arc::new(AlwaysGrantPrompter)));
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(6), ForeignValue::U32(2)],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1)]);
+
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        let expected = canonicalize_path(&home.join(".config/main")).unwrap();
+        {
+            let process_property = thread_context0.process_property.lock().unwrap();
+            let dir_access = &process_property.capability.dir_access;
+            assert_eq!(dir_access.len(), 1);
+            assert_eq!(dir_access[0].path, expected.to_str().unwrap());
+            assert_eq!(dir_access[0].type_, FileAccessType::ReadWrite);
+        }
+
+        // invalid dir type
+        let result1 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(9), ForeignValue::U32(0)],
+        );
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(0)]);
+    }
+}
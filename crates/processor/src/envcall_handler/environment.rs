@@ -4,16 +4,18 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 
-pub fn program_path_length(thread_context: &mut ThreadContext) {
+pub fn program_path_length(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     let process_property = thread_context.process_property.lock().unwrap();
     let size = process_property.program_path.to_str().unwrap().len();
     thread_context.stack.push_i32_u(size as u32);
+
+    Ok(())
 }
 
-pub fn program_path_read(thread_context: &mut ThreadContext) {
+pub fn program_path_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> i32`
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -35,7 +37,7 @@ pub fn program_path_read(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         actual_read_length,
-    );
+    )?;
 
     let src_ptr = content_bytes.as_ptr();
     target_data_object.accessor.write_idx(
@@ -46,17 +48,21 @@ pub fn program_path_read(thread_context: &mut ThreadContext) {
     );
 
     thread_context.stack.push_i32_u(actual_read_length as u32);
+
+    Ok(())
 }
 
-pub fn program_source_type(thread_context: &mut ThreadContext) {
+pub fn program_source_type(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     let process_property = thread_context.process_property.lock().unwrap();
     let size = process_property.program_source_type as u32;
 
     thread_context.stack.push_i32_u(size as u32);
+
+    Ok(())
 }
 
-pub fn arguments_length(thread_context: &mut ThreadContext) {
+pub fn arguments_length(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     let process_property = thread_context.process_property.lock().unwrap();
 
@@ -72,9 +78,11 @@ pub fn arguments_length(thread_context: &mut ThreadContext) {
         content_length += process_property.arguments.len() - 1;
         thread_context.stack.push_i32_u(content_length as u32);
     }
+
+    Ok(())
 }
 
-pub fn arguments_read(thread_context: &mut ThreadContext) {
+pub fn arguments_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> i32`
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -96,7 +104,7 @@ pub fn arguments_read(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         actual_read_length,
-    );
+    )?;
 
     let src_ptr = content_bytes.as_ptr();
     target_data_object.accessor.write_idx(
@@ -107,9 +115,11 @@ pub fn arguments_read(thread_context: &mut ThreadContext) {
     );
 
     thread_context.stack.push_i32_u(actual_read_length as u32);
+
+    Ok(())
 }
 
-pub fn environment_variables_length(thread_context: &mut ThreadContext) {
+pub fn environment_variables_length(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     let process_property = thread_context.process_property.lock().unwrap();
 
@@ -127,9 +137,11 @@ pub fn environment_variables_length(thread_context: &mut ThreadContext) {
         content_length += process_property.environments.len() - 1;
         thread_context.stack.push_i32_u(content_length as u32);
     }
+
+    Ok(())
 }
 
-pub fn environment_variables_read(thread_context: &mut ThreadContext) {
+pub fn environment_variables_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> i32`
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -156,7 +168,7 @@ pub fn environment_variables_read(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         actual_read_length,
-    );
+    )?;
 
     let src_ptr = content_bytes.as_ptr();
     target_data_object.accessor.write_idx(
@@ -167,9 +179,11 @@ pub fn environment_variables_read(thread_context: &mut ThreadContext) {
     );
 
     thread_context.stack.push_i32_u(actual_read_length as u32);
+
+    Ok(())
 }
 
-pub fn environment_variable_find(thread_context: &mut ThreadContext) {
+pub fn environment_variable_find(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, data_length_in_bytes: i32) -> i32`
 
     let data_length_in_bytes = thread_context.stack.pop_i32_u();
@@ -181,7 +195,7 @@ pub fn environment_variable_find(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         data_length_in_bytes as usize,
-    );
+    )?;
 
     let content_address = target_data_object
         .accessor
@@ -204,9 +218,13 @@ pub fn environment_variable_find(thread_context: &mut ThreadContext) {
         // If not found, return -1.
         thread_context.stack.push_i32_u(u32::MAX);
     }
+
+    Ok(())
 }
 
-pub fn environment_variable_item_length(thread_context: &mut ThreadContext) {
+pub fn environment_variable_item_length(
+    thread_context: &mut ThreadContext,
+) -> Result<(), TrapKind> {
     // `fn (environment_variable_index: i32) -> i32`
 
     let environment_variable_index = thread_context.stack.pop_i32_u();
@@ -224,9 +242,11 @@ pub fn environment_variable_item_length(thread_context: &mut ThreadContext) {
         // If the environment variable does not exist, return 0.
         thread_context.stack.push_i32_u(0);
     }
+
+    Ok(())
 }
 
-pub fn environment_variable_item_read(thread_context: &mut ThreadContext) {
+pub fn environment_variable_item_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (environment_variable_index: i32, module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> i32`
 
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
@@ -257,7 +277,7 @@ pub fn environment_variable_item_read(thread_context: &mut ThreadContext) {
             data_access_index as usize,
             0,
             actual_read_length,
-        );
+        )?;
 
         let src_ptr = content_bytes.as_ptr();
         target_data_object.accessor.write_idx(
@@ -272,9 +292,11 @@ pub fn environment_variable_item_read(thread_context: &mut ThreadContext) {
         // If the environment variable does not exist, return 0.
         thread_context.stack.push_i32_u(0);
     }
+
+    Ok(())
 }
 
-pub fn environment_variable_set(thread_context: &mut ThreadContext) {
+pub fn environment_variable_set(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, data_length_in_bytes: i32)`
     let data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -285,7 +307,7 @@ pub fn environment_variable_set(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         data_length_in_bytes as usize,
-    );
+    )?;
 
     let content_address = target_data_object
         .accessor
@@ -313,9 +335,11 @@ pub fn environment_variable_set(thread_context: &mut ThreadContext) {
         // If not found, add a new environment variable.
         process_property.environments.push((name, value));
     }
+
+    Ok(())
 }
 
-pub fn environment_variable_remove(thread_context: &mut ThreadContext) {
+pub fn environment_variable_remove(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, data_length_in_bytes: i32)`
     let data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -326,7 +350,7 @@ pub fn environment_variable_remove(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         data_length_in_bytes as usize,
-    );
+    )?;
 
     let content_address = target_data_object
         .accessor
@@ -346,4 +370,6 @@ pub fn environment_variable_remove(thread_context: &mut ThreadContext) {
         // If the environment variable is found, remove it.
         process_property.environments.remove(pos);
     }
+
+    Ok(())
 }
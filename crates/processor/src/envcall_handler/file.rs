@@ -0,0 +1,997 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+use std::{
+    fs::OpenOptions,
+    io::{ErrorKind, SeekFrom},
+    path::PathBuf,
+};
+
+use anc_context::{
+    capability::{canonicalize_path, FileAccessType},
+    thread_context::{ThreadContext, TrapKind},
+    thread_resources::FileObject,
+};
+
+// Access mode (bit flags), see `EnvCallNum::file_open`.
+const ACCESS_MODE_READ: u32 = 1;
+const ACCESS_MODE_WRITE: u32 = 2;
+const ACCESS_MODE_APPEND: u32 = 4;
+
+// Open options (bit flags), see `EnvCallNum::file_open`.
+const OPEN_OPTION_TRUNCATE: u32 = 1;
+const OPEN_OPTION_CREATE_OR_OPEN: u32 = 2;
+const OPEN_OPTION_CREATE_ONLY_NON_EXIST: u32 = 4;
+
+// File error numbers, see `EnvCallNum::file_open`.
+pub const FILE_ERROR_NUMBER_NOT_FOUND: u32 = 1;
+pub const FILE_ERROR_NUMBER_PERMISSION_DENIED: u32 = 2;
+pub const FILE_ERROR_NUMBER_ALREADY_EXISTS: u32 = 3;
+pub const FILE_ERROR_NUMBER_INVALID_INPUT: u32 = 4;
+pub const FILE_ERROR_NUMBER_UNSUPPORTED: u32 = 5;
+pub const FILE_ERROR_NUMBER_OTHER: u32 = 6;
+
+pub fn get_file_error_number(error: &std::io::Error) -> u32 {
+    match error.kind() {
+        ErrorKind::NotFound => FILE_ERROR_NUMBER_NOT_FOUND,
+        ErrorKind::PermissionDenied => FILE_ERROR_NUMBER_PERMISSION_DENIED,
+        ErrorKind::AlreadyExists => FILE_ERROR_NUMBER_ALREADY_EXISTS,
+        ErrorKind::InvalidInput => FILE_ERROR_NUMBER_INVALID_INPUT,
+        ErrorKind::Unsupported => FILE_ERROR_NUMBER_UNSUPPORTED,
+        _ => FILE_ERROR_NUMBER_OTHER,
+    }
+}
+
+/// Copies the content of the specified data object, e.g. a file path.
+pub fn read_data_bytes(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    data_access_index: usize,
+    data_offset: usize,
+    length_in_bytes: usize,
+) -> Result<Vec<u8>, TrapKind> {
+    let target_data_object = thread_context.get_target_data_object(
+        module_index,
+        data_access_index,
+        data_offset,
+        length_in_bytes,
+    )?;
+
+    let mut bytes = vec![0u8; length_in_bytes];
+    target_data_object.accessor.read_idx(
+        target_data_object.data_internal_index_in_section,
+        data_offset,
+        length_in_bytes,
+        bytes.as_mut_ptr(),
+    );
+
+    Ok(bytes)
+}
+
+/// Reads the file path from the specified data object and checks it against
+/// the capability of the process, the I/O error is:
+///
+/// - `InvalidInput` if the path is not a valid UTF-8 string.
+/// - `PermissionDenied` if the process is not capable of accessing the path
+///   with the specified access type, see `Capability::is_path_accessible`,
+///   or the last component of the path is a symbolic link, or the path
+///   can not be canonicalized (e.g. it contains a dangling symbolic link).
+///
+/// The canonical form of the path (i.e. `..` and symbolic links are resolved) is checked
+/// and returned, so that the program can not escape from the accessible directories, nor
+/// replace a component of the path with a symbolic link between the checking and the accessing.
+///
+/// The symbolic link on the last component is refused instead of being resolved,
+/// otherwise e.g. `fs_remove_file` would remove the target instead of the link.
+pub fn read_path(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    data_access_index: usize,
+    content_length_in_bytes: usize,
+    access_type: FileAccessType,
+) -> Result<std::io::Result<PathBuf>, TrapKind> {
+    let path_bytes = read_data_bytes(
+        thread_context,
+        module_index,
+        data_access_index,
+        0,
+        content_length_in_bytes,
+    )?;
+
+    let Ok(path) = String::from_utf8(path_bytes).map(PathBuf::from) else {
+        return Ok(Err(ErrorKind::InvalidInput.into()));
+    };
+
+    if path
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+    {
+        return Ok(Err(ErrorKind::PermissionDenied.into()));
+    }
+
+    let Ok(canonical_path) = canonicalize_path(&path) else {
+        return Ok(Err(ErrorKind::PermissionDenied.into()));
+    };
+
+    let accessible = thread_context
+        .process_property
+        .lock()
+        .unwrap()
+        .capability
+        .is_path_accessible(&canonical_path, access_type);
+
+    if accessible {
+        Ok(Ok(canonical_path))
+    } else {
+        Ok(Err(ErrorKind::PermissionDenied.into()))
+    }
+}
+
+pub fn file_open(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32, open_options: i32, access_mode: i32) -> (file_index: i32, io_error_number: i32)`
+
+    let access_mode = thread_context.stack.pop_i32_u();
+    let open_options = thread_context.stack.pop_i32_u();
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    // Creating or truncating the file requires the write access.
+    let is_read = access_mode & ACCESS_MODE_READ != 0;
+    let is_write = access_mode & (ACCESS_MODE_WRITE | ACCESS_MODE_APPEND) != 0
+        || open_options
+            & (OPEN_OPTION_TRUNCATE
+                | OPEN_OPTION_CREATE_OR_OPEN
+                | OPEN_OPTION_CREATE_ONLY_NON_EXIST)
+            != 0;
+    let access_type = if is_read && is_write {
+        FileAccessType::ReadWrite
+    } else if is_write {
+        FileAccessType::Write
+    } else {
+        FileAccessType::Read
+    };
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        access_type,
+    )?;
+
+    let result_file = result_path.and_then(|path| {
+        let mut options = OpenOptions::new();
+        options
+            .read(access_mode & ACCESS_MODE_READ != 0)
+            .write(access_mode & ACCESS_MODE_WRITE != 0)
+            .append(access_mode & ACCESS_MODE_APPEND != 0)
+            .truncate(open_options & OPEN_OPTION_TRUNCATE != 0)
+            .create(open_options & OPEN_OPTION_CREATE_OR_OPEN != 0)
+            .create_new(open_options & OPEN_OPTION_CREATE_ONLY_NON_EXIST != 0);
+
+        // Do not follow the symbolic link which replaces the file after `read_path` checked it.
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NOFOLLOW);
+
+        options.open(path)
+    });
+
+    match result_file {
+        Ok(file) => {
+            let file_index = thread_context
+                .thread_resources
+                .add_file(FileObject::User(file));
+            thread_context.stack.push_i32_u(file_index as u32);
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i32_u(u32::MAX);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn file_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32, module_index: i32, data_access_index: i64, data_offset: i32, expected_bytes: i32) -> (actual_read_bytes: i32, io_error_number: i32)`
+
+    let expected_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_offset = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    // Check the bounds of the data before consuming the content of the file.
+    thread_context.get_target_data_object(
+        module_index,
+        data_access_index,
+        data_offset,
+        expected_bytes,
+    )?;
+
+    let mut buf = vec![0u8; expected_bytes];
+    match thread_context
+        .thread_resources
+        .read_file(file_index, &mut buf)
+    {
+        Ok(actual_read_bytes) => {
+            let target_data_object = thread_context.get_target_data_object(
+                module_index,
+                data_access_index,
+                data_offset,
+                actual_read_bytes,
+            )?;
+
+            target_data_object.accessor.write_idx(
+                buf.as_ptr(),
+                target_data_object.data_internal_index_in_section,
+                data_offset,
+                actual_read_bytes,
+            );
+
+            thread_context.stack.push_i32_u(actual_read_bytes as u32);
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i32_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn file_write(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32, module_index: i32, data_access_index: i64, data_offset: i32, bytes_to_write: i32) -> (actual_write_bytes: i32, io_error_number: i32)`
+
+    let bytes_to_write = thread_context.stack.pop_i32_u() as usize;
+    let data_offset = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    let buf = read_data_bytes(
+        thread_context,
+        module_index,
+        data_access_index,
+        data_offset,
+        bytes_to_write,
+    )?;
+
+    match thread_context.thread_resources.write_file(file_index, &buf) {
+        Ok(actual_write_bytes) => {
+            thread_context.stack.push_i32_u(actual_write_bytes as u32);
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i32_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn file_seek(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32, seek_from: i32, bytes: i64) -> (position: i64, io_error_number: i32)`
+
+    let bytes = thread_context.stack.pop_i64_s();
+    let seek_from = thread_context.stack.pop_i32_u();
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_position = match seek_from {
+        0 => {
+            if bytes < 0 {
+                Err(ErrorKind::InvalidInput.into())
+            } else {
+                thread_context
+                    .thread_resources
+                    .seek_file(file_index, SeekFrom::Start(bytes as u64))
+            }
+        }
+        1 => thread_context
+            .thread_resources
+            .seek_file(file_index, SeekFrom::End(bytes)),
+        2 => thread_context
+            .thread_resources
+            .seek_file(file_index, SeekFrom::Current(bytes)),
+        _ => Err(ErrorKind::InvalidInput.into()),
+    };
+
+    match result_position {
+        Ok(position) => {
+            thread_context.stack.push_i64_u(position);
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i64_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn file_flush(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32) -> io_error_number: i32`
+
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    let error_number = match thread_context.thread_resources.flush_file(file_index) {
+        Ok(_) => 0,
+        Err(error) => get_file_error_number(&error),
+    };
+
+    thread_context.stack.push_i32_u(error_number);
+    Ok(())
+}
+
+pub fn file_close(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32) -> io_error_number: i32`
+
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    // The file is flushed and closed when it is dropped.
+    let error_number = if thread_context
+        .thread_resources
+        .get_file(file_index)
+        .is_some()
+    {
+        thread_context.thread_resources.remove_file(file_index);
+        0
+    } else {
+        FILE_ERROR_NUMBER_INVALID_INPUT
+    };
+
+    thread_context.stack.push_i32_u(error_number);
+    Ok(())
+}
+
+pub fn file_is_terminal(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (file_index: i32) -> i32`
+
+    let file_index = thread_context.stack.pop_i32_u() as usize;
+
+    let is_terminal = thread_context.thread_resources.is_terminal_file(file_index);
+
+    thread_context
+        .stack
+        .push_i32_u(if is_terminal { 1 } else { 0 });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        io::Cursor,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    use anc_context::{
+        capability::{FileAccess, FileAccessType},
+        process_property::ProcessProperty,
+        program_source::ProgramSource,
+        standard_streams::StandardStreams,
+        thread_context::TrapKind,
+        thread_resources::FileObject,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadWriteDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_data_and_external_functions,
+            helper_build_module_binary_with_single_function_and_data, HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::EnvCallNum, in_memory_program_source::InMemoryProgramSource,
+        process::process_function, program_embed::get_data_view, ProcessorError,
+        ProcessorErrorType,
+    };
+
+    // Builds the code which passes all the arguments of the function to the envcall.
+    fn build_envcall_code(envcall_num: EnvCallNum, params: &[OperandDataType]) -> Vec<u8> {
+        let mut code = BytecodeWriterHelper::new();
+        for (index, data_type) in params.iter().enumerate() {
+            let opcode = match data_type {
+                OperandDataType::I64 => Opcode::local_load_i64,
+                _ => Opcode::local_load_i32_u,
+            };
+            code = code.append_opcode_i16_i32(opcode, 0, index as u32);
+        }
+        code.append_opcode_i32(Opcode::envcall, envcall_num as u32)
+            .append_opcode(Opcode::end)
+            .to_bytes()
+    }
+
+    // Builds a function which passes all its arguments to the envcall.
+    fn build_binary(
+        envcall_num: EnvCallNum,
+        params: &[OperandDataType],
+        results: &[OperandDataType],
+        read_write_datas: &[ReadWriteDataEntry],
+    ) -> Vec<u8> {
+        let code0 = build_envcall_code(envcall_num, params);
+
+        helper_build_module_binary_with_single_function_and_data(
+            params,
+            results,
+            &[], // local variables
+            code0,
+            &[],
+            read_write_datas,
+            &[],
+        )
+    }
+
+    fn create_temporary_file_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("anc_envcall_file_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_envcall_file_open() {
+        let file_path = create_temporary_file_path("open");
+        let path_bytes = file_path.to_str().unwrap().as_bytes().to_vec();
+        let path_length = path_bytes.len() as u32;
+
+        let binary0 = build_binary(
+            EnvCallNum::file_open,
+            &[
+                OperandDataType::I32, // module index
+                OperandDataType::I64, // data access index
+                OperandDataType::I32, // content length
+                OperandDataType::I32, // open options
+                OperandDataType::I32, // access mode
+            ],
+            &[OperandDataType::I32, OperandDataType::I32],
+            &[ReadWriteDataEntry::from_bytes(path_bytes, 1)],
+        );
+
+        let create_process_property = |type_: FileAccessType| {
+            let mut process_property = ProcessProperty::default();
+            process_property.capability.file_access = vec![FileAccess {
+                path: file_path.to_str().unwrap().to_owned(),
+                type_,
+            }];
+            process_property
+        };
+
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0.clone()],
+            create_process_property(FileAccessType::ReadWrite),
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let mut open = |length: u32, open_options: u32, access_mode: u32| {
+            process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                    ForeignValue::U32(length),
+                    ForeignValue::U32(open_options),
+                    ForeignValue::U32(access_mode),
+                ],
+            )
+        };
+
+        // not found
+        assert_eq!(
+            open(path_length, 0, 1).unwrap(),
+            vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(1)]
+        );
+
+        // create_or_open + write
+        assert_eq!(
+            open(path_length, 2, 2).unwrap(),
+            vec![ForeignValue::U32(3), ForeignValue::U32(0)]
+        );
+        assert!(file_path.exists());
+
+        // create_only_non_exist + write, the file already exists
+        assert_eq!(
+            open(path_length, 4, 2).unwrap(),
+            vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(3)]
+        );
+
+        // truncate without write access
+        assert_eq!(
+            open(path_length, 1, 1).unwrap(),
+            vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(4)]
+        );
+
+        // read
+        assert_eq!(
+            open(path_length, 0, 1).unwrap(),
+            vec![ForeignValue::U32(4), ForeignValue::U32(0)]
+        );
+
+        // the path exceeds the data
+        assert!(matches!(
+            open(path_length + 1, 0, 1),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::DataAccessOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            thread_context0.thread_resources.get_file(3),
+            Some(FileObject::User(_))
+        ));
+
+        // the process is only capable of reading the file
+        let resource1 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            create_process_property(FileAccessType::Read),
+        );
+        let process_context1 = resource1.create_process_context().unwrap();
+        let mut thread_context1 = process_context1.create_thread_context();
+
+        let mut open = |open_options: u32, access_mode: u32| {
+            process_function(
+                &mut thread_context1,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                    ForeignValue::U32(path_length),
+                    ForeignValue::U32(open_options),
+                    ForeignValue::U32(access_mode),
+                ],
+            )
+            .unwrap()
+        };
+
+        // read
+        assert_eq!(open(0, 1), vec![ForeignValue::U32(3), ForeignValue::U32(0)]);
+
+        // write, append, read-write and create are denied
+        for (open_options, access_mode) in [(0, 2), (0, 4), (0, 3), (2, 1)] {
+            assert_eq!(
+                open(open_options, access_mode),
+                vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(2)]
+            );
+        }
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_file_write_and_read() {
+        let file_path = create_temporary_file_path("write_and_read");
+        let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+        let mut process_property = ProcessProperty::default();
+        process_property.standard_streams = StandardStreams::new()
+            .with_stdin(Cursor::new(b"abc".to_vec()))
+            .with_stdout(output.clone());
+
+        let params = [
+            OperandDataType::I32, // file index
+            OperandDataType::I32, // module index
+            OperandDataType::I64, // data access index
+            OperandDataType::I32, // data offset
+            OperandDataType::I32, // length
+        ];
+        let results = [OperandDataType::I32, OperandDataType::I32];
+        let datas = [
+            ReadWriteDataEntry::from_bytes(b"hello, world".to_vec(), 1),
+            ReadWriteDataEntry::from_bytes(vec![0u8; 16], 1),
+        ];
+
+        let arguments = |file_index: u32, data_index: u64, offset: u32, length: u32| {
+            [
+                ForeignValue::U32(file_index),
+                ForeignValue::U32(0),
+                ForeignValue::U64(data_index),
+                ForeignValue::U32(offset),
+                ForeignValue::U32(length),
+            ]
+        };
+
+        // write
+        let binary0 = build_binary(EnvCallNum::file_write, &params, &results, &datas);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], process_property.clone());
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let file_index = thread_context0
+            .thread_resources
+            .add_file(FileObject::User(File::create(&file_path).unwrap()));
+        assert_eq!(file_index, 3);
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &arguments(3, 0, 7, 5));
+        assert_eq!(
+            result0.unwrap(),
+            vec![ForeignValue::U32(5), ForeignValue::U32(0)]
+        );
+
+        // stdout
+        let result1 = process_function(&mut thread_context0, 0, 0, &arguments(1, 0, 0, 5));
+        assert_eq!(
+            result1.unwrap(),
+            vec![ForeignValue::U32(5), ForeignValue::U32(0)]
+        );
+        assert_eq!(output.lock().unwrap().as_slice(), b"hello");
+
+        // stdin is not writable
+        let result2 = process_function(&mut thread_context0, 0, 0, &arguments(0, 0, 0, 5));
+        assert_eq!(
+            result2.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(5)]
+        );
+
+        // the file does not exist
+        let result3 = process_function(&mut thread_context0, 0, 0, &arguments(9, 0, 0, 5));
+        assert_eq!(
+            result3.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+
+        thread_context0.thread_resources.remove_file(3);
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"world");
+
+        // read
+        let binary1 = build_binary(EnvCallNum::file_read, &params, &results, &datas);
+        let resource1 = InMemoryProgramSource::with_property(vec![binary1], process_property);
+        let process_context1 = resource1.create_process_context().unwrap();
+        let mut thread_context1 = process_context1.create_thread_context();
+
+        thread_context1
+            .thread_resources
+            .add_file(FileObject::User(File::open(&file_path).unwrap()));
+
+        let result4 = process_function(&mut thread_context1, 0, 0, &arguments(3, 1, 2, 10));
+        assert_eq!(
+            result4.unwrap(),
+            vec![ForeignValue::U32(5), ForeignValue::U32(0)]
+        );
+
+        let data1 = get_data_view(&thread_context1, "main", "data1").unwrap();
+        assert_eq!(data1.read_bytes(0, 8).unwrap(), b"\0\0world\0");
+
+        // end of file
+        let result5 = process_function(&mut thread_context1, 0, 0, &arguments(3, 1, 0, 10));
+        assert_eq!(
+            result5.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(0)]
+        );
+
+        // stdin
+        let result6 = process_function(&mut thread_context1, 0, 0, &arguments(0, 1, 8, 8));
+        assert_eq!(
+            result6.unwrap(),
+            vec![ForeignValue::U32(3), ForeignValue::U32(0)]
+        );
+
+        let data1 = get_data_view(&thread_context1, "main", "data1").unwrap();
+        assert_eq!(data1.read_bytes(8, 3).unwrap(), b"abc");
+
+        // stdout is not readable
+        let result7 = process_function(&mut thread_context1, 0, 0, &arguments(1, 1, 0, 8));
+        assert_eq!(
+            result7.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(5)]
+        );
+
+        // the buffer exceeds the data
+        assert!(matches!(
+            process_function(&mut thread_context1, 0, 0, &arguments(3, 1, 8, 10)),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::DataAccessOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_file_seek() {
+        let file_path = create_temporary_file_path("seek");
+        std::fs::write(&file_path, b"0123456789").unwrap();
+
+        let binary0 = build_binary(
+            EnvCallNum::file_seek,
+            &[
+                OperandDataType::I32, // file index
+                OperandDataType::I32, // seek from
+                OperandDataType::I64, // bytes
+            ],
+            &[OperandDataType::I64, OperandDataType::I32],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        thread_context0
+            .thread_resources
+            .add_file(FileObject::User(File::open(&file_path).unwrap()));
+
+        let mut seek = |file_index: u32, seek_from: u32, bytes: i64| {
+            process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(file_index),
+                    ForeignValue::U32(seek_from),
+                    ForeignValue::U64(bytes as u64),
+                ],
+            )
+            .unwrap()
+        };
+
+        // start
+        assert_eq!(
+            seek(3, 0, 3),
+            vec![ForeignValue::U64(3), ForeignValue::U32(0)]
+        );
+
+        // current
+        assert_eq!(
+            seek(3, 2, 2),
+            vec![ForeignValue::U64(5), ForeignValue::U32(0)]
+        );
+
+        // end
+        assert_eq!(
+            seek(3, 1, -1),
+            vec![ForeignValue::U64(9), ForeignValue::U32(0)]
+        );
+
+        // negative offset from the start
+        assert_eq!(
+            seek(3, 0, -1),
+            vec![ForeignValue::U64(0), ForeignValue::U32(4)]
+        );
+
+        // invalid `seek_from`
+        assert_eq!(
+            seek(3, 9, 0),
+            vec![ForeignValue::U64(0), ForeignValue::U32(4)]
+        );
+
+        // the standard streams are not seekable
+        assert_eq!(
+            seek(0, 0, 0),
+            vec![ForeignValue::U64(0), ForeignValue::U32(5)]
+        );
+
+        // the file does not exist
+        assert_eq!(
+            seek(9, 0, 0),
+            vec![ForeignValue::U64(0), ForeignValue::U32(4)]
+        );
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_file_flush() {
+        let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let mut process_property = ProcessProperty::default();
+        process_property.standard_streams = StandardStreams::new().with_stdout(output);
+
+        let binary0 = build_binary(
+            EnvCallNum::file_flush,
+            &[OperandDataType::I32],
+            &[OperandDataType::I32],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(1)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+
+        let result1 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(9)]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(4)]);
+    }
+
+    #[test]
+    fn test_envcall_file_close() {
+        let file_path = create_temporary_file_path("close");
+
+        let binary0 = build_binary(
+            EnvCallNum::file_close,
+            &[OperandDataType::I32],
+            &[OperandDataType::I32],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        thread_context0
+            .thread_resources
+            .add_file(FileObject::User(File::create(&file_path).unwrap()));
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(3)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+        assert!(thread_context0.thread_resources.get_file(3).is_none());
+
+        // close again
+        let result1 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(3)]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(4)]);
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_file_error_paths() {
+        let file_path = create_temporary_file_path("error_paths");
+        std::fs::write(&file_path, b"0123456789").unwrap();
+
+        // fn0 file_read
+        // fn1 file_write
+        // fn2 file_close
+        let read_write_params = vec![
+            OperandDataType::I32, // file index
+            OperandDataType::I32, // module index
+            OperandDataType::I64, // data access index
+            OperandDataType::I32, // data offset
+            OperandDataType::I32, // length
+        ];
+        let read_write_results = vec![OperandDataType::I32, OperandDataType::I32];
+
+        let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[
+                HelperFunctionEntry {
+                    params: read_write_params.clone(),
+                    results: read_write_results.clone(),
+                    local_variable_item_entries_without_args: vec![],
+                    code: build_envcall_code(EnvCallNum::file_read, &read_write_params),
+                },
+                HelperFunctionEntry {
+                    params: read_write_params.clone(),
+                    results: read_write_results.clone(),
+                    local_variable_item_entries_without_args: vec![],
+                    code: build_envcall_code(EnvCallNum::file_write, &read_write_params),
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: build_envcall_code(EnvCallNum::file_close, &[OperandDataType::I32]),
+                },
+            ],
+            &[],
+            &[ReadWriteDataEntry::from_bytes(vec![0u8; 8], 1)],
+            &[],
+            &[],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // the file is opened read-only
+        thread_context0
+            .thread_resources
+            .add_file(FileObject::User(File::open(&file_path).unwrap()));
+
+        let mut call = |function_public_index: usize, arguments: &[ForeignValue]| {
+            process_function(&mut thread_context0, 0, function_public_index, arguments).unwrap()
+        };
+
+        let read_write_arguments = |file_index: u32| {
+            [
+                ForeignValue::U32(file_index),
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(0),
+                ForeignValue::U32(4),
+            ]
+        };
+
+        // invalid file index
+        assert_eq!(
+            call(0, &read_write_arguments(9)),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+        assert_eq!(
+            call(1, &read_write_arguments(9)),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+        assert_eq!(call(2, &[ForeignValue::U32(9)]), vec![ForeignValue::U32(4)]);
+
+        // write to a read-only file, the error number depends on the platform
+        let result0 = call(1, &read_write_arguments(3));
+        assert_eq!(result0[0], ForeignValue::U32(0));
+        assert_ne!(result0[1], ForeignValue::U32(0));
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"0123456789");
+
+        // read before closing
+        assert_eq!(
+            call(0, &read_write_arguments(3)),
+            vec![ForeignValue::U32(4), ForeignValue::U32(0)]
+        );
+
+        // close
+        assert_eq!(call(2, &[ForeignValue::U32(3)]), vec![ForeignValue::U32(0)]);
+
+        // read and write after closing
+        assert_eq!(
+            call(0, &read_write_arguments(3)),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+        assert_eq!(
+            call(1, &read_write_arguments(3)),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_file_is_terminal() {
+        let file_path = create_temporary_file_path("is_terminal");
+
+        let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let mut process_property = ProcessProperty::default();
+        process_property.standard_streams = StandardStreams::new().with_stdout(output);
+
+        let binary0 = build_binary(
+            EnvCallNum::file_is_terminal,
+            &[OperandDataType::I32],
+            &[OperandDataType::I32],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        thread_context0
+            .thread_resources
+            .add_file(FileObject::User(File::create(&file_path).unwrap()));
+
+        // the redirected stdout, a regular file and a file which does not exist
+        for file_index in [1, 3, 9] {
+            let result0 =
+                process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(file_index)]);
+            assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+        }
+
+        std::fs::remove_file(&file_path).unwrap();
+    }
+}
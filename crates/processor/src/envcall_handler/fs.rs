@@ -0,0 +1,1233 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+use std::{io::ErrorKind, path::PathBuf};
+
+use anc_context::{
+    capability::{canonicalize_path, get_personal_dir, FileAccessType, PERSONAL_DIRS},
+    thread_context::{ThreadContext, TrapKind},
+};
+
+use super::{
+    capability::get_application_id,
+    file::{get_file_error_number, read_path, FILE_ERROR_NUMBER_INVALID_INPUT},
+};
+
+// Writes the path to the data and pushes `(path_length, fs_error_number)`.
+fn push_path(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    data_access_index: usize,
+    expected_data_length_in_bytes: usize,
+    result_path: std::io::Result<PathBuf>,
+) -> Result<(), TrapKind> {
+    let path = match result_path {
+        Ok(path) => path,
+        Err(error) => {
+            thread_context.stack.push_i32_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+            return Ok(());
+        }
+    };
+
+    let content = path.to_string_lossy();
+    let content_bytes = content.as_bytes();
+    let actual_write_length = content_bytes.len().min(expected_data_length_in_bytes);
+
+    let target_data_object = thread_context.get_target_data_object(
+        module_index,
+        data_access_index,
+        0,
+        actual_write_length,
+    )?;
+
+    target_data_object.accessor.write_idx(
+        content_bytes.as_ptr(),
+        target_data_object.data_internal_index_in_section,
+        0,
+        actual_write_length,
+    );
+
+    thread_context.stack.push_i32_u(content_bytes.len() as u32);
+    thread_context.stack.push_i32_u(0);
+
+    Ok(())
+}
+
+// Returns the canonical form of the directory if the process is capable of reading it,
+// otherwise `PermissionDenied`, because the path of a host directory (e.g. the current
+// working directory) may reveal the information of the host, such as the user name.
+fn check_dir_readable(
+    thread_context: &ThreadContext,
+    result_path: std::io::Result<PathBuf>,
+) -> std::io::Result<PathBuf> {
+    let Ok(canonical_path) = result_path.and_then(|path| canonicalize_path(&path)) else {
+        return Err(ErrorKind::PermissionDenied.into());
+    };
+
+    let accessible = thread_context
+        .process_property
+        .lock()
+        .unwrap()
+        .capability
+        .is_path_accessible(&canonical_path, FileAccessType::Read);
+
+    if accessible {
+        Ok(canonical_path)
+    } else {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+}
+
+fn push_error_number(thread_context: &mut ThreadContext, result: std::io::Result<()>) {
+    let error_number = match result {
+        Ok(_) => 0,
+        Err(error) => get_file_error_number(&error),
+    };
+    thread_context.stack.push_i32_u(error_number);
+}
+
+pub fn fs_get_current_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
+
+    let expected_data_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = check_dir_readable(thread_context, std::env::current_dir());
+
+    push_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        expected_data_length_in_bytes,
+        result_path,
+    )
+}
+
+pub fn fs_get_temporary_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
+
+    let expected_data_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = check_dir_readable(thread_context, Ok(std::env::temp_dir()));
+
+    push_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        expected_data_length_in_bytes,
+        result_path,
+    )
+}
+
+pub fn fs_get_application_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (dir_type: i32, module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
+
+    let expected_data_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+    let dir_type = thread_context.stack.pop_i32_u() as usize;
+
+    let application_id = {
+        let process_property = thread_context.process_property.lock().unwrap();
+        get_application_id(thread_context, &process_property)
+    };
+
+    let result_path = if dir_type < PERSONAL_DIRS.len() {
+        get_personal_dir(dir_type)
+            .map(|dir| dir.join(application_id))
+            .ok_or_else(|| ErrorKind::NotFound.into())
+    } else {
+        Err(ErrorKind::InvalidInput.into())
+    };
+
+    push_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        expected_data_length_in_bytes,
+        result_path,
+    )
+}
+
+pub fn fs_list_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> (dir_index: i32, fs_error_number: i32)`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        FileAccessType::Read,
+    )?;
+
+    match result_path.and_then(std::fs::read_dir) {
+        Ok(read_dir) => {
+            let dir_index = thread_context.thread_resources.add_dir(read_dir);
+            thread_context.stack.push_i32_u(dir_index as u32);
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i32_u(u32::MAX);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn fs_list_dir_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (dir_index: i32, module_index: i32, data_access_index: i64) -> (bytes_read:i32, fs_error_number: i32)`
+
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+    let dir_index = thread_context.stack.pop_i32_u() as usize;
+
+    let data_length_in_bytes = {
+        let target_data_object =
+            thread_context.get_target_data_object(module_index, data_access_index, 0, 0)?;
+        target_data_object
+            .accessor
+            .get_data_length(target_data_object.data_internal_index_in_section)
+    };
+
+    let Some(dir) = thread_context.thread_resources.get_dir_mut(dir_index) else {
+        thread_context.stack.push_i32_u(0);
+        thread_context
+            .stack
+            .push_i32_u(FILE_ERROR_NUMBER_INVALID_INPUT);
+        return Ok(());
+    };
+
+    // Peek the entry first so that it is not lost if the data is too small.
+    let result_name = match dir.peek() {
+        None => None,
+        Some(Ok(entry)) => {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.len() <= data_length_in_bytes {
+                dir.next();
+            }
+            Some(Ok(name))
+        }
+        Some(Err(_)) => Some(Err(dir.next().unwrap().unwrap_err())),
+    };
+
+    match result_name {
+        None => {
+            // The last entry has been read.
+            thread_context.thread_resources.remove_dir(dir_index);
+            thread_context.stack.push_i32_u(0);
+            thread_context.stack.push_i32_u(0);
+        }
+        Some(Ok(name)) if name.len() > data_length_in_bytes => {
+            thread_context.stack.push_i32_u(name.len() as u32);
+            thread_context
+                .stack
+                .push_i32_u(FILE_ERROR_NUMBER_INVALID_INPUT);
+        }
+        Some(Ok(name)) => {
+            let target_data_object = thread_context.get_target_data_object(
+                module_index,
+                data_access_index,
+                0,
+                name.len(),
+            )?;
+
+            target_data_object.accessor.write_idx(
+                name.as_ptr(),
+                target_data_object.data_internal_index_in_section,
+                0,
+                name.len(),
+            );
+
+            thread_context.stack.push_i32_u(name.len() as u32);
+            thread_context.stack.push_i32_u(0);
+        }
+        Some(Err(error)) => {
+            thread_context.stack.push_i32_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn fs_create_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> fs_error_number: i32`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        FileAccessType::Write,
+    )?;
+
+    push_error_number(thread_context, result_path.and_then(std::fs::create_dir));
+    Ok(())
+}
+
+pub fn fs_remove_dir(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> fs_error_number: i32`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        FileAccessType::Write,
+    )?;
+
+    push_error_number(thread_context, result_path.and_then(std::fs::remove_dir));
+    Ok(())
+}
+
+pub fn fs_remove_file(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> fs_error_number: i32`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        FileAccessType::Write,
+    )?;
+
+    push_error_number(thread_context, result_path.and_then(std::fs::remove_file));
+    Ok(())
+}
+
+pub fn fs_rename(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // ```
+    // fn (source_module_index: i32, source_data_access_index: i64, source_content_length_in_bytes:i32,
+    // dest_module_index: i32, dest_data_access_index: i64, dest_content_length_in_bytes:i32) -> fs_error_number: i32`
+    // ```
+
+    let dest_content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let dest_data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let dest_module_index = thread_context.stack.pop_i32_u() as usize;
+    let source_content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let source_data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let source_module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_source_path = read_path(
+        thread_context,
+        source_module_index,
+        source_data_access_index,
+        source_content_length_in_bytes,
+        FileAccessType::Write,
+    )?;
+
+    let result_dest_path = read_path(
+        thread_context,
+        dest_module_index,
+        dest_data_access_index,
+        dest_content_length_in_bytes,
+        FileAccessType::Write,
+    )?;
+
+    let result = result_source_path.and_then(|source_path| {
+        result_dest_path.and_then(|dest_path| std::fs::rename(source_path, dest_path))
+    });
+
+    push_error_number(thread_context, result);
+    Ok(())
+}
+
+pub fn fs_exists(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> (i32, fs_error_number: i32)`
+
+    let content_length_in_bytes = thread_context.stack.pop_i32_u() as usize;
+    let data_access_index = thread_context.stack.pop_i64_u() as usize;
+    let module_index = thread_context.stack.pop_i32_u() as usize;
+
+    let result_path = read_path(
+        thread_context,
+        module_index,
+        data_access_index,
+        content_length_in_bytes,
+        FileAccessType::Read,
+    )?;
+
+    match result_path.and_then(|path| path.try_exists()) {
+        Ok(exists) => {
+            thread_context.stack.push_i32_u(if exists { 1 } else { 0 });
+            thread_context.stack.push_i32_u(0);
+        }
+        Err(error) => {
+            thread_context.stack.push_i32_u(0);
+            thread_context
+                .stack
+                .push_i32_u(get_file_error_number(&error));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use anc_context::{
+        capability::{Capability, FileAccess, FileAccessType},
+        process_property::{ProcessProperty, ProgramSourceType},
+        program_source::ProgramSource,
+        thread_context::ThreadContext,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper, entry::ReadWriteDataEntry,
+        utils::helper_build_module_binary_with_single_function_and_data,
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::EnvCallNum, in_memory_program_source::InMemoryProgramSource,
+        process::process_function, program_embed::get_data_view,
+    };
+
+    // Builds a function which passes all its arguments to the envcall.
+    fn build_binary(
+        envcall_num: EnvCallNum,
+        params: &[OperandDataType],
+        results: &[OperandDataType],
+        read_write_datas: &[ReadWriteDataEntry],
+    ) -> Vec<u8> {
+        let mut code0 = BytecodeWriterHelper::new();
+        for (index, data_type) in params.iter().enumerate() {
+            let opcode = match data_type {
+                OperandDataType::I64 => Opcode::local_load_i64,
+                _ => Opcode::local_load_i32_u,
+            };
+            code0 = code0.append_opcode_i16_i32(opcode, 0, index as u32);
+        }
+        let code0 = code0
+            .append_opcode_i32(Opcode::envcall, envcall_num as u32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function_and_data(
+            params,
+            results,
+            &[], // local variables
+            code0,
+            &[],
+            read_write_datas,
+            &[],
+        )
+    }
+
+    fn create_temporary_dir(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("anc_envcall_fs_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    // The process is capable of accessing the temporary directory.
+    fn create_process_property() -> ProcessProperty {
+        let mut process_property = ProcessProperty::default();
+        process_property.capability.dir_access = vec![FileAccess {
+            path: std::env::temp_dir().to_str().unwrap().to_owned(),
+            type_: FileAccessType::ReadWrite,
+        }];
+        process_property
+    }
+
+    // Calls the envcall which takes a path, i.e. `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> ...`
+    fn call_with_path(
+        envcall_num: EnvCallNum,
+        results: &[OperandDataType],
+        path: &Path,
+    ) -> Vec<ForeignValue> {
+        call_with_path_and_property(envcall_num, results, path, create_process_property())
+    }
+
+    fn call_with_path_and_property(
+        envcall_num: EnvCallNum,
+        results: &[OperandDataType],
+        path: &Path,
+        process_property: ProcessProperty,
+    ) -> Vec<ForeignValue> {
+        let path_bytes = path.to_str().unwrap().as_bytes().to_vec();
+        let path_length = path_bytes.len() as u32;
+
+        let binary0 = build_binary(
+            envcall_num,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+            ],
+            results,
+            &[ReadWriteDataEntry::from_bytes(path_bytes, 1)],
+        );
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(path_length),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_envcall_fs_get_current_and_temporary_dir() {
+        let params = [
+            OperandDataType::I32,
+            OperandDataType::I64,
+            OperandDataType::I32,
+        ];
+        let results = [OperandDataType::I32, OperandDataType::I32];
+        let datas = [ReadWriteDataEntry::from_bytes(vec![0u8; 1024], 1)];
+
+        for (envcall_num, expected_path) in [
+            (
+                EnvCallNum::fs_get_current_dir,
+                std::env::current_dir().unwrap(),
+            ),
+            (EnvCallNum::fs_get_temporary_dir, std::env::temp_dir()),
+        ] {
+            let expected_path = expected_path.canonicalize().unwrap();
+            let expected = expected_path.to_str().unwrap().to_owned();
+
+            let mut process_property = ProcessProperty::default();
+            process_property.capability.dir_access = vec![FileAccess {
+                path: expected.clone(),
+                type_: FileAccessType::Read,
+            }];
+
+            let binary0 = build_binary(envcall_num, &params, &results, &datas);
+            let resource0 =
+                InMemoryProgramSource::with_property(vec![binary0.clone()], process_property);
+            let process_context0 = resource0.create_process_context().unwrap();
+            let mut thread_context0 = process_context0.create_thread_context();
+
+            let result0 = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                    ForeignValue::U32(1024),
+                ],
+            );
+            assert_eq!(
+                result0.unwrap(),
+                vec![
+                    ForeignValue::U32(expected.len() as u32),
+                    ForeignValue::U32(0)
+                ]
+            );
+
+            let data0 = get_data_view(&thread_context0, "main", "data0").unwrap();
+            assert_eq!(data0.read_str(0, expected.len()).unwrap(), expected);
+
+            // the path is truncated
+            let result1 = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                    ForeignValue::U32(1),
+                ],
+            );
+            assert_eq!(
+                result1.unwrap(),
+                vec![
+                    ForeignValue::U32(expected.len() as u32),
+                    ForeignValue::U32(0)
+                ]
+            );
+
+            // the process is not capable of reading the directory
+            let resource1 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context1 = resource1.create_process_context().unwrap();
+            let mut thread_context1 = process_context1.create_thread_context();
+
+            let result2 = process_function(
+                &mut thread_context1,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                    ForeignValue::U32(1024),
+                ],
+            );
+            assert_eq!(
+                result2.unwrap(),
+                vec![ForeignValue::U32(0), ForeignValue::U32(2)]
+            );
+
+            let data1 = get_data_view(&thread_context1, "main", "data0").unwrap();
+            assert_eq!(data1.read_bytes(0, 8).unwrap(), vec![0u8; 8]);
+        }
+    }
+
+    fn get_application_dir(thread_context: &mut ThreadContext, dir_type: u32) -> Vec<ForeignValue> {
+        process_function(
+            thread_context,
+            0,
+            0,
+            &[
+                ForeignValue::U32(dir_type),
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(1024),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_envcall_fs_get_application_dir() {
+        let binary0 = build_binary(
+            EnvCallNum::fs_get_application_dir,
+            &[
+                OperandDataType::I32, // dir type
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+            ],
+            &[OperandDataType::I32, OperandDataType::I32],
+            &[ReadWriteDataEntry::from_bytes(vec![0u8; 1024], 1)],
+        );
+
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+
+        let mut process_property = ProcessProperty::default();
+        process_property.program_path = PathBuf::from("/path/to/scripts/hello-world.anc");
+        process_property.program_source_type = ProgramSourceType::ScriptFile;
+
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0.clone()], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let expected = home.join(".config/hello-world");
+        let expected = expected.to_str().unwrap();
+        assert_eq!(
+            get_application_dir(&mut thread_context0, 6),
+            vec![
+                ForeignValue::U32(expected.len() as u32),
+                ForeignValue::U32(0)
+            ]
+        );
+        let data0 = get_data_view(&thread_context0, "main", "data0").unwrap();
+        assert_eq!(data0.read_str(0, expected.len()).unwrap(), expected);
+
+        // invalid dir type
+        assert_eq!(
+            get_application_dir(&mut thread_context0, 9),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+
+        // the program is built in memory, the name of the main module is used
+        let mut process_property = ProcessProperty::default();
+        process_property.program_source_type = ProgramSourceType::Memory;
+
+        let resource1 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context1 = resource1.create_process_context().unwrap();
+        let mut thread_context1 = process_context1.create_thread_context();
+
+        let expected = home.join("Desktop/main");
+        let expected = expected.to_str().unwrap();
+        assert_eq!(
+            get_application_dir(&mut thread_context1, 0),
+            vec![
+                ForeignValue::U32(expected.len() as u32),
+                ForeignValue::U32(0)
+            ]
+        );
+        let data0 = get_data_view(&thread_context1, "main", "data0").unwrap();
+        assert_eq!(data0.read_str(0, expected.len()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_envcall_fs_create_remove_and_exists() {
+        let dir_path = create_temporary_dir("create_remove");
+        let sub_dir_path = dir_path.join("sub");
+        let file_path = dir_path.join("file.txt");
+
+        let error_number = [OperandDataType::I32];
+        let exists = [OperandDataType::I32, OperandDataType::I32];
+
+        // create dir
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_create_dir, &error_number, &sub_dir_path),
+            vec![ForeignValue::U32(0)]
+        );
+        assert!(sub_dir_path.is_dir());
+
+        // already exists
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_create_dir, &error_number, &sub_dir_path),
+            vec![ForeignValue::U32(3)]
+        );
+
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_exists, &exists, &sub_dir_path),
+            vec![ForeignValue::U32(1), ForeignValue::U32(0)]
+        );
+
+        // remove dir
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_remove_dir, &error_number, &sub_dir_path),
+            vec![ForeignValue::U32(0)]
+        );
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_exists, &exists, &sub_dir_path),
+            vec![ForeignValue::U32(0), ForeignValue::U32(0)]
+        );
+
+        // not found
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_remove_dir, &error_number, &sub_dir_path),
+            vec![ForeignValue::U32(1)]
+        );
+
+        // remove file
+        std::fs::write(&file_path, b"hello").unwrap();
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_exists, &exists, &file_path),
+            vec![ForeignValue::U32(1), ForeignValue::U32(0)]
+        );
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_remove_file, &error_number, &file_path),
+            vec![ForeignValue::U32(0)]
+        );
+        assert!(!file_path.exists());
+        assert_eq!(
+            call_with_path(EnvCallNum::fs_remove_file, &error_number, &file_path),
+            vec![ForeignValue::U32(1)]
+        );
+
+        std::fs::remove_dir_all(&dir_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_rename() {
+        let dir_path = create_temporary_dir("rename");
+        let source_path = dir_path.join("a.txt");
+        let dest_path = dir_path.join("b.txt");
+        std::fs::write(&source_path, b"hello").unwrap();
+
+        let source_bytes = source_path.to_str().unwrap().as_bytes().to_vec();
+        let dest_bytes = dest_path.to_str().unwrap().as_bytes().to_vec();
+        let arguments = [
+            ForeignValue::U32(0),
+            ForeignValue::U64(0),
+            ForeignValue::U32(source_bytes.len() as u32),
+            ForeignValue::U32(0),
+            ForeignValue::U64(1),
+            ForeignValue::U32(dest_bytes.len() as u32),
+        ];
+
+        let binary0 = build_binary(
+            EnvCallNum::fs_rename,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+            ],
+            &[OperandDataType::I32],
+            &[
+                ReadWriteDataEntry::from_bytes(source_bytes, 1),
+                ReadWriteDataEntry::from_bytes(dest_bytes, 1),
+            ],
+        );
+
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_process_property());
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &arguments);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+        assert!(!source_path.exists());
+        assert_eq!(std::fs::read(&dest_path).unwrap(), b"hello");
+
+        // the source file does not exist
+        let result1 = process_function(&mut thread_context0, 0, 0, &arguments);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(1)]);
+
+        std::fs::remove_dir_all(&dir_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_list_dir() {
+        let dir_path = create_temporary_dir("list_dir");
+        std::fs::write(dir_path.join("a.txt"), b"").unwrap();
+        std::fs::create_dir(dir_path.join("b")).unwrap();
+
+        // open
+        let path_bytes = dir_path.to_str().unwrap().as_bytes().to_vec();
+        let path_length = path_bytes.len() as u32;
+
+        let binary0 = build_binary(
+            EnvCallNum::fs_list_dir,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I64,
+                OperandDataType::I32,
+            ],
+            &[OperandDataType::I32, OperandDataType::I32],
+            &[ReadWriteDataEntry::from_bytes(path_bytes, 1)],
+        );
+
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_process_property());
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+                ForeignValue::U32(path_length),
+            ],
+        );
+        assert_eq!(
+            result0.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(0)]
+        );
+        assert!(thread_context0.thread_resources.get_dir_mut(0).is_some());
+
+        // the directory does not exist
+        assert_eq!(
+            call_with_path(
+                EnvCallNum::fs_list_dir,
+                &[OperandDataType::I32, OperandDataType::I32],
+                &dir_path.join("nonexistent")
+            ),
+            vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(1)]
+        );
+
+        // read
+        std::fs::write(dir_path.join("a_long_file_name.txt"), b"").unwrap();
+
+        let binary1 = build_binary(
+            EnvCallNum::fs_list_dir_read,
+            &[
+                OperandDataType::I32,
+                OperandDataType::I32,
+                OperandDataType::I64,
+            ],
+            &[OperandDataType::I32, OperandDataType::I32],
+            &[
+                ReadWriteDataEntry::from_bytes(vec![0u8; 8], 1),
+                ReadWriteDataEntry::from_bytes(vec![0u8; 32], 1),
+            ],
+        );
+
+        let resource1 = InMemoryProgramSource::new(vec![binary1]);
+        let process_context1 = resource1.create_process_context().unwrap();
+        let mut thread_context1 = process_context1.create_thread_context();
+
+        let dir_index = thread_context1
+            .thread_resources
+            .add_dir(std::fs::read_dir(&dir_path).unwrap());
+
+        let mut names = vec![];
+        loop {
+            let results = process_function(
+                &mut thread_context1,
+                0,
+                0,
+                &[
+                    ForeignValue::U32(dir_index as u32),
+                    ForeignValue::U32(0),
+                    ForeignValue::U64(0),
+                ],
+            )
+            .unwrap();
+
+            let (length, data_name) = match results.as_slice() {
+                [ForeignValue::U32(0), ForeignValue::U32(0)] => break,
+                [ForeignValue::U32(length), ForeignValue::U32(0)] => (*length as usize, "data0"),
+                [ForeignValue::U32(length), ForeignValue::U32(4)] => {
+                    // the name is too long for the data0, read it again with data1
+                    assert_eq!(*length, 20);
+                    let results = process_function(
+                        &mut thread_context1,
+                        0,
+                        0,
+                        &[
+                            ForeignValue::U32(dir_index as u32),
+                            ForeignValue::U32(0),
+                            ForeignValue::U64(1),
+                        ],
+                    )
+                    .unwrap();
+                    assert_eq!(results, vec![ForeignValue::U32(20), ForeignValue::U32(0)]);
+                    (20, "data1")
+                }
+                _ => unreachable!(),
+            };
+
+            let data = get_data_view(&thread_context1, "main", data_name).unwrap();
+            names.push(data.read_str(0, length).unwrap().to_owned());
+        }
+
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "a_long_file_name.txt", "b"]);
+
+        // the stream is closed after the last entry
+        assert!(thread_context1
+            .thread_resources
+            .get_dir_mut(dir_index)
+            .is_none());
+
+        let result1 = process_function(
+            &mut thread_context1,
+            0,
+            0,
+            &[
+                ForeignValue::U32(dir_index as u32),
+                ForeignValue::U32(0),
+                ForeignValue::U64(0),
+            ],
+        );
+        assert_eq!(
+            result1.unwrap(),
+            vec![ForeignValue::U32(0), ForeignValue::U32(4)]
+        );
+
+        std::fs::remove_dir_all(&dir_path).unwrap();
+    }
+
+    // Creates the following directories and files, and the capability which
+    // is described at the right side.
+    //
+    // root/
+    //   allowed/           read-write
+    //     file.txt
+    //     sub/
+    //   allowed_sibling/   not accessible, shares the prefix with `allowed`
+    //   readonly/          read
+    //     file.txt
+    //   single.txt         read (file access)
+    //   secret.txt         not accessible
+    fn create_sandbox(name: &str) -> (PathBuf, Capability) {
+        let root_path = create_temporary_dir(name);
+        let allowed_path = root_path.join("allowed");
+        let readonly_path = root_path.join("readonly");
+
+        std::fs::create_dir_all(allowed_path.join("sub")).unwrap();
+        std::fs::create_dir_all(root_path.join("allowed_sibling")).unwrap();
+        std::fs::create_dir_all(&readonly_path).unwrap();
+        std::fs::write(allowed_path.join("file.txt"), b"").unwrap();
+        std::fs::write(readonly_path.join("file.txt"), b"").unwrap();
+        std::fs::write(root_path.join("single.txt"), b"").unwrap();
+        std::fs::write(root_path.join("secret.txt"), b"").unwrap();
+
+        let capability = Capability {
+            dir_access: vec![
+                FileAccess {
+                    path: allowed_path.to_str().unwrap().to_owned(),
+                    type_: FileAccessType::ReadWrite,
+                },
+                FileAccess {
+                    path: readonly_path.to_str().unwrap().to_owned(),
+                    type_: FileAccessType::Read,
+                },
+            ],
+            file_access: vec![FileAccess {
+                path: root_path.join("single.txt").to_str().unwrap().to_owned(),
+                type_: FileAccessType::Read,
+            }],
+            ..Capability::default()
+        };
+
+        (root_path, capability)
+    }
+
+    fn call_with_capability(
+        envcall_num: EnvCallNum,
+        results: &[OperandDataType],
+        path: &Path,
+        capability: &Capability,
+    ) -> Vec<ForeignValue> {
+        let mut process_property = ProcessProperty::default();
+        process_property.capability = capability.clone();
+        call_with_path_and_property(envcall_num, results, path, process_property)
+    }
+
+    fn exists_with_capability(path: &Path, capability: &Capability) -> Vec<ForeignValue> {
+        call_with_capability(
+            EnvCallNum::fs_exists,
+            &[OperandDataType::I32, OperandDataType::I32],
+            path,
+            capability,
+        )
+    }
+
+    fn create_dir_with_capability(path: &Path, capability: &Capability) -> Vec<ForeignValue> {
+        call_with_capability(
+            EnvCallNum::fs_create_dir,
+            &[OperandDataType::I32],
+            path,
+            capability,
+        )
+    }
+
+    const EXISTING: [ForeignValue; 2] = [ForeignValue::U32(1), ForeignValue::U32(0)];
+    const NONEXISTENT: [ForeignValue; 2] = [ForeignValue::U32(0), ForeignValue::U32(0)];
+    const DENIED: [ForeignValue; 2] = [ForeignValue::U32(0), ForeignValue::U32(2)];
+
+    #[test]
+    fn test_envcall_fs_path_sandbox_allowed() {
+        let (root_path, capability) = create_sandbox("sandbox_allowed");
+        let allowed_path = root_path.join("allowed");
+        let exists = |path: &Path| exists_with_capability(path, &capability);
+
+        assert_eq!(exists(&allowed_path), EXISTING);
+        assert_eq!(exists(&allowed_path.join("file.txt")), EXISTING);
+        assert_eq!(exists(&allowed_path.join("sub/../file.txt")), EXISTING);
+        assert_eq!(exists(&allowed_path.join("./sub/.")), EXISTING);
+        assert_eq!(exists(&allowed_path.join("nonexistent")), NONEXISTENT);
+        assert_eq!(exists(&root_path.join("readonly/file.txt")), EXISTING);
+        assert_eq!(exists(&root_path.join("single.txt")), EXISTING);
+        assert_eq!(
+            exists(&root_path.join("readonly/../allowed/file.txt")),
+            EXISTING
+        );
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_path_sandbox_denied() {
+        let (root_path, capability) = create_sandbox("sandbox_denied");
+        let exists = |path: &Path| exists_with_capability(path, &capability);
+
+        assert_eq!(exists(&root_path), DENIED);
+        assert_eq!(exists(&root_path.join("secret.txt")), DENIED);
+        assert_eq!(exists(&root_path.join("allowed_sibling")), DENIED);
+        assert_eq!(exists(Path::new("/")), DENIED);
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_path_sandbox_parent_dir_escape() {
+        let (root_path, capability) = create_sandbox("sandbox_parent_dir");
+        let allowed_path = root_path.join("allowed");
+        let exists = |path: &Path| exists_with_capability(path, &capability);
+
+        assert_eq!(exists(&allowed_path.join("../secret.txt")), DENIED);
+        assert_eq!(exists(&allowed_path.join("sub/../../secret.txt")), DENIED);
+        assert_eq!(
+            exists(&allowed_path.join("nonexistent/../../secret.txt")),
+            DENIED
+        );
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("new/../../new"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("../new"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(!root_path.join("new").exists());
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_path_sandbox_access_type() {
+        let (root_path, capability) = create_sandbox("sandbox_access_type");
+        let allowed_path = root_path.join("allowed");
+        let readonly_path = root_path.join("readonly");
+
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("new"), &capability),
+            vec![ForeignValue::U32(0)]
+        );
+        assert!(allowed_path.join("new").exists());
+
+        assert_eq!(
+            create_dir_with_capability(&readonly_path.join("new"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(!readonly_path.join("new").exists());
+
+        assert_eq!(
+            call_with_capability(
+                EnvCallNum::fs_remove_file,
+                &[OperandDataType::I32],
+                &root_path.join("single.txt"),
+                &capability
+            ),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(root_path.join("single.txt").exists());
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_envcall_fs_path_sandbox_symlink() {
+        let (root_path, capability) = create_sandbox("sandbox_symlink");
+        let allowed_path = root_path.join("allowed");
+        let exists = |path: &Path| exists_with_capability(path, &capability);
+
+        std::os::unix::fs::symlink(root_path.join("secret.txt"), allowed_path.join("link.txt"))
+            .unwrap();
+        std::os::unix::fs::symlink(&root_path, allowed_path.join("link_dir")).unwrap();
+        std::os::unix::fs::symlink(allowed_path.join("file.txt"), root_path.join("in.txt"))
+            .unwrap();
+        std::os::unix::fs::symlink(allowed_path.join("file.txt"), allowed_path.join("in.txt"))
+            .unwrap();
+
+        // the links which point outside are denied
+        assert_eq!(exists(&allowed_path.join("link.txt")), DENIED);
+        assert_eq!(exists(&allowed_path.join("link_dir/secret.txt")), DENIED);
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("link_dir/new"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(!root_path.join("new").exists());
+
+        // the directory links which are resolved inside are allowed
+        assert_eq!(
+            exists(&allowed_path.join("link_dir/allowed/file.txt")),
+            EXISTING
+        );
+
+        // the links on the last component are refused, even if they point inside
+        assert_eq!(exists(&root_path.join("in.txt")), DENIED);
+        assert_eq!(exists(&allowed_path.join("in.txt")), DENIED);
+        assert_eq!(
+            call_with_capability(
+                EnvCallNum::fs_remove_file,
+                &[OperandDataType::I32],
+                &allowed_path.join("in.txt"),
+                &capability
+            ),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(allowed_path.join("file.txt").exists());
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_envcall_fs_path_sandbox_dangling_symlink() {
+        let (root_path, capability) = create_sandbox("sandbox_dangling_symlink");
+        let allowed_path = root_path.join("allowed");
+
+        // the targets do not exist
+        std::os::unix::fs::symlink(root_path.join("outside"), allowed_path.join("dangling"))
+            .unwrap();
+        std::os::unix::fs::symlink(
+            root_path.join("outside_dir"),
+            allowed_path.join("dangling_dir"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            exists_with_capability(&allowed_path.join("dangling"), &capability),
+            DENIED
+        );
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("dangling"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert_eq!(
+            create_dir_with_capability(&allowed_path.join("dangling_dir/new"), &capability),
+            vec![ForeignValue::U32(2)]
+        );
+        assert!(!root_path.join("outside").exists());
+        assert!(!root_path.join("outside_dir").exists());
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+
+    #[test]
+    fn test_envcall_fs_path_sandbox_all_files() {
+        let (root_path, _) = create_sandbox("sandbox_all_files");
+
+        let capability = Capability {
+            file_access_all: Some(FileAccessType::Read),
+            ..Capability::default()
+        };
+
+        assert_eq!(
+            exists_with_capability(&root_path.join("secret.txt"), &capability),
+            EXISTING
+        );
+        assert_eq!(
+            call_with_capability(
+                EnvCallNum::fs_remove_file,
+                &[OperandDataType::I32],
+                &root_path.join("secret.txt"),
+                &capability
+            ),
+            vec![ForeignValue::U32(2)]
+        );
+
+        // nothing is accessible by default
+        assert_eq!(
+            call_with_capability(
+                EnvCallNum::fs_list_dir,
+                &[OperandDataType::I32, OperandDataType::I32],
+                &root_path.join("allowed"),
+                &Capability::default()
+            ),
+            vec![ForeignValue::U32(u32::MAX), ForeignValue::U32(2)]
+        );
+
+        std::fs::remove_dir_all(&root_path).unwrap();
+    }
+}
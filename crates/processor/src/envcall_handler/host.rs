@@ -4,27 +4,27 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 
-pub fn host_arch(thread_context: &mut ThreadContext) {
+pub fn host_arch(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64) -> i32`
     let content = std::env::consts::ARCH;
-    do_host_information_str(thread_context, content);
+    do_host_information_str(thread_context, content)
 }
 
-pub fn host_os(thread_context: &mut ThreadContext) {
+pub fn host_os(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64) -> i32`
     let content = std::env::consts::OS;
-    do_host_information_str(thread_context, content);
+    do_host_information_str(thread_context, content)
 }
 
-pub fn host_family(thread_context: &mut ThreadContext) {
+pub fn host_family(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64) -> i32`
     let content = std::env::consts::FAMILY;
-    do_host_information_str(thread_context, content);
+    do_host_information_str(thread_context, content)
 }
 
-pub fn host_endian(thread_context: &mut ThreadContext) {
+pub fn host_endian(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
 
     // ref:
@@ -39,15 +39,22 @@ pub fn host_endian(thread_context: &mut ThreadContext) {
     };
 
     thread_context.stack.push_i32_u(endian);
+
+    Ok(())
 }
 
-pub fn host_memory_width(thread_context: &mut ThreadContext) {
+pub fn host_memory_width(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     let size = size_of::<usize>();
     thread_context.stack.push_i32_u(size as u32);
+
+    Ok(())
 }
 
-fn do_host_information_str(thread_context: &mut ThreadContext, content: &str) {
+fn do_host_information_str(
+    thread_context: &mut ThreadContext,
+    content: &str,
+) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64) -> i32`
     let data_access_index = thread_context.stack.pop_i64_u();
     let module_index = thread_context.stack.pop_i32_u();
@@ -60,7 +67,7 @@ fn do_host_information_str(thread_context: &mut ThreadContext, content: &str) {
         data_access_index as usize,
         0,
         content_length,
-    );
+    )?;
 
     let src_ptr = content_bytes.as_ptr();
     target_data_object
@@ -68,6 +75,8 @@ fn do_host_information_str(thread_context: &mut ThreadContext, content: &str) {
         .write_idx(src_ptr, data_access_index as usize, 0, content_length);
 
     thread_context.stack.push_i32_u(content_length as u32);
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -114,7 +123,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -157,7 +165,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -200,7 +207,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -236,7 +242,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -276,7 +281,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
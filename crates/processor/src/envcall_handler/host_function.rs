@@ -0,0 +1,346 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+use anc_context::thread_context::{ThreadContext, TrapKind};
+use anc_isa::{ForeignValue, OperandDataType, OPERAND_SIZE_IN_BYTES};
+
+pub fn host_function_call(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
+    // `fn (args...) -> (values...)`, see the signature of the host function.
+
+    // The program counter still points to the instruction `envcall`,
+    // the item of the envcall number is the index of the host function.
+    let envcall_num = thread_context.get_param_i32();
+    let host_function_index = (envcall_num & 0xffff) as usize;
+
+    let host_function_table = thread_context.host_function_table;
+    let host_function_item = host_function_table
+        .get_host_function(host_function_index)
+        .ok_or(TrapKind::InvalidEnvCallNumber)?;
+
+    // Pop the arguments according to the declared parameters.
+    let params = &host_function_item.params;
+    let params_ptr = thread_context.stack.pop_operands_to_memory(params.len());
+    let arguments = params
+        .iter()
+        .enumerate()
+        .map(|(idx, data_type)| {
+            let ptr = unsafe { params_ptr.add(idx * OPERAND_SIZE_IN_BYTES) };
+            unsafe {
+                match data_type {
+                    OperandDataType::I32 => ForeignValue::U32(std::ptr::read(ptr as *const u32)),
+                    OperandDataType::I64 => ForeignValue::U64(std::ptr::read(ptr as *const u64)),
+                    OperandDataType::F32 => ForeignValue::F32(std::ptr::read(ptr as *const f32)),
+                    OperandDataType::F64 => ForeignValue::F64(std::ptr::read(ptr as *const f64)),
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let results =
+        (host_function_item.function)(&arguments).map_err(|_| TrapKind::HostFunctionFailed)?;
+
+    // The results returned by the closure must match the declared results,
+    // otherwise the operand stack would be corrupted.
+    let results_match = results.len() == host_function_item.results.len()
+        && results
+            .iter()
+            .zip(&host_function_item.results)
+            .all(|(value, data_type)| {
+                matches!(
+                    (value, data_type),
+                    (ForeignValue::U32(_), OperandDataType::I32)
+                        | (ForeignValue::U64(_), OperandDataType::I64)
+                        | (ForeignValue::F32(_), OperandDataType::F32)
+                        | (ForeignValue::F64(_), OperandDataType::F64)
+                )
+            });
+
+    if !results_match {
+        return Err(TrapKind::HostFunctionResultsMismatch);
+    }
+
+    for value in results {
+        match value {
+            ForeignValue::U32(value) => thread_context.stack.push_i32_u(value),
+            ForeignValue::U64(value) => thread_context.stack.push_i64_u(value),
+            ForeignValue::F32(value) => thread_context.stack.push_f32(value),
+            ForeignValue::F64(value) => thread_context.stack.push_f64(value),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    use anc_context::{
+        host_function_table::{HostFunctionTable, HostFunctionTableError, MAX_HOST_FUNCTIONS},
+        program_source::ProgramSource,
+        thread_context::TrapKind,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::helper_build_module_binary_with_single_function,
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::get_host_function_envcall_num,
+        in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
+        ProcessorErrorType,
+    };
+
+    #[test]
+    fn test_envcall_host_function() {
+        // fn (a: i32, b: f64) -> (i64, f64)
+        //     local_load_i32_u(0, 0)
+        //     local_load_f64(0, 1)
+        //     envcall(host function 1)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_f64, 0, 1)
+            .append_opcode_i32(Opcode::envcall, get_host_function_envcall_num(1))
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[OperandDataType::I32, OperandDataType::F64], // params
+            &[OperandDataType::I64, OperandDataType::F64], // results
+            &[],                                           // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let mut process_context0 = resource0.create_process_context().unwrap();
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let calls_in_closure = calls.clone();
+
+        assert_eq!(
+            process_context0.host_function_table.register(
+                "config::version",
+                &[],
+                &[OperandDataType::I32],
+                |_| Ok(vec![ForeignValue::U32(1)]),
+            ),
+            Ok(0)
+        );
+
+        assert_eq!(
+            process_context0.host_function_table.register(
+                "math::scale",
+                &[OperandDataType::I32, OperandDataType::F64],
+                &[OperandDataType::I64, OperandDataType::F64],
+                move |args| {
+                    calls_in_closure.fetch_add(1, Ordering::SeqCst);
+                    let [ForeignValue::U32(a), ForeignValue::F64(b)] = args else {
+                        unreachable!()
+                    };
+                    Ok(vec![
+                        ForeignValue::U64(*a as u64 * 2),
+                        ForeignValue::F64(b * *a as f64),
+                    ])
+                },
+            ),
+            Ok(1)
+        );
+
+        // the name of host function must be unique
+        assert_eq!(
+            process_context0
+                .host_function_table
+                .register("math::scale", &[], &[], |_| Ok(vec![])),
+            Err(HostFunctionTableError::DuplicateName(
+                "math::scale".to_owned()
+            ))
+        );
+
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(11), ForeignValue::F64(1.5)],
+        );
+        assert_eq!(
+            result0.unwrap(),
+            vec![ForeignValue::U64(22), ForeignValue::F64(16.5)]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_envcall_host_function_not_found() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::envcall, get_host_function_envcall_num(0))
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::InvalidEnvCallNumber,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_envcall_host_function_failed() {
+        // fn () -> (i32)
+        //     envcall(host function 0)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::envcall, get_host_function_envcall_num(0))
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let mut process_context0 = resource0.create_process_context().unwrap();
+
+        process_context0
+            .host_function_table
+            .register("db::get", &[], &[OperandDataType::I32], |_| {
+                Err("connection refused".into())
+            })
+            .unwrap();
+
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::HostFunctionFailed,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_envcall_host_function_results_mismatch() {
+        // fn () -> (i32)
+        //     envcall(host function 0)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::envcall, get_host_function_envcall_num(0))
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+
+        // the closure returns a value with the wrong data type
+        let mut process_context0 = resource0.create_process_context().unwrap();
+        process_context0
+            .host_function_table
+            .register("config::version", &[], &[OperandDataType::I32], |_| {
+                Ok(vec![ForeignValue::F64(1.0)])
+            })
+            .unwrap();
+
+        let mut thread_context0 = process_context0.create_thread_context();
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::HostFunctionResultsMismatch,
+                    ..
+                },
+                ..
+            })
+        ));
+
+        // the closure returns too many values
+        let mut process_context1 = resource0.create_process_context().unwrap();
+        process_context1
+            .host_function_table
+            .register("config::version", &[], &[OperandDataType::I32], |_| {
+                Ok(vec![ForeignValue::U32(1), ForeignValue::U32(2)])
+            })
+            .unwrap();
+
+        let mut thread_context1 = process_context1.create_thread_context();
+        let result1 = process_function(&mut thread_context1, 0, 0, &[]);
+        assert!(matches!(
+            result1,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::HostFunctionResultsMismatch,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_host_function_table_full() {
+        let mut host_function_table = HostFunctionTable::new();
+        host_function_table
+            .register("func0", &[], &[], |_| Ok(vec![]))
+            .unwrap();
+
+        // fill the table, the names are not checked here.
+        let item = host_function_table.items[0].clone();
+        host_function_table
+            .items
+            .resize(MAX_HOST_FUNCTIONS - 1, item);
+
+        assert_eq!(
+            host_function_table.register("func1", &[], &[], |_| Ok(vec![])),
+            Ok(MAX_HOST_FUNCTIONS - 1)
+        );
+
+        // the index of host function would exceed the item of the envcall number
+        assert_eq!(
+            host_function_table.register("func2", &[], &[], |_| Ok(vec![])),
+            Err(HostFunctionTableError::TooManyFunctions)
+        );
+    }
+}
@@ -6,27 +6,36 @@
 
 use std::{thread, time::Duration};
 
-use anc_context::thread_context::ThreadContext;
-
-use crate::multithread_handler::{
-    create_thread, ThreadStartFunction, CHILD_THREADS, CURRENT_THREAD_ID, LAST_THREAD_MESSAGE, RX,
-    THREAD_START_DATA, TX,
+use anc_context::thread_context::{ThreadContext, TrapKind};
+
+use crate::{
+    multithread_handler::{
+        create_thread, ThreadStartFunction, CHILD_THREADS, CURRENT_THREAD_ID, LAST_THREAD_MESSAGE,
+        RX, THREAD_START_DATA, TX,
+    },
+    ProcessorError, ProcessorErrorType,
 };
 
 pub const THREAD_RUNNING_STATUS_RUNNING: u32 = 0;
 pub const THREAD_RUNNING_STATUS_FINISH: u32 = 1;
 pub const THREAD_ERROR_NUMBER_SUCCESS: u32 = 0;
 pub const THREAD_ERROR_NUMBER_NOT_FOUND: u32 = 1;
+pub const THREAD_ERROR_NUMBER_INTERRUPTED: u32 = 2;
+pub const THREAD_ERROR_NUMBER_TRAPPED: u32 = 3;
+pub const THREAD_ERROR_NUMBER_TERMINATED: u32 = 4;
+pub const THREAD_ERROR_NUMBER_FAILED: u32 = 5;
 
-pub fn thread_id(thread_context: &mut ThreadContext) {
+pub fn thread_id(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i32`
     CURRENT_THREAD_ID.with(|id_cell| {
         let id = *id_cell.borrow();
         thread_context.stack.push_i32_u(id);
     });
+
+    Ok(())
 }
 
-pub fn thread_create(thread_context: &mut ThreadContext) {
+pub fn thread_create(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // ```
     // fn (function_public_index: i32,
     //     thread_start_data_access_index: i64,
@@ -47,7 +56,7 @@ pub fn thread_create(thread_context: &mut ThreadContext) {
         thread_start_data_access_index,
         0,
         thread_start_data_length,
-    );
+    )?;
 
     let mut thread_start_data = vec![0_u8; thread_start_data_length];
     thread_start_data_object.accessor.read_idx(
@@ -65,9 +74,11 @@ pub fn thread_create(thread_context: &mut ThreadContext) {
     let child_thread_id = create_thread(thread_start_function, thread_start_data);
 
     thread_context.stack.push_i32_u(child_thread_id);
+
+    Ok(())
 }
 
-pub fn thread_start_data_length(thread_context: &mut ThreadContext) {
+pub fn thread_start_data_length(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i64`
 
     let data_length = THREAD_START_DATA.with(|data_cell| {
@@ -76,9 +87,11 @@ pub fn thread_start_data_length(thread_context: &mut ThreadContext) {
     });
 
     thread_context.stack.push_i64_u(data_length as u64);
+
+    Ok(())
 }
 
-pub fn thread_start_data_read(thread_context: &mut ThreadContext) {
+pub fn thread_start_data_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // ```
     // fn (module_index: i32,
     //     data_access_index: i64,
@@ -91,13 +104,13 @@ pub fn thread_start_data_read(thread_context: &mut ThreadContext) {
     let data_access_index = thread_context.stack.pop_i64_u() as usize;
     let module_index = thread_context.stack.pop_i32_u() as usize;
 
-    let actual_read_length = THREAD_START_DATA.with(|data_cell| {
+    let actual_read_length = THREAD_START_DATA.with(|data_cell| -> Result<usize, TrapKind> {
         let data = data_cell.borrow();
         let data_length = data.len();
 
         if offset_of_thread_start_data >= data_length {
             // Offset of thread start data is out of bounds.
-            0
+            Ok(0)
         } else {
             let available_length_in_bytes =
                 if offset_of_thread_start_data + expected_length_in_bytes > data_length {
@@ -111,7 +124,7 @@ pub fn thread_start_data_read(thread_context: &mut ThreadContext) {
                 data_access_index,
                 0,
                 available_length_in_bytes,
-            );
+            )?;
 
             let src_ptr = data[offset_of_thread_start_data..].as_ptr();
             target_data_object.accessor.write_idx(
@@ -120,19 +133,27 @@ pub fn thread_start_data_read(thread_context: &mut ThreadContext) {
                 0,
                 available_length_in_bytes,
             );
-            available_length_in_bytes
+            Ok(available_length_in_bytes)
         }
-    });
+    })?;
 
     thread_context.stack.push_i64_u(actual_read_length as u64);
+
+    Ok(())
 }
 
-pub fn thread_wait_and_collect(thread_context: &mut ThreadContext) {
+pub fn thread_wait_and_collect(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (child_thread_id: i32) -> (thread_exit_code: i32, thread_error_number: i32)`
     //
     // Returns:
-    // - thread_exit_code: The value returned by the "thread start function."
-    // - thread_error_number: 0 for success, 1 for thread not found.
+    // - thread_exit_code: The value returned by the "thread start function,"
+    //   or the terminate code if the child thread was terminated.
+    // - thread_error_number: 0 for success, 1 for thread not found,
+    //   2 for the child thread was interrupted by the host, 3 for trapped,
+    //   4 for terminated, 5 for the other failures.
+    //
+    // An error in the child thread never brings down the current thread,
+    // it is reported to the parent by the error number.
 
     let child_thread_id = thread_context.stack.pop_i32_u();
 
@@ -143,24 +164,35 @@ pub fn thread_wait_and_collect(thread_context: &mut ThreadContext) {
         let opt_child_thread = child_threads.remove(&child_thread_id);
 
         match opt_child_thread {
-            Some(child_thread) => {
-                let result = child_thread.join_handle.join().unwrap();
-                match result {
-                    Ok(thread_exit_code) => (thread_exit_code, THREAD_ERROR_NUMBER_SUCCESS),
-                    // there is no way to return the details of ProcessorError in the
-                    // child thread, so only the panic can be thrown.
-                    Err(e) => panic!("Child thread panic: {}", e),
-                }
-            }
+            Some(child_thread) => match child_thread.join_handle.join() {
+                Ok(Ok(thread_exit_code)) => (thread_exit_code, THREAD_ERROR_NUMBER_SUCCESS),
+                Ok(Err(e)) => match e.downcast_ref::<ProcessorError>().map(|e| &e.error_type) {
+                    // The child thread was stopped by the interrupt handle, the current
+                    // thread is also stopped at the next instruction if the whole
+                    // process is interrupted.
+                    Some(ProcessorErrorType::Interrupted) => (0, THREAD_ERROR_NUMBER_INTERRUPTED),
+                    Some(ProcessorErrorType::Trap { .. }) => (0, THREAD_ERROR_NUMBER_TRAPPED),
+                    Some(ProcessorErrorType::Terminate(terminate_code)) => {
+                        (*terminate_code as u32, THREAD_ERROR_NUMBER_TERMINATED)
+                    }
+                    // e.g., out of fuel, or the thread start function returns
+                    // unexpected results.
+                    _ => (0, THREAD_ERROR_NUMBER_FAILED),
+                },
+                // the host thread panicked
+                Err(_) => (0, THREAD_ERROR_NUMBER_FAILED),
+            },
             None => (0, THREAD_ERROR_NUMBER_NOT_FOUND), // thread not found
         }
     });
 
     thread_context.stack.push_i32_u(thread_exit_code);
     thread_context.stack.push_i32_u(thread_error_number);
+
+    Ok(())
 }
 
-pub fn thread_running_status(thread_context: &mut ThreadContext) {
+pub fn thread_running_status(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (child_thread_id: i32) -> (running_status: i32, thread_error_number: i32)`
     //
     // Returns:
@@ -189,9 +221,11 @@ pub fn thread_running_status(thread_context: &mut ThreadContext) {
 
     thread_context.stack.push_i32_u(thread_running_status);
     thread_context.stack.push_i32_u(thread_error_number);
+
+    Ok(())
 }
 
-pub fn thread_terminate(thread_context: &mut ThreadContext) {
+pub fn thread_terminate(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (child_thread_id: i32) -> ()`
     //
     // Note: Dropping the sender (TX) will cause the receiver (RX) in the child thread to stop.
@@ -210,9 +244,11 @@ pub fn thread_terminate(thread_context: &mut ThreadContext) {
         // remove the child thread object from 'child thread collection'
         let _ = child_threads.remove(&child_thread_id);
     });
+
+    Ok(())
 }
 
-pub fn thread_send_msg(thread_context: &mut ThreadContext) {
+pub fn thread_send_msg(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (child_thread_id: i32, module_index: i32, data_access_index: i64, content_length_in_bytes: i64) -> thread_error_number: i32`
     //
     // Returns 0 for success, 1 for failure (if the child thread has finished or does not exist).
@@ -234,7 +270,7 @@ pub fn thread_send_msg(thread_context: &mut ThreadContext) {
                     data_access_index as usize,
                     0,
                     content_length_in_bytes as usize,
-                );
+                )?;
 
                 let mut data_send = vec![0_u8; content_length_in_bytes as usize];
                 target_data_object.accessor.read_idx(
@@ -254,13 +290,16 @@ pub fn thread_send_msg(thread_context: &mut ThreadContext) {
 
         // push 'thread_error_number' to stack
         thread_context.stack.push_i32_u(thread_error_number);
-    });
+
+        Ok(())
+    })
 }
 
-pub fn thread_send_msg_to_parent(thread_context: &mut ThreadContext,) {
+pub fn thread_send_msg_to_parent(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes: i64) -> ()`
     //
     // This function is non-blocking and returns immediately.
+    // Traps if the current thread has no parent thread.
 
     let content_length_in_bytes = thread_context.stack.pop_i64_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -276,7 +315,7 @@ pub fn thread_send_msg_to_parent(thread_context: &mut ThreadContext,) {
                     data_access_index as usize,
                     0,
                     content_length_in_bytes as usize,
-                );
+                )?;
                 let mut data_send = vec![0_u8; content_length_in_bytes as usize];
                 target_data_object.accessor.read_idx(
                     data_access_index as usize,
@@ -285,17 +324,17 @@ pub fn thread_send_msg_to_parent(thread_context: &mut ThreadContext,) {
                     data_send.as_mut_ptr(),
                 );
 
-                tx.send(data_send)
-                    .expect("Send message to parent thread failed.");
-            }
-            None => {
-                unreachable!("TX of channel to parent thread is not set.")
+                // ignore errors when sending messages to the parent thread,
+                // because it means that the current thread is being terminated.
+                let _ = tx.send(data_send);
+                Ok(())
             }
-        };
-    });
+            None => Err(TrapKind::NoParentThread),
+        }
+    })
 }
 
-pub fn thread_receive_msg(thread_context: &mut ThreadContext) {
+pub fn thread_receive_msg(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (child_thread_id: i32) -> (length: i64, thread_error_number: i32)`
     //
     // Returns:
@@ -329,47 +368,49 @@ pub fn thread_receive_msg(thread_context: &mut ThreadContext) {
         thread_context.stack.push_i64_u(length as u64);
         thread_context.stack.push_i32_u(thread_error_number);
     });
+
+    Ok(())
 }
 
-pub fn thread_receive_msg_from_parent(
-    thread_context: &mut ThreadContext,
-) {
+pub fn thread_receive_msg_from_parent(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i64`
     //
     // Returns the length (in bytes) of the new message.
+    // Traps if the current thread has no parent thread.
 
     RX.with(|rx_refcell| {
         let rx_ref = rx_refcell.borrow();
         let rx_opt = rx_ref.as_ref();
         match rx_opt {
             Some(rx) => {
-                match rx.recv() {
+                let length = match rx.recv() {
                     Ok(data) => {
                         // store the received data
                         LAST_THREAD_MESSAGE.with(|msg_refcell| {
                             let length = data.len();
                             msg_refcell.replace(data);
-
-                            // push 'length' to stack
-                            thread_context.stack.push_i64_u(length as u64);
-                        });
+                            length
+                        })
                     }
                     Err(_) => {
                         // ignore errors when receiving messages from the
                         // parent thread, because it means that the current thread
                         // is being terminated, there is no longer any sense in
                         // dealing with errors.
+                        0
                     }
                 };
+
+                // push 'length' to stack
+                thread_context.stack.push_i64_u(length as u64);
+                Ok(())
             }
-            None => {
-                unreachable!("RX of channel to parent is not set.")
-            }
-        };
-    });
+            None => Err(TrapKind::NoParentThread),
+        }
+    })
 }
 
-pub fn thread_msg_length(thread_context: &mut ThreadContext) {
+pub fn thread_msg_length(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i64`
 
     LAST_THREAD_MESSAGE.with(|msg_refcell| {
@@ -379,9 +420,11 @@ pub fn thread_msg_length(thread_context: &mut ThreadContext) {
         // push 'length' to stack
         thread_context.stack.push_i64_u(length as u64);
     });
+
+    Ok(())
 }
 
-pub fn thread_msg_read(thread_context: &mut ThreadContext) {
+pub fn thread_msg_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, offset_of_message: i64, expected_size_in_bytes: i64) -> i64`
 
     let expected_size_in_bytes = thread_context.stack.pop_i64_u() as usize;
@@ -389,55 +432,60 @@ pub fn thread_msg_read(thread_context: &mut ThreadContext) {
     let data_access_index = thread_context.stack.pop_i64_u() as usize;
     let module_index = thread_context.stack.pop_i32_u() as usize;
 
-    let available_length_in_bytes = LAST_THREAD_MESSAGE.with(|msg_refcell| {
-        let msg_ref = msg_refcell.borrow();
-        let msg_length = msg_ref.len();
+    let available_length_in_bytes =
+        LAST_THREAD_MESSAGE.with(|msg_refcell| -> Result<usize, TrapKind> {
+            let msg_ref = msg_refcell.borrow();
+            let msg_length = msg_ref.len();
+
+            if offset_of_message >= msg_length {
+                // Offset of message is out of bounds.
+                Ok(0)
+            } else {
+                let available_length_in_bytes =
+                    if offset_of_message + expected_size_in_bytes > msg_length {
+                        msg_length - offset_of_message
+                    } else {
+                        expected_size_in_bytes
+                    };
 
-        if offset_of_message >= msg_length {
-            // Offset of message is out of bounds.
-            0
-        } else {
-            let available_length_in_bytes =
-                if offset_of_message + expected_size_in_bytes > msg_length {
-                    msg_length - offset_of_message
-                } else {
-                    expected_size_in_bytes
-                };
-
-            let target_data_object = thread_context.get_target_data_object(
-                module_index,
-                data_access_index,
-                0,
-                available_length_in_bytes,
-            );
+                let target_data_object = thread_context.get_target_data_object(
+                    module_index,
+                    data_access_index,
+                    0,
+                    available_length_in_bytes,
+                )?;
 
-            let src_ptr = msg_ref[offset_of_message..].as_ptr();
-            target_data_object.accessor.write_idx(
-                src_ptr,
-                data_access_index,
-                0,
-                available_length_in_bytes,
-            );
+                let src_ptr = msg_ref[offset_of_message..].as_ptr();
+                target_data_object.accessor.write_idx(
+                    src_ptr,
+                    data_access_index,
+                    0,
+                    available_length_in_bytes,
+                );
 
-            available_length_in_bytes
-        }
-    });
+                Ok(available_length_in_bytes)
+            }
+        })?;
 
     // push 'length' to stack
     thread_context
         .stack
         .push_i64_u(available_length_in_bytes as u64);
+
+    Ok(())
 }
 
 // ref:
 // https://linux.die.net/man/2/nanosleep
-pub fn thread_sleep(thread_context: &mut ThreadContext) {
+pub fn thread_sleep(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // Blocks the current thread for the specified number of milliseconds.
     //
     // Signature: `fn (milliseconds: i64) -> ()`
 
     let milliseconds = thread_context.stack.pop_i64_u();
     thread::sleep(Duration::from_millis(milliseconds));
+
+    Ok(())
 }
 
 // Note:
@@ -4,10 +4,10 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 use regex_anre::Regex;
 
-pub fn regex_create(thread_context: &mut ThreadContext) {
+pub fn regex_create(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, data_length_in_bytes:i32, flavour:i32) -> i32`
     // Returns `regex_index` if the compilation is successful, or -1 if it fails.
 
@@ -21,7 +21,7 @@ pub fn regex_create(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         data_length_in_bytes as usize,
-    );
+    )?;
 
     let content_address = target_data_object
         .accessor
@@ -49,9 +49,11 @@ pub fn regex_create(thread_context: &mut ThreadContext) {
             thread_context.stack.push_i32_u(u32::MAX);
         }
     }
+
+    Ok(())
 }
 
-pub fn regex_capture_group_count(thread_context: &mut ThreadContext) {
+pub fn regex_capture_group_count(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (regex_index: i32) -> i32`
     // Returns -1 if the regex object does not exist.
 
@@ -65,15 +67,19 @@ pub fn regex_capture_group_count(thread_context: &mut ThreadContext) {
         None => {
             // If the regex object does not exist, return -1.
             thread_context.stack.push_i32_u(u32::MAX);
-            return;
+            return Ok(());
         }
     };
 
     let count = regex.object_file.capture_group_names.len();
     thread_context.stack.push_i32_u(count as u32);
+
+    Ok(())
 }
 
-pub fn regex_capture_group_names_length(thread_context: &mut ThreadContext) {
+pub fn regex_capture_group_names_length(
+    thread_context: &mut ThreadContext,
+) -> Result<(), TrapKind> {
     // `fn (regex_index: i32) -> i32`
     // Returns -1 if the regex object does not exist.
 
@@ -87,7 +93,7 @@ pub fn regex_capture_group_names_length(thread_context: &mut ThreadContext) {
         None => {
             // If the regex object does not exist, return -1.
             thread_context.stack.push_i32_u(u32::MAX);
-            return;
+            return Ok(());
         }
     };
 
@@ -112,9 +118,11 @@ pub fn regex_capture_group_names_length(thread_context: &mut ThreadContext) {
 
         thread_context.stack.push_i32_u(total_length as u32);
     }
+
+    Ok(())
 }
 
-pub fn regex_capture_group_names_read(thread_context: &mut ThreadContext) {
+pub fn regex_capture_group_names_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (regex_index: i32, module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> i32`
 
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
@@ -130,7 +138,7 @@ pub fn regex_capture_group_names_read(thread_context: &mut ThreadContext) {
         None => {
             // If the regex object does not exist, return -1.
             thread_context.stack.push_i32_u(u32::MAX);
-            return;
+            return Ok(());
         }
     };
 
@@ -166,7 +174,7 @@ pub fn regex_capture_group_names_read(thread_context: &mut ThreadContext) {
             data_access_index as usize,
             0,
             actual_read_length,
-        );
+        )?;
 
         let src_ptr = content_bytes.as_ptr();
         target_data_object.accessor.write_idx(
@@ -178,9 +186,11 @@ pub fn regex_capture_group_names_read(thread_context: &mut ThreadContext) {
 
         thread_context.stack.push_i32_u(actual_read_length as u32);
     }
+
+    Ok(())
 }
 
-pub fn regex_match(thread_context: &mut ThreadContext) {
+pub fn regex_match(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // ```
     // fn (
     //   regex_index: i32,
@@ -206,7 +216,7 @@ pub fn regex_match(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         data_length_in_bytes as usize,
-    );
+    )?;
 
     let content_address = target_data_object
         .accessor
@@ -225,7 +235,7 @@ pub fn regex_match(thread_context: &mut ThreadContext) {
             // If the regex object does not exist, return (0, -1).
             thread_context.stack.push_i32_u(0);
             thread_context.stack.push_i32_u(u32::MAX);
-            return;
+            return Ok(());
         }
     };
 
@@ -241,7 +251,7 @@ pub fn regex_match(thread_context: &mut ThreadContext) {
         // no match found, return `(0, 0)`
         thread_context.stack.push_i32_u(0);
         thread_context.stack.push_i32_u(0);
-        return;
+        return Ok(());
     }
 
     let range = context.match_ranges[0].clone();
@@ -254,9 +264,11 @@ pub fn regex_match(thread_context: &mut ThreadContext) {
     // Returns `(match_start:i32, match_end_exclusive:i32)`
     thread_context.stack.push_i32_u(range.start as u32);
     thread_context.stack.push_i32_u(range.end as u32);
+
+    Ok(())
 }
 
-pub fn regex_last_captures_read(thread_context: &mut ThreadContext) {
+pub fn regex_last_captures_read(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64, data_length_in_bytes:i32) -> i32`
     let expected_data_length_in_bytes = thread_context.stack.pop_i32_u();
     let data_access_index = thread_context.stack.pop_i64_u();
@@ -285,7 +297,7 @@ pub fn regex_last_captures_read(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         actual_read_length,
-    );
+    )?;
 
     let src_ptr = content_bytes.as_ptr();
     target_data_object.accessor.write_idx(
@@ -296,9 +308,11 @@ pub fn regex_last_captures_read(thread_context: &mut ThreadContext) {
     );
 
     thread_context.stack.push_i32_u(actual_read_length as u32);
+
+    Ok(())
 }
 
-pub fn regex_remove(thread_context: &mut ThreadContext) {
+pub fn regex_remove(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (regex_index: i32)`
 
     let regex_index = thread_context.stack.pop_i32_u();
@@ -306,4 +320,6 @@ pub fn regex_remove(thread_context: &mut ThreadContext) {
     thread_context
         .thread_resources
         .remove_regex(regex_index as usize);
+
+    Ok(())
 }
@@ -4,10 +4,10 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 use anc_isa::RUNTIME_EDITION;
 
-pub fn runtime_edition(thread_context: &mut ThreadContext) {
+pub fn runtime_edition(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn (module_index: i32, data_access_index: i64)`
 
     const CONTENT_LENGTH_IN_BYTES: usize = RUNTIME_EDITION.len();
@@ -20,7 +20,7 @@ pub fn runtime_edition(thread_context: &mut ThreadContext) {
         data_access_index as usize,
         0,
         CONTENT_LENGTH_IN_BYTES,
-    );
+    )?;
 
     let src_ptr = RUNTIME_EDITION.as_ptr();
 
@@ -30,17 +30,28 @@ pub fn runtime_edition(thread_context: &mut ThreadContext) {
         0,
         CONTENT_LENGTH_IN_BYTES,
     );
+
+    Ok(())
 }
 
-pub fn runtime_version(thread_context: &mut ThreadContext) {
+pub fn runtime_version(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> i64`
-    //
-    // 0x0000_0000_0000_0000
-    //        |    |    |
-    //        |    |    |patch version
-    //        |    |minor
-    //        |major
+    let version_number = get_runtime_version_number();
+    thread_context.stack.push_i64_u(version_number);
+
+    Ok(())
+}
 
+/// Returns the version number of the runtime, it is also used by the AOT runtime.
+///
+/// ```text
+/// 0x0000_0000_0000_0000
+///        |    |    |
+///        |    |    |patch version
+///        |    |minor
+///        |major
+/// ```
+pub fn get_runtime_version_number() -> u64 {
     // Cargo environment variables provide the version information of the package.
     // - `CARGO_PKG_VERSION_MAJOR`: The major version of your package.
     // - `CARGO_PKG_VERSION_MINOR`: The minor version of your package.
@@ -51,10 +62,7 @@ pub fn runtime_version(thread_context: &mut ThreadContext) {
     let version_minor = env!("CARGO_PKG_VERSION_MINOR").parse::<u16>().unwrap();
     let version_major = env!("CARGO_PKG_VERSION_MAJOR").parse::<u16>().unwrap();
 
-    let version_number =
-        version_patch as u64 | (version_minor as u64) << 16 | (version_major as u64) << 32;
-
-    thread_context.stack.push_i64_u(version_number);
+    version_patch as u64 | (version_minor as u64) << 16 | (version_major as u64) << 32
 }
 
 #[cfg(test)]
@@ -102,7 +110,6 @@ mod tests {
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -136,7 +143,6 @@ mod tests {
             code0,
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
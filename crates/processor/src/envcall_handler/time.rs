@@ -6,7 +6,7 @@
 
 use std::time::SystemTime;
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 
 // The current time can be obtained using the `libc::clock_gettime` function.
 // Example:
@@ -29,9 +29,19 @@ use anc_context::thread_context::ThreadContext;
 //
 // Reference:
 // https://linux.die.net/man/3/clock_gettime
-pub fn time_now(thread_context: &mut ThreadContext) {
+pub fn time_now(thread_context: &mut ThreadContext) -> Result<(), TrapKind> {
     // `fn () -> (seconds: u64, nano_seconds: u64)`
+    let (secs, nanos) = get_time_now();
 
+    thread_context.stack.push_i64_u(secs);
+    thread_context.stack.push_i64_u(nanos);
+
+    Ok(())
+}
+
+/// Returns the seconds and the nanoseconds since the UNIX epoch,
+/// it is also used by the AOT runtime.
+pub fn get_time_now() -> (u64, u64) {
     let total_nanos = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
         Ok(n) => n.as_nanos(),
         Err(_) => 0, // SystemTime before UNIX EPOCH
@@ -40,8 +50,7 @@ pub fn time_now(thread_context: &mut ThreadContext) {
     let secs = (total_nanos / 1_000_000_000_u128) as u64;
     let nanos = (total_nanos % 1_000_000_000_u128) as u64;
 
-    thread_context.stack.push_i64_u(secs);
-    thread_context.stack.push_i64_u(nanos);
+    (secs, nanos)
 }
 
 #[cfg(test)]
@@ -478,8 +478,14 @@ pub enum EnvCallNum {
     // `fn (child_thread_id: i32) -> (thread_exit_code: i32, thread_error_number: i32)`
     //
     // Returns:
-    // - thread_exit_code: The value returned by the "thread start function."
-    // - thread_error_number: 0 for success, 1 for thread not found.
+    // - thread_exit_code: The value returned by the "thread start function,"
+    //   or the terminate code if the child thread was terminated.
+    // - thread_error_number: 0 for success, 1 for thread not found,
+    //   2 for the child thread was interrupted by the host (see `InterruptHandle`),
+    //   3 for the child thread trapped, 4 for the child thread was terminated,
+    //   5 for the other failures (e.g., out of fuel).
+    //
+    // An error in the child thread does not stop the caller.
     //
     // The caller will be blocked if the child thread is running. When the child thread finishes,
     // this function returns a tuple `(thread_exit_code, thread_error_number)`,
@@ -519,6 +525,7 @@ pub enum EnvCallNum {
     // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes: i64) -> ()`
     //
     // This function is non-blocking and returns immediately.
+    // It traps if the current thread is the main thread.
     thread_send_msg_to_parent,
 
     // Receive a message from the specified child thread.
@@ -554,6 +561,7 @@ pub enum EnvCallNum {
     // Returns the length (in bytes) of the new message.
     //
     // If the parent thread terminates this thread, the thread will exit even if blocked in this function.
+    // It traps if the current thread is the main thread.
     //
     // Notes:
     // - The message is copied to a runtime temporary buffer ("letter paper"). Use `thread_msg_read` to access the message.
@@ -603,36 +611,76 @@ pub enum EnvCallNum {
     // if it is the first time the program requests it.
     // The user can choose to grant (and remember this choice for future runs of the program)
     // or deny the capability.
-    // If this capability is granted,
+    // If this capability is granted, it is added to the capability of the process,
+    // and all threads of the process have it.
+    //
+    // All the functions in this category return 1 if the capability is granted, or 0 if denied.
+    // The request is granted without prompting if the process already has the capability.
+    //
+    // Access type
+    // -----------
+    // 0: read
+    // 1: write
+    // 2: read and write
 
     // Request the capability to perform a system call.
     //
     // `fn () -> i32`
     //
+    // The instruction `syscall` terminates the program with the code `TERMINATE_CODE_CAPABILITY_DENIED`
+    // if the process is not capable, or the syscall number is not permitted by `Capability::syscall_filter`.
     capable_syscall = 0x0008_0000,
 
+    // Request the capability to call the external functions.
+    //
+    // `fn () -> i32`
+    //
+    // The instruction `extcall` terminates the program with the code `TERMINATE_CODE_CAPABILITY_DENIED`
+    // if the process is not capable, or the function is not permitted by `Capability::extcall_filter`.
     capable_extcall,
 
+    // Request the capability to execute the specified shell command.
+    //
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> i32`
+    //
+    // Parameters `module_index` and `data_access_index` specify a data object representing
+    // the command name, e.g. "ls".
     capable_shell_execute,
 
+    // Request the capability to execute all shell commands.
+    //
+    // `fn () -> i32`
     capable_shell_execute_all,
 
+    // Request the capability to execute the specified executable file.
+    //
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32) -> i32`
+    //
+    // Parameters `module_index` and `data_access_index` specify a data object representing
+    // the file path.
     capable_file_execute,
 
+    // Request the capability to execute all executable files.
+    //
+    // `fn () -> i32`
     capable_file_execute_all,
 
     // Request personal directory access capabilities.
     //
-    // - PERSONAL_DESKTOP_DIR="$HOME/Desktop"
-    // - PERSONAL_DOCUMENTS_DIR="$HOME/Documents"
-    // - PERSONAL_DOWNLOAD_DIR="$HOME/Downloads"
-    // - PERSONAL_PICTURES_DIR="$HOME/Pictures"
-    // - PERSONAL_MUSIC_DIR="$HOME/Music"
-    // - PERSONAL_VIDEOS_DIR="$HOME/Videos"
-    // - PERSONAL_CONFIG_DIR="$HOME/.config"
-    // - PERSONAL_CACHE_DIR="$HOME/.cache"
-    // - PERSONAL_DATA_DIR="$HOME/.local/share"
+    // `fn (dir_type: i32, access_type: i32) -> i32`
     //
+    // Directory type:
+    // - 0: PERSONAL_DESKTOP_DIR="$HOME/Desktop"
+    // - 1: PERSONAL_DOCUMENTS_DIR="$HOME/Documents"
+    // - 2: PERSONAL_DOWNLOAD_DIR="$HOME/Downloads"
+    // - 3: PERSONAL_PICTURES_DIR="$HOME/Pictures"
+    // - 4: PERSONAL_MUSIC_DIR="$HOME/Music"
+    // - 5: PERSONAL_VIDEOS_DIR="$HOME/Videos"
+    // - 6: PERSONAL_CONFIG_DIR="$HOME/.config"
+    // - 7: PERSONAL_CACHE_DIR="$HOME/.cache"
+    // - 8: PERSONAL_DATA_DIR="$HOME/.local/share"
+    //
+    // Returns 0 if the `dir_type` or `access_type` is invalid.
     //
     // See:
     // - https://wiki.archlinux.org/title/XDG_Base_Directory
@@ -641,21 +689,35 @@ pub enum EnvCallNum {
 
     // Request application directory access capabilities.
     //
-    // - APPLICATION_DESKTOP_DIR="$HOME/Desktop/$APP_ID"
-    // - APPLICATION_DOCUMENTS_DIR="$HOME/Documents/$APP_ID"
-    // - APPLICATION_DOWNLOAD_DIR="$HOME/Downloads/$APP_ID"
-    // - APPLICATION_PICTURES_DIR="$HOME/Pictures/$APP_ID"
-    // - APPLICATION_MUSIC_DIR="$HOME/Music/$APP_ID"
-    // - APPLICATION_VIDEOS_DIR="$HOME/Videos/$APP_ID"
-    // - APPLICATION_CONFIG_DIR="$HOME/.config/$APP_ID"
-    // - APPLICATION_CACHE_DIR="$HOME/.cache/$APP_ID"
-    // - APPLICATION_DATA_DIR="$HOME/.local/share/$APP_ID"
+    // `fn (dir_type: i32, access_type: i32) -> i32`
+    //
+    // Directory type:
+    // - 0: APPLICATION_DESKTOP_DIR="$HOME/Desktop/$APP_ID"
+    // - 1: APPLICATION_DOCUMENTS_DIR="$HOME/Documents/$APP_ID"
+    // - 2: APPLICATION_DOWNLOAD_DIR="$HOME/Downloads/$APP_ID"
+    // - 3: APPLICATION_PICTURES_DIR="$HOME/Pictures/$APP_ID"
+    // - 4: APPLICATION_MUSIC_DIR="$HOME/Music/$APP_ID"
+    // - 5: APPLICATION_VIDEOS_DIR="$HOME/Videos/$APP_ID"
+    // - 6: APPLICATION_CONFIG_DIR="$HOME/.config/$APP_ID"
+    // - 7: APPLICATION_CACHE_DIR="$HOME/.cache/$APP_ID"
+    // - 8: APPLICATION_DATA_DIR="$HOME/.local/share/$APP_ID"
+    //
+    // Returns 0 if the `dir_type` or `access_type` is invalid.
     //
     // Only installed applications can access these directories.
     capable_application_dir_access,
 
+    // Request the capability to access the specified file.
+    //
+    // `fn (module_index: i32, data_access_index: i64, content_length_in_bytes:i32, access_type: i32) -> i32`
+    //
+    // Parameters `module_index` and `data_access_index` specify a data object representing
+    // the file path.
     capable_file_access,
 
+    // Request the capability to access all files.
+    //
+    // `fn (access_type: i32) -> i32`
     capable_file_access_all,
 
     // Category: I/O
@@ -668,7 +730,7 @@ pub enum EnvCallNum {
     // The file path can be absolute (e.g., /home/yang/Documents/readme.txt) or relative (e.g., ../images/banner.png),
     // but cannot include shell-specific paths like `~/Downloads/123.dat` or `${HOME}/projects`.
     //
-    // Returns `(file_index: i32, io_error_number: i32)`.
+    // Returns `(file_index: i32, io_error_number: i32)`, the `file_index` is -1 if it fails.
     file_open = 0x0009_0000,
 
     // Access mode (bit flags)
@@ -715,10 +777,19 @@ pub enum EnvCallNum {
     // 2: PermissionDenied
     //     - The user lacks permission to get the specified access rights for the file.
     //     - The user lacks permission to open one of the directory components of the specified path.
+    //     - The process is not capable of accessing the path with the requested access type,
+    //       see `Capability::file_access`, `Capability::dir_access` and `capable_file_access`.
+    //       The path is checked after resolving `..` and symbolic links. Opening a file with
+    //       `write`, `append`, `truncate` or any create option requires the write access.
     // 3: AlreadyExists
     //     create_only_non_exist was specified and the file already exists.
     // 4: InvalidInput
-    //     Invalid combinations of open options (e.g., `truncate` without `write` access, etc.).
+    //     Invalid combinations of open options (e.g., `truncate` without `write` access, etc.),
+    //     the file path is not a valid UTF-8 string, or the file index does not exist.
+    // 5: Unsupported
+    //     The operation is not supported by the file, e.g. writing to the stdin or seeking the stdout.
+    // 6: Other
+    //     Any other I/O error.
 
     // File index
     // ----------
@@ -763,14 +834,14 @@ pub enum EnvCallNum {
     // Flushes the output stream,
     // ensuring that all intermediately buffered contents reach their destination.
     //
-    // `fn () -> io_error_number: i32`
+    // `fn (file_index: i32) -> io_error_number: i32`
     //
     // Returns the file error number.
     file_flush,
 
     // Close the specified file.
     //
-    // `fn () -> io_error_number: i32`
+    // `fn (file_index: i32) -> io_error_number: i32`
     file_close,
 
     // Returns true if the file index refers to a terminal/tty.
@@ -779,21 +850,59 @@ pub enum EnvCallNum {
     file_is_terminal,
 
     // Category: File System
+
+    // File System Error Number
+    // ------------------------
+    // The same as the file error number, see `file_open`.
+    //
+    // The paths passed to `fs_list_dir` and `fs_exists` require the read access,
+    // and the paths passed to `fs_create_dir`, `fs_remove_dir`, `fs_remove_file` and
+    // `fs_rename` (both source and destination) require the write access.
+
+    // Retrieve the current working directory of the process.
+    //
+    // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
+    //
+    // Returns the full length of the path in bytes, only the first `expected_data_length_in_bytes`
+    // bytes are written if the data is too small, so the path is truncated
+    // if `path_length > expected_data_length_in_bytes`.
+    //
+    // The canonical form of the directory is returned, and the read access to it is required,
+    // otherwise the `fs_error_number` is `PermissionDenied` and no path is written,
+    // because the path may reveal the information of the host (e.g. the user name).
     fs_get_current_dir = 0x000A_0000,
 
+    // Retrieve the temporary directory of the host.
+    //
+    // `fn (module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
+    //
+    // See `fs_get_current_dir` for the return values and the required access.
     fs_get_temporary_dir,
 
-    // Such as:
+    // Retrieve the directory of the current application.
     //
-    // - `$HOME/Documents/{app_name}`
-    // - `$HOME/Downloads/{app_name}`
-    // - `$HOME/Pictures/{app_name} `
-    // - `$HOME/Music/{app_name}`
-    // - `$HOME/Videos/{app_name}`
-    // - `$HOME/.config/{app_name}`
-    // - `$HOME/.cache/{app_name}`
-    // - `$HOME/.local/share/{app_name}`
+    // `fn (dir_type: i32, module_index: i32, data_access_index: i64, expected_data_length_in_bytes:i32) -> (path_length: i32, fs_error_number: i32)`
     //
+    // The possible values for `dir_type` are:
+    //
+    // - 0: `$HOME/Desktop/{app_name}`
+    // - 1: `$HOME/Documents/{app_name}`
+    // - 2: `$HOME/Downloads/{app_name}`
+    // - 3: `$HOME/Pictures/{app_name}`
+    // - 4: `$HOME/Music/{app_name}`
+    // - 5: `$HOME/Videos/{app_name}`
+    // - 6: `$HOME/.config/{app_name}`
+    // - 7: `$HOME/.cache/{app_name}`
+    // - 8: `$HOME/.local/share/{app_name}`
+    //
+    // The values are the same as the ones of `capable_application_dir_access`.
+    //
+    // The `app_name` is the name of the module directory, or the file name (without the extension)
+    // of the script file or package image, or the name of the main module if the program
+    // is built in memory. The directory is not created by this function.
+    //
+    // See `fs_get_current_dir` for the return values, the `fs_error_number` is `NotFound`
+    // if the home directory is unknown and `InvalidInput` if the `dir_type` is invalid.
     fs_get_application_dir,
 
     // Opens a directory stream corresponding to the directory name and returns an index
@@ -810,7 +919,12 @@ pub enum EnvCallNum {
     // Retrieve the next entry in the directory stream.
     // The data buffer must be at least 256 bytes long. The exact size requirement may vary depending on the platform.
     //
-    // If the last entry has been read, this function returns `(0, 0)`
+    // Only the file name of the entry is written. If the name is longer than the data,
+    // `(name_length, InvalidInput)` is returned and the entry is kept for the next call,
+    // so it can be read again with a larger data.
+    //
+    // If the last entry has been read, this function returns `(0, 0)` and the directory
+    // stream is closed.
     fs_list_dir_read,
 
     // Creates a new, empty directory at the provided path.
@@ -851,3 +965,19 @@ pub enum EnvCallNum {
     // You should only use it in scenarios where those bugs are not an issue.
     fs_exists,
 }
+
+// Category: Host functions
+//
+// The category `0x0100` is reserved for the host functions registered by
+// the application which embeds the VM (see `anc_context::host_function_table`),
+// the item of the envcall number is the index of the host function, e.g.
+// the envcall number of the first host function is `0x0100_0000`.
+//
+// The signature of the envcall is the one declared when registering the host function.
+pub const ENVCALL_CATEGORY_HOST_FUNCTION: u32 = 0x0100;
+
+/// Returns the envcall number of the specified host function, the index is
+/// less than `MAX_HOST_FUNCTIONS` since the registration refuses the others.
+pub fn get_host_function_envcall_num(host_function_index: usize) -> u32 {
+    (ENVCALL_CATEGORY_HOST_FUNCTION << 16) | host_function_index as u32
+}
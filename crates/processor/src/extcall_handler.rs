@@ -28,6 +28,43 @@ use crate::{ProcessorError, ProcessorErrorType};
 
 static LAST_WRAPPER_FUNCTION_ID: Mutex<usize> = Mutex::new(0);
 
+/// Returns the name of the external library entry and the name of the external function,
+/// or `None` if the external function index is out of bounds.
+pub fn get_external_library_and_function_name(
+    thread_context: &ThreadContext,
+    module_index: usize,
+    external_function_index: usize,
+) -> Option<(String, String)> {
+    let module_linking_instance = &thread_context.module_linking_instance;
+
+    if external_function_index
+        >= module_linking_instance
+            .external_function_index_section
+            .get_items_count(module_index)
+    {
+        return None;
+    }
+
+    let unified_external_function_index = module_linking_instance
+        .external_function_index_section
+        .get_item_unified_external_function_index(module_index, external_function_index);
+
+    let (external_function_name, unified_external_library_index, _) = module_linking_instance
+        .unified_external_function_section
+        .get_item_name_and_external_library_index_and_type_index(unified_external_function_index);
+
+    let (external_library_name, _, _) = module_linking_instance
+        .unified_external_library_section
+        .get_item_name_and_external_library_dependent_type_and_value(
+            unified_external_library_index,
+        );
+
+    Some((
+        external_library_name.to_owned(),
+        external_function_name.to_owned(),
+    ))
+}
+
 pub fn get_or_create_external_function_wrapper_function(
     thread_context: &mut ThreadContext,
     module_index: usize,
@@ -79,9 +116,9 @@ pub fn get_or_create_external_function_wrapper_function(
 
     // For C ABI compatibility, only zero or one result is allowed.
     if result_datatypes.len() > 1 {
-        return Err(ProcessorError {
-            error_type: ProcessorErrorType::ExternalFunctionMoreThanOneResult,
-        });
+        return Err(ProcessorError::new(
+            ProcessorErrorType::ExternalFunctionMoreThanOneResult,
+        ));
     }
 
     let contains_return_value = !result_datatypes.is_empty();
@@ -208,9 +245,7 @@ fn create_wrapper_function_item(
             if let Ok(p) = load_library(external_library_file_path_or_system_library_name) {
                 p
             } else {
-                return Err(ProcessorError {
-                    error_type: ProcessorErrorType::ItemNotFound,
-                });
+                return Err(ProcessorError::new(ProcessorErrorType::ItemNotFound));
             };
 
         external_function_table.unified_external_library_pointer_list
@@ -226,9 +261,7 @@ fn create_wrapper_function_item(
         if let Ok(p) = load_symbol(library_pointer, external_function_name) {
             p
         } else {
-            return Err(ProcessorError {
-                error_type: ProcessorErrorType::ItemNotFound,
-            });
+            return Err(ProcessorError::new(ProcessorErrorType::ItemNotFound));
         };
 
     // Find or create the wrapper function index.
@@ -0,0 +1,530 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// File Program Source
+// -------------------
+//
+// Loads the module images of a program from the file system, the type of the
+// program is determined by the program path:
+//
+// - A directory: a module (`ProgramSourceType::Module`), the compiled module images
+//   are located in the folder `target` of the module, the main module image is
+//   `target/main.ancm`, and the other `*.ancm` files are the dependencies, e.g.
+//
+//   ```text
+//   /path/to/projects/hello-world
+//   |-- module.anc.toml
+//   |-- src
+//   |   \-- main.anc
+//   \-- target
+//       |-- main.ancm
+//       \-- http.ancm
+//   ```
+//
+// - A `*.ancp` file: a package image (`ProgramSourceType::PackageImage`), see the
+//   layout below.
+//
+// - A `*.anc`, `*.ancr` or `*.anca` file: a script file (`ProgramSourceType::ScriptFile`),
+//   the compiled module image is located next to the script file with the
+//   extension `ancm`, e.g. `/path/to/scripts/hello-world.ancm`. A compiled module image
+//   can also be specified directly.
+//
+// The layout of package image:
+//
+// ```text
+// | "ancp" | 0u32 (reserved) | module images count: u32 | 0u32 (padding) |
+// | offset of image 0: u32 | length of image 0: u32 | ... |
+// | image 0 | padding | image 1 | ... |
+// ```
+//
+// The first module image is the main module, the offsets are relative to the start of
+// the file and aligned to 8 bytes, so that the images can be used in place when the
+// file is memory-mapped (see `mmap_program_source`). All numbers are little-endian.
+
+use std::{
+    fmt::Display,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anc_context::{
+    process_context::ProcessContext,
+    process_property::{ProcessProperty, ProgramSourceType},
+    program_source::ProgramSource,
+};
+use anc_image::{utils::helper_load_modules_from_binaries, ImageError};
+
+pub const MODULE_IMAGE_FILE_EXTENSION: &str = "ancm";
+pub const PACKAGE_IMAGE_FILE_EXTENSION: &str = "ancp";
+pub const SCRIPT_FILE_EXTENSIONS: [&str; 3] = ["anc", "ancr", "anca"];
+
+pub const MODULE_IMAGES_FOLDER_NAME: &str = "target";
+pub const MAIN_MODULE_IMAGE_FILE_NAME: &str = "main.ancm";
+
+const PACKAGE_IMAGE_MAGIC: &[u8; 4] = b"ancp";
+const PACKAGE_IMAGE_HEADER_LENGTH: usize = 16;
+const PACKAGE_IMAGE_TABLE_ITEM_LENGTH: usize = 8;
+const PACKAGE_IMAGE_ALIGNMENT: usize = 8;
+
+#[derive(Debug)]
+pub enum FileProgramSourceError {
+    // The program path, the module image file or the package image file does not exist.
+    NotFound(PathBuf),
+
+    // Failed to read the file or the directory.
+    Io(PathBuf, std::io::Error),
+
+    // The program path is neither a directory nor a supported file.
+    UnsupportedFileType(PathBuf),
+
+    // The package image file is malformed, the second item is the details.
+    MalformedPackageImage(PathBuf, String),
+}
+
+impl Display for FileProgramSourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileProgramSourceError::NotFound(path) => {
+                write!(f, "File or directory \"{}\" not found.", path.display())
+            }
+            FileProgramSourceError::Io(path, error) => {
+                write!(f, "Failed to read \"{}\": {}", path.display(), error)
+            }
+            FileProgramSourceError::UnsupportedFileType(path) => write!(
+                f,
+                "Unsupported program \"{}\", it should be a module directory, a package image or a script file.",
+                path.display()
+            ),
+            FileProgramSourceError::MalformedPackageImage(path, details) => write!(
+                f,
+                "Malformed package image \"{}\": {}",
+                path.display(),
+                details
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FileProgramSourceError {}
+
+/// An implement of `ProgramSource` which loads the module images from the file system.
+pub struct FileProgramSource {
+    process_property: ProcessProperty,
+    module_binaries: Vec<Vec<u8>>,
+}
+
+impl FileProgramSource {
+    /// Loads the module images of the specified program with the default process property.
+    pub fn open<P: AsRef<Path>>(program_path: P) -> Result<Self, FileProgramSourceError> {
+        Self::with_property(program_path, ProcessProperty::default())
+    }
+
+    /// Loads the module images of the specified program, the `program_path` and
+    /// `program_source_type` of the process property are updated accordingly.
+    pub fn with_property<P: AsRef<Path>>(
+        program_path: P,
+        mut process_property: ProcessProperty,
+    ) -> Result<Self, FileProgramSourceError> {
+        let program_location = locate_program(program_path.as_ref())?;
+
+        let mut module_binaries = vec![];
+        for image_file_path in &program_location.image_file_paths {
+            let bytes = read_file(image_file_path)?;
+            for range in get_module_image_ranges(
+                program_location.program_source_type,
+                image_file_path,
+                &bytes,
+            )? {
+                module_binaries.push(bytes[range].to_vec());
+            }
+        }
+
+        process_property.program_path = program_location.program_path;
+        process_property.program_source_type = program_location.program_source_type;
+
+        Ok(Self {
+            process_property,
+            module_binaries,
+        })
+    }
+
+    pub fn get_process_property(&self) -> &ProcessProperty {
+        &self.process_property
+    }
+}
+
+impl ProgramSource for FileProgramSource {
+    fn create_process_context(&self) -> Result<ProcessContext, ImageError> {
+        let binaries_ref = self
+            .module_binaries
+            .iter()
+            .map(|e| &e[..])
+            .collect::<Vec<_>>();
+
+        let module_images = helper_load_modules_from_binaries(&binaries_ref)?;
+
+        Ok(ProcessContext::new(
+            self.process_property.clone(),
+            module_images,
+        ))
+    }
+}
+
+/// Packs the module images into a package image, the first module image is the main module.
+pub fn build_package_image(module_binaries: &[Vec<u8>]) -> Vec<u8> {
+    let mut package_image = vec![];
+
+    package_image.extend_from_slice(PACKAGE_IMAGE_MAGIC);
+    package_image.extend_from_slice(&0u32.to_le_bytes());
+    package_image.extend_from_slice(&(module_binaries.len() as u32).to_le_bytes());
+    package_image.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut offset = align_to(
+        PACKAGE_IMAGE_HEADER_LENGTH + module_binaries.len() * PACKAGE_IMAGE_TABLE_ITEM_LENGTH,
+    );
+
+    for binary in module_binaries {
+        package_image.extend_from_slice(&(offset as u32).to_le_bytes());
+        package_image.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+        offset = align_to(offset + binary.len());
+    }
+
+    for binary in module_binaries {
+        package_image.resize(align_to(package_image.len()), 0);
+        package_image.extend_from_slice(binary);
+    }
+
+    package_image
+}
+
+fn align_to(offset: usize) -> usize {
+    offset.div_ceil(PACKAGE_IMAGE_ALIGNMENT) * PACKAGE_IMAGE_ALIGNMENT
+}
+
+/// The canonical path, the type and the image files of a program.
+pub(crate) struct ProgramLocation {
+    pub program_path: PathBuf,
+    pub program_source_type: ProgramSourceType,
+
+    // The first image file contains the main module, a package image file
+    // contains multiple module images, see `get_module_image_ranges`.
+    pub image_file_paths: Vec<PathBuf>,
+}
+
+pub(crate) fn locate_program(
+    program_path: &Path,
+) -> Result<ProgramLocation, FileProgramSourceError> {
+    // The absolute path is required for resolving the relative paths of external libraries.
+    let program_path = program_path.canonicalize().map_err(|error| {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            FileProgramSourceError::NotFound(program_path.to_path_buf())
+        } else {
+            FileProgramSourceError::Io(program_path.to_path_buf(), error)
+        }
+    })?;
+
+    let (program_source_type, image_file_paths) = if program_path.is_dir() {
+        (
+            ProgramSourceType::Module,
+            locate_module_images(&program_path)?,
+        )
+    } else {
+        match program_path.extension().and_then(|ext| ext.to_str()) {
+            Some(PACKAGE_IMAGE_FILE_EXTENSION) => {
+                (ProgramSourceType::PackageImage, vec![program_path.clone()])
+            }
+            Some(MODULE_IMAGE_FILE_EXTENSION) => {
+                (ProgramSourceType::ScriptFile, vec![program_path.clone()])
+            }
+            Some(ext) if SCRIPT_FILE_EXTENSIONS.contains(&ext) => (
+                ProgramSourceType::ScriptFile,
+                vec![program_path.with_extension(MODULE_IMAGE_FILE_EXTENSION)],
+            ),
+            _ => {
+                return Err(FileProgramSourceError::UnsupportedFileType(program_path));
+            }
+        }
+    };
+
+    Ok(ProgramLocation {
+        program_path,
+        program_source_type,
+        image_file_paths,
+    })
+}
+
+/// Returns the ranges of the module images in the content of an image file.
+pub(crate) fn get_module_image_ranges(
+    program_source_type: ProgramSourceType,
+    image_file_path: &Path,
+    bytes: &[u8],
+) -> Result<Vec<Range<usize>>, FileProgramSourceError> {
+    if program_source_type != ProgramSourceType::PackageImage {
+        return Ok(vec![0..bytes.len()]);
+    }
+
+    let malformed = |details: &str| {
+        FileProgramSourceError::MalformedPackageImage(
+            image_file_path.to_path_buf(),
+            details.to_owned(),
+        )
+    };
+
+    let read_u32 = |offset: usize| -> Option<usize> {
+        let bytes = bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+
+    if bytes.len() < PACKAGE_IMAGE_HEADER_LENGTH || &bytes[0..4] != PACKAGE_IMAGE_MAGIC {
+        return Err(malformed("invalid file header"));
+    }
+
+    let count = read_u32(8).unwrap();
+    if count == 0 {
+        return Err(malformed("no module image"));
+    }
+
+    // The count comes from the file, check it against the length of the file
+    // before allocating the ranges.
+    if count > (bytes.len() - PACKAGE_IMAGE_HEADER_LENGTH) / PACKAGE_IMAGE_TABLE_ITEM_LENGTH {
+        return Err(malformed("the module image count exceeds the file length"));
+    }
+
+    let mut ranges = Vec::with_capacity(count);
+    for idx in 0..count {
+        let item_offset = PACKAGE_IMAGE_HEADER_LENGTH + idx * PACKAGE_IMAGE_TABLE_ITEM_LENGTH;
+        let (Some(offset), Some(length)) = (read_u32(item_offset), read_u32(item_offset + 4))
+        else {
+            return Err(malformed("unexpected end of file"));
+        };
+
+        if offset % PACKAGE_IMAGE_ALIGNMENT != 0 {
+            return Err(malformed("misaligned module image"));
+        }
+
+        if offset + length > bytes.len() {
+            return Err(malformed("unexpected end of file"));
+        }
+
+        ranges.push(offset..offset + length);
+    }
+
+    Ok(ranges)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, FileProgramSourceError> {
+    std::fs::read(path).map_err(|error| map_io_error(path, error))
+}
+
+pub(crate) fn map_io_error(path: &Path, error: std::io::Error) -> FileProgramSourceError {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        FileProgramSourceError::NotFound(path.to_path_buf())
+    } else {
+        FileProgramSourceError::Io(path.to_path_buf(), error)
+    }
+}
+
+fn locate_module_images(module_path: &Path) -> Result<Vec<PathBuf>, FileProgramSourceError> {
+    let images_path = module_path.join(MODULE_IMAGES_FOLDER_NAME);
+    let main_image_path = images_path.join(MAIN_MODULE_IMAGE_FILE_NAME);
+
+    if !main_image_path.is_file() {
+        return Err(FileProgramSourceError::NotFound(main_image_path));
+    }
+
+    let entries = std::fs::read_dir(&images_path)
+        .map_err(|error| FileProgramSourceError::Io(images_path.clone(), error))?;
+
+    let mut dependency_image_paths = vec![];
+    for entry in entries {
+        let path = entry
+            .map_err(|error| FileProgramSourceError::Io(images_path.clone(), error))?
+            .path();
+
+        if path.is_file()
+            && path.extension().and_then(|ext| ext.to_str()) == Some(MODULE_IMAGE_FILE_EXTENSION)
+            && path != main_image_path
+        {
+            dependency_image_paths.push(path);
+        }
+    }
+
+    // Keep the order of modules stable across runs.
+    dependency_image_paths.sort();
+
+    let mut image_file_paths = vec![main_image_path];
+    image_file_paths.extend(dependency_image_paths);
+    Ok(image_file_paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anc_context::{process_property::ProgramSourceType, program_source::ProgramSource};
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::helper_build_module_binary_with_single_function,
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::process::process_function;
+
+    use super::{
+        build_package_image, FileProgramSource, FileProgramSourceError,
+        PACKAGE_IMAGE_HEADER_LENGTH, PACKAGE_IMAGE_TABLE_ITEM_LENGTH,
+    };
+
+    fn build_binary() -> Vec<u8> {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0x11)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        )
+    }
+
+    fn create_temporary_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "anc_file_program_source_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn check_program_source(program_source: &FileProgramSource) {
+        let process_context0 = program_source.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0x11)]);
+    }
+
+    #[test]
+    fn test_file_program_source_module_directory() {
+        let module_path = create_temporary_dir("module");
+        std::fs::create_dir(module_path.join("target")).unwrap();
+        std::fs::write(module_path.join("target/main.ancm"), build_binary()).unwrap();
+
+        let program_source = FileProgramSource::open(&module_path).unwrap();
+        let process_property = program_source.get_process_property();
+        assert_eq!(
+            process_property.program_source_type,
+            ProgramSourceType::Module
+        );
+        assert_eq!(
+            process_property.program_path,
+            module_path.canonicalize().unwrap()
+        );
+        check_program_source(&program_source);
+
+        // the main module image is missing
+        std::fs::remove_file(module_path.join("target/main.ancm")).unwrap();
+        assert!(matches!(
+            FileProgramSource::open(&module_path),
+            Err(FileProgramSourceError::NotFound(path)) if path.ends_with("target/main.ancm")
+        ));
+
+        std::fs::remove_dir_all(&module_path).unwrap();
+    }
+
+    #[test]
+    fn test_file_program_source_script_file() {
+        let scripts_path = create_temporary_dir("script");
+        let script_file_path = scripts_path.join("hello.anc");
+        std::fs::write(&script_file_path, "fn main() {}").unwrap();
+
+        // the script has not been compiled yet
+        assert!(matches!(
+            FileProgramSource::open(&script_file_path),
+            Err(FileProgramSourceError::NotFound(path)) if path.ends_with("hello.ancm")
+        ));
+
+        std::fs::write(scripts_path.join("hello.ancm"), build_binary()).unwrap();
+
+        let program_source = FileProgramSource::open(&script_file_path).unwrap();
+        let process_property = program_source.get_process_property();
+        assert_eq!(
+            process_property.program_source_type,
+            ProgramSourceType::ScriptFile
+        );
+        assert!(process_property.program_path.ends_with("hello.anc"));
+        check_program_source(&program_source);
+
+        // unsupported file
+        std::fs::write(scripts_path.join("hello.txt"), "").unwrap();
+        assert!(matches!(
+            FileProgramSource::open(scripts_path.join("hello.txt")),
+            Err(FileProgramSourceError::UnsupportedFileType(_))
+        ));
+
+        std::fs::remove_dir_all(&scripts_path).unwrap();
+    }
+
+    #[test]
+    fn test_file_program_source_package_image() {
+        let package_folder_path = create_temporary_dir("package");
+        let package_path = package_folder_path.join("hello.ancp");
+        std::fs::write(&package_path, build_package_image(&[build_binary()])).unwrap();
+
+        let program_source = FileProgramSource::open(&package_path).unwrap();
+        assert_eq!(
+            program_source.get_process_property().program_source_type,
+            ProgramSourceType::PackageImage
+        );
+        check_program_source(&program_source);
+
+        // truncated package image
+        let mut package_image = build_package_image(&[build_binary()]);
+        package_image.truncate(package_image.len() - 1);
+        std::fs::write(&package_path, package_image).unwrap();
+        assert!(matches!(
+            FileProgramSource::open(&package_path),
+            Err(FileProgramSourceError::MalformedPackageImage(..))
+        ));
+
+        // the count of module images exceeds the file length
+        let mut package_image = build_package_image(&[build_binary()]);
+        package_image[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&package_path, package_image).unwrap();
+        assert!(matches!(
+            FileProgramSource::open(&package_path),
+            Err(FileProgramSourceError::MalformedPackageImage(_, details)) if details.contains("count")
+        ));
+
+        // the table of module images is truncated
+        let mut package_image = build_package_image(&[build_binary()]);
+        package_image[8..12].copy_from_slice(&2u32.to_le_bytes());
+        package_image.truncate(PACKAGE_IMAGE_HEADER_LENGTH + PACKAGE_IMAGE_TABLE_ITEM_LENGTH);
+        std::fs::write(&package_path, package_image).unwrap();
+        assert!(matches!(
+            FileProgramSource::open(&package_path),
+            Err(FileProgramSourceError::MalformedPackageImage(..))
+        ));
+
+        // invalid header
+        std::fs::write(&package_path, b"hello").unwrap();
+        assert!(matches!(
+            FileProgramSource::open(&package_path),
+            Err(FileProgramSourceError::MalformedPackageImage(..))
+        ));
+
+        // not found
+        assert!(matches!(
+            FileProgramSource::open(package_folder_path.join("nonexistent.ancp")),
+            Err(FileProgramSourceError::NotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&package_folder_path).unwrap();
+    }
+}
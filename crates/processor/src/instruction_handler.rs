@@ -4,13 +4,20 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
-use anc_image::bytecode_reader::format_bytecode_as_text;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 use anc_isa::opcode::Opcode;
 use anc_stack::ProgramCounter;
 
 pub type HandlerFunc = fn(&mut ThreadContext) -> HandleResult;
 
+// The handler used by the pre-decoded interpreter loop, it takes the
+// parameters of the instruction which are decoded in advance instead of
+// reading them from the bytecode.
+//
+// The parameters are stored in the order they appear in the instruction,
+// an `i16` parameter is zero-extended to `u32`, and the unused ones are 0.
+pub type PredecodedHandlerFunc = fn(&mut ThreadContext, &[u32; 3]) -> HandleResult;
+
 mod arithmetic;
 mod bitwise;
 mod calling;
@@ -75,33 +82,16 @@ pub enum HandleResult {
 
     // Program terminated.
     Terminate(/* terminate_code */ i32),
-}
 
-fn unreachable_handler(thread_context: &mut ThreadContext) -> HandleResult {
-    let pc = &thread_context.pc;
-    let function_item = &thread_context.module_common_instances[pc.module_index]
-        .function_section
-        .items[pc.function_internal_index];
-    let codes = &thread_context.module_common_instances[pc.module_index]
-        .function_section
-        .codes_data[function_item.code_offset as usize
-        ..(function_item.code_offset + function_item.code_length) as usize];
-    let code_text = format_bytecode_as_text(codes);
+    // A recoverable fault occurred (e.g. out of bounds, division by zero),
+    // the program is stopped and the program counter remains at the faulting instruction.
+    Trap(TrapKind),
+}
 
-    unreachable!(
-        "\
-Invalid Opcode: 0x{:04x}
-Module index: {}
-Function internal index: {}
-Instruction address: 0x{:04x}
-Bytecode:
-{}",
-        thread_context.get_opcode_num(),
-        pc.module_index,
-        pc.function_internal_index,
-        pc.instruction_address,
-        code_text
-    );
+pub(crate) fn unreachable_handler(_thread_context: &mut ThreadContext) -> HandleResult {
+    // The opcode is invalid, raise a trap instead of panicking,
+    // the location of the instruction is attached by the interpreter loop.
+    HandleResult::Trap(TrapKind::InvalidOpcode)
 }
 
 #[inline]
@@ -438,3 +428,75 @@ pub fn get_instruction_handler(opcode_integer: u16) -> HandlerFunc {
         _ => unreachable_handler,
     }
 }
+
+/// Returns the handler which takes pre-decoded parameters for the given opcode.
+///
+/// Only the frequently executed instructions which have parameters have such
+/// handlers, `None` is returned for others and `get_instruction_handler()` should be
+/// used instead.
+pub fn get_predecoded_instruction_handler(opcode_integer: u16) -> Option<PredecodedHandlerFunc> {
+    let opcode = unsafe { std::mem::transmute::<u16, Opcode>(opcode_integer) };
+    let category = opcode_integer >> 8;
+    let handler: PredecodedHandlerFunc = match category {
+        0x01 => {
+            // fundamental
+            match opcode {
+                Opcode::imm_i32 => fundamental::imm_i32_predecoded,
+                Opcode::imm_i64 => fundamental::imm_i64_predecoded,
+                Opcode::imm_f32 => fundamental::imm_f32_predecoded,
+                Opcode::imm_f64 => fundamental::imm_f64_predecoded,
+                _ => return None,
+            }
+        }
+        0x02 => {
+            // local
+            match opcode {
+                Opcode::local_load_i64 => local::local_load_i64_predecoded,
+                Opcode::local_load_i32_s => local::local_load_i32_s_predecoded,
+                Opcode::local_load_i32_u => local::local_load_i32_u_predecoded,
+                Opcode::local_load_i16_s => local::local_load_i16_s_predecoded,
+                Opcode::local_load_i16_u => local::local_load_i16_u_predecoded,
+                Opcode::local_load_i8_s => local::local_load_i8_s_predecoded,
+                Opcode::local_load_i8_u => local::local_load_i8_u_predecoded,
+                Opcode::local_load_f32 => local::local_load_f32_predecoded,
+                Opcode::local_load_f64 => local::local_load_f64_predecoded,
+                Opcode::local_store_i64 => local::local_store_i64_predecoded,
+                Opcode::local_store_i32 => local::local_store_i32_predecoded,
+                Opcode::local_store_i16 => local::local_store_i16_predecoded,
+                Opcode::local_store_i8 => local::local_store_i8_predecoded,
+                Opcode::local_store_f64 => local::local_store_i64_predecoded, // reuse store i64
+                Opcode::local_store_f32 => local::local_store_i32_predecoded, // reuse store i32
+                _ => return None,
+            }
+        }
+        0x04 => {
+            // arithmetic
+            match opcode {
+                Opcode::add_imm_i32 => arithmetic::add_imm_i32_predecoded,
+                Opcode::sub_imm_i32 => arithmetic::sub_imm_i32_predecoded,
+                Opcode::add_imm_i64 => arithmetic::add_imm_i64_predecoded,
+                Opcode::sub_imm_i64 => arithmetic::sub_imm_i64_predecoded,
+                _ => return None,
+            }
+        }
+        0x09 => {
+            // control_flow
+            match opcode {
+                Opcode::break_ => control_flow::break_predecoded,
+                Opcode::recur => control_flow::recur_predecoded,
+                Opcode::break_alt => control_flow::break_alt_predecoded,
+                _ => return None,
+            }
+        }
+        0x0A => {
+            // calling
+            match opcode {
+                Opcode::call => calling::call_predecoded,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(handler)
+}
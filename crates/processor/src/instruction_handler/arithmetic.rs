@@ -4,7 +4,7 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::thread_context::{ThreadContext, TrapKind};
 use anc_memory::MemoryError;
 
 use crate::TERMINATE_CODE_UNSUPPORTED_FLOATING_POINT_VARIANTS;
@@ -31,6 +31,15 @@ pub fn add_imm_i32(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(4)
 }
 
+pub fn add_imm_i32_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    let value = load_operand_i32_u(thread_context);
+    store_i32_u(thread_context, value.wrapping_add(params[0] as u32));
+    HandleResult::Move(4)
+}
+
 pub fn sub_imm_i32(thread_context: &mut ThreadContext) -> HandleResult {
     let imm = thread_context.get_param_i16();
     let value = load_operand_i32_u(thread_context);
@@ -38,6 +47,15 @@ pub fn sub_imm_i32(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(4)
 }
 
+pub fn sub_imm_i32_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    let value = load_operand_i32_u(thread_context);
+    store_i32_u(thread_context, value.wrapping_sub(params[0] as u32));
+    HandleResult::Move(4)
+}
+
 pub fn mul_i32(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i32_u(thread_context);
     store_i32_u(thread_context, left.wrapping_mul(right));
@@ -46,24 +64,47 @@ pub fn mul_i32(thread_context: &mut ThreadContext) -> HandleResult {
 
 pub fn div_i32_s(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i32_s(thread_context);
-    store_i32_s(thread_context, left / right);
-    HandleResult::Move(2)
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
+    // `i32::MIN / -1` overflows.
+    match left.checked_div(right) {
+        Some(value) => {
+            store_i32_s(thread_context, value);
+            HandleResult::Move(2)
+        }
+        None => HandleResult::Trap(TrapKind::IntegerOverflow),
+    }
 }
 
 pub fn div_i32_u(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i32_u(thread_context);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
     store_i32_u(thread_context, left / right);
     HandleResult::Move(2)
 }
 
 pub fn rem_i32_s(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i32_s(thread_context);
-    store_i32_s(thread_context, left % right);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
+    // `i32::MIN % -1` overflows in Rust, but the mathematical result is 0.
+    store_i32_s(thread_context, left.wrapping_rem(right));
     HandleResult::Move(2)
 }
 
 pub fn rem_i32_u(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i32_u(thread_context);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
     store_i32_u(thread_context, left % right);
     HandleResult::Move(2)
 }
@@ -87,6 +128,15 @@ pub fn add_imm_i64(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(4)
 }
 
+pub fn add_imm_i64_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    let value = load_operand_i64_u(thread_context);
+    store_i64_u(thread_context, value.wrapping_add(params[0] as u64));
+    HandleResult::Move(4)
+}
+
 pub fn sub_imm_i64(thread_context: &mut ThreadContext) -> HandleResult {
     let imm = thread_context.get_param_i16();
     let value = load_operand_i64_u(thread_context);
@@ -94,6 +144,15 @@ pub fn sub_imm_i64(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(4)
 }
 
+pub fn sub_imm_i64_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    let value = load_operand_i64_u(thread_context);
+    store_i64_u(thread_context, value.wrapping_sub(params[0] as u64));
+    HandleResult::Move(4)
+}
+
 pub fn mul_i64(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i64_u(thread_context);
     store_i64_u(thread_context, left.wrapping_mul(right));
@@ -102,24 +161,47 @@ pub fn mul_i64(thread_context: &mut ThreadContext) -> HandleResult {
 
 pub fn div_i64_s(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i64_s(thread_context);
-    store_i64_s(thread_context, left / right);
-    HandleResult::Move(2)
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
+    // `i64::MIN / -1` overflows.
+    match left.checked_div(right) {
+        Some(value) => {
+            store_i64_s(thread_context, value);
+            HandleResult::Move(2)
+        }
+        None => HandleResult::Trap(TrapKind::IntegerOverflow),
+    }
 }
 
 pub fn div_i64_u(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i64_u(thread_context);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
     store_i64_u(thread_context, left / right);
     HandleResult::Move(2)
 }
 
 pub fn rem_i64_s(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i64_s(thread_context);
-    store_i64_s(thread_context, left % right);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
+    // `i64::MIN % -1` overflows in Rust, but the mathematical result is 0.
+    store_i64_s(thread_context, left.wrapping_rem(right));
     HandleResult::Move(2)
 }
 
 pub fn rem_i64_u(thread_context: &mut ThreadContext) -> HandleResult {
     let (left, right) = load_operands_i64_u(thread_context);
+    if right == 0 {
+        return HandleResult::Trap(TrapKind::IntegerDivideByZero);
+    }
+
     store_i64_u(thread_context, left % right);
     HandleResult::Move(2)
 }
@@ -288,9 +370,12 @@ fn store_f64(thread_context: &mut ThreadContext, v: f64) {
 
 #[cfg(test)]
 mod tests {
-    use crate::{in_memory_program_source::InMemoryProgramSource, process::process_function};
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
+        ProcessorErrorType,
+    };
 
-    use anc_context::program_source::ProgramSource;
+    use anc_context::{program_source::ProgramSource, thread_context::TrapKind};
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
         utils::helper_build_module_binary_with_single_function,
@@ -777,4 +862,144 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_handler_arithmetic_integer_traps() {
+        // fn (left:i32, right:i32) -> (i32)    ;; opcode is specified by the test case
+        //     local_load32(0, 0)
+        //     local_load32(0, 1)
+        //     div/rem
+        // end
+
+        let cases = [
+            (Opcode::div_i32_s, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::div_i32_u, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::rem_i32_s, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::rem_i32_u, 11, 0, TrapKind::IntegerDivideByZero),
+            (
+                Opcode::div_i32_s,
+                i32::MIN as u32,
+                -1i32 as u32,
+                TrapKind::IntegerOverflow,
+            ),
+        ];
+
+        for (opcode, left, right, expect_kind) in cases {
+            let code0 = BytecodeWriterHelper::new()
+                .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+                .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+                .append_opcode(opcode)
+                .append_opcode(Opcode::end)
+                .to_bytes();
+
+            let binary0 = helper_build_module_binary_with_single_function(
+                &[OperandDataType::I32, OperandDataType::I32], // params
+                &[OperandDataType::I32],                       // results
+                &[],                                           // local variables
+                code0,
+            );
+
+            let resource0 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context0 = resource0.create_process_context().unwrap();
+            let mut thread_context0 = process_context0.create_thread_context();
+
+            let result0 = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[ForeignValue::U32(left), ForeignValue::U32(right)],
+            );
+
+            // the trap is raised by the 3rd instruction (at address 0x10)
+            assert!(matches!(
+                result0,
+                Err(ProcessorError {
+                    error_type: ProcessorErrorType::Trap {
+                        kind,
+                        module_index: 0,
+                        function_internal_index: 0,
+                        instruction_address: 0x10,
+                    }, .. }) if kind == expect_kind
+            ));
+        }
+
+        // `i32::MIN % -1` does not trap, the result is 0.
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::rem_i32_s)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[OperandDataType::I32, OperandDataType::I32], // params
+            &[OperandDataType::I32],                       // results
+            &[],                                           // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[
+                ForeignValue::U32(i32::MIN as u32),
+                ForeignValue::U32(-1i32 as u32),
+            ],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0)]);
+    }
+
+    #[test]
+    fn test_handler_arithmetic_integer_traps_i64() {
+        let cases = [
+            (Opcode::div_i64_s, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::div_i64_u, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::rem_i64_s, 11, 0, TrapKind::IntegerDivideByZero),
+            (Opcode::rem_i64_u, 11, 0, TrapKind::IntegerDivideByZero),
+            (
+                Opcode::div_i64_s,
+                i64::MIN as u64,
+                -1i64 as u64,
+                TrapKind::IntegerOverflow,
+            ),
+        ];
+
+        for (opcode, left, right, expect_kind) in cases {
+            let code0 = BytecodeWriterHelper::new()
+                .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+                .append_opcode_i16_i32(Opcode::local_load_i64, 0, 1)
+                .append_opcode(opcode)
+                .append_opcode(Opcode::end)
+                .to_bytes();
+
+            let binary0 = helper_build_module_binary_with_single_function(
+                &[OperandDataType::I64, OperandDataType::I64], // params
+                &[OperandDataType::I64],                       // results
+                &[],                                           // local variables
+                code0,
+            );
+
+            let resource0 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context0 = resource0.create_process_context().unwrap();
+            let mut thread_context0 = process_context0.create_thread_context();
+
+            let result0 = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[ForeignValue::U64(left), ForeignValue::U64(right)],
+            );
+
+            assert!(matches!(
+                result0,
+                Err(ProcessorError {
+                    error_type: ProcessorErrorType::Trap { kind, .. }, .. }) if kind == expect_kind
+            ));
+        }
+    }
 }
@@ -4,18 +4,24 @@
 // the Mozilla Public License version 2.0 and additional exceptions.
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
-use anc_context::thread_context::ThreadContext;
+use anc_context::{
+    call_audit_log::AuditedCall, capability::CallFilter, thread_context::ThreadContext,
+};
 use anc_isa::OPERAND_SIZE_IN_BYTES;
 use anc_stack::ProgramCounter;
 
 use crate::{
     envcall_handler::get_envcall_handlers,
-    extcall_handler::get_or_create_external_function_wrapper_function,
-    syscall_handler::get_syscall_handler, TERMINATE_CODE_FAILED_TO_LOAD_EXTERNAL_FUNCTION,
+    extcall_handler::{
+        get_external_library_and_function_name, get_or_create_external_function_wrapper_function,
+    },
+    jit_compiler::{call_compiled_function, get_or_compile_function},
+    syscall_handler::get_syscall_handler,
+    TERMINATE_CODE_CAPABILITY_DENIED, TERMINATE_CODE_FAILED_TO_LOAD_EXTERNAL_FUNCTION,
     TERMINATE_CODE_STACK_OVERFLOW,
 };
 
-use super::HandleResult;
+use super::{control_flow::end, HandleResult};
 
 pub fn call(thread_context: &mut ThreadContext) -> HandleResult {
     // (param function_public_index:i32) (operand args...) -> (values)
@@ -28,6 +34,10 @@ pub fn call(thread_context: &mut ThreadContext) -> HandleResult {
     )
 }
 
+pub fn call_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    do_call(thread_context, thread_context.pc.module_index, params[0], 8)
+}
+
 pub fn call_dynamic(thread_context: &mut ThreadContext) -> HandleResult {
     // () (operand args... function_module_index:i32 function_public_index:i32) -> (values)
     let function_public_index = thread_context.stack.pop_i32_u();
@@ -47,8 +57,12 @@ fn do_call(
         module_index: return_module_index,
     } = thread_context.pc;
 
-    let target_function_object =
-        thread_context.get_target_function_object(module_index, function_public_index as usize);
+    let target_function_object = match thread_context
+        .get_target_function_object(module_index, function_public_index as usize)
+    {
+        Ok(target_function_object) => target_function_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     let function_info = thread_context.get_function_info(
         target_function_object.module_index,
         target_function_object.function_internal_index,
@@ -81,6 +95,17 @@ fn do_call(
                 module_index: target_function_object.module_index,
             };
 
+            // Dispatch to the native code if the function has been compiled by the JIT compiler.
+            if let Some(compiled_function) = get_or_compile_function(
+                thread_context,
+                target_function_object.module_index,
+                target_function_object.function_internal_index,
+            ) {
+                // The frame is removed by the instruction `end` when the native function finishes.
+                return call_compiled_function(thread_context, &compiled_function, target_pc)
+                    .unwrap_or_else(|| end(thread_context));
+            }
+
             HandleResult::Jump(target_pc)
         }
         Err(_) => HandleResult::Terminate(TERMINATE_CODE_STACK_OVERFLOW),
@@ -111,10 +136,34 @@ pub fn syscall(thread_context: &mut ThreadContext) -> HandleResult {
     // the value is negative (i.e., rax < 0).
     //
     // Note: Unlike the C standard library, there is no "errno" when calling syscalls directly from assembly.
+    //
+    // The program is terminated if the syscall is not permitted by the capability of the process.
 
     let syscall_num = thread_context.stack.pop_i32_u();
     let params_count = thread_context.stack.pop_i32_u();
 
+    let (permitted, opt_call_audit_log) = {
+        let process_property = thread_context.process_property.lock().unwrap();
+        let permitted = process_property
+            .capability
+            .permits_syscall(syscall_num as usize);
+        (permitted, process_property.call_audit_log.clone())
+    };
+
+    // The auditor is called without holding the `process_property` lock.
+    if let Some(call_audit_log) = opt_call_audit_log {
+        call_audit_log.record(
+            AuditedCall::Syscall {
+                syscall_num: syscall_num as usize,
+            },
+            permitted,
+        );
+    }
+
+    if !permitted {
+        return HandleResult::Terminate(TERMINATE_CODE_CAPABILITY_DENIED);
+    }
+
     let function = get_syscall_handler(params_count as usize);
     let result = function(thread_context, syscall_num as usize);
 
@@ -139,8 +188,10 @@ pub fn envcall(thread_context: &mut ThreadContext) -> HandleResult {
 
     let envcall_num = thread_context.get_param_i32();
     let function = get_envcall_handlers(envcall_num);
-    function(thread_context);
-    HandleResult::Move(8)
+    match function(thread_context) {
+        Ok(_) => HandleResult::Move(8),
+        Err(trap_kind) => HandleResult::Trap(trap_kind),
+    }
 }
 
 pub fn extcall(thread_context: &mut ThreadContext) -> HandleResult {
@@ -152,6 +203,43 @@ pub fn extcall(thread_context: &mut ThreadContext) -> HandleResult {
     let external_function_index = thread_context.get_param_i32() as usize;
     let module_index = thread_context.pc.module_index;
 
+    // Check the capability before loading the external library, the names of
+    // the library and the function are only looked up if they are filtered or audited.
+    let (permitted, opt_audited_call) = {
+        let process_property = thread_context.process_property.lock().unwrap();
+        let capability = &process_property.capability;
+
+        if capability.extcall_filter == CallFilter::All && process_property.call_audit_log.is_none()
+        {
+            (capability.extcall, None)
+        } else {
+            let Some((library, function)) = get_external_library_and_function_name(
+                thread_context,
+                module_index,
+                external_function_index,
+            ) else {
+                return HandleResult::Terminate(TERMINATE_CODE_FAILED_TO_LOAD_EXTERNAL_FUNCTION);
+            };
+
+            let permitted = capability.permits_extcall(&library, &function);
+            let opt_audited_call = process_property
+                .call_audit_log
+                .clone()
+                .map(|call_audit_log| (call_audit_log, AuditedCall::Extcall { library, function }));
+
+            (permitted, opt_audited_call)
+        }
+    };
+
+    // The auditor is called without holding the `process_property` lock.
+    if let Some((call_audit_log, audited_call)) = opt_audited_call {
+        call_audit_log.record(audited_call, permitted);
+    }
+
+    if !permitted {
+        return HandleResult::Terminate(TERMINATE_CODE_CAPABILITY_DENIED);
+    }
+
     let (external_function_pointer, wrapper_function, params_count, contains_return_value) =
         if let Ok(pwr) = get_or_create_external_function_wrapper_function(
             // handler,
@@ -190,12 +278,20 @@ pub fn extcall(thread_context: &mut ThreadContext) -> HandleResult {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex, OnceLock},
+    };
 
     use anc_context::{
-        capability::Capability,
+        call_audit_log::{
+            AuditedCall, CallAuditEntry, CallAuditLog, CallAuditor, MemoryCallAuditor,
+        },
+        capability::{CallFilter, Capability, ExternalFunctionRule},
+        process_context::ProcessContext,
         process_property::{ProcessProperty, ProgramSourceType},
         program_source::ProgramSource,
+        thread_context::TrapKind,
     };
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
@@ -211,7 +307,18 @@ mod tests {
     use dyncall_util::cstr_pointer_to_str;
     use syscall_util::{errno::Errno, number::SysCallNum};
 
-    use crate::{in_memory_program_source::InMemoryProgramSource, process::process_function};
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
+        ProcessorErrorType, TERMINATE_CODE_CAPABILITY_DENIED,
+    };
+
+    // The process is capable of invoking all syscalls and external functions.
+    fn create_capable_process_property() -> ProcessProperty {
+        let mut process_property = ProcessProperty::default();
+        process_property.capability.syscall = true;
+        process_property.capability.extcall = true;
+        process_property
+    }
 
     #[test]
     fn test_handler_function_call() {
@@ -471,6 +578,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handler_function_call_dynamic_index_out_of_bounds() {
+        // fn test () -> (i32)
+        //     imm_i32(0)      ;; module index
+        //     imm_i32(7)      ;; function public index (non-existent)
+        //     call_dynamic()
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode_i32(Opcode::imm_i32, 7)
+            .append_opcode(Opcode::call_dynamic)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::FunctionIndexOutOfBounds,
+                    module_index: 0,
+                    function_internal_index: 0,
+                    instruction_address: 0x10,
+                },
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_handler_syscall_without_args() {
         // pesudo code:
@@ -494,7 +642,8 @@ mod tests {
             code0,
         );
 
-        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_capable_process_property());
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
@@ -534,7 +683,8 @@ mod tests {
             code0,
         );
 
-        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_capable_process_property());
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
@@ -594,7 +744,8 @@ mod tests {
             code0,
         );
 
-        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_capable_process_property());
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
@@ -667,7 +818,8 @@ mod tests {
             }],
         );
 
-        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_capable_process_property());
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
@@ -713,7 +865,8 @@ mod tests {
             }],
         );
 
-        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let resource0 =
+            InMemoryProgramSource::with_property(vec![binary0], create_capable_process_property());
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
@@ -793,7 +946,10 @@ mod tests {
                 ProgramSourceType::Module,
                 vec![],
                 vec![],
-                Capability::default(),
+                Capability {
+                    extcall: true,
+                    ..Capability::default()
+                },
             ),
         );
         let process_context0 = resource0.create_process_context().unwrap();
@@ -815,4 +971,296 @@ mod tests {
         );
         assert_eq!(result1.unwrap(), vec![ForeignValue::U32(434)]);
     }
+
+    fn build_syscall_getpid_binary() -> Vec<u8> {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0) // the amount of syscall args
+            .append_opcode_i32(Opcode::imm_i32, SysCallNum::getpid as u32) // syscall num
+            .append_opcode(Opcode::syscall)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function(
+            &[],                                           // params
+            &[OperandDataType::I64, OperandDataType::I32], // results
+            &[],                                           // local variables
+            code0,
+        )
+    }
+
+    fn build_extcall_libc_getuid_binary() -> Vec<u8> {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::extcall, 0) // 0 is the external function index
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[HelperFunctionEntry {
+                params: vec![],
+                results: vec![OperandDataType::I32],
+                local_variable_item_entries_without_args: vec![],
+                code: code0,
+            }],
+            &[],
+            &[],
+            &[],
+            &[ExternalLibraryEntry::new(
+                "libc".to_owned(),
+                Box::new(ExternalLibraryDependency::File(
+                    "system:libc.so.6".to_owned(),
+                )),
+            )],
+            &[HelperExternalFunctionEntry {
+                name: "getuid".to_string(),
+                params: vec![],
+                result: Some(OperandDataType::I32),
+                external_library_index: 0,
+            }],
+        )
+    }
+
+    // Runs the function with the specified capability and audit log,
+    // returns whether the call is permitted.
+    fn run_with_capability(
+        binary: Vec<u8>,
+        capability: Capability,
+        call_audit_log: Option<CallAuditLog>,
+    ) -> bool {
+        let mut process_property = ProcessProperty::default();
+        process_property.capability = capability;
+        process_property.call_audit_log = call_audit_log;
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        match process_function(&mut thread_context0, 0, 0, &[]) {
+            Ok(_) => true,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_CAPABILITY_DENIED),
+                ..
+            }) => false,
+            Err(error) => panic!("unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn test_handler_syscall_capability() {
+        let getpid = SysCallNum::getpid as usize;
+        let getcwd = SysCallNum::getcwd as usize;
+
+        // not capable
+        assert!(!run_with_capability(
+            build_syscall_getpid_binary(),
+            Capability::default(),
+            None
+        ));
+
+        // capable
+        let capable = |syscall_filter: CallFilter<usize>| Capability {
+            syscall: true,
+            syscall_filter,
+            ..Capability::default()
+        };
+
+        assert!(run_with_capability(
+            build_syscall_getpid_binary(),
+            capable(CallFilter::All),
+            None
+        ));
+
+        // allowlist
+        assert!(run_with_capability(
+            build_syscall_getpid_binary(),
+            capable(CallFilter::Allow(vec![getcwd, getpid])),
+            None
+        ));
+        assert!(!run_with_capability(
+            build_syscall_getpid_binary(),
+            capable(CallFilter::Allow(vec![getcwd])),
+            None
+        ));
+
+        // denylist
+        assert!(run_with_capability(
+            build_syscall_getpid_binary(),
+            capable(CallFilter::Deny(vec![getcwd])),
+            None
+        ));
+        assert!(!run_with_capability(
+            build_syscall_getpid_binary(),
+            capable(CallFilter::Deny(vec![getpid])),
+            None
+        ));
+
+        // the filter does not grant the capability
+        assert!(!run_with_capability(
+            build_syscall_getpid_binary(),
+            Capability {
+                syscall_filter: CallFilter::Allow(vec![getpid]),
+                ..Capability::default()
+            },
+            None
+        ));
+    }
+
+    #[test]
+    fn test_handler_extcall_capability() {
+        // not capable
+        assert!(!run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            Capability::default(),
+            None
+        ));
+
+        let capable = |extcall_filter: CallFilter<ExternalFunctionRule>| Capability {
+            extcall: true,
+            extcall_filter,
+            ..Capability::default()
+        };
+
+        // allowlist
+        assert!(run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capable(CallFilter::Allow(vec![ExternalFunctionRule::library(
+                "libc"
+            )])),
+            None
+        ));
+        assert!(run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capable(CallFilter::Allow(vec![ExternalFunctionRule::function(
+                "libc", "getuid"
+            )])),
+            None
+        ));
+        assert!(!run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capable(CallFilter::Allow(vec![
+                ExternalFunctionRule::function("libc", "getenv"),
+                ExternalFunctionRule::library("libtest"),
+            ])),
+            None
+        ));
+
+        // denylist
+        assert!(run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capable(CallFilter::Deny(vec![ExternalFunctionRule::function(
+                "libc", "getenv"
+            )])),
+            None
+        ));
+        assert!(!run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capable(CallFilter::Deny(vec![ExternalFunctionRule::library(
+                "libc"
+            )])),
+            None
+        ));
+    }
+
+    #[test]
+    fn test_handler_call_audit_log() {
+        let auditor = Arc::new(MemoryCallAuditor::default());
+        let call_audit_log = CallAuditLog::new(auditor.clone());
+
+        let capability = Capability {
+            syscall: true,
+            extcall: true,
+            extcall_filter: CallFilter::Deny(vec![ExternalFunctionRule::function(
+                "libc", "getuid",
+            )]),
+            ..Capability::default()
+        };
+
+        assert!(run_with_capability(
+            build_syscall_getpid_binary(),
+            capability.clone(),
+            Some(call_audit_log.clone())
+        ));
+        assert!(!run_with_capability(
+            build_extcall_libc_getuid_binary(),
+            capability,
+            Some(call_audit_log.clone())
+        ));
+        assert!(!run_with_capability(
+            build_syscall_getpid_binary(),
+            Capability::default(),
+            Some(call_audit_log)
+        ));
+
+        let entries = auditor.get_entries();
+        assert_eq!(
+            entries,
+            vec![
+                CallAuditEntry {
+                    call: AuditedCall::Syscall {
+                        syscall_num: SysCallNum::getpid as usize
+                    },
+                    permitted: true
+                },
+                CallAuditEntry {
+                    call: AuditedCall::Extcall {
+                        library: "libc".to_owned(),
+                        function: "getuid".to_owned()
+                    },
+                    permitted: false
+                },
+                CallAuditEntry {
+                    call: AuditedCall::Syscall {
+                        syscall_num: SysCallNum::getpid as usize
+                    },
+                    permitted: false
+                },
+            ]
+        );
+        assert_eq!(entries[1].to_string(), "extcall libc::getuid denied");
+    }
+
+    // Records whether the `process_property` lock is free when called.
+    struct LockCheckingAuditor {
+        process_property: OnceLock<&'static Mutex<ProcessProperty>>,
+        lock_free: Mutex<Vec<bool>>,
+    }
+
+    impl CallAuditor for LockCheckingAuditor {
+        fn record(&self, _entry: &CallAuditEntry) {
+            let lock_free = self.process_property.get().unwrap().try_lock().is_ok();
+            self.lock_free.lock().unwrap().push(lock_free);
+        }
+    }
+
+    #[test]
+    fn test_handler_call_audit_log_without_lock() {
+        for binary0 in [
+            build_syscall_getpid_binary(),
+            build_extcall_libc_getuid_binary(),
+        ] {
+            let auditor = Arc::new(LockCheckingAuditor {
+                process_property: OnceLock::new(),
+                lock_free: Mutex::new(vec![]),
+            });
+
+            let mut process_property = ProcessProperty::default();
+            process_property.call_audit_log = Some(CallAuditLog::new(auditor.clone()));
+
+            // The auditor refers to the process property, so the process
+            // context is leaked to live as long as the auditor.
+            let resource0: &'static InMemoryProgramSource = Box::leak(Box::new(
+                InMemoryProgramSource::with_property(vec![binary0], process_property),
+            ));
+            let process_context0: &'static ProcessContext =
+                Box::leak(Box::new(resource0.create_process_context().unwrap()));
+            let _ = auditor
+                .process_property
+                .set(&process_context0.process_property);
+
+            // The call is denied by the default capability.
+            let mut thread_context0 = process_context0.create_thread_context();
+            let _ = process_function(&mut thread_context0, 0, 0, &[]);
+
+            assert_eq!(*auditor.lock_free.lock().unwrap(), vec![true]);
+        }
+    }
 }
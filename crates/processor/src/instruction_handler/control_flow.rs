@@ -138,6 +138,10 @@ pub fn break_(thread_context: &mut ThreadContext) -> HandleResult {
     do_break(thread_context, layers, next_inst_offset)
 }
 
+pub fn break_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    do_break(thread_context, params[0] as u16, params[1])
+}
+
 // `break_alt next` == `break 0 next`
 pub fn break_alt(thread_context: &mut ThreadContext) -> HandleResult {
     // (param next_inst_offset:i32) -> NO_RETURN
@@ -145,6 +149,10 @@ pub fn break_alt(thread_context: &mut ThreadContext) -> HandleResult {
     do_break(thread_context, 0, next_inst_offset)
 }
 
+pub fn break_alt_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    do_break(thread_context, 0, params[0])
+}
+
 fn do_break(
     thread_context: &mut ThreadContext,
     layers: u16,
@@ -192,6 +200,10 @@ pub fn recur(thread_context: &mut ThreadContext) -> HandleResult {
     do_recur(thread_context, layers, start_inst_offset)
 }
 
+pub fn recur_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    do_recur(thread_context, params[0] as u16, params[1])
+}
+
 fn do_recur(
     thread_context: &mut ThreadContext,
     layers: u16,
@@ -1230,7 +1242,8 @@ mod tests {
         assert!(matches!(
             result2,
             Err(ProcessorError {
-                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE)
+                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE),
+                ..
             })
         ));
 
@@ -1238,7 +1251,8 @@ mod tests {
         assert!(matches!(
             result3,
             Err(ProcessorError {
-                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE)
+                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE),
+                ..
             })
         ));
     }
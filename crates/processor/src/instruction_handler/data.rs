@@ -62,12 +62,15 @@ fn do_data_load_i64(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -124,12 +127,15 @@ fn do_data_load_i32_s(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i32_s_to_i64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -186,12 +192,15 @@ fn do_data_load_i32_u(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i32_u_to_u64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -248,12 +257,15 @@ fn do_data_load_i16_s(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i16_s_to_i64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -310,12 +322,15 @@ fn do_data_load_i16_u(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i16_u_to_u64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -372,12 +387,15 @@ fn do_data_load_i8_s(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i8_s_to_i64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -434,12 +452,15 @@ fn do_data_load_i8_u(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.read_idx_i8_u_to_u64(
         target_data_object.data_internal_index_in_section,
         offset_bytes,
@@ -496,12 +517,15 @@ fn do_data_load_f32(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     match target_data_object.accessor.read_idx_f32(
         target_data_object.data_internal_index_in_section,
@@ -560,12 +584,15 @@ fn do_data_load_f64(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     match target_data_object.accessor.read_idx_f64(
         target_data_object.data_internal_index_in_section,
@@ -630,12 +657,15 @@ fn do_data_store_i64(
     src_ptr: *const u8,
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.write_idx_i64(
         src_ptr,
         target_data_object.data_internal_index_in_section,
@@ -698,12 +728,15 @@ fn do_data_store_i32(
     src_ptr: *const u8,
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.write_idx_i32(
         src_ptr,
         target_data_object.data_internal_index_in_section,
@@ -766,12 +799,15 @@ fn do_data_store_i16(
     src_ptr: *const u8,
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.write_idx_i16(
         src_ptr,
         target_data_object.data_internal_index_in_section,
@@ -834,12 +870,15 @@ fn do_data_store_i8(
     src_ptr: *const u8,
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         module_index,
         data_access_index,
         offset_bytes,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     target_data_object.accessor.write_idx_i8(
         src_ptr,
         target_data_object.data_internal_index_in_section,
@@ -852,7 +891,7 @@ fn do_data_store_i8(
 #[cfg(test)]
 mod tests {
 
-    use anc_context::program_source::ProgramSource;
+    use anc_context::{program_source::ProgramSource, thread_context::TrapKind};
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
         entry::{ReadOnlyDataEntry, ReadWriteDataEntry},
@@ -1664,23 +1703,21 @@ mod tests {
             &[],
         );
 
-        // capture the panic and keep silent
-        // it is also possible to check the panic by
-        // adding `#[should_panic]` attribute to the function.
-        let prev_hook = std::panic::take_hook();
-        std::panic::set_hook(Box::new(|_| {}));
-
-        let result = std::panic::catch_unwind(move || {
-            let resource0 = InMemoryProgramSource::new(vec![binary0]);
-            let process_context0 = resource0.create_process_context().unwrap();
-            let mut thread_context0 = process_context0.create_thread_context();
-            // Error: Attempting to load `i32` data with offset 2 (data length exceeded).
-            let _ = process_function(&mut thread_context0, 0, 0, &[]);
-        });
-
-        std::panic::set_hook(prev_hook);
-
-        assert!(result.is_err());
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        // Error: Attempting to load `i32` data with offset 2 (data length exceeded).
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::DataAccessOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -1703,20 +1740,21 @@ mod tests {
             &[],
         );
 
-        let prev_hook = std::panic::take_hook(); // silent panic
-        std::panic::set_hook(Box::new(|_| {}));
-
-        let result = std::panic::catch_unwind(move || {
-            let resource0 = InMemoryProgramSource::new(vec![binary0]);
-            let process_context0 = resource0.create_process_context().unwrap();
-            let mut thread_context0 = process_context0.create_thread_context();
-            // Error: Attempting to load `i64` from an `i32` variable (data length exceeded).
-            let _ = process_function(&mut thread_context0, 0, 0, &[]);
-        });
-
-        std::panic::set_hook(prev_hook);
-
-        assert!(result.is_err());
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        // Error: Attempting to load `i64` from an `i32` variable (data length exceeded).
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::DataAccessOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -1726,7 +1764,7 @@ mod tests {
 
         let code0 = BytecodeWriterHelper::new()
             .append_opcode_i32(Opcode::imm_i32, 11)
-            .append_opcode_i16_i32(Opcode::data_store_i32, 2, 0)
+            .append_opcode_i16_i32(Opcode::data_store_i32, 0, 2)
             .append_opcode(Opcode::end)
             .to_bytes();
 
@@ -1740,20 +1778,21 @@ mod tests {
             &[],
         );
 
-        let prev_hook = std::panic::take_hook(); // silent panic
-        std::panic::set_hook(Box::new(|_| {}));
-
-        let result = std::panic::catch_unwind(move || {
-            let resource0 = InMemoryProgramSource::new(vec![binary0]);
-            let process_context0 = resource0.create_process_context().unwrap();
-            let mut thread_context0 = process_context0.create_thread_context();
-            // Error: Attempting to access a non-existent local variable (index out of range).
-            let _ = process_function(&mut thread_context0, 0, 0, &[]);
-        });
-
-        std::panic::set_hook(prev_hook);
-
-        assert!(result.is_err());
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        // Error: Attempting to access a non-existent data (index out of range).
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::DataIndexOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -1785,7 +1824,8 @@ mod tests {
             Err(ProcessorError {
                 error_type: ProcessorErrorType::Terminate(
                     TERMINATE_CODE_UNSUPPORTED_FLOATING_POINT_VARIANTS
-                )
+                ),
+                ..
             })
         ));
     }
@@ -21,6 +21,11 @@ pub fn imm_i32(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(8) // Move instruction pointer forward by 8 bytes.
 }
 
+pub fn imm_i32_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    thread_context.stack.push_i32_u(params[0]);
+    HandleResult::Move(8)
+}
+
 pub fn imm_i64(thread_context: &mut ThreadContext) -> HandleResult {
     // Pushes a 64-bit unsigned integer (i64) onto the stack.
     // Combines two 32-bit integers (low and high) into a single 64-bit value.
@@ -33,6 +38,12 @@ pub fn imm_i64(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(12) // Move instruction pointer forward by 12 bytes.
 }
 
+pub fn imm_i64_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    let value = ((params[1] as u64) << 32) | (params[0] as u64);
+    thread_context.stack.push_i64_u(value);
+    HandleResult::Move(12)
+}
+
 pub fn imm_f32(thread_context: &mut ThreadContext) -> HandleResult {
     // Pushes a 32-bit floating-point number (f32) onto the stack.
     // Converts the raw bits of an i32 value into an f32.
@@ -43,6 +54,11 @@ pub fn imm_f32(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(8) // Move instruction pointer forward by 8 bytes.
 }
 
+pub fn imm_f32_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    thread_context.stack.push_f32(f32::from_bits(params[0]));
+    HandleResult::Move(8)
+}
+
 pub fn imm_f64(thread_context: &mut ThreadContext) -> HandleResult {
     // Pushes a 64-bit floating-point number (f64) onto the stack.
     // Combines two 32-bit integers (low and high) into an f64 value.
@@ -61,6 +77,12 @@ pub fn imm_f64(thread_context: &mut ThreadContext) -> HandleResult {
     HandleResult::Move(12) // Move instruction pointer forward by 12 bytes.
 }
 
+pub fn imm_f64_predecoded(thread_context: &mut ThreadContext, params: &[u32; 3]) -> HandleResult {
+    let value = f64::from_bits(((params[1] as u64) << 32) | (params[0] as u64));
+    thread_context.stack.push_f64(value);
+    HandleResult::Move(12)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{in_memory_program_source::InMemoryProgramSource, process::process_function};
@@ -18,7 +18,21 @@ const DATA_LENGTH_IN_BYTES_8_BIT: usize = 1;
 pub fn local_load_i64(thread_context: &mut ThreadContext) -> HandleResult {
     // (params: layers: i16, local_variable_index: i32) -> i64
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i64(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i64_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i64(thread_context, params[0] as u16, params[1])
+}
 
+fn do_local_load_i64(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     // There are two approaches to transfer data from memory to the stack:
     // 1. Read data (integer or floating-point number) from memory into a temporary variable,
     //    and then push the variable onto the stack. For example:
@@ -32,11 +46,14 @@ pub fn local_load_i64(thread_context: &mut ThreadContext) -> HandleResult {
     //    This method is used here.
 
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i64(data_address, 0, dst_ptr as *mut u64);
@@ -47,12 +64,30 @@ pub fn local_load_i64(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i32_s(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i32
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i32_s(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i32_s_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i32_s(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i32_s(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i32_s_to_i64(data_address, 0, dst_ptr as *mut i64);
@@ -63,12 +98,30 @@ pub fn local_load_i32_s(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i32_u(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i32
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i32_u(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i32_u_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i32_u(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i32_u(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i32_u_to_u64(data_address, 0, dst_ptr as *mut u64);
@@ -79,12 +132,30 @@ pub fn local_load_i32_u(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i16_s(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i16
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i16_s(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i16_s_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i16_s(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i16_s(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i16_s_to_i64(data_address, 0, dst_ptr as *mut i64);
@@ -95,12 +166,30 @@ pub fn local_load_i16_s(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i16_u(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i16
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i16_u(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i16_u_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i16_u(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i16_u(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i16_u_to_u64(data_address, 0, dst_ptr as *mut u64);
@@ -111,12 +200,30 @@ pub fn local_load_i16_u(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i8_s(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i8
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i8_s(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i8_s_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i8_s(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i8_s(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i8_s_to_i64(data_address, 0, dst_ptr as *mut i64);
@@ -127,12 +234,30 @@ pub fn local_load_i8_s(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_i8_u(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> i8
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_i8_u(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_i8_u_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_i8_u(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_i8_u(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context
         .stack
         .read_i8_u_to_u64(data_address, 0, dst_ptr as *mut u64);
@@ -143,12 +268,30 @@ pub fn local_load_i8_u(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_f32(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> f32
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_f32(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_f32_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_f32(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_f32(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     // Handle potential errors when reading floating-point data.
     match thread_context
@@ -163,12 +306,30 @@ pub fn local_load_f32(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_load_f64(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) -> f64
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_load_f64(thread_context, layers, local_variable_index)
+}
+
+pub fn local_load_f64_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_load_f64(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_load_f64(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let dst_ptr = thread_context.stack.push_operand_from_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     match thread_context
         .stack
@@ -182,12 +343,30 @@ pub fn local_load_f64(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_store_i64(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) (operand value:i64) -> (remain_values)
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_store_i64(thread_context, layers, local_variable_index)
+}
+
+pub fn local_store_i64_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_store_i64(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_store_i64(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let src_ptr = thread_context.stack.pop_operand_to_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_64_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context.stack.write_i64(src_ptr, data_address, 0);
 
     HandleResult::Move(8)
@@ -196,12 +375,30 @@ pub fn local_store_i64(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_store_i32(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) (operand value:i32) -> (remain_values)
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_store_i32(thread_context, layers, local_variable_index)
+}
+
+pub fn local_store_i32_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_store_i32(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_store_i32(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let src_ptr = thread_context.stack.pop_operand_to_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_32_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context.stack.write_i32(src_ptr, data_address, 0);
 
     HandleResult::Move(8)
@@ -210,12 +407,30 @@ pub fn local_store_i32(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_store_i16(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) (operand value:i32) -> (remain_values)
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_store_i16(thread_context, layers, local_variable_index)
+}
+
+pub fn local_store_i16_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_store_i16(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_store_i16(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let src_ptr = thread_context.stack.pop_operand_to_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_16_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context.stack.write_i16(src_ptr, data_address, 0);
 
     HandleResult::Move(8)
@@ -224,12 +439,30 @@ pub fn local_store_i16(thread_context: &mut ThreadContext) -> HandleResult {
 pub fn local_store_i8(thread_context: &mut ThreadContext) -> HandleResult {
     // (param layers:i16 local_variable_index:i32) (operand value:i32) -> (remain_values)
     let (layers, local_variable_index) = thread_context.get_param_i16_i32();
+    do_local_store_i8(thread_context, layers, local_variable_index)
+}
+
+pub fn local_store_i8_predecoded(
+    thread_context: &mut ThreadContext,
+    params: &[u32; 3],
+) -> HandleResult {
+    do_local_store_i8(thread_context, params[0] as u16, params[1])
+}
+
+fn do_local_store_i8(
+    thread_context: &mut ThreadContext,
+    layers: u16,
+    local_variable_index: u32,
+) -> HandleResult {
     let src_ptr = thread_context.stack.pop_operand_to_memory();
-    let data_address = thread_context.get_local_variable_start_address(
+    let data_address = match thread_context.get_local_variable_start_address(
         layers,
         local_variable_index as usize,
         DATA_LENGTH_IN_BYTES_8_BIT,
-    );
+    ) {
+        Ok(data_address) => data_address,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
     thread_context.stack.write_i8(src_ptr, data_address, 0);
 
     HandleResult::Move(8)
@@ -240,7 +473,7 @@ pub fn local_store_i8(thread_context: &mut ThreadContext) -> HandleResult {
 // The `layers` parameter will be tested in the module `interpreter/control_flow`.
 #[cfg(test)]
 mod tests {
-    use anc_context::program_source::ProgramSource;
+    use anc_context::{program_source::ProgramSource, thread_context::TrapKind};
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
         utils::helper_build_module_binary_with_single_function,
@@ -384,20 +617,21 @@ mod tests {
             code0,
         );
 
-        let prev_hook = std::panic::take_hook(); // Silence panic output.
-        std::panic::set_hook(Box::new(|_| {}));
-
-        let result = std::panic::catch_unwind(move || {
-            let resource0 = InMemoryProgramSource::new(vec![binary0]);
-            let process_context0 = resource0.create_process_context().unwrap();
-            let mut thread_context0 = process_context0.create_thread_context();
-            // Error: Attempting to load `i64` from an `i32` variable (data length exceeded).
-            let _ = process_function(&mut thread_context0, 0, 0, &[]);
-        });
-
-        std::panic::set_hook(prev_hook); // Restore the original panic hook.
-
-        assert!(result.is_err()); // Assert that the operation results in an error.
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        // Error: Attempting to load `i64` from an `i32` variable (data length exceeded).
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::LocalVariableAccessOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -418,21 +652,21 @@ mod tests {
             code0,
         );
 
-        let prev_hook = std::panic::take_hook(); // Silence panic output.
-        std::panic::set_hook(Box::new(|_| {}));
-
-        let result = std::panic::catch_unwind(move || {
-            let resource0 = InMemoryProgramSource::new(vec![binary0]);
-            let process_context0 = resource0.create_process_context().unwrap();
-
-            let mut thread_context0 = process_context0.create_thread_context();
-            // Error: Attempting to access a non-existent local variable (index out of range).
-            let _ = process_function(&mut thread_context0, 0, 0, &[]);
-        });
-
-        std::panic::set_hook(prev_hook); // Restore the original panic hook.
-
-        assert!(result.is_err()); // Assert that the operation results in an error.
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        // Error: Attempting to access a non-existent local variable (index out of range).
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::LocalVariableIndexOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -466,7 +700,8 @@ mod tests {
             Err(ProcessorError {
                 error_type: ProcessorErrorType::Terminate(
                     TERMINATE_CODE_UNSUPPORTED_FLOATING_POINT_VARIANTS
-                )
+                ),
+                ..
             })
         ));
     }
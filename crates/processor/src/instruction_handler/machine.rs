@@ -6,6 +6,11 @@
 
 use anc_context::thread_context::ThreadContext;
 
+use crate::{
+    callback_delegate_handler::get_or_create_callback_delegate_function,
+    TERMINATE_CODE_FAILED_TO_CREATE_DELEGATE_FUNCTION,
+};
+
 use super::HandleResult;
 
 pub fn terminate(thread: &mut ThreadContext) -> HandleResult {
@@ -78,7 +83,10 @@ fn do_host_addr_data(
     instruction_length_in_bytes: isize,
 ) -> HandleResult {
     let target_data_object =
-        thread_context.get_target_data_object(module_index, data_access_index, 0, 0);
+        match thread_context.get_target_data_object(module_index, data_access_index, 0, 0) {
+            Ok(target_data_object) => target_data_object,
+            Err(trap_kind) => return HandleResult::Trap(trap_kind),
+        };
     let start_address = target_data_object
         .accessor
         .get_start_address_by_index(target_data_object.data_internal_index_in_section);
@@ -91,29 +99,50 @@ fn do_host_addr_data(
 
 pub fn host_addr_function(thread_context: &mut ThreadContext) -> HandleResult {
     // (param function_public_index:i32) -> pointer
-    //
-    //     let function_public_index = thread_context.get_param_i32() as usize;
-    //     let module_index = thread_context.pc.module_index;
-    //
-    //     if let Ok(callback_function_ptr) = get_or_create_bridge_callback_function(
-    //         handler,
-    //         thread_context,
-    //         module_index,
-    //         function_public_index,
-    //     ) {
-    //         store_pointer_to_operand_stack(thread_context, callback_function_ptr);
-    //         HandleResult::Move(8)
-    //     } else {
-    //         HandleResult::Terminate(TERMINATE_CODE_FAILED_TO_CREATE_DELEGATE_FUNCTION)
-    //     }
-    todo!()
+    let function_public_index = thread_context.get_param_i32();
+    do_host_addr_function(
+        thread_context,
+        thread_context.pc.module_index,
+        function_public_index as usize,
+        8,
+    )
 }
 
-pub fn host_addr_function_dynamic(
+pub fn host_addr_function_dynamic(thread_context: &mut ThreadContext) -> HandleResult {
+    // () (operand function_module_index:i32 function_public_index:i32) -> pointer
+    let function_public_index = thread_context.stack.pop_i32_u();
+    let module_index = thread_context.stack.pop_i32_u();
+    do_host_addr_function(
+        thread_context,
+        module_index as usize,
+        function_public_index as usize,
+        2,
+    )
+}
+
+fn do_host_addr_function(
     thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_public_index: usize,
+    instruction_length_in_bytes: isize,
 ) -> HandleResult {
-    // () (operand function_module_index:i32 function_public_index:i32) -> pointer
-    todo!()
+    let target_function_object =
+        match thread_context.get_target_function_object(module_index, function_public_index) {
+            Ok(target_function_object) => target_function_object,
+            Err(trap_kind) => return HandleResult::Trap(trap_kind),
+        };
+
+    match get_or_create_callback_delegate_function(
+        thread_context,
+        target_function_object.module_index,
+        target_function_object.function_internal_index,
+    ) {
+        Ok(callback_delegate_function_ptr) => {
+            store_pointer_to_operand_stack(thread_context, callback_delegate_function_ptr);
+            HandleResult::Move(instruction_length_in_bytes)
+        }
+        Err(_) => HandleResult::Terminate(TERMINATE_CODE_FAILED_TO_CREATE_DELEGATE_FUNCTION),
+    }
 }
 
 fn store_pointer_to_operand_stack(thread_context: &mut ThreadContext, ptr: *const u8) {
@@ -123,14 +152,13 @@ fn store_pointer_to_operand_stack(thread_context: &mut ThreadContext, ptr: *cons
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use anc_context::{
+        capability::Capability,
         process_property::{ProcessProperty, ProgramSourceType},
         program_source::ProgramSource,
+        thread_context::TrapKind,
     };
     use anc_image::{
-        bytecode_reader::format_bytecode_as_text,
         bytecode_writer::BytecodeWriterHelper,
         entry::{ExternalLibraryEntry, ReadOnlyDataEntry, ReadWriteDataEntry, UninitDataEntry},
         utils::{
@@ -140,10 +168,7 @@ mod tests {
             HelperFunctionEntry,
         },
     };
-    use anc_isa::{
-        opcode::Opcode, DependencyCondition, DependencyLocal, ExternalLibraryDependency,
-        ForeignValue, OperandDataType,
-    };
+    use anc_isa::{opcode::Opcode, ExternalLibraryDependency, ForeignValue, OperandDataType};
 
     use crate::{
         in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
@@ -210,7 +235,8 @@ mod tests {
         assert!(matches!(
             result0,
             Err(ProcessorError {
-                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE)
+                error_type: ProcessorErrorType::Terminate(TERMINATE_CODE_UNREACHABLE),
+                ..
             })
         ));
     }
@@ -574,152 +600,235 @@ mod tests {
         // todo
     }
 
-    //     #[test]
-    //     fn test_handler_host_addr_function() {}
-
-    //     #[test]
-    //     fn test_handler_host_addr_function_and_callback_function() {
-    //         // `do_something` is an external function (a C function) in "libtest0.so.1":
-    //         //
-    //         // ```c
-    //         // int do_something(int (*callback_function)(int), int a, int b)
-    //         // {
-    //         //     int s = (callback_function)(a);
-    //         //     return s + b;
-    //         // }
-    //         // ```
-    //         //
-    //         // `function0` and `function1` are VM functions:
-    //         //
-    //         // ```code
-    //         // ;; the entry function
-    //         // fn function0 (a:i32, b:i32)->i32 {
-    //         //     call(do_something, host_addr_function(function1), a, b)
-    //         // }
-    //         //
-    //         // ;; used as callback function for external function 'do_something'
-    //         // fn function1 (a:i32) -> i32 {
-    //         //     a*2
-    //         // }
-    //         // ```
-    //         //
-    //         // the calling path:
-    //         //
-    //         // ```diagram
-    //         // (11,13) ->
-    //         //   function0 (VM function) ->
-    //         //     do_something (external function) ->
-    //         //       function1 (VM function) ->
-    //         //     return to do_something ->
-    //         //   return to function0 ->
-    //         // returns 35
-    //         // ```
-    //
-    //         // VM function 0
-    //         let code0 = BytecodeWriterHelper::new()
-    //             .append_opcode_i32(Opcode::host_addr_function, 1) // get host address of the func1, for external func param 0
-    //             .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0) // for external func param 1
-    //             .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1) // for external func param 2
-    //             //
-    //             .append_opcode_i32(Opcode::extcall, 0) // call external function, external function index = 0
-    //             //
-    //             .append_opcode(Opcode::end)
-    //             .to_bytes();
-    //
-    //         // VM function 1
-    //         let code1 = BytecodeWriterHelper::new()
-    //             .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
-    //             .append_opcode_i32(Opcode::imm_i32, 2)
-    //             .append_opcode(Opcode::mul_i32)
-    //             //
-    //             .append_opcode(Opcode::end)
-    //             .to_bytes();
-    //
-    //         let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
-    //             &[
-    //                 HelperFunctionEntry {
-    //                     // type_index: 1,
-    //                     params: vec![OperandDataType::I32, OperandDataType::I32],
-    //                     results: vec![OperandDataType::I32],
-    //                     local_variable_item_entries_without_args: vec![],
-    //                     code: code0,
-    //                 },
-    //                 HelperFunctionEntry {
-    //                     // type_index: 2,
-    //                     params: vec![OperandDataType::I32],
-    //                     results: vec![OperandDataType::I32],
-    //                     local_variable_item_entries_without_args: vec![],
-    //                     code: code1,
-    //                 },
-    //             ],
-    //             &[],
-    //             &[],
-    //             &[],
-    //             &[ExternalLibraryEntry::new(
-    //                 "libtest0".to_owned(),
-    //                 Box::new(ExternalLibraryDependency::Local(Box::new(
-    //                     DependencyLocal {
-    //                         path: "lib/libtest0.so.1".to_owned(),
-    //                         condition: DependencyCondition::True,
-    //                         parameters: HashMap::default(),
-    //                     },
-    //                 ))),
-    //             )],
-    //             &[HelperExternalFunctionEntry {
-    //                 name: "do_something".to_string(),
-    //                 external_library_index: 0,
-    //                 params: vec![
-    //                     OperandDataType::I64,
-    //                     OperandDataType::I32,
-    //                     OperandDataType::I32,
-    //                 ],
-    //                 result: Some(OperandDataType::I32),
-    //             }],
-    //         );
-    //
-    //         let mut pwd = std::env::current_dir().unwrap();
-    //         // let pkg_name = env!("CARGO_PKG_NAME");
-    //         let crate_folder_name = "processor";
-    //         if !pwd.ends_with(crate_folder_name) {
-    //             // in the VSCode `Debug` environment, the `current_dir()`
-    //             // the project root folder.
-    //             // while in both `$ cargo test` and VSCode `Run Test` environment
-    //             // the `current_dir()` return the current crate path.
-    //             pwd.push("crates");
-    //             pwd.push(crate_folder_name);
-    //         }
-    //         pwd.push("tests");
-    //         // let application_path = pwd.to_str().unwrap();
-    //
-    //
-    //         let resource0 = InMemoryProgramSource::with_property(
-    //             vec![binary0],
-    //             ProcessProperty::new(
-    //                 pwd,
-    //                 ProgramSourceType::Module,
-    //                 vec![],
-    //                 HashMap::<String, String>::new(),
-    //             ),
-    //         );
-    //         let process_context0 = resource0.create_process_context().unwrap();
-    //         let mut thread_context0 = process_context0.create_thread_context();
-    //
-    //         let result0 = process_function(
-    //             &handler,
-    //             &mut thread_context0,
-    //             0,
-    //             0,
-    //             &[ForeignValue::U32(11), ForeignValue::U32(13)],
-    //         );
-    //         assert_eq!(result0.unwrap(), vec![ForeignValue::U32(11 * 2 + 13)]);
-    //
-    //         let result1 = process_function(
-    //             &handler,
-    //             &mut thread_context0,
-    //             0,
-    //             0,
-    //             &[ForeignValue::U32(211), ForeignValue::U32(223)],
-    //         );
-    //         assert_eq!(result1.unwrap(), vec![ForeignValue::U32(211 * 2 + 223)]);
-    //     }
+    #[test]
+    fn test_handler_host_addr_function_and_callback_function() {
+        // `do_something` is an external function (a C function) in "libtest.so.1":
+        //
+        // ```c
+        // int do_something(int (*callback_func)(int), int a, int b)
+        // {
+        //     int s = (callback_func)(a);
+        //     return s + b;
+        // }
+        // ```
+        //
+        // `function0` and `function1` are VM functions:
+        //
+        // ```code
+        // ;; the entry function
+        // fn function0 (a:i32, b:i32)->i32 {
+        //     extcall(do_something, host_addr_function(function1), a, b)
+        // }
+        //
+        // ;; used as callback function for external function 'do_something'
+        // fn function1 (a:i32) -> i32 {
+        //     a*2
+        // }
+        // ```
+        //
+        // the calling path:
+        //
+        // ```diagram
+        // (11,13) ->
+        //   function0 (VM function) ->
+        //     do_something (external function) ->
+        //       function1 (VM function) ->
+        //     return to do_something ->
+        //   return to function0 ->
+        // returns 35
+        // ```
+
+        // VM function 0
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::host_addr_function, 1) // get host address of the function1, for external function param 0
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0) // for external function param 1
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1) // for external function param 2
+            //
+            .append_opcode_i32(Opcode::extcall, 0) // call external function, external function index = 0
+            //
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        // VM function 1
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i32(Opcode::imm_i32, 2)
+            .append_opcode(Opcode::mul_i32)
+            //
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = build_binary_with_callback_function(code0, code1);
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            build_process_property_with_crate_folder(),
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(11), ForeignValue::U32(13)],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(11 * 2 + 13)]);
+
+        // the callback delegate function is created only once
+        let result1 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(211), ForeignValue::U32(223)],
+        );
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(211 * 2 + 223)]);
+
+        assert_eq!(
+            thread_context0
+                .callback_delegate_function_table
+                .functions_by_modules[0]
+                .callback_delegate_function_items
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_handler_host_addr_function_dynamic() {
+        // fn function0 (a:i32, b:i32)->i32 {
+        //     extcall(do_something, host_addr_function_dynamic(get_function(function1)), a, b)
+        // }
+        //
+        // fn function1 (a:i32) -> i32 {
+        //     a*2
+        // }
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::get_function, 1)
+            .append_opcode(Opcode::host_addr_function_dynamic)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            //
+            .append_opcode_i32(Opcode::extcall, 0)
+            //
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i32(Opcode::imm_i32, 2)
+            .append_opcode(Opcode::mul_i32)
+            //
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = build_binary_with_callback_function(code0, code1);
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            build_process_property_with_crate_folder(),
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(17), ForeignValue::U32(19)],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(17 * 2 + 19)]);
+    }
+
+    #[test]
+    fn test_handler_host_addr_function_with_function_index_out_of_bounds() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::host_addr_function, 3)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I64], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::FunctionIndexOutOfBounds,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    fn build_binary_with_callback_function(code0: Vec<u8>, code1: Vec<u8>) -> Vec<u8> {
+        helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+            ],
+            &[],
+            &[],
+            &[],
+            &[ExternalLibraryEntry::new(
+                "libtest".to_owned(),
+                Box::new(ExternalLibraryDependency::File(
+                    "tests/resources/libtest/libtest.so.1".to_owned(),
+                )),
+            )],
+            &[HelperExternalFunctionEntry {
+                name: "do_something".to_string(),
+                external_library_index: 0,
+                params: vec![
+                    OperandDataType::I64,
+                    OperandDataType::I32,
+                    OperandDataType::I32,
+                ],
+                result: Some(OperandDataType::I32),
+            }],
+        )
+    }
+
+    fn build_process_property_with_crate_folder() -> ProcessProperty {
+        let mut pwd = std::env::current_dir().unwrap();
+        let crate_folder_name = "processor";
+        if !pwd.ends_with(crate_folder_name) {
+            // in the VSCode editor `Debug` environment, the `current_dir()` returns
+            // the project's root folder.
+            // while in both `$ cargo test` and VSCode editor `Run Test` environment,
+            // the `current_dir()` returns the current crate path.
+            pwd.push("crates");
+            pwd.push(crate_folder_name);
+        }
+
+        ProcessProperty::new(
+            pwd,
+            ProgramSourceType::Module,
+            vec![],
+            vec![],
+            Capability {
+                extcall: true,
+                ..Capability::default()
+            },
+        )
+    }
 }
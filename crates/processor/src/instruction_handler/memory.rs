@@ -5,7 +5,7 @@
 // For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
 
 use anc_context::thread_context::{
-    ThreadContext, MEMORY_DATA_ACCESS_INDEX_MASK, MEMORY_DATA_ACCESS_INDEX_MSB,
+    ThreadContext, TrapKind, MEMORY_DATA_ACCESS_INDEX_MASK, MEMORY_DATA_ACCESS_INDEX_MSB,
 };
 
 use super::HandleResult;
@@ -40,6 +40,15 @@ pub fn memory_reallocate(thread_context: &mut ThreadContext) -> HandleResult {
     // Clear the MSB to get the original address
     let data_internal_index = data_access_index as usize & MEMORY_DATA_ACCESS_INDEX_MASK;
 
+    // The index comes from the operands, reallocating a forged index crashes the host.
+    if thread_context
+        .allocator
+        .get_size(data_internal_index)
+        .is_none()
+    {
+        return HandleResult::Trap(TrapKind::DataIndexOutOfBounds);
+    }
+
     let reallocated_data_internal_index = thread_context.allocator.reallocate(
         data_internal_index,
         new_size_in_bytes as usize,
@@ -64,6 +73,15 @@ pub fn memory_free(thread_context: &mut ThreadContext) -> HandleResult {
     // Clear the MSB to get the original address
     let data_internal_index = data_access_index as usize & MEMORY_DATA_ACCESS_INDEX_MASK;
 
+    // The index comes from the operands, freeing a forged index crashes the host.
+    if thread_context
+        .allocator
+        .get_size(data_internal_index)
+        .is_none()
+    {
+        return HandleResult::Trap(TrapKind::DataIndexOutOfBounds);
+    }
+
     thread_context.allocator.free(data_internal_index);
     HandleResult::Move(2)
 }
@@ -82,12 +100,15 @@ pub fn memory_fill(thread_context: &mut ThreadContext) -> HandleResult {
     let data_access_index = thread_context.stack.pop_i64_u() as usize;
     let data_module_index = thread_context.stack.pop_i32_u() as usize;
 
-    let target_data_object = thread_context.get_target_data_object(
+    let target_data_object = match thread_context.get_target_data_object(
         data_module_index,
         data_access_index,
         offset_in_bytes,
         size_in_bytes,
-    );
+    ) {
+        Ok(target_data_object) => target_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     let address = target_data_object
         .accessor
@@ -118,12 +139,15 @@ pub fn memory_copy(thread_context: &mut ThreadContext) -> HandleResult {
     let source_data_access_index = thread_context.stack.pop_i64_u() as usize;
     let source_data_module_index = thread_context.stack.pop_i32_u() as usize;
 
-    let source_data_object = thread_context.get_target_data_object(
+    let source_data_object = match thread_context.get_target_data_object(
         source_data_module_index,
         source_data_access_index,
         source_offset_in_bytes,
         size_in_bytes,
-    );
+    ) {
+        Ok(source_data_object) => source_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     let source_address = source_data_object
         .accessor
@@ -132,12 +156,15 @@ pub fn memory_copy(thread_context: &mut ThreadContext) -> HandleResult {
         .accessor
         .get_ptr(source_address, source_offset_in_bytes);
 
-    let dest_data_object = thread_context.get_target_data_object(
+    let dest_data_object = match thread_context.get_target_data_object(
         dest_data_module_index,
         dest_data_access_index,
         dest_offset_in_bytes,
         size_in_bytes,
-    );
+    ) {
+        Ok(dest_data_object) => dest_data_object,
+        Err(trap_kind) => return HandleResult::Trap(trap_kind),
+    };
 
     let dest_address = dest_data_object
         .accessor
@@ -154,7 +181,7 @@ pub fn memory_copy(thread_context: &mut ThreadContext) -> HandleResult {
 #[cfg(test)]
 mod tests {
 
-    use anc_context::program_source::ProgramSource;
+    use anc_context::{program_source::ProgramSource, thread_context::TrapKind};
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
         entry::{ReadOnlyDataEntry, UninitDataEntry},
@@ -163,7 +190,10 @@ mod tests {
     use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
     use pretty_assertions::assert_eq;
 
-    use crate::{in_memory_program_source::InMemoryProgramSource, process::process_function};
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource, process::process_function, ProcessorError,
+        ProcessorErrorType,
+    };
 
     #[test]
     fn test_memory_allocate_reallocate_and_free() {
@@ -284,6 +314,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_memory_bounds_check() {
+        // Testing: Access the allocated memory out of bounds and access
+        // a forged memory index, both should raise a trap instead of
+        // crashing the host.
+
+        // (the allocated memory address + 4) is not an allocated memory index.
+        let code_forged_index = BytecodeWriterHelper::new()
+            .append_opcode_i64(Opcode::imm_i64, 8) // size
+            .append_opcode_i32(Opcode::imm_i32, 8) // align
+            .append_opcode(Opcode::memory_allocate)
+            .append_opcode_i64(Opcode::imm_i64, 4)
+            .append_opcode(Opcode::add_i64)
+            .append_opcode_i16_i32(Opcode::local_store_i64, 0, 0)
+            .append_opcode_i32(Opcode::imm_i32, 0) // module index
+            .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+            .append_opcode_i64(Opcode::imm_i64, 0) // offset
+            .append_opcode(Opcode::memory_load_i32_u)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        // read i64 at offset 4 of an 8 bytes memory.
+        let code_out_of_bounds = BytecodeWriterHelper::new()
+            .append_opcode_i64(Opcode::imm_i64, 8) // size
+            .append_opcode_i32(Opcode::imm_i32, 8) // align
+            .append_opcode(Opcode::memory_allocate)
+            .append_opcode_i16_i32(Opcode::local_store_i64, 0, 0)
+            .append_opcode_i32(Opcode::imm_i32, 0) // module index
+            .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+            .append_opcode_i64(Opcode::imm_i64, 4) // offset
+            .append_opcode(Opcode::memory_load_i64)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        for (code0, expected_trap_kind) in [
+            (code_forged_index, TrapKind::DataIndexOutOfBounds),
+            (code_out_of_bounds, TrapKind::DataAccessOutOfBounds),
+        ] {
+            let binary0 = helper_build_module_binary_with_single_function_and_data(
+                &[], // params
+                &[], // results
+                &[
+                    OperandDataType::I64, // for storing the allocated memory address
+                ], // local variables
+                code0,
+                &[], // read_only_data_entries
+                &[], //read_write_data_entries
+                &[], //uninit_uninit_data_entries
+            );
+
+            let resource0 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context0 = resource0.create_process_context().unwrap();
+            let mut thread_context0 = process_context0.create_thread_context();
+
+            let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+            assert!(matches!(
+                result0,
+                Err(ProcessorError {
+                    error_type: ProcessorErrorType::Trap { kind, .. },
+                    ..
+                }) if kind == expected_trap_kind
+            ));
+        }
+    }
+
     #[test]
     fn test_memory_fill() {
         // API:
@@ -0,0 +1,1042 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// JIT compiler
+// ------------
+//
+// Functions are executed by the interpreter at first, and the processor counts
+// the calls of each function. Once the number of calls reaches the threshold
+// (see `ThreadContext::jit_threshold`), the bytecode of the function is translated
+// into Cranelift IR and compiled into native code, the subsequent calls of the
+// function are dispatched to the native code.
+//
+// ```diagram
+//                 calls < threshold
+//                   /----------\
+//                   |          |
+//                   v          |
+// | Interpreted(calls) |-------/
+//           |
+//           | calls == threshold
+//           v
+//      translation ---- unsupported instruction ---> | Unsupported |
+//           |
+//           v
+//     | Compiled |
+// ```
+//
+// Only the functions that consist of the following instructions are compiled:
+//
+// - fundamental: `nop`, `imm_i32` and `imm_i64`.
+// - local: `local_load_*` and `local_store_*`, except `local_load_f32/f64`.
+// - data: `data_load_*` and `data_store_*`, except `data_load_f32/f64`
+//   and the "extend" variants.
+// - arithmetic: the integer instructions.
+// - comparison: the integer instructions.
+// - control flow: all instructions.
+//
+// The other instructions (e.g. `call`, `envcall` and the floating-point instructions,
+// which have to check the NaN and Inf variants) are not supported, functions which
+// contain them are always executed by the interpreter.
+//
+// The bytecode is translated by the native code translator (see `native_translator.rs`),
+// which is shared with the AOT compiler, the `JitEnvironment` translates the instructions
+// that depend on the running thread.
+//
+// The native function
+// -------------------
+//
+// The frame of the function is created by the `call` instruction as usual, the native
+// function accesses the arguments and the local variables through the frame, while the
+// operands and the local variables of blocks are kept in registers and the native stack.
+//
+// ```rust
+// extern "C" fn native_function(
+//     local_variables_ptr: *mut u8,
+//     results_ptr: *mut u8,
+//     deoptimization_buffer_ptr: *mut u8,
+//     thread_interrupt_flag_ptr: *const u8,
+//     process_interrupt_flag_ptr: *const u8,
+// ) -> u64 /* status */;
+// ```
+//
+// The status returned by the native function:
+//
+// | bits 63..8                | bits 7..0                                   |
+// |---------------------------|---------------------------------------------|
+// | 0                         | 0: finished, the results are written        |
+// | instruction address       | 1: trap "integer divide by zero"            |
+// | instruction address       | 2: trap "integer overflow"                  |
+// | deoptimization site index | 3: deoptimization, see the following        |
+//
+// The local variables and the data are resolved when the function is compiled,
+// accesses that would raise a trap are never compiled (the function is left to the
+// interpreter instead). Note that the addresses of the data are embedded into the native
+// code, so the compiled functions belong to the thread that compiles them.
+//
+// Deoptimization
+// --------------
+//
+// The native code does not consume fuel, so the JIT compiler is bypassed when the fuel
+// of the thread is limited. However, the native code checks the interrupt flags at the
+// back edges of loops (i.e., the instruction `recur`). When the thread is interrupted,
+// the native function writes the operands and the local variables of the blocks to the
+// deoptimization buffer and returns, the processor then rebuilds the block frames on the
+// stack and resumes the function at the start of the loop by the interpreter, which
+// stops with the "interrupted" error immediately.
+
+use std::sync::{Arc, Mutex};
+
+use anc_context::{
+    module_common_instance::FunctionTier,
+    thread_context::{ThreadContext, TrapKind},
+};
+use anc_image::common_sections::{
+    local_variable_section::LocalVariableSection, type_section::TypeSection,
+};
+use anc_isa::{DataSectionType, OPERAND_SIZE_IN_BYTES};
+use anc_stack::ProgramCounter;
+use cranelift_codegen::ir::{
+    types, AbiParam, Function, InstBuilder, MemFlags, Type, UserFuncName, Value,
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{Linkage, Module};
+
+use crate::{
+    instruction_handler::HandleResult,
+    native_translator::{ControlFrame, Translator, TranslatorEnvironment, STATUS_FINISHED},
+    TERMINATE_CODE_STACK_OVERFLOW,
+};
+
+static LAST_COMPILED_FUNCTION_ID: Mutex<usize> = Mutex::new(0);
+
+const STATUS_KIND_MASK: u64 = 0xff;
+const STATUS_INTEGER_DIVIDE_BY_ZERO: u64 = 1;
+const STATUS_INTEGER_OVERFLOW: u64 = 2;
+const STATUS_DEOPTIMIZATION: u64 = 3;
+
+type NativeFunction = extern "C" fn(
+    /* local_variables_ptr */ *mut u8,
+    /* results_ptr */ *mut u8,
+    /* deoptimization_buffer_ptr */ *mut u8,
+    /* thread_interrupt_flag_ptr */ *const u8,
+    /* process_interrupt_flag_ptr */ *const u8,
+) -> u64;
+
+pub struct CompiledFunction {
+    native_function: NativeFunction,
+    results_count: usize,
+    deoptimization_sites: Vec<DeoptimizationSite>,
+    deoptimization_buffer_length: usize,
+}
+
+// The location where the interpreter resumes the function
+// when the native function is deoptimized, i.e. the start of a loop.
+struct DeoptimizationSite {
+    resume_instruction_address: usize,
+
+    // The block frames to be rebuilt, from the outermost to the innermost.
+    frames: Vec<DeoptimizationFrame>,
+}
+
+// Each block frame occupies the following content in the deoptimization buffer:
+// the operands of the parent frame (which are below the block frame on the stack),
+// followed by the local variables (includes the arguments) of the block.
+struct DeoptimizationFrame {
+    parent_operands_count: usize,
+    params_count: u16,
+    results_count: u16,
+    local_variable_list_index: u32,
+    local_variables_with_arguments_allocated_bytes: u32,
+}
+
+/// Counts the call of the function, and compiles the function into native code
+/// when the number of calls reaches the threshold.
+///
+/// Returns the compiled function if the function has been compiled, or `None` if
+/// the function should be executed by the interpreter.
+pub fn get_or_compile_function(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_internal_index: usize,
+) -> Option<Arc<CompiledFunction>> {
+    let threshold = thread_context.jit_threshold?;
+
+    // The native code does not consume fuel.
+    if thread_context.fuel.is_some() {
+        return None;
+    }
+
+    match &mut thread_context.module_common_instances[module_index].function_tiers
+        [function_internal_index]
+    {
+        FunctionTier::Compiled(compiled_function) => {
+            return Arc::clone(compiled_function)
+                .downcast::<CompiledFunction>()
+                .ok();
+        }
+        FunctionTier::Unsupported => {
+            return None;
+        }
+        FunctionTier::Interpreted(calls) => {
+            *calls = calls.saturating_add(1);
+            if *calls < threshold {
+                return None;
+            }
+        }
+    }
+
+    let opt_compiled_function =
+        compile_function(thread_context, module_index, function_internal_index).map(Arc::new);
+
+    thread_context.module_common_instances[module_index].function_tiers[function_internal_index] =
+        match &opt_compiled_function {
+            Some(compiled_function) => FunctionTier::Compiled(compiled_function.clone()),
+            None => FunctionTier::Unsupported,
+        };
+
+    opt_compiled_function
+}
+
+/// Calls the native code of the compiled function,
+/// the frame of the function should have been created.
+///
+/// Returns `None` if the function is finished, the results have been pushed onto the
+/// operand stack, and the frame should be removed as the instruction `end` does.
+/// Otherwise returns:
+///
+/// - `Jump`: the function is deoptimized, the execution should be resumed by the interpreter.
+/// - `Trap`: a trap is raised, the program counter is set to the faulting instruction.
+/// - `Terminate`: the block frames can not be rebuilt because of stack overflow.
+pub fn call_compiled_function(
+    thread_context: &mut ThreadContext,
+    compiled_function: &CompiledFunction,
+    function_pc: ProgramCounter,
+) -> Option<HandleResult> {
+    let (_, local_variables_start_address) = thread_context
+        .stack
+        .get_local_variable_list_index_and_start_address_by_layers(0);
+    let local_variables_ptr = thread_context
+        .stack
+        .get_mut_ptr(local_variables_start_address, 0);
+
+    // The native function writes the results to the operand stack directly.
+    let results_ptr = thread_context
+        .stack
+        .push_operands_from_memory(compiled_function.results_count);
+
+    let mut deoptimization_buffer = vec![0u8; compiled_function.deoptimization_buffer_length];
+
+    let status = (compiled_function.native_function)(
+        local_variables_ptr,
+        results_ptr,
+        deoptimization_buffer.as_mut_ptr(),
+        thread_context.interrupt_handle.get_flag_address(),
+        thread_context.process_interrupt_handle.get_flag_address(),
+    );
+
+    let status_kind = status & STATUS_KIND_MASK;
+    if status_kind == STATUS_FINISHED {
+        return None;
+    }
+
+    // Discard the space of the results.
+    thread_context
+        .stack
+        .pop_operands_to_memory(compiled_function.results_count);
+
+    let payload = (status >> 8) as usize;
+    let handle_result = match status_kind {
+        STATUS_DEOPTIMIZATION => deoptimize(
+            thread_context,
+            &compiled_function.deoptimization_sites[payload],
+            &deoptimization_buffer,
+            function_pc,
+        ),
+        _ => {
+            let trap_kind = if status_kind == STATUS_INTEGER_DIVIDE_BY_ZERO {
+                TrapKind::IntegerDivideByZero
+            } else {
+                TrapKind::IntegerOverflow
+            };
+
+            thread_context.pc = ProgramCounter {
+                instruction_address: payload,
+                function_internal_index: function_pc.function_internal_index,
+                module_index: function_pc.module_index,
+            };
+            HandleResult::Trap(trap_kind)
+        }
+    };
+
+    Some(handle_result)
+}
+
+// Rebuilds the block frames from the deoptimization buffer, and returns
+// the location where the interpreter resumes the function.
+fn deoptimize(
+    thread_context: &mut ThreadContext,
+    deoptimization_site: &DeoptimizationSite,
+    deoptimization_buffer: &[u8],
+    function_pc: ProgramCounter,
+) -> HandleResult {
+    let mut offset = 0;
+
+    for frame in &deoptimization_site.frames {
+        let operands_length = frame.parent_operands_count * OPERAND_SIZE_IN_BYTES;
+        let dst_ptr = thread_context
+            .stack
+            .push_operands_from_memory(frame.parent_operands_count);
+        unsafe {
+            std::ptr::copy(
+                deoptimization_buffer[offset..].as_ptr(),
+                dst_ptr,
+                operands_length,
+            )
+        };
+        offset += operands_length;
+
+        // The arguments are the first local variables of the block, push them
+        // as operands so that they are moved into the new frame.
+        let params_count = frame.params_count as usize;
+        let dst_ptr = thread_context.stack.push_operands_from_memory(params_count);
+        unsafe {
+            std::ptr::copy(
+                deoptimization_buffer[offset..].as_ptr(),
+                dst_ptr,
+                params_count * OPERAND_SIZE_IN_BYTES,
+            )
+        };
+
+        if thread_context
+            .stack
+            .create_frame(
+                frame.params_count,
+                frame.results_count,
+                frame.local_variable_list_index,
+                frame.local_variables_with_arguments_allocated_bytes,
+                None,
+            )
+            .is_err()
+        {
+            return HandleResult::Terminate(TERMINATE_CODE_STACK_OVERFLOW);
+        }
+
+        let local_variables_length = frame.local_variables_with_arguments_allocated_bytes as usize;
+        let (_, local_variables_start_address) = thread_context
+            .stack
+            .get_local_variable_list_index_and_start_address_by_layers(0);
+        let dst_ptr = thread_context
+            .stack
+            .get_mut_ptr(local_variables_start_address, 0);
+        unsafe {
+            std::ptr::copy(
+                deoptimization_buffer[offset..].as_ptr(),
+                dst_ptr,
+                local_variables_length,
+            )
+        };
+        offset += local_variables_length;
+    }
+
+    HandleResult::Jump(ProgramCounter {
+        instruction_address: deoptimization_site.resume_instruction_address,
+        function_internal_index: function_pc.function_internal_index,
+        module_index: function_pc.module_index,
+    })
+}
+
+// Translates the bytecode of the function into native code,
+// returns `None` if the function contains unsupported instructions.
+fn compile_function(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_internal_index: usize,
+) -> Option<CompiledFunction> {
+    let function_info = thread_context.get_function_info(module_index, function_internal_index);
+    let module_common_instance = &thread_context.module_common_instances[module_index];
+    let type_item = &module_common_instance.type_section.items[function_info.type_index];
+    let params_count = type_item.params_count as usize;
+    let results_count = type_item.results_count as usize;
+    let codes_data = module_common_instance.function_section.codes_data;
+    let code_length =
+        module_common_instance.function_section.items[function_internal_index].code_length as usize;
+
+    let jit_generator_mutex = thread_context.jit_generator;
+    let mut jit_generator_guard = jit_generator_mutex.lock().unwrap();
+    let jit_generator = &mut *jit_generator_guard;
+    let pointer_type = jit_generator.module.isa().pointer_type();
+
+    let mut signature = jit_generator.module.make_signature();
+    signature.params.push(AbiParam::new(pointer_type)); // local_variables_ptr
+    signature.params.push(AbiParam::new(pointer_type)); // results_ptr
+    signature.params.push(AbiParam::new(pointer_type)); // deoptimization_buffer_ptr
+    signature.params.push(AbiParam::new(pointer_type)); // thread_interrupt_flag_ptr
+    signature.params.push(AbiParam::new(pointer_type)); // process_interrupt_flag_ptr
+    signature.returns.push(AbiParam::new(types::I64)); // status
+
+    let mut function = Function::with_name_signature(UserFuncName::default(), signature.clone());
+
+    // A dedicated builder context is used instead of the one in the generator, because
+    // the translation is abandoned halfway when an unsupported instruction is encountered,
+    // which leaves the builder context dirty.
+    let mut function_builder_context = FunctionBuilderContext::new();
+
+    let (deoptimization_sites, deoptimization_buffer_length) = {
+        let mut function_builder =
+            FunctionBuilder::new(&mut function, &mut function_builder_context);
+        let entry_block = function_builder.create_block();
+        function_builder.append_block_params_for_function_params(entry_block);
+        function_builder.switch_to_block(entry_block);
+
+        let params = function_builder.block_params(entry_block).to_vec();
+        let environment = JitEnvironment {
+            thread_context,
+            module_index,
+            pointer_type,
+            deoptimization_buffer_ptr: params[2],
+            thread_interrupt_flag_ptr: params[3],
+            process_interrupt_flag_ptr: params[4],
+            deoptimization_sites: vec![],
+            deoptimization_buffer_length: 0,
+        };
+
+        let mut translator = Translator::new(
+            function_builder,
+            environment,
+            pointer_type,
+            params[0],
+            params[1],
+        );
+        translator.translate(
+            &codes_data[function_info.code_offset..(function_info.code_offset + code_length)],
+            function_info.code_offset,
+            params_count,
+            results_count,
+            function_info.local_variable_list_index,
+            function_info.local_variables_with_arguments_allocated_bytes,
+        )?;
+
+        let environment = translator.finish();
+        (
+            environment.deoptimization_sites,
+            environment.deoptimization_buffer_length,
+        )
+    };
+
+    // The name of the function needs to be constructed using a
+    // "process global unique id" to avoid duplicate ids in parallel unit tests.
+    let function_name = {
+        let mut last_id = LAST_COMPILED_FUNCTION_ID.lock().unwrap();
+        let next_id: usize = *last_id;
+        *last_id = next_id + 1;
+        format!("compiled_function_{}", next_id)
+    };
+
+    let function_declare = jit_generator
+        .module
+        .declare_function(&function_name, Linkage::Local, &signature)
+        .ok()?;
+    function.name = UserFuncName::user(0, function_declare.as_u32());
+
+    // Generate the (machine/native) code of the function.
+    jit_generator.context.func = function;
+    let define_result = jit_generator
+        .module
+        .define_function(function_declare, &mut jit_generator.context);
+    jit_generator
+        .module
+        .clear_context(&mut jit_generator.context);
+    define_result.ok()?;
+
+    // Link the function.
+    jit_generator.module.finalize_definitions().ok()?;
+
+    let function_pointer = jit_generator
+        .module
+        .get_finalized_function(function_declare);
+
+    Some(CompiledFunction {
+        native_function: unsafe {
+            std::mem::transmute::<*const u8, NativeFunction>(function_pointer)
+        },
+        results_count,
+        deoptimization_sites,
+        deoptimization_buffer_length,
+    })
+}
+
+// Translates the instructions that depend on the running thread, the local variables
+// and the data are resolved when the function is compiled.
+struct JitEnvironment<'a, 'b> {
+    thread_context: &'b mut ThreadContext<'a>,
+    module_index: usize,
+    pointer_type: Type,
+
+    // The parameters of the native function.
+    deoptimization_buffer_ptr: Value,
+    thread_interrupt_flag_ptr: Value,
+    process_interrupt_flag_ptr: Value,
+
+    deoptimization_sites: Vec<DeoptimizationSite>,
+    deoptimization_buffer_length: usize,
+}
+
+impl TranslatorEnvironment for JitEnvironment<'_, '_> {
+    fn get_sections(&self) -> (&TypeSection, &LocalVariableSection) {
+        let module_common_instance =
+            &self.thread_context.module_common_instances[self.module_index];
+        (
+            &module_common_instance.type_section,
+            &module_common_instance.local_variable_section,
+        )
+    }
+
+    // The address of the data is embedded into the native code.
+    fn translate_data_address(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        data_public_index: usize,
+        offset_bytes: usize,
+        data_length: usize,
+        for_writing: bool,
+    ) -> Option<Value> {
+        let target_data_object = self
+            .thread_context
+            .get_target_data_object(
+                self.module_index,
+                data_public_index,
+                offset_bytes,
+                data_length,
+            )
+            .ok()?;
+
+        let data_internal_index = target_data_object.data_internal_index_in_section;
+        let accessor = target_data_object.accessor;
+
+        let start_address = accessor.get_start_address_by_index(data_internal_index);
+        let address = if for_writing {
+            if matches!(
+                target_data_object.data_section_type,
+                DataSectionType::ReadOnly
+            ) {
+                return None;
+            }
+            accessor.get_mut_ptr(start_address, offset_bytes) as usize
+        } else {
+            accessor.get_ptr(start_address, offset_bytes) as usize
+        };
+
+        Some(builder.ins().iconst(self.pointer_type, address as i64))
+    }
+
+    fn translate_trap(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        trap_kind: TrapKind,
+        instruction_address: usize,
+    ) {
+        let status_kind = match trap_kind {
+            TrapKind::IntegerDivideByZero => STATUS_INTEGER_DIVIDE_BY_ZERO,
+            TrapKind::IntegerOverflow => STATUS_INTEGER_OVERFLOW,
+            _ => unreachable!("the trap is not raised by the native code"),
+        };
+
+        let status = builder.ins().iconst(
+            types::I64,
+            (((instruction_address as u64) << 8) | status_kind) as i64,
+        );
+        builder.ins().return_(&[status]);
+    }
+
+    // Checks the interrupt flags at the back edge of the loop,
+    // and deoptimizes the function if the thread is interrupted.
+    fn translate_back_edge(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        frames: &[ControlFrame],
+        target_frame_index: usize,
+    ) {
+        let mem_flags = MemFlags::trusted();
+        let thread_interrupted =
+            builder
+                .ins()
+                .atomic_load(types::I8, mem_flags, self.thread_interrupt_flag_ptr);
+        let process_interrupted =
+            builder
+                .ins()
+                .atomic_load(types::I8, mem_flags, self.process_interrupt_flag_ptr);
+        let interrupted = builder.ins().bor(thread_interrupted, process_interrupted);
+
+        let deoptimization_block = builder.create_block();
+        builder.set_cold_block(deoptimization_block);
+        let body_block = frames[target_frame_index].body_block;
+        builder
+            .ins()
+            .brif(interrupted, deoptimization_block, &[], body_block, &[]);
+
+        builder.switch_to_block(deoptimization_block);
+        self.deoptimize(builder, frames, target_frame_index);
+    }
+}
+
+impl JitEnvironment<'_, '_> {
+    // Writes the block frames from the function frame to the target frame into
+    // the deoptimization buffer, and returns from the native function.
+    fn deoptimize(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        frames: &[ControlFrame],
+        target_frame_index: usize,
+    ) {
+        let mem_flags = MemFlags::new();
+        let mut offset = 0;
+        let mut deoptimization_frames = vec![];
+
+        for frame_index in 1..=target_frame_index {
+            for value in &frames[frame_index - 1].operands {
+                builder.ins().store(
+                    mem_flags,
+                    *value,
+                    self.deoptimization_buffer_ptr,
+                    offset as i32,
+                );
+                offset += OPERAND_SIZE_IN_BYTES;
+            }
+
+            let frame = &frames[frame_index];
+            let allocated_bytes = frame.local_variables_with_arguments_allocated_bytes;
+            deoptimization_frames.push(DeoptimizationFrame {
+                parent_operands_count: frames[frame_index - 1].operands.len(),
+                params_count: frame.params_count as u16,
+                results_count: frame.results_count as u16,
+                local_variable_list_index: frame.local_variable_list_index as u32,
+                local_variables_with_arguments_allocated_bytes: allocated_bytes as u32,
+            });
+
+            if let Some(stack_slot) = frame.stack_slot {
+                for slot_offset in (0..allocated_bytes).step_by(OPERAND_SIZE_IN_BYTES) {
+                    let value =
+                        builder
+                            .ins()
+                            .stack_load(types::I64, stack_slot, slot_offset as i32);
+                    builder.ins().store(
+                        mem_flags,
+                        value,
+                        self.deoptimization_buffer_ptr,
+                        offset as i32,
+                    );
+                    offset += OPERAND_SIZE_IN_BYTES;
+                }
+            }
+        }
+
+        let deoptimization_site_index = self.deoptimization_sites.len();
+        self.deoptimization_sites.push(DeoptimizationSite {
+            resume_instruction_address: frames[target_frame_index].body_address,
+            frames: deoptimization_frames,
+        });
+        self.deoptimization_buffer_length = self.deoptimization_buffer_length.max(offset);
+
+        let status = builder.ins().iconst(
+            types::I64,
+            (((deoptimization_site_index as u64) << 8) | STATUS_DEOPTIMIZATION) as i64,
+        );
+        builder.ins().return_(&[status]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_context::{
+        module_common_instance::FunctionTier, process_property::ProcessProperty,
+        program_source::ProgramSource, thread_context::TrapKind,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadWriteDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_blocks,
+            helper_build_module_binary_with_functions_and_data_and_external_functions,
+            HelperBlockEntry, HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource,
+        instruction_handler::HandleResult,
+        process::{prepare_function, process_function, resume_function},
+        ProcessorError, ProcessorErrorType,
+    };
+
+    use super::{call_compiled_function, get_or_compile_function};
+
+    // Builds a module with two functions, the first one calls the second one
+    // with the argument, and the second one is the function to be compiled.
+    fn build_module_binary_with_callee(
+        callee_params: Vec<OperandDataType>,
+        callee_results: Vec<OperandDataType>,
+        callee_code: Vec<u8>,
+        blocks: &[HelperBlockEntry],
+    ) -> Vec<u8> {
+        let mut code_main = BytecodeWriterHelper::new();
+        for (index, data_type) in callee_params.iter().enumerate() {
+            let opcode = match data_type {
+                OperandDataType::I64 => Opcode::local_load_i64,
+                OperandDataType::I32 => Opcode::local_load_i32_u,
+                OperandDataType::F64 => Opcode::local_load_f64,
+                OperandDataType::F32 => Opcode::local_load_f32,
+            };
+            code_main = code_main.append_opcode_i16_i32(opcode, 0, index as u32);
+        }
+        let code_main = code_main
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: callee_params.clone(),
+                    results: callee_results.clone(),
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_main,
+                },
+                HelperFunctionEntry {
+                    params: callee_params,
+                    results: callee_results,
+                    local_variable_item_entries_without_args: vec![],
+                    code: callee_code,
+                },
+            ],
+            blocks,
+        )
+    }
+
+    // fn sum (n/0:i32) -> (i32)
+    //     imm_i32(0)
+    //     local_load32(0, 0)
+    //     block (sum/0:i32, n/1:i32) -> (i32)  ;; type 2, let (sum,n) = (0,n)
+    //         local_load32(0, 1)
+    //         eqz_i32
+    //         block_alt () -> (i32)            ;; type 3, if n == 0 then
+    //             local_load32(1, 0)           ;; sum
+    //         break_alt()                      ;; else
+    //             local_load32(1, 0)
+    //             local_load32(1, 1)
+    //             add_i32                      ;; sum + n
+    //             local_load32(1, 1)
+    //             sub_imm_i32(1)               ;; n - 1
+    //             recur(1)
+    //         end                              ;; end if
+    //     end
+    // end
+    fn build_module_binary_with_sum_function() -> Vec<u8> {
+        let code_sum = BytecodeWriterHelper::new()
+            // let (sum,n) = (0,n)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i32_i32(Opcode::block, 2, 2)
+            // if n == 0
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::eqz_i32)
+            .append_opcode_i32_i32_i32(Opcode::block_alt, 3, 3, 0x20)
+            // then sum
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            // else
+            .append_opcode_i32(Opcode::break_alt, 0x32)
+            // sum + n
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode(Opcode::add_i32)
+            // n - 1
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode_i16(Opcode::sub_imm_i32, 1)
+            // recur
+            .append_opcode_i16_i32(Opcode::recur, 1, 0x4c)
+            // end if
+            .append_opcode(Opcode::end)
+            .append_opcode(Opcode::end)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        build_module_binary_with_callee(
+            vec![OperandDataType::I32],
+            vec![OperandDataType::I32],
+            code_sum,
+            &[
+                HelperBlockEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                },
+                HelperBlockEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_jit_compile_hot_function() {
+        let binary0 = build_module_binary_with_sum_function();
+
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            ProcessProperty {
+                jit_threshold: Some(2),
+                ..ProcessProperty::default()
+            },
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // the first call is executed by the interpreter
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(10)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(55)]);
+        assert!(matches!(
+            thread_context0.module_common_instances[0].function_tiers[1],
+            FunctionTier::Interpreted(1)
+        ));
+
+        // the second call reaches the threshold
+        let result1 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(100)]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(5050)]);
+        assert!(matches!(
+            thread_context0.module_common_instances[0].function_tiers[1],
+            FunctionTier::Compiled(_)
+        ));
+
+        let result2 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(1000)]);
+        assert_eq!(result2.unwrap(), vec![ForeignValue::U32(500500)]);
+    }
+
+    #[test]
+    fn test_jit_disabled_when_fuel_limited() {
+        let binary0 = build_module_binary_with_sum_function();
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.jit_threshold = Some(1);
+        thread_context0.set_fuel(Some(1_000_000));
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(10)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(55)]);
+        assert!(matches!(
+            thread_context0.module_common_instances[0].function_tiers[1],
+            FunctionTier::Interpreted(0)
+        ));
+    }
+
+    #[test]
+    fn test_jit_trap() {
+        // fn div (left/0:i32, right/1:i32) -> (i32)
+        //     local_load32(0, 0)
+        //     local_load32(0, 1)
+        //     div_i32_s
+        // end
+        let code_div = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::div_i32_s)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = build_module_binary_with_callee(
+            vec![OperandDataType::I32, OperandDataType::I32],
+            vec![OperandDataType::I32],
+            code_div,
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.jit_threshold = Some(1);
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(-12i32 as u32), ForeignValue::U32(5)],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(-2i32 as u32)]);
+        assert!(matches!(
+            thread_context0.module_common_instances[0].function_tiers[1],
+            FunctionTier::Compiled(_)
+        ));
+
+        let expected_instruction_address =
+            thread_context0.get_function_info(0, 1).code_offset + 0x10;
+
+        for (left, right, expect_kind) in [
+            (11, 0, TrapKind::IntegerDivideByZero),
+            (i32::MIN as u32, -1i32 as u32, TrapKind::IntegerOverflow),
+        ] {
+            let result = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[ForeignValue::U32(left), ForeignValue::U32(right)],
+            );
+
+            assert!(matches!(
+                result,
+                Err(ProcessorError {
+                    error_type: ProcessorErrorType::Trap {
+                        kind,
+                        module_index: 0,
+                        function_internal_index: 1,
+                        instruction_address,
+                    }, .. }) if kind == expect_kind && instruction_address == expected_instruction_address
+            ));
+        }
+    }
+
+    #[test]
+    fn test_jit_unsupported_function() {
+        // fn add (left/0:f32, right/1:f32) -> (f32)
+        //     local_load_f32(0, 0)
+        //     local_load_f32(0, 1)
+        //     add_f32
+        // end
+        let code_add = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_f32, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_f32, 0, 1)
+            .append_opcode(Opcode::add_f32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = build_module_binary_with_callee(
+            vec![OperandDataType::F32, OperandDataType::F32],
+            vec![OperandDataType::F32],
+            code_add,
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.jit_threshold = Some(1);
+
+        for _ in 0..2 {
+            let result0 = process_function(
+                &mut thread_context0,
+                0,
+                0,
+                &[ForeignValue::F32(1.5), ForeignValue::F32(2.25)],
+            );
+            assert_eq!(result0.unwrap(), vec![ForeignValue::F32(3.75)]);
+            assert!(matches!(
+                thread_context0.module_common_instances[0].function_tiers[1],
+                FunctionTier::Unsupported
+            ));
+        }
+    }
+
+    #[test]
+    fn test_jit_data_load_and_store() {
+        // fn inc () -> (i64)
+        //     data_load_i64(0, 0)
+        //     add_imm_i64(5)
+        //     data_store_i64(0, 0)
+        //     data_load_i64(0, 0)
+        // end
+        let code_main = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code_inc = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i64, 0, 0)
+            .append_opcode_i16(Opcode::add_imm_i64, 5)
+            .append_opcode_i16_i32(Opcode::data_store_i64, 0, 0)
+            .append_opcode_i16_i32(Opcode::data_load_i64, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I64],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_main,
+                },
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I64],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_inc,
+                },
+            ],
+            &[],
+            &[ReadWriteDataEntry::from_i64(10)],
+            &[],
+            &[],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.jit_threshold = Some(1);
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U64(15)]);
+        assert!(matches!(
+            thread_context0.module_common_instances[0].function_tiers[1],
+            FunctionTier::Compiled(_)
+        ));
+
+        // the data is updated by the native code
+        thread_context0.jit_threshold = None;
+        let result1 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U64(20)]);
+    }
+
+    #[test]
+    fn test_jit_deoptimization() {
+        let binary0 = build_module_binary_with_sum_function();
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.jit_threshold = Some(1);
+
+        // call the function `sum` as the entry function
+        prepare_function(&mut thread_context0, 0, 1, &[ForeignValue::U32(10)]).unwrap();
+        let function_pc = thread_context0.pc;
+        let compiled_function = get_or_compile_function(&mut thread_context0, 0, 1).unwrap();
+
+        // the native code stops at the first `recur`
+        let interrupt_handle = thread_context0.get_interrupt_handle();
+        interrupt_handle.interrupt();
+
+        let handle_result =
+            call_compiled_function(&mut thread_context0, &compiled_function, function_pc);
+        let resume_pc = match handle_result {
+            Some(HandleResult::Jump(pc)) => pc,
+            _ => panic!("the native code should be deoptimized"),
+        };
+        assert_eq!(
+            resume_pc.instruction_address,
+            thread_context0.get_function_info(0, 1).code_offset + 0x1c
+        );
+
+        // the rest of the loop is executed by the interpreter
+        interrupt_handle.clear();
+        thread_context0.jit_threshold = None;
+        thread_context0.pc = resume_pc;
+
+        let result0 = resume_function(&mut thread_context0, 0, 1);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(55)]);
+    }
+}
@@ -6,16 +6,35 @@
 
 use std::fmt::Display;
 
+use anc_context::thread_context::TrapKind;
+use backtrace::BacktraceFrame;
+
+mod bridge_handler;
+mod callback_delegate_handler;
 mod envcall_handler;
 mod extcall_handler;
+mod jit_compiler;
 mod multithread_handler;
+mod native_translator;
 mod syscall_handler;
 
+pub mod aot_compiler;
+pub mod aot_runtime;
+pub mod backtrace;
+pub mod capi;
+pub mod debug_adapter;
+pub mod debugger;
 pub mod envcall_num;
+pub mod file_program_source;
 pub mod in_memory_program_source;
 pub mod instruction_handler;
+pub mod mmap_program_source;
+pub mod predecode;
 pub mod process;
 pub mod program;
+pub mod program_embed;
+pub mod verifier;
+pub mod vm;
 
 pub const TERMINATE_CODE_PANIC: i32 = 0x1000_0000;
 pub const TERMINATE_CODE_UNREACHABLE: i32 = 0x1000_0001;
@@ -24,9 +43,18 @@ pub const TERMINATE_CODE_UNSUPPORTED_FLOATING_POINT_VARIANTS: i32 = 0x1000_0003;
 pub const TERMINATE_CODE_FAILED_TO_LOAD_EXTERNAL_FUNCTION: i32 = 0x1000_0010;
 pub const TERMINATE_CODE_FAILED_TO_CREATE_DELEGATE_FUNCTION: i32 = 0x1000_0011;
 
+// The instruction `syscall` or `extcall` is not permitted by the capability of the process,
+// see `Capability::permits_syscall` and `Capability::permits_extcall`.
+pub const TERMINATE_CODE_CAPABILITY_DENIED: i32 = 0x1000_0020;
+
 #[derive(Debug)]
 pub struct ProcessorError {
     pub error_type: ProcessorErrorType,
+
+    // The VM call stack at the moment the program terminated or trapped,
+    // the first frame is the innermost one.
+    // It is empty for the errors which are not raised by the instructions.
+    pub backtrace: Vec<BacktraceFrame>,
 }
 
 #[repr(u16)]
@@ -36,16 +64,41 @@ pub enum ProcessorErrorType {
     ResultsAmountMissmatch,    // The number of return values does not match the specified function.
     DataTypeMissmatch,         // The data type does not match the expected type.
     ItemNotFound, // The specified item (such as module, function, local variable, or data) was not found.
+    DataReadOnly, // The data is located in the read-only data section and cannot be written.
+    DataOutOfBounds, // The range of the access exceeds the length of the data.
     StackOverflow, // Stack overflow occurred.
     UnsupportedFloatingPointVariants, // Unsupported floating point values: NaN, +Inf, or -Inf.
     ExternalFunctionMoreThanOneResult, // The external function has more than one return value, which is not supported.
     EntryPointNotFound(String),        // The specified entry point was not found.
     Terminate(i32),                    // Program terminated with the given code.
+    OutOfFuel, // The instruction budget of the thread is exhausted, the execution can be resumed after topping up.
+    Interrupted, // The execution was interrupted by the host through an interrupt handle.
+    DebuggingNotStarted, // The debugger is not started or the debugging has been finished.
+    UnexpectedNativeStatus(u64), // The AOT compiled code returned a failure status without raising a trap.
+
+    // A recoverable fault (e.g. out of bounds, division by zero, invalid opcode)
+    // raised by the instruction at the given location.
+    Trap {
+        kind: TrapKind,
+        module_index: usize,
+        function_internal_index: usize,
+        instruction_address: usize,
+    },
 }
 
 impl ProcessorError {
     pub fn new(type_: ProcessorErrorType) -> Self {
-        Self { error_type: type_ }
+        Self {
+            error_type: type_,
+            backtrace: vec![],
+        }
+    }
+
+    pub fn with_backtrace(type_: ProcessorErrorType, backtrace: Vec<BacktraceFrame>) -> Self {
+        Self {
+            error_type: type_,
+            backtrace,
+        }
     }
 }
 
@@ -60,6 +113,8 @@ impl Display for ProcessorError {
             }
             ProcessorErrorType::DataTypeMissmatch => f.write_str("Data type missmatch."),
             ProcessorErrorType::ItemNotFound => f.write_str("Item not found."),
+            ProcessorErrorType::DataReadOnly => f.write_str("The data is read-only."),
+            ProcessorErrorType::DataOutOfBounds => f.write_str("Data access out of bounds."),
             ProcessorErrorType::StackOverflow => f.write_str("Stack overflow."),
             ProcessorErrorType::UnsupportedFloatingPointVariants => {
                 f.write_str("Unsupported floating point variants: NaN, +Inf, and -Inf.")
@@ -73,7 +128,42 @@ impl Display for ProcessorError {
             ProcessorErrorType::Terminate(terminate_code) => {
                 write!(f, "Program terminated, code: {}.", terminate_code)
             }
+            ProcessorErrorType::OutOfFuel => f.write_str("Out of fuel."),
+            ProcessorErrorType::Interrupted => f.write_str("Interrupted by the host."),
+            ProcessorErrorType::DebuggingNotStarted => {
+                f.write_str("The debugging is not started or it has been finished.")
+            }
+            ProcessorErrorType::UnexpectedNativeStatus(status) => {
+                write!(f, "Unexpected status of the native code: {}.", status)
+            }
+            ProcessorErrorType::Trap {
+                kind,
+                module_index,
+                function_internal_index,
+                instruction_address,
+            } => write!(
+                f,
+                "Trap: {}, module index: {}, function internal index: {}, instruction address: 0x{:04x}.",
+                kind, module_index, function_internal_index, instruction_address
+            ),
+        }?;
+
+        // Print the backtrace like a native stack trace, e.g.
+        //
+        // ```text
+        // stack backtrace:
+        //    0: hello::inner (module index: 0, function internal index: 2, instruction address: 0x0040)
+        //    1: hello::outer (module index: 0, function internal index: 1, instruction address: 0x0028)
+        //    2: hello::<function 0> (module index: 0, function internal index: 0, instruction address: 0x0010)
+        // ```
+        if !self.backtrace.is_empty() {
+            f.write_str("\nstack backtrace:")?;
+            for (idx, frame) in self.backtrace.iter().enumerate() {
+                write!(f, "\n{:>4}: {}", idx, frame)?;
+            }
         }
+
+        Ok(())
     }
 }
 
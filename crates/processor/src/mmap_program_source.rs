@@ -0,0 +1,306 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Memory-mapped Program Source
+// ----------------------------
+//
+// Like `FileProgramSource`, but the image files are memory-mapped read-only
+// instead of being read into the heap:
+//
+// - The code (`FunctionSection::codes_data`) and the read-only data (`ReadOnlyDatas`)
+//   are used in place, the pages are loaded by the OS on demand and shared by
+//   all threads (and all processes which run the same program).
+// - The read-write data of each thread is a private copy-on-write mapping of
+//   the data section in the image file, so only the pages which are written
+//   are copied.
+//
+// This reduces the startup time and the resident memory of large programs,
+// see the benchmark `benches/mmap_loading.rs`.
+//
+// Note that the image files must not be modified while the program is running,
+// otherwise the behavior is undefined.
+
+use std::{fs::File, ops::Range, path::Path, sync::Arc};
+
+use anc_context::{
+    datas::{DataBuffer, ReadWriteDataSource},
+    process_context::ProcessContext,
+    process_property::ProcessProperty,
+    program_source::ProgramSource,
+};
+use anc_image::{utils::helper_load_modules_from_binaries, ImageError};
+use memmap2::{Mmap, MmapOptions};
+
+use crate::file_program_source::{
+    get_module_image_ranges, locate_program, map_io_error, FileProgramSourceError,
+};
+
+struct MappedImageFile {
+    file: Arc<File>,
+    mmap: Mmap,
+}
+
+/// An implement of `ProgramSource` which memory-maps the module images from the file system.
+pub struct MmapProgramSource {
+    process_property: ProcessProperty,
+    image_files: Vec<MappedImageFile>,
+
+    // The index of the image file and the range in the file of each module image.
+    module_images: Vec<(usize, Range<usize>)>,
+}
+
+impl MmapProgramSource {
+    /// Maps the module images of the specified program with the default process property.
+    pub fn open<P: AsRef<Path>>(program_path: P) -> Result<Self, FileProgramSourceError> {
+        Self::with_property(program_path, ProcessProperty::default())
+    }
+
+    /// Maps the module images of the specified program, the `program_path` and
+    /// `program_source_type` of the process property are updated accordingly.
+    pub fn with_property<P: AsRef<Path>>(
+        program_path: P,
+        mut process_property: ProcessProperty,
+    ) -> Result<Self, FileProgramSourceError> {
+        let program_location = locate_program(program_path.as_ref())?;
+
+        let mut image_files = vec![];
+        let mut module_images = vec![];
+
+        for image_file_path in &program_location.image_file_paths {
+            let file = File::open(image_file_path)
+                .map_err(|error| map_io_error(image_file_path, error))?;
+
+            // Safety: the image files are not expected to be modified while they are mapped.
+            let mmap = unsafe { Mmap::map(&file) }
+                .map_err(|error| map_io_error(image_file_path, error))?;
+
+            let file_index = image_files.len();
+            for range in get_module_image_ranges(
+                program_location.program_source_type,
+                image_file_path,
+                &mmap,
+            )? {
+                module_images.push((file_index, range));
+            }
+
+            image_files.push(MappedImageFile {
+                file: Arc::new(file),
+                mmap,
+            });
+        }
+
+        process_property.program_path = program_location.program_path;
+        process_property.program_source_type = program_location.program_source_type;
+
+        Ok(Self {
+            process_property,
+            image_files,
+            module_images,
+        })
+    }
+
+    pub fn get_process_property(&self) -> &ProcessProperty {
+        &self.process_property
+    }
+}
+
+impl ProgramSource for MmapProgramSource {
+    fn create_process_context(&self) -> Result<ProcessContext, ImageError> {
+        let binaries_ref = self
+            .module_images
+            .iter()
+            .map(|(file_index, range)| &self.image_files[*file_index].mmap[range.clone()])
+            .collect::<Vec<_>>();
+
+        let module_images = helper_load_modules_from_binaries(&binaries_ref)?;
+
+        // Locate the read-write data of each module in the image files.
+        let mappings = module_images
+            .iter()
+            .zip(&self.module_images)
+            .map(|(module_image, (file_index, _))| {
+                let image_file = &self.image_files[*file_index];
+                module_image
+                    .get_optional_read_write_data_section()
+                    .filter(|section| !section.datas_data.is_empty())
+                    .map(|section| ReadWriteDataMapping {
+                        file: image_file.file.clone(),
+                        offset: section.datas_data.as_ptr() as usize
+                            - image_file.mmap.as_ptr() as usize,
+                        length: section.datas_data.len(),
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let mut process_context = ProcessContext::new(self.process_property.clone(), module_images);
+        process_context.read_write_data_source = Some(Box::new(MappedReadWriteDatas { mappings }));
+
+        Ok(process_context)
+    }
+}
+
+struct ReadWriteDataMapping {
+    file: Arc<File>,
+    offset: usize,
+    length: usize,
+}
+
+struct MappedReadWriteDatas {
+    // Indexed by the module index, `None` if the module has no read-write data.
+    mappings: Vec<Option<ReadWriteDataMapping>>,
+}
+
+impl ReadWriteDataSource for MappedReadWriteDatas {
+    fn create_read_write_data(&self, module_index: usize) -> Option<DataBuffer> {
+        let mapping = self.mappings.get(module_index)?.as_ref()?;
+
+        // The private mapping never writes back to the file, and falls back
+        // to copying the data from the module image if it fails.
+        let mmap = unsafe {
+            MmapOptions::new()
+                .offset(mapping.offset as u64)
+                .len(mapping.length)
+                .map_copy(&*mapping.file)
+        }
+        .ok()?;
+
+        Some(DataBuffer::new(mmap))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anc_context::{process_property::ProgramSourceType, program_source::ProgramSource};
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::{ReadOnlyDataEntry, ReadWriteDataEntry},
+        utils::helper_build_module_binary_with_single_function_and_data,
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        file_program_source::{build_package_image, FileProgramSourceError},
+        process::process_function,
+    };
+
+    use super::MmapProgramSource;
+
+    fn build_binary() -> Vec<u8> {
+        // fn (n:i32) -> (i32)
+        //     data_load_i32_u(0, 1)    ;; counter = counter + n
+        //     local_load_i32_u(0, 0)
+        //     add_i32
+        //     data_store_i32(0, 1)
+        //     data_load_i32_u(0, 1)
+        //     data_load_i32_u(0, 0)    ;; + read-only data
+        //     add_i32
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode_i16_i32(Opcode::data_store_i32, 0, 1)
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 0)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function_and_data(
+            &[OperandDataType::I32], // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+            &[ReadOnlyDataEntry::from_i32(1000)],
+            &[ReadWriteDataEntry::from_i32(0)],
+            &[],
+        )
+    }
+
+    fn create_temporary_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "anc_mmap_program_source_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_mmap_program_source() {
+        let scripts_path = create_temporary_dir("script");
+        let image_file_path = scripts_path.join("counter.ancm");
+        let binary0 = build_binary();
+        std::fs::write(&image_file_path, &binary0).unwrap();
+
+        let program_source = MmapProgramSource::open(scripts_path.join("counter.anc")).unwrap();
+        assert_eq!(
+            program_source.get_process_property().program_source_type,
+            ProgramSourceType::ScriptFile
+        );
+
+        let process_context0 = program_source.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        let mut thread_context1 = process_context0.create_thread_context();
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(7)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1007)]);
+
+        let result1 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(3)]);
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(1010)]);
+
+        // the read-write data is private to each thread
+        let result2 = process_function(&mut thread_context1, 0, 0, &[ForeignValue::U32(5)]);
+        assert_eq!(result2.unwrap(), vec![ForeignValue::U32(1005)]);
+
+        // snapshots work with the mapped data
+        let snapshot0 = thread_context1.take_snapshot();
+        let result3 = process_function(&mut thread_context1, 0, 0, &[ForeignValue::U32(5)]);
+        assert_eq!(result3.unwrap(), vec![ForeignValue::U32(1010)]);
+        thread_context1.restore_snapshot(&snapshot0);
+        let result4 = process_function(&mut thread_context1, 0, 0, &[ForeignValue::U32(1)]);
+        assert_eq!(result4.unwrap(), vec![ForeignValue::U32(1006)]);
+
+        // the image file is never written
+        drop(thread_context0);
+        drop(thread_context1);
+        assert_eq!(std::fs::read(&image_file_path).unwrap(), binary0);
+
+        std::fs::remove_dir_all(&scripts_path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_program_source_package_image() {
+        let package_folder_path = create_temporary_dir("package");
+        let package_path = package_folder_path.join("counter.ancp");
+        std::fs::write(&package_path, build_package_image(&[build_binary()])).unwrap();
+
+        let program_source = MmapProgramSource::open(&package_path).unwrap();
+        assert_eq!(
+            program_source.get_process_property().program_source_type,
+            ProgramSourceType::PackageImage
+        );
+
+        // the module image is located after the header of the package image
+        let process_context0 = program_source.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        let result0 = process_function(&mut thread_context0, 0, 0, &[ForeignValue::U32(7)]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(1007)]);
+
+        // not found
+        assert!(matches!(
+            MmapProgramSource::open(package_folder_path.join("nonexistent.ancp")),
+            Err(FileProgramSourceError::NotFound(_))
+        ));
+
+        std::fs::remove_dir_all(&package_folder_path).unwrap();
+    }
+}
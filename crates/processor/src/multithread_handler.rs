@@ -14,7 +14,10 @@ use std::{
 use anc_context::process_context::ProcessContext;
 use anc_isa::ForeignValue;
 
-use crate::{process::process_function, GenericError, ProcessorError, ProcessorErrorType};
+use crate::{
+    debug_adapter::{process_function_or_debug, DEBUG_SESSION},
+    GenericError, ProcessorError, ProcessorErrorType,
+};
 
 thread_local! {
     // PROCESS_CONTEXT_ADDRESS should be process-global, but unit tests run in parallel
@@ -116,6 +119,9 @@ pub fn create_thread(
         process_context_address = *data.borrow();
     });
 
+    // The child thread joins the debug session (if any) of the parent thread.
+    let opt_debug_session = DEBUG_SESSION.with(|data| data.borrow().clone());
+
     CHILD_THREAD_NEXT_ID.with(|max_id_cell| {
         let last_thread_id = *max_id_cell.borrow();
         next_thread_id = last_thread_id + 1;
@@ -156,18 +162,23 @@ pub fn create_thread(
                 data.replace(thread_start_data);
             });
 
+            DEBUG_SESSION.with(|data| {
+                data.replace(opt_debug_session);
+            });
+
             // SAFETY: The process context pointer is valid for the lifetime of the process.
             let process_context_ptr = process_context_address as *const u8 as *const ProcessContext;
             let process_context = unsafe { &*process_context_ptr };
 
             let mut thread_context = process_context.create_thread_context();
 
-            let result_foreign_values = process_function(
+            let result_foreign_values = process_function_or_debug(
                 &mut thread_context,
                 thread_start_function.module_index,
                 thread_start_function.function_public_index,
                 // The thread start function must not take any parameters.
                 &[],
+                &format!("thread {}", next_thread_id),
             );
 
             // Returns `Result<Vec<ForeignValue>, Box<ProcessorError>>`.
@@ -0,0 +1,1198 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Native code translator
+// ----------------------
+//
+// Translates the bytecode of a function into Cranelift IR, it is shared by
+// the JIT compiler (see `jit_compiler.rs`) and the AOT compiler (see `aot_compiler.rs`).
+//
+// The operands (all of them are `i64`) are kept in SSA values, and the local variables
+// of blocks are kept in the stack slots of the native function. The local variables
+// (includes the arguments) of the function are accessed through a pointer which is
+// provided by the compiler, and the results are written to the memory pointed by
+// another pointer when the function returns.
+//
+// The instructions which depend on the environment, e.g. the access of data, the
+// calling of functions, the raising of traps and the back edges of loops, are
+// translated by the `TranslatorEnvironment`.
+//
+// The translation fails (i.e. returns `None`) when an instruction is not supported,
+// note that the floating-point instructions are not supported because they have to
+// check the NaN and Inf variants.
+
+use anc_context::thread_context::TrapKind;
+use anc_image::common_sections::{
+    local_variable_section::LocalVariableSection, type_section::TypeSection,
+};
+use anc_isa::{opcode::Opcode, OPERAND_SIZE_IN_BYTES};
+use cranelift_codegen::ir::{
+    condcodes::IntCC, types, Block, BlockArg, InstBuilder, MemFlags, StackSlot, StackSlotData,
+    StackSlotKind, Type, Value,
+};
+use cranelift_frontend::FunctionBuilder;
+
+use crate::predecode::get_opcode;
+
+/// The status returned by the native function when the function finishes,
+/// the other values are defined by the compilers.
+pub const STATUS_FINISHED: u64 = 0;
+
+const DATA_LENGTH_IN_BYTES_64_BIT: usize = 8;
+const DATA_LENGTH_IN_BYTES_32_BIT: usize = 4;
+const DATA_LENGTH_IN_BYTES_16_BIT: usize = 2;
+const DATA_LENGTH_IN_BYTES_8_BIT: usize = 1;
+
+/// The target of the instructions which call other functions.
+pub enum CallTarget {
+    Function(/* function_public_index */ usize),
+    EnvCall(/* envcall_num */ u32),
+    SysCall,
+    ExtCall(/* external_function_index */ usize),
+}
+
+pub trait TranslatorEnvironment {
+    /// Returns the type section and the local variable section of the module
+    /// which the function belongs to.
+    fn get_sections(&self) -> (&TypeSection, &LocalVariableSection);
+
+    /// Emits the instructions which compute the address of the data (the offset is included),
+    /// returns `None` if the access is not supported, e.g. out of bounds.
+    fn translate_data_address(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        data_public_index: usize,
+        offset_bytes: usize,
+        data_length: usize,
+        for_writing: bool,
+    ) -> Option<Value>;
+
+    /// Emits the instructions `call`, `envcall`, `syscall` and `extcall`, the arguments
+    /// should be popped from the operands, and the results should be pushed onto them.
+    ///
+    /// Returns `None` if the call is not supported.
+    fn translate_call(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _call_target: CallTarget,
+        _instruction_address: usize,
+        _operands: &mut Vec<Value>,
+    ) -> Option<()> {
+        None
+    }
+
+    /// Emits the instructions which raise the trap, the current block should be terminated.
+    fn translate_trap(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        trap_kind: TrapKind,
+        instruction_address: usize,
+    );
+
+    /// Emits the back edge of a loop (i.e. the instruction `recur`) which jumps to the body
+    /// of the target frame, the local variables of the target frame have been reset.
+    fn translate_back_edge(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        frames: &[ControlFrame],
+        target_frame_index: usize,
+    );
+}
+
+// Returns the length of the instruction, or `None` if the instruction is not supported.
+fn get_supported_instruction_length(opcode: Opcode) -> Option<usize> {
+    let length = match opcode {
+        // fundamental
+        Opcode::nop => 2,
+        Opcode::imm_i32 => 8,
+        Opcode::imm_i64 => 12,
+
+        // local
+        Opcode::local_load_i64
+        | Opcode::local_load_i32_s
+        | Opcode::local_load_i32_u
+        | Opcode::local_load_i16_s
+        | Opcode::local_load_i16_u
+        | Opcode::local_load_i8_s
+        | Opcode::local_load_i8_u
+        | Opcode::local_store_i64
+        | Opcode::local_store_i32
+        | Opcode::local_store_i16
+        | Opcode::local_store_i8
+        | Opcode::local_store_f64
+        | Opcode::local_store_f32 => 8,
+
+        // data
+        Opcode::data_load_i64
+        | Opcode::data_load_i32_s
+        | Opcode::data_load_i32_u
+        | Opcode::data_load_i16_s
+        | Opcode::data_load_i16_u
+        | Opcode::data_load_i8_s
+        | Opcode::data_load_i8_u
+        | Opcode::data_store_i64
+        | Opcode::data_store_i32
+        | Opcode::data_store_i16
+        | Opcode::data_store_i8
+        | Opcode::data_store_f64
+        | Opcode::data_store_f32 => 8,
+
+        // arithmetic
+        Opcode::add_i32
+        | Opcode::sub_i32
+        | Opcode::mul_i32
+        | Opcode::div_i32_s
+        | Opcode::div_i32_u
+        | Opcode::rem_i32_s
+        | Opcode::rem_i32_u
+        | Opcode::add_i64
+        | Opcode::sub_i64
+        | Opcode::mul_i64
+        | Opcode::div_i64_s
+        | Opcode::div_i64_u
+        | Opcode::rem_i64_s
+        | Opcode::rem_i64_u => 2,
+        Opcode::add_imm_i32 | Opcode::sub_imm_i32 | Opcode::add_imm_i64 | Opcode::sub_imm_i64 => 4,
+
+        // comparison
+        Opcode::eqz_i32
+        | Opcode::nez_i32
+        | Opcode::eq_i32
+        | Opcode::ne_i32
+        | Opcode::lt_i32_s
+        | Opcode::lt_i32_u
+        | Opcode::gt_i32_s
+        | Opcode::gt_i32_u
+        | Opcode::le_i32_s
+        | Opcode::le_i32_u
+        | Opcode::ge_i32_s
+        | Opcode::ge_i32_u
+        | Opcode::eqz_i64
+        | Opcode::nez_i64
+        | Opcode::eq_i64
+        | Opcode::ne_i64
+        | Opcode::lt_i64_s
+        | Opcode::lt_i64_u
+        | Opcode::gt_i64_s
+        | Opcode::gt_i64_u
+        | Opcode::le_i64_s
+        | Opcode::le_i64_u
+        | Opcode::ge_i64_s
+        | Opcode::ge_i64_u => 2,
+
+        // control flow
+        Opcode::end => 2,
+        Opcode::block | Opcode::block_nez => 12,
+        Opcode::block_alt => 16,
+        Opcode::break_ | Opcode::recur | Opcode::break_alt => 8,
+
+        // calling
+        Opcode::call | Opcode::envcall | Opcode::extcall => 8,
+        Opcode::syscall => 2,
+
+        _ => return None,
+    };
+
+    Some(length)
+}
+
+#[inline]
+fn read_param_u16(instruction: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(instruction[offset..(offset + 2)].try_into().unwrap())
+}
+
+#[inline]
+fn read_param_u32(instruction: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(instruction[offset..(offset + 4)].try_into().unwrap())
+}
+
+fn to_block_args(values: &[Value]) -> Vec<BlockArg> {
+    values.iter().map(|value| BlockArg::Value(*value)).collect()
+}
+
+/// The counterpart of the frame on the stack at compile time.
+pub struct ControlFrame {
+    pub params_count: usize,
+    pub results_count: usize,
+    pub local_variable_list_index: usize,
+    pub local_variables_with_arguments_allocated_bytes: usize,
+
+    // The storage of the local variables of the block, it is `None` for the
+    // function frame (whose local variables are accessed through the pointer
+    // provided by the compiler), and the blocks without local variables.
+    pub stack_slot: Option<StackSlot>,
+
+    // The address of the first instruction of the body, i.e. the target of `recur`.
+    pub body_address: usize,
+    pub body_block: Block,
+
+    // The block following the instruction `end`, the results are passed as
+    // block parameters. It is `None` for the function frame.
+    exit_block: Option<Block>,
+
+    // The address following the instruction `end`, it is specified by the
+    // instructions `block_nez` and `break`, and is checked when the `end` is reached.
+    exit_address: Option<usize>,
+
+    // The address and the block of the alternative part of `block_alt`.
+    alternative: Option<(usize, Block)>,
+
+    // The operands (all of them are `i64`) which are pushed in this frame.
+    pub operands: Vec<Value>,
+}
+
+pub struct Translator<'a, E>
+where
+    E: TranslatorEnvironment,
+{
+    builder: FunctionBuilder<'a>,
+    environment: E,
+    pointer_type: Type,
+
+    // The pointer to the local variables (includes the arguments) of the function.
+    local_variables_ptr: Value,
+
+    // The pointer to the memory where the results are written when the function returns.
+    results_ptr: Value,
+
+    frames: Vec<ControlFrame>,
+
+    // The instructions following `break` and `recur` are unreachable until the
+    // `end` of the current frame or the alternative part of `block_alt` is reached.
+    reachable: bool,
+
+    // The number of blocks that are started in the unreachable code.
+    unreachable_blocks_depth: usize,
+}
+
+impl<'a, E> Translator<'a, E>
+where
+    E: TranslatorEnvironment,
+{
+    /// Creates a translator, the builder should have been switched to the entry block
+    /// of the native function, and the instructions following the entry block are
+    /// appended by the `translate()`.
+    pub fn new(
+        builder: FunctionBuilder<'a>,
+        environment: E,
+        pointer_type: Type,
+        local_variables_ptr: Value,
+        results_ptr: Value,
+    ) -> Self {
+        Self {
+            builder,
+            environment,
+            pointer_type,
+            local_variables_ptr,
+            results_ptr,
+            frames: vec![],
+            reachable: true,
+            unreachable_blocks_depth: 0,
+        }
+    }
+
+    /// Finalizes the native function, and returns the environment.
+    pub fn finish(mut self) -> E {
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+        self.environment
+    }
+
+    /// Translates the bytecode of the function, returns `None` if the function
+    /// contains unsupported instructions.
+    pub fn translate(
+        &mut self,
+        codes: &[u8],
+        code_offset: usize,
+        params_count: usize,
+        results_count: usize,
+        local_variable_list_index: usize,
+        local_variables_with_arguments_allocated_bytes: usize,
+    ) -> Option<()> {
+        if local_variables_with_arguments_allocated_bytes % OPERAND_SIZE_IN_BYTES != 0 {
+            return None;
+        }
+
+        // The entry block can not be the target of `recur`.
+        let body_block = self.builder.create_block();
+        self.builder.ins().jump(body_block, &[]);
+        self.builder.switch_to_block(body_block);
+
+        self.frames.push(ControlFrame {
+            params_count,
+            results_count,
+            local_variable_list_index,
+            local_variables_with_arguments_allocated_bytes,
+            stack_slot: None,
+            body_address: code_offset,
+            body_block,
+            exit_block: None,
+            exit_address: None,
+            alternative: None,
+            operands: vec![],
+        });
+
+        let mut offset = 0;
+
+        // The translation is finished when the `end` of the function frame is reached.
+        while !self.frames.is_empty() {
+            if offset + 2 > codes.len() {
+                return None;
+            }
+
+            let address = code_offset + offset;
+
+            // Switch to the alternative part of `block_alt`.
+            if self.unreachable_blocks_depth == 0 {
+                if let Some((alternative_address, alternative_block)) =
+                    self.frames.last().unwrap().alternative
+                {
+                    if address == alternative_address {
+                        if self.reachable {
+                            // The consequent part falls through to the alternative part.
+                            if !self.frames.last().unwrap().operands.is_empty() {
+                                return None;
+                            }
+                            self.builder.ins().jump(alternative_block, &[]);
+                        }
+
+                        // The alternative part starts with an empty operand stack.
+                        let frame = self.frames.last_mut().unwrap();
+                        frame.alternative = None;
+                        frame.operands.clear();
+
+                        self.builder.switch_to_block(alternative_block);
+                        self.reachable = true;
+                    }
+                }
+            }
+
+            let opcode_integer = read_param_u16(codes, offset);
+            let opcode = get_opcode(opcode_integer)?;
+            let length = get_supported_instruction_length(opcode)?;
+            if offset + length > codes.len() {
+                return None;
+            }
+
+            let instruction = &codes[offset..(offset + length)];
+            if self.reachable {
+                self.translate_instruction(opcode, instruction, address)?;
+            } else {
+                self.skip_instruction(opcode, address)?;
+            }
+
+            offset += length;
+        }
+
+        Some(())
+    }
+
+    fn skip_instruction(&mut self, opcode: Opcode, address: usize) -> Option<()> {
+        match opcode {
+            Opcode::block | Opcode::block_alt | Opcode::block_nez => {
+                self.unreachable_blocks_depth += 1;
+            }
+            Opcode::end => {
+                if self.unreachable_blocks_depth > 0 {
+                    self.unreachable_blocks_depth -= 1;
+                } else {
+                    self.close_frame(address)?;
+                }
+            }
+            _ => {
+                // skip
+            }
+        }
+
+        Some(())
+    }
+
+    fn translate_instruction(
+        &mut self,
+        opcode: Opcode,
+        instruction: &[u8],
+        address: usize,
+    ) -> Option<()> {
+        match opcode {
+            // fundamental
+            Opcode::nop => {}
+            Opcode::imm_i32 => {
+                let value = read_param_u32(instruction, 4);
+                let value = self.builder.ins().iconst(types::I64, value as i64);
+                self.push(value);
+            }
+            Opcode::imm_i64 => {
+                let low = read_param_u32(instruction, 4);
+                let high = read_param_u32(instruction, 8);
+                let value = ((high as u64) << 32) | (low as u64);
+                let value = self.builder.ins().iconst(types::I64, value as i64);
+                self.push(value);
+            }
+
+            // local
+            Opcode::local_load_i64
+            | Opcode::local_load_i32_s
+            | Opcode::local_load_i32_u
+            | Opcode::local_load_i16_s
+            | Opcode::local_load_i16_u
+            | Opcode::local_load_i8_s
+            | Opcode::local_load_i8_u => {
+                let layers = read_param_u16(instruction, 2);
+                let local_variable_index = read_param_u32(instruction, 4);
+                let data_length = get_data_length_of_load_and_store(opcode);
+                let (address_value, offset) =
+                    self.resolve_local_variable(layers, local_variable_index, data_length)?;
+                let value = self.load(opcode, address_value, offset);
+                self.push(value);
+            }
+            Opcode::local_store_i64
+            | Opcode::local_store_i32
+            | Opcode::local_store_i16
+            | Opcode::local_store_i8
+            | Opcode::local_store_f64
+            | Opcode::local_store_f32 => {
+                let layers = read_param_u16(instruction, 2);
+                let local_variable_index = read_param_u32(instruction, 4);
+                let data_length = get_data_length_of_load_and_store(opcode);
+                let value = self.pop()?;
+                let (address_value, offset) =
+                    self.resolve_local_variable(layers, local_variable_index, data_length)?;
+                self.store(data_length, value, address_value, offset);
+            }
+
+            // data
+            Opcode::data_load_i64
+            | Opcode::data_load_i32_s
+            | Opcode::data_load_i32_u
+            | Opcode::data_load_i16_s
+            | Opcode::data_load_i16_u
+            | Opcode::data_load_i8_s
+            | Opcode::data_load_i8_u => {
+                let offset_bytes = read_param_u16(instruction, 2);
+                let data_public_index = read_param_u32(instruction, 4);
+                let data_length = get_data_length_of_load_and_store(opcode);
+                let address_value = self.environment.translate_data_address(
+                    &mut self.builder,
+                    data_public_index as usize,
+                    offset_bytes as usize,
+                    data_length,
+                    false,
+                )?;
+                let value = self.load(opcode, address_value, 0);
+                self.push(value);
+            }
+            Opcode::data_store_i64
+            | Opcode::data_store_i32
+            | Opcode::data_store_i16
+            | Opcode::data_store_i8
+            | Opcode::data_store_f64
+            | Opcode::data_store_f32 => {
+                let offset_bytes = read_param_u16(instruction, 2);
+                let data_public_index = read_param_u32(instruction, 4);
+                let data_length = get_data_length_of_load_and_store(opcode);
+                let value = self.pop()?;
+                let address_value = self.environment.translate_data_address(
+                    &mut self.builder,
+                    data_public_index as usize,
+                    offset_bytes as usize,
+                    data_length,
+                    true,
+                )?;
+                self.store(data_length, value, address_value, 0);
+            }
+
+            // arithmetic
+            Opcode::add_i32 => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                let value = self.builder.ins().iadd(left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::sub_i32 => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                let value = self.builder.ins().isub(left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::add_imm_i32 | Opcode::sub_imm_i32 => {
+                let imm = read_param_u16(instruction, 2);
+                let value = self.pop_with_type(types::I32)?;
+                let imm = self.builder.ins().iconst(types::I32, imm as i64);
+                let value = if opcode == Opcode::add_imm_i32 {
+                    self.builder.ins().iadd(value, imm)
+                } else {
+                    self.builder.ins().isub(value, imm)
+                };
+                self.push_extended(value, false);
+            }
+            Opcode::mul_i32 => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                let value = self.builder.ins().imul(left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::div_i32_s => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                self.check_divisor(right, address);
+                self.check_signed_division_overflow(left, right, i32::MIN as i64, address);
+                let value = self.builder.ins().sdiv(left, right);
+                self.push_extended(value, true);
+            }
+            Opcode::div_i32_u => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().udiv(left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::rem_i32_s => {
+                // `i32::MIN % -1` is 0 in Cranelift, which is the same as the interpreter.
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().srem(left, right);
+                self.push_extended(value, true);
+            }
+            Opcode::rem_i32_u => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().urem(left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::add_i64 => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                let value = self.builder.ins().iadd(left, right);
+                self.push(value);
+            }
+            Opcode::sub_i64 => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                let value = self.builder.ins().isub(left, right);
+                self.push(value);
+            }
+            Opcode::add_imm_i64 | Opcode::sub_imm_i64 => {
+                let imm = read_param_u16(instruction, 2);
+                let value = self.pop()?;
+                let imm = self.builder.ins().iconst(types::I64, imm as i64);
+                let value = if opcode == Opcode::add_imm_i64 {
+                    self.builder.ins().iadd(value, imm)
+                } else {
+                    self.builder.ins().isub(value, imm)
+                };
+                self.push(value);
+            }
+            Opcode::mul_i64 => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                let value = self.builder.ins().imul(left, right);
+                self.push(value);
+            }
+            Opcode::div_i64_s => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                self.check_divisor(right, address);
+                self.check_signed_division_overflow(left, right, i64::MIN, address);
+                let value = self.builder.ins().sdiv(left, right);
+                self.push(value);
+            }
+            Opcode::div_i64_u => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().udiv(left, right);
+                self.push(value);
+            }
+            Opcode::rem_i64_s => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().srem(left, right);
+                self.push(value);
+            }
+            Opcode::rem_i64_u => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                self.check_divisor(right, address);
+                let value = self.builder.ins().urem(left, right);
+                self.push(value);
+            }
+
+            // comparison
+            Opcode::eqz_i32 | Opcode::nez_i32 | Opcode::eqz_i64 | Opcode::nez_i64 => {
+                let value = if opcode == Opcode::eqz_i32 || opcode == Opcode::nez_i32 {
+                    self.pop_with_type(types::I32)?
+                } else {
+                    self.pop()?
+                };
+                let condition_code = if opcode == Opcode::eqz_i32 || opcode == Opcode::eqz_i64 {
+                    IntCC::Equal
+                } else {
+                    IntCC::NotEqual
+                };
+                let value = self.builder.ins().icmp_imm(condition_code, value, 0);
+                self.push_extended(value, false);
+            }
+            Opcode::eq_i32
+            | Opcode::ne_i32
+            | Opcode::lt_i32_s
+            | Opcode::lt_i32_u
+            | Opcode::gt_i32_s
+            | Opcode::gt_i32_u
+            | Opcode::le_i32_s
+            | Opcode::le_i32_u
+            | Opcode::ge_i32_s
+            | Opcode::ge_i32_u => {
+                let (left, right) = self.pop_operands_with_type(types::I32)?;
+                let value = self
+                    .builder
+                    .ins()
+                    .icmp(get_condition_code(opcode), left, right);
+                self.push_extended(value, false);
+            }
+            Opcode::eq_i64
+            | Opcode::ne_i64
+            | Opcode::lt_i64_s
+            | Opcode::lt_i64_u
+            | Opcode::gt_i64_s
+            | Opcode::gt_i64_u
+            | Opcode::le_i64_s
+            | Opcode::le_i64_u
+            | Opcode::ge_i64_s
+            | Opcode::ge_i64_u => {
+                let (left, right) = self.pop_operands_with_type(types::I64)?;
+                let value = self
+                    .builder
+                    .ins()
+                    .icmp(get_condition_code(opcode), left, right);
+                self.push_extended(value, false);
+            }
+
+            // control flow
+            Opcode::end => {
+                self.close_frame(address)?;
+            }
+            Opcode::block => {
+                let type_index = read_param_u32(instruction, 4);
+                let local_variable_list_index = read_param_u32(instruction, 8);
+                let (params_count, results_count) =
+                    self.get_type_params_and_results_count(type_index)?;
+                let arguments = self.pop_operands(params_count)?;
+                self.open_frame(
+                    params_count,
+                    results_count,
+                    local_variable_list_index,
+                    &arguments,
+                    address + 12,
+                    None,
+                )?;
+            }
+            Opcode::block_alt => {
+                let type_index = read_param_u32(instruction, 4);
+                let local_variable_list_index = read_param_u32(instruction, 8);
+                let next_inst_offset = read_param_u32(instruction, 12);
+                let condition = self.pop_with_type(types::I32)?;
+                let (params_count, results_count) =
+                    self.get_type_params_and_results_count(type_index)?;
+                let arguments = self.pop_operands(params_count)?;
+                self.open_frame(
+                    params_count,
+                    results_count,
+                    local_variable_list_index,
+                    &arguments,
+                    address + 16,
+                    Some((condition, address + next_inst_offset as usize)),
+                )?;
+            }
+            Opcode::block_nez => {
+                let local_variable_list_index = read_param_u32(instruction, 4);
+                let next_inst_offset = read_param_u32(instruction, 8);
+                let condition = self.pop_with_type(types::I32)?;
+
+                // Skip the block if the condition is false.
+                let init_block = self.builder.create_block();
+                let skip_block = self.builder.create_block();
+                self.builder
+                    .ins()
+                    .brif(condition, init_block, &[], skip_block, &[]);
+                self.builder.switch_to_block(init_block);
+
+                self.open_frame(0, 0, local_variable_list_index, &[], address + 12, None)?;
+
+                // `block_nez` has no results, so the exit block is the same as the skip block.
+                let frame = self.frames.last_mut().unwrap();
+                let exit_block = frame.exit_block.unwrap();
+                frame.exit_address = Some(address + next_inst_offset as usize);
+
+                let current_block = self.builder.current_block().unwrap();
+                self.builder.switch_to_block(skip_block);
+                self.builder.ins().jump(exit_block, &[]);
+                self.builder.switch_to_block(current_block);
+            }
+            Opcode::break_ => {
+                let layers = read_param_u16(instruction, 2);
+                let next_inst_offset = read_param_u32(instruction, 4);
+                self.translate_break(layers, address + next_inst_offset as usize)?;
+            }
+            Opcode::break_alt => {
+                let next_inst_offset = read_param_u32(instruction, 4);
+                self.translate_break(0, address + next_inst_offset as usize)?;
+            }
+            Opcode::recur => {
+                let layers = read_param_u16(instruction, 2);
+                let start_inst_offset = read_param_u32(instruction, 4);
+                self.translate_recur(layers, address, start_inst_offset as usize)?;
+            }
+
+            // calling
+            Opcode::call => {
+                let function_public_index = read_param_u32(instruction, 4);
+                self.translate_call(
+                    CallTarget::Function(function_public_index as usize),
+                    address,
+                )?;
+            }
+            Opcode::envcall => {
+                let envcall_num = read_param_u32(instruction, 4);
+                self.translate_call(CallTarget::EnvCall(envcall_num), address)?;
+            }
+            Opcode::syscall => {
+                self.translate_call(CallTarget::SysCall, address)?;
+            }
+            Opcode::extcall => {
+                let external_function_index = read_param_u32(instruction, 4);
+                self.translate_call(
+                    CallTarget::ExtCall(external_function_index as usize),
+                    address,
+                )?;
+            }
+
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    #[inline]
+    fn push(&mut self, value: Value) {
+        self.frames.last_mut().unwrap().operands.push(value);
+    }
+
+    // Pushes a value whose type is narrower than `i64`, the value is extended
+    // to `i64` as the interpreter does (e.g. `push_i32_s()` and `push_i32_u()`).
+    #[inline]
+    fn push_extended(&mut self, value: Value, signed: bool) {
+        let value = if signed {
+            self.builder.ins().sextend(types::I64, value)
+        } else {
+            self.builder.ins().uextend(types::I64, value)
+        };
+        self.push(value);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Value> {
+        self.frames.last_mut().unwrap().operands.pop()
+    }
+
+    #[inline]
+    fn pop_with_type(&mut self, data_type: Type) -> Option<Value> {
+        let value = self.pop()?;
+        if data_type == types::I64 {
+            Some(value)
+        } else {
+            Some(self.builder.ins().ireduce(data_type, value))
+        }
+    }
+
+    #[inline]
+    fn pop_operands_with_type(&mut self, data_type: Type) -> Option<(Value, Value)> {
+        let right = self.pop_with_type(data_type)?;
+        let left = self.pop_with_type(data_type)?;
+        Some((left, right))
+    }
+
+    // Pops the specified number of operands, the first item is the bottom one.
+    fn pop_operands(&mut self, count: usize) -> Option<Vec<Value>> {
+        let operands = &mut self.frames.last_mut().unwrap().operands;
+        let start = operands.len().checked_sub(count)?;
+        Some(operands.split_off(start))
+    }
+
+    fn load(&mut self, opcode: Opcode, address_value: Value, offset: i32) -> Value {
+        let mem_flags = MemFlags::new();
+        let ins = self.builder.ins();
+        match opcode {
+            Opcode::local_load_i32_s | Opcode::data_load_i32_s => {
+                ins.sload32(mem_flags, address_value, offset)
+            }
+            Opcode::local_load_i32_u | Opcode::data_load_i32_u => {
+                ins.uload32(mem_flags, address_value, offset)
+            }
+            Opcode::local_load_i16_s | Opcode::data_load_i16_s => {
+                ins.sload16(types::I64, mem_flags, address_value, offset)
+            }
+            Opcode::local_load_i16_u | Opcode::data_load_i16_u => {
+                ins.uload16(types::I64, mem_flags, address_value, offset)
+            }
+            Opcode::local_load_i8_s | Opcode::data_load_i8_s => {
+                ins.sload8(types::I64, mem_flags, address_value, offset)
+            }
+            Opcode::local_load_i8_u | Opcode::data_load_i8_u => {
+                ins.uload8(types::I64, mem_flags, address_value, offset)
+            }
+            _ => ins.load(types::I64, mem_flags, address_value, offset),
+        }
+    }
+
+    fn store(&mut self, data_length: usize, value: Value, address_value: Value, offset: i32) {
+        let mem_flags = MemFlags::new();
+        let ins = self.builder.ins();
+        match data_length {
+            DATA_LENGTH_IN_BYTES_32_BIT => ins.istore32(mem_flags, value, address_value, offset),
+            DATA_LENGTH_IN_BYTES_16_BIT => ins.istore16(mem_flags, value, address_value, offset),
+            DATA_LENGTH_IN_BYTES_8_BIT => ins.istore8(mem_flags, value, address_value, offset),
+            _ => ins.store(mem_flags, value, address_value, offset),
+        };
+    }
+
+    // Returns the address and the offset of the local variable, or `None` if
+    // the access would raise a trap.
+    fn resolve_local_variable(
+        &mut self,
+        layers: u16,
+        local_variable_index: u32,
+        data_length: usize,
+    ) -> Option<(Value, i32)> {
+        let frame_index = (self.frames.len() - 1).checked_sub(layers as usize)?;
+        let frame = &self.frames[frame_index];
+
+        let (_, local_variable_section) = self.environment.get_sections();
+        local_variable_section
+            .lists
+            .get(frame.local_variable_list_index)?;
+        let variable_item = local_variable_section
+            .get_local_variable_list(frame.local_variable_list_index)
+            .get(local_variable_index as usize)?;
+
+        if data_length > variable_item.variable_actual_size_in_bytes as usize {
+            return None;
+        }
+
+        let offset = variable_item.variable_offset as i32;
+        let address_value = if frame_index == 0 {
+            self.local_variables_ptr
+        } else {
+            let stack_slot = frame.stack_slot?;
+            self.builder
+                .ins()
+                .stack_addr(self.pointer_type, stack_slot, 0)
+        };
+
+        Some((address_value, offset))
+    }
+
+    fn get_type_params_and_results_count(&self, type_index: u32) -> Option<(usize, usize)> {
+        let (type_section, _) = self.environment.get_sections();
+        let type_item = type_section.items.get(type_index as usize)?;
+        Some((
+            type_item.params_count as usize,
+            type_item.results_count as usize,
+        ))
+    }
+
+    // Emits a trap if the divisor is zero.
+    fn check_divisor(&mut self, divisor: Value, address: usize) {
+        let condition = self.builder.ins().icmp_imm(IntCC::Equal, divisor, 0);
+        self.trap_if(condition, TrapKind::IntegerDivideByZero, address);
+    }
+
+    // Emits a trap for `MIN / -1`.
+    fn check_signed_division_overflow(
+        &mut self,
+        left: Value,
+        right: Value,
+        min: i64,
+        address: usize,
+    ) {
+        let is_min = self.builder.ins().icmp_imm(IntCC::Equal, left, min);
+        let is_minus_one = self.builder.ins().icmp_imm(IntCC::Equal, right, -1);
+        let condition = self.builder.ins().band(is_min, is_minus_one);
+        self.trap_if(condition, TrapKind::IntegerOverflow, address);
+    }
+
+    fn trap_if(&mut self, condition: Value, trap_kind: TrapKind, address: usize) {
+        let trap_block = self.builder.create_block();
+        let continue_block = self.builder.create_block();
+        self.builder.set_cold_block(trap_block);
+        self.builder
+            .ins()
+            .brif(condition, trap_block, &[], continue_block, &[]);
+
+        self.builder.switch_to_block(trap_block);
+        self.environment
+            .translate_trap(&mut self.builder, trap_kind, address);
+
+        self.builder.switch_to_block(continue_block);
+    }
+
+    // Creates a block frame and switches to its body, the arguments
+    // are stored into the local variables of the block.
+    #[allow(clippy::too_many_arguments)]
+    fn open_frame(
+        &mut self,
+        params_count: usize,
+        results_count: usize,
+        local_variable_list_index: u32,
+        arguments: &[Value],
+        body_address: usize,
+        opt_alternative: Option<(Value, usize)>,
+    ) -> Option<()> {
+        let (_, local_variable_section) = self.environment.get_sections();
+        let local_variables_with_arguments_allocated_bytes = local_variable_section
+            .lists
+            .get(local_variable_list_index as usize)?
+            .allocated_bytes as usize;
+
+        if local_variables_with_arguments_allocated_bytes % OPERAND_SIZE_IN_BYTES != 0 {
+            return None;
+        }
+
+        let stack_slot = if local_variables_with_arguments_allocated_bytes > 0 {
+            Some(self.builder.create_sized_stack_slot(StackSlotData::new(
+                StackSlotKind::ExplicitSlot,
+                local_variables_with_arguments_allocated_bytes as u32,
+                3,
+            )))
+        } else {
+            None
+        };
+
+        let body_block = self.builder.create_block();
+        let exit_block = self.builder.create_block();
+        for _ in 0..results_count {
+            self.builder.append_block_param(exit_block, types::I64);
+        }
+
+        self.frames.push(ControlFrame {
+            params_count,
+            results_count,
+            local_variable_list_index: local_variable_list_index as usize,
+            local_variables_with_arguments_allocated_bytes,
+            stack_slot,
+            body_address,
+            body_block,
+            exit_block: Some(exit_block),
+            exit_address: None,
+            alternative: None,
+            operands: vec![],
+        });
+
+        let frame_index = self.frames.len() - 1;
+        self.initialize_local_variables(frame_index, arguments);
+
+        match opt_alternative {
+            Some((condition, alternative_address)) => {
+                let alternative_block = self.builder.create_block();
+                self.builder
+                    .ins()
+                    .brif(condition, body_block, &[], alternative_block, &[]);
+                self.frames.last_mut().unwrap().alternative =
+                    Some((alternative_address, alternative_block));
+            }
+            None => {
+                self.builder.ins().jump(body_block, &[]);
+            }
+        }
+
+        self.builder.switch_to_block(body_block);
+        Some(())
+    }
+
+    // Handles the instruction `end`.
+    fn close_frame(&mut self, address: usize) -> Option<()> {
+        let mut frame = self.frames.pop().unwrap();
+        if frame.alternative.is_some() {
+            return None;
+        }
+
+        let opt_results = if self.reachable {
+            let start = frame.operands.len().checked_sub(frame.results_count)?;
+            Some(frame.operands.split_off(start))
+        } else {
+            None
+        };
+
+        if self.frames.is_empty() {
+            // The end of the function.
+            if let Some(results) = opt_results {
+                self.return_results(&results);
+            }
+            return Some(());
+        }
+
+        if matches!(frame.exit_address, Some(exit_address) if exit_address != address + 2) {
+            return None;
+        }
+
+        let exit_block = frame.exit_block.unwrap();
+        if let Some(results) = opt_results {
+            self.builder
+                .ins()
+                .jump(exit_block, &to_block_args(&results));
+        }
+
+        self.builder.switch_to_block(exit_block);
+        let results = self.builder.block_params(exit_block).to_vec();
+        self.frames.last_mut().unwrap().operands.extend(results);
+        self.reachable = true;
+        Some(())
+    }
+
+    fn translate_break(&mut self, layers: u16, exit_address: usize) -> Option<()> {
+        let target_frame_index = (self.frames.len() - 1).checked_sub(layers as usize)?;
+        let results = self.pop_operands(self.frames[target_frame_index].results_count)?;
+
+        if target_frame_index == 0 {
+            // Break the function frame, i.e. return from the function.
+            self.return_results(&results);
+        } else {
+            let target_frame = &mut self.frames[target_frame_index];
+            if matches!(target_frame.exit_address, Some(address) if address != exit_address) {
+                return None;
+            }
+            target_frame.exit_address = Some(exit_address);
+
+            let exit_block = target_frame.exit_block.unwrap();
+            self.builder
+                .ins()
+                .jump(exit_block, &to_block_args(&results));
+        }
+
+        self.reachable = false;
+        Some(())
+    }
+
+    fn translate_recur(
+        &mut self,
+        layers: u16,
+        address: usize,
+        start_inst_offset: usize,
+    ) -> Option<()> {
+        let target_frame_index = (self.frames.len() - 1).checked_sub(layers as usize)?;
+
+        // The `start_inst_offset` is ignored when the target frame is a function frame.
+        if target_frame_index > 0
+            && address.checked_sub(start_inst_offset)?
+                != self.frames[target_frame_index].body_address
+        {
+            return None;
+        }
+
+        let arguments = self.pop_operands(self.frames[target_frame_index].params_count)?;
+        self.initialize_local_variables(target_frame_index, &arguments);
+
+        self.environment
+            .translate_back_edge(&mut self.builder, &self.frames, target_frame_index);
+
+        self.reachable = false;
+        Some(())
+    }
+
+    fn translate_call(&mut self, call_target: CallTarget, address: usize) -> Option<()> {
+        let operands = &mut self.frames.last_mut().unwrap().operands;
+        self.environment
+            .translate_call(&mut self.builder, call_target, address, operands)
+    }
+
+    // Stores the arguments into the local variables of the frame,
+    // and resets the other local variables to 0.
+    fn initialize_local_variables(&mut self, frame_index: usize, arguments: &[Value]) {
+        let frame = &self.frames[frame_index];
+        let allocated_bytes = frame.local_variables_with_arguments_allocated_bytes;
+        let opt_stack_slot = frame.stack_slot;
+
+        if frame_index > 0 && opt_stack_slot.is_none() {
+            // There is no local variable in the block.
+            return;
+        }
+
+        let zero = self.builder.ins().iconst(types::I64, 0);
+        let mem_flags = MemFlags::new();
+        let arguments_length = arguments.len() * OPERAND_SIZE_IN_BYTES;
+
+        for offset in (0..allocated_bytes).step_by(OPERAND_SIZE_IN_BYTES) {
+            let value = if offset < arguments_length {
+                arguments[offset / OPERAND_SIZE_IN_BYTES]
+            } else {
+                zero
+            };
+
+            match opt_stack_slot {
+                Some(stack_slot) => {
+                    self.builder
+                        .ins()
+                        .stack_store(value, stack_slot, offset as i32);
+                }
+                None => {
+                    self.builder.ins().store(
+                        mem_flags,
+                        value,
+                        self.local_variables_ptr,
+                        offset as i32,
+                    );
+                }
+            }
+        }
+    }
+
+    fn return_results(&mut self, results: &[Value]) {
+        let mem_flags = MemFlags::new();
+        for (index, value) in results.iter().enumerate() {
+            self.builder.ins().store(
+                mem_flags,
+                *value,
+                self.results_ptr,
+                (index * OPERAND_SIZE_IN_BYTES) as i32,
+            );
+        }
+
+        let status = self
+            .builder
+            .ins()
+            .iconst(types::I64, STATUS_FINISHED as i64);
+        self.builder.ins().return_(&[status]);
+    }
+}
+
+fn get_data_length_of_load_and_store(opcode: Opcode) -> usize {
+    match opcode {
+        Opcode::local_load_i32_s
+        | Opcode::local_load_i32_u
+        | Opcode::local_store_i32
+        | Opcode::local_store_f32
+        | Opcode::data_load_i32_s
+        | Opcode::data_load_i32_u
+        | Opcode::data_store_i32
+        | Opcode::data_store_f32 => DATA_LENGTH_IN_BYTES_32_BIT,
+        Opcode::local_load_i16_s
+        | Opcode::local_load_i16_u
+        | Opcode::local_store_i16
+        | Opcode::data_load_i16_s
+        | Opcode::data_load_i16_u
+        | Opcode::data_store_i16 => DATA_LENGTH_IN_BYTES_16_BIT,
+        Opcode::local_load_i8_s
+        | Opcode::local_load_i8_u
+        | Opcode::local_store_i8
+        | Opcode::data_load_i8_s
+        | Opcode::data_load_i8_u
+        | Opcode::data_store_i8 => DATA_LENGTH_IN_BYTES_8_BIT,
+        _ => DATA_LENGTH_IN_BYTES_64_BIT,
+    }
+}
+
+fn get_condition_code(opcode: Opcode) -> IntCC {
+    match opcode {
+        Opcode::eq_i32 | Opcode::eq_i64 => IntCC::Equal,
+        Opcode::ne_i32 | Opcode::ne_i64 => IntCC::NotEqual,
+        Opcode::lt_i32_s | Opcode::lt_i64_s => IntCC::SignedLessThan,
+        Opcode::lt_i32_u | Opcode::lt_i64_u => IntCC::UnsignedLessThan,
+        Opcode::gt_i32_s | Opcode::gt_i64_s => IntCC::SignedGreaterThan,
+        Opcode::gt_i32_u | Opcode::gt_i64_u => IntCC::UnsignedGreaterThan,
+        Opcode::le_i32_s | Opcode::le_i64_s => IntCC::SignedLessThanOrEqual,
+        Opcode::le_i32_u | Opcode::le_i64_u => IntCC::UnsignedLessThanOrEqual,
+        Opcode::ge_i32_s | Opcode::ge_i64_s => IntCC::SignedGreaterThanOrEqual,
+        _ => IntCC::UnsignedGreaterThanOrEqual,
+    }
+}
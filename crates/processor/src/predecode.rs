@@ -0,0 +1,1027 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Pre-decoding
+// ------------
+//
+// The default interpreter loop decodes each instruction every time it is executed:
+// read the opcode from the bytecode, look up the handler through two nested `match`
+// statements, and then the handler reads its parameters from the bytecode again.
+//
+// The pre-decoding pass converts the bytecode of a function into a dense array
+// of `PredecodedInstruction`, each item contains the handler and the decoded
+// parameters of an instruction, so the interpreter loop only needs to fetch the
+// next item and call its handler.
+//
+// ```diagram
+// | bytecode                    |       | pre-decoded instructions                 |
+// |-----------------------------|       |------------------------------------------|
+// | 0x0000 imm_i32 11           |  -->  | 0: imm_i32_predecoded,          [11,0,0] |
+// | 0x0008 local_load_i32_u 0 1 |  -->  | 1: local_load_i32_u_predecoded, [0,1,0]  |
+// | 0x0010 add_i32              |  -->  | 2: add_i32,                     [0,0,0]  |
+// | 0x0012 end                  |  -->  | 3: end,                         [0,0,0]  |
+// ```
+//
+// Functions are decoded lazily the first time they are executed, and the result is
+// cached in the `ModuleCommonInstance` (per thread), see `ThreadContext::predecode`.
+
+use std::sync::{Arc, OnceLock};
+
+use anc_context::thread_context::{ThreadContext, TrapKind};
+use anc_isa::opcode::Opcode;
+
+use crate::{
+    instruction_handler::{
+        get_instruction_handler, get_predecoded_instruction_handler, unreachable_handler,
+        HandleResult, HandlerFunc, PredecodedHandlerFunc,
+    },
+    ProcessorErrorType,
+};
+
+// Instructions are aligned to 2 bytes (the length of the opcode),
+// so each 2-byte slot of the function code maps to at most one instruction.
+const INSTRUCTION_ALIGNMENT_IN_BYTES: usize = 2;
+
+// Marks the slots which are not the start of an instruction.
+const INVALID_INSTRUCTION_INDEX: u32 = u32::MAX;
+
+#[derive(Clone, Copy)]
+pub enum PredecodedHandler {
+    // The handler reads the parameters from the bytecode by itself,
+    // it is used by the instructions without parameters
+    // and the infrequently executed ones.
+    Plain(HandlerFunc),
+
+    // The handler takes the pre-decoded parameters.
+    WithParams(PredecodedHandlerFunc),
+}
+
+#[derive(Clone, Copy)]
+pub struct PredecodedInstruction {
+    pub handler: PredecodedHandler,
+    pub length_in_bytes: usize,
+    pub params: [u32; 3],
+}
+
+pub struct PredecodedFunction {
+    // The address of the first instruction of the function, i.e.
+    // the offset of the function code in the code section.
+    pub code_offset: usize,
+    pub instructions: Vec<PredecodedInstruction>,
+
+    // Maps each 2-byte slot of the function code to the index of the instruction
+    // which starts at that slot, it is used to translate the instruction address
+    // of jumps and returns into the index of the pre-decoded instruction.
+    instruction_indices: Vec<u32>,
+}
+
+impl PredecodedFunction {
+    /// Decodes the code of a function.
+    ///
+    /// Decoding stops at a truncated instruction, jumping to the
+    /// address beyond it raises an "invalid opcode" trap.
+    pub fn new(codes_data: &[u8], code_offset: usize, code_length: usize) -> Self {
+        let codes = &codes_data[code_offset..(code_offset + code_length)];
+
+        let mut instructions = vec![];
+        let mut instruction_indices =
+            vec![INVALID_INSTRUCTION_INDEX; code_length.div_ceil(INSTRUCTION_ALIGNMENT_IN_BYTES)];
+
+        let mut offset = 0;
+        while offset + 2 <= codes.len() {
+            let opcode_integer =
+                u16::from_le_bytes(codes[offset..(offset + 2)].try_into().unwrap());
+            let opt_opcode = get_opcode(opcode_integer);
+            let layout = match opt_opcode {
+                Some(opcode) => get_instruction_param_layout(opcode),
+                None => ParamLayout::None,
+            };
+            let length_in_bytes = layout.get_length_in_bytes();
+
+            if offset + length_in_bytes > codes.len() {
+                break;
+            }
+
+            let instruction = &codes[offset..(offset + length_in_bytes)];
+            let params = layout.decode_params(instruction);
+            let handler = if opt_opcode.is_none() {
+                // Invalid opcodes raise the "invalid opcode" trap when executed.
+                PredecodedHandler::Plain(unreachable_handler)
+            } else {
+                match get_predecoded_instruction_handler(opcode_integer) {
+                    Some(handler) => PredecodedHandler::WithParams(handler),
+                    None => PredecodedHandler::Plain(get_instruction_handler(opcode_integer)),
+                }
+            };
+
+            instruction_indices[offset / INSTRUCTION_ALIGNMENT_IN_BYTES] =
+                instructions.len() as u32;
+            instructions.push(PredecodedInstruction {
+                handler,
+                length_in_bytes,
+                params,
+            });
+
+            offset += length_in_bytes;
+        }
+
+        Self {
+            code_offset,
+            instructions,
+            instruction_indices,
+        }
+    }
+
+    /// Returns the index of the pre-decoded instruction at the given address,
+    /// `None` if the address is not the start of an instruction of this function.
+    #[inline]
+    pub fn get_instruction_index(&self, instruction_address: usize) -> Option<usize> {
+        let relative_address = instruction_address.checked_sub(self.code_offset)?;
+        if relative_address % INSTRUCTION_ALIGNMENT_IN_BYTES != 0 {
+            return None;
+        }
+
+        match self
+            .instruction_indices
+            .get(relative_address / INSTRUCTION_ALIGNMENT_IN_BYTES)
+        {
+            Some(&index) if index != INVALID_INSTRUCTION_INDEX => Some(index as usize),
+            _ => None,
+        }
+    }
+}
+
+/// The layout of the parameters of an instruction.
+///
+/// See `ThreadContext::get_instruction()` for the instruction encoding table.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ParamLayout {
+    None,      // [opcode 16-bit]
+    I16,       // [opcode 16-bit] - [param i16]
+    I32,       // [opcode 16-bit] - [padding 16-bit] + [param i32]
+    I16I32,    // [opcode 16-bit] - [param i16] + [param i32]
+    I32I32,    // [opcode 16-bit] - [padding 16-bit] + [param i32] + [param i32]
+    I32I32I32, // [opcode 16-bit] - [padding 16-bit] + [param i32] + [param i32] + [param i32]
+}
+
+impl ParamLayout {
+    pub(crate) fn get_length_in_bytes(&self) -> usize {
+        match self {
+            ParamLayout::None => 2,
+            ParamLayout::I16 => 4,
+            ParamLayout::I32 | ParamLayout::I16I32 => 8,
+            ParamLayout::I32I32 => 12,
+            ParamLayout::I32I32I32 => 16,
+        }
+    }
+
+    pub(crate) fn decode_params(&self, instruction: &[u8]) -> [u32; 3] {
+        let read_u16 = |offset: usize| {
+            u16::from_le_bytes(instruction[offset..(offset + 2)].try_into().unwrap()) as u32
+        };
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes(instruction[offset..(offset + 4)].try_into().unwrap())
+        };
+
+        match self {
+            ParamLayout::None => [0, 0, 0],
+            ParamLayout::I16 => [read_u16(2), 0, 0],
+            ParamLayout::I32 => [read_u32(4), 0, 0],
+            ParamLayout::I16I32 => [read_u16(2), read_u32(4), 0],
+            ParamLayout::I32I32 => [read_u32(4), read_u32(8), 0],
+            ParamLayout::I32I32I32 => [read_u32(4), read_u32(8), read_u32(12)],
+        }
+    }
+}
+
+// All opcodes, the order is the same as the dispatching of `get_instruction_handler()`.
+const OPCODES: [Opcode; 251] = [
+    Opcode::nop,
+    Opcode::imm_i32,
+    Opcode::imm_i64,
+    Opcode::imm_f32,
+    Opcode::imm_f64,
+    Opcode::local_load_i64,
+    Opcode::local_load_i32_s,
+    Opcode::local_load_i32_u,
+    Opcode::local_load_i16_s,
+    Opcode::local_load_i16_u,
+    Opcode::local_load_i8_s,
+    Opcode::local_load_i8_u,
+    Opcode::local_load_f32,
+    Opcode::local_load_f64,
+    Opcode::local_store_i64,
+    Opcode::local_store_i32,
+    Opcode::local_store_i16,
+    Opcode::local_store_i8,
+    Opcode::local_store_f64,
+    Opcode::local_store_f32,
+    Opcode::data_load_i64,
+    Opcode::data_load_i32_s,
+    Opcode::data_load_i32_u,
+    Opcode::data_load_i16_s,
+    Opcode::data_load_i16_u,
+    Opcode::data_load_i8_s,
+    Opcode::data_load_i8_u,
+    Opcode::data_load_f64,
+    Opcode::data_load_f32,
+    Opcode::data_store_i64,
+    Opcode::data_store_i32,
+    Opcode::data_store_i16,
+    Opcode::data_store_i8,
+    Opcode::data_store_f64,
+    Opcode::data_store_f32,
+    Opcode::data_load_extend_i64,
+    Opcode::data_load_extend_i32_s,
+    Opcode::data_load_extend_i32_u,
+    Opcode::data_load_extend_i16_s,
+    Opcode::data_load_extend_i16_u,
+    Opcode::data_load_extend_i8_s,
+    Opcode::data_load_extend_i8_u,
+    Opcode::data_load_extend_f64,
+    Opcode::data_load_extend_f32,
+    Opcode::data_store_extend_i64,
+    Opcode::data_store_extend_i32,
+    Opcode::data_store_extend_i16,
+    Opcode::data_store_extend_i8,
+    Opcode::data_store_extend_f64,
+    Opcode::data_store_extend_f32,
+    Opcode::add_i32,
+    Opcode::sub_i32,
+    Opcode::add_imm_i32,
+    Opcode::sub_imm_i32,
+    Opcode::mul_i32,
+    Opcode::div_i32_s,
+    Opcode::div_i32_u,
+    Opcode::rem_i32_s,
+    Opcode::rem_i32_u,
+    Opcode::add_i64,
+    Opcode::sub_i64,
+    Opcode::add_imm_i64,
+    Opcode::sub_imm_i64,
+    Opcode::mul_i64,
+    Opcode::div_i64_s,
+    Opcode::div_i64_u,
+    Opcode::rem_i64_s,
+    Opcode::rem_i64_u,
+    Opcode::add_f32,
+    Opcode::sub_f32,
+    Opcode::mul_f32,
+    Opcode::div_f32,
+    Opcode::add_f64,
+    Opcode::sub_f64,
+    Opcode::mul_f64,
+    Opcode::div_f64,
+    Opcode::and,
+    Opcode::or,
+    Opcode::xor,
+    Opcode::not,
+    Opcode::count_leading_zeros_i32,
+    Opcode::count_leading_ones_i32,
+    Opcode::count_trailing_zeros_i32,
+    Opcode::count_ones_i32,
+    Opcode::shift_left_i32,
+    Opcode::shift_right_i32_s,
+    Opcode::shift_right_i32_u,
+    Opcode::rotate_left_i32,
+    Opcode::rotate_right_i32,
+    Opcode::count_leading_zeros_i64,
+    Opcode::count_leading_ones_i64,
+    Opcode::count_trailing_zeros_i64,
+    Opcode::count_ones_i64,
+    Opcode::shift_left_i64,
+    Opcode::shift_right_i64_s,
+    Opcode::shift_right_i64_u,
+    Opcode::rotate_left_i64,
+    Opcode::rotate_right_i64,
+    Opcode::abs_i32,
+    Opcode::neg_i32,
+    Opcode::abs_i64,
+    Opcode::neg_i64,
+    Opcode::abs_f32,
+    Opcode::neg_f32,
+    Opcode::copysign_f32,
+    Opcode::sqrt_f32,
+    Opcode::min_f32,
+    Opcode::max_f32,
+    Opcode::ceil_f32,
+    Opcode::floor_f32,
+    Opcode::round_half_away_from_zero_f32,
+    Opcode::round_half_to_even_f32,
+    Opcode::trunc_f32,
+    Opcode::fract_f32,
+    Opcode::cbrt_f32,
+    Opcode::exp_f32,
+    Opcode::exp2_f32,
+    Opcode::ln_f32,
+    Opcode::log2_f32,
+    Opcode::log10_f32,
+    Opcode::sin_f32,
+    Opcode::cos_f32,
+    Opcode::tan_f32,
+    Opcode::asin_f32,
+    Opcode::acos_f32,
+    Opcode::atan_f32,
+    Opcode::pow_f32,
+    Opcode::log_f32,
+    Opcode::abs_f64,
+    Opcode::neg_f64,
+    Opcode::copysign_f64,
+    Opcode::sqrt_f64,
+    Opcode::min_f64,
+    Opcode::max_f64,
+    Opcode::ceil_f64,
+    Opcode::floor_f64,
+    Opcode::round_half_away_from_zero_f64,
+    Opcode::round_half_to_even_f64,
+    Opcode::trunc_f64,
+    Opcode::fract_f64,
+    Opcode::cbrt_f64,
+    Opcode::exp_f64,
+    Opcode::exp2_f64,
+    Opcode::ln_f64,
+    Opcode::log2_f64,
+    Opcode::log10_f64,
+    Opcode::sin_f64,
+    Opcode::cos_f64,
+    Opcode::tan_f64,
+    Opcode::asin_f64,
+    Opcode::acos_f64,
+    Opcode::atan_f64,
+    Opcode::pow_f64,
+    Opcode::log_f64,
+    Opcode::truncate_i64_to_i32,
+    Opcode::extend_i32_s_to_i64,
+    Opcode::extend_i32_u_to_i64,
+    Opcode::demote_f64_to_f32,
+    Opcode::promote_f32_to_f64,
+    Opcode::convert_f32_to_i32_s,
+    Opcode::convert_f32_to_i32_u,
+    Opcode::convert_f64_to_i32_s,
+    Opcode::convert_f64_to_i32_u,
+    Opcode::convert_f32_to_i64_s,
+    Opcode::convert_f32_to_i64_u,
+    Opcode::convert_f64_to_i64_s,
+    Opcode::convert_f64_to_i64_u,
+    Opcode::convert_i32_s_to_f32,
+    Opcode::convert_i32_u_to_f32,
+    Opcode::convert_i64_s_to_f32,
+    Opcode::convert_i64_u_to_f32,
+    Opcode::convert_i32_s_to_f64,
+    Opcode::convert_i32_u_to_f64,
+    Opcode::convert_i64_s_to_f64,
+    Opcode::convert_i64_u_to_f64,
+    Opcode::eqz_i32,
+    Opcode::nez_i32,
+    Opcode::eq_i32,
+    Opcode::ne_i32,
+    Opcode::lt_i32_s,
+    Opcode::lt_i32_u,
+    Opcode::gt_i32_s,
+    Opcode::gt_i32_u,
+    Opcode::le_i32_s,
+    Opcode::le_i32_u,
+    Opcode::ge_i32_s,
+    Opcode::ge_i32_u,
+    Opcode::eqz_i64,
+    Opcode::nez_i64,
+    Opcode::eq_i64,
+    Opcode::ne_i64,
+    Opcode::lt_i64_s,
+    Opcode::lt_i64_u,
+    Opcode::gt_i64_s,
+    Opcode::gt_i64_u,
+    Opcode::le_i64_s,
+    Opcode::le_i64_u,
+    Opcode::ge_i64_s,
+    Opcode::ge_i64_u,
+    Opcode::eq_f32,
+    Opcode::ne_f32,
+    Opcode::lt_f32,
+    Opcode::gt_f32,
+    Opcode::le_f32,
+    Opcode::ge_f32,
+    Opcode::eq_f64,
+    Opcode::ne_f64,
+    Opcode::lt_f64,
+    Opcode::gt_f64,
+    Opcode::le_f64,
+    Opcode::ge_f64,
+    Opcode::end,
+    Opcode::block,
+    Opcode::break_,
+    Opcode::recur,
+    Opcode::block_alt,
+    Opcode::break_alt,
+    Opcode::block_nez,
+    Opcode::call,
+    Opcode::call_dynamic,
+    Opcode::syscall,
+    Opcode::envcall,
+    Opcode::extcall,
+    Opcode::memory_load_i64,
+    Opcode::memory_load_i32_s,
+    Opcode::memory_load_i32_u,
+    Opcode::memory_load_i16_s,
+    Opcode::memory_load_i16_u,
+    Opcode::memory_load_i8_s,
+    Opcode::memory_load_i8_u,
+    Opcode::memory_load_f64,
+    Opcode::memory_load_f32,
+    Opcode::memory_store_i64,
+    Opcode::memory_store_i32,
+    Opcode::memory_store_i16,
+    Opcode::memory_store_i8,
+    Opcode::memory_store_f64,
+    Opcode::memory_store_f32,
+    Opcode::memory_allocate,
+    Opcode::memory_reallocate,
+    Opcode::memory_free,
+    Opcode::memory_fill,
+    Opcode::memory_copy,
+    Opcode::terminate,
+    Opcode::get_function,
+    Opcode::get_data,
+    Opcode::host_addr_function,
+    Opcode::host_addr_function_dynamic,
+    Opcode::host_addr_data,
+    Opcode::host_addr_data_extend,
+    Opcode::host_addr_memory,
+];
+
+/// Converts the opcode integer read from the bytecode into `Opcode`,
+/// returns `None` if the integer is not a valid opcode.
+///
+/// Transmuting an invalid integer into `Opcode` is undefined behavior, so the
+/// bytecode which has not been verified must be converted by this function.
+pub fn get_opcode(opcode_integer: u16) -> Option<Opcode> {
+    // The opcodes sorted by their integers.
+    static SORTED_OPCODES: OnceLock<Vec<(u16, Opcode)>> = OnceLock::new();
+
+    let sorted_opcodes = SORTED_OPCODES.get_or_init(|| {
+        let mut items = OPCODES
+            .iter()
+            .map(|opcode| (*opcode as u16, *opcode))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(integer, _)| *integer);
+        items
+    });
+
+    sorted_opcodes
+        .binary_search_by_key(&opcode_integer, |(integer, _)| *integer)
+        .ok()
+        .map(|index| sorted_opcodes[index].1)
+}
+
+pub(crate) fn get_instruction_param_layout(opcode: Opcode) -> ParamLayout {
+    match opcode {
+        // fundamental
+        Opcode::imm_i32 | Opcode::imm_f32 => ParamLayout::I32,
+        Opcode::imm_i64 | Opcode::imm_f64 => ParamLayout::I32I32,
+
+        // local
+        Opcode::local_load_i64
+        | Opcode::local_load_i32_s
+        | Opcode::local_load_i32_u
+        | Opcode::local_load_i16_s
+        | Opcode::local_load_i16_u
+        | Opcode::local_load_i8_s
+        | Opcode::local_load_i8_u
+        | Opcode::local_load_f32
+        | Opcode::local_load_f64
+        | Opcode::local_store_i64
+        | Opcode::local_store_i32
+        | Opcode::local_store_i16
+        | Opcode::local_store_i8
+        | Opcode::local_store_f64
+        | Opcode::local_store_f32 => ParamLayout::I16I32,
+
+        // data
+        Opcode::data_load_i64
+        | Opcode::data_load_i32_s
+        | Opcode::data_load_i32_u
+        | Opcode::data_load_i16_s
+        | Opcode::data_load_i16_u
+        | Opcode::data_load_i8_s
+        | Opcode::data_load_i8_u
+        | Opcode::data_load_f64
+        | Opcode::data_load_f32
+        | Opcode::data_store_i64
+        | Opcode::data_store_i32
+        | Opcode::data_store_i16
+        | Opcode::data_store_i8
+        | Opcode::data_store_f64
+        | Opcode::data_store_f32 => ParamLayout::I16I32,
+
+        Opcode::data_load_extend_i64
+        | Opcode::data_load_extend_i32_s
+        | Opcode::data_load_extend_i32_u
+        | Opcode::data_load_extend_i16_s
+        | Opcode::data_load_extend_i16_u
+        | Opcode::data_load_extend_i8_s
+        | Opcode::data_load_extend_i8_u
+        | Opcode::data_load_extend_f64
+        | Opcode::data_load_extend_f32
+        | Opcode::data_store_extend_i64
+        | Opcode::data_store_extend_i32
+        | Opcode::data_store_extend_i16
+        | Opcode::data_store_extend_i8
+        | Opcode::data_store_extend_f64
+        | Opcode::data_store_extend_f32 => ParamLayout::I32,
+
+        // arithmetic
+        Opcode::add_imm_i32 | Opcode::sub_imm_i32 | Opcode::add_imm_i64 | Opcode::sub_imm_i64 => {
+            ParamLayout::I16
+        }
+
+        // control flow
+        Opcode::block | Opcode::block_nez => ParamLayout::I32I32,
+        Opcode::block_alt => ParamLayout::I32I32I32,
+        Opcode::break_ | Opcode::recur => ParamLayout::I16I32,
+        Opcode::break_alt => ParamLayout::I32,
+
+        // calling
+        Opcode::call | Opcode::envcall | Opcode::extcall => ParamLayout::I32,
+
+        // machine
+        Opcode::terminate
+        | Opcode::get_function
+        | Opcode::get_data
+        | Opcode::host_addr_function
+        | Opcode::host_addr_data_extend => ParamLayout::I32,
+        Opcode::host_addr_data => ParamLayout::I16I32,
+
+        _ => ParamLayout::None,
+    }
+}
+
+/// Returns the pre-decoded function, the function is decoded and
+/// cached the first time it is requested.
+pub fn get_or_create_predecoded_function(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_internal_index: usize,
+) -> Arc<PredecodedFunction> {
+    let module_common_instance = &mut thread_context.module_common_instances[module_index];
+
+    if let Some(cached) = &module_common_instance.predecoded_functions[function_internal_index] {
+        if let Ok(predecoded_function) = Arc::clone(cached).downcast::<PredecodedFunction>() {
+            return predecoded_function;
+        }
+    }
+
+    let function_section = &module_common_instance.function_section;
+    let function_item = &function_section.items[function_internal_index];
+    let predecoded_function = Arc::new(PredecodedFunction::new(
+        function_section.codes_data,
+        function_item.code_offset as usize,
+        function_item.code_length as usize,
+    ));
+
+    module_common_instance.predecoded_functions[function_internal_index] =
+        Some(predecoded_function.clone());
+    predecoded_function
+}
+
+/// The counterpart of `process_continuous_instructions()` which dispatches
+/// instructions from the pre-decoded functions.
+///
+/// The semantics (fuel, interrupting, traps and the program counter) are the same as
+/// the default interpreter loop, so the two loops can be switched between calls.
+pub fn process_continuous_predecoded_instructions(
+    thread_context: &mut ThreadContext,
+) -> Result<(), ProcessorErrorType> {
+    let mut function = get_or_create_predecoded_function(
+        thread_context,
+        thread_context.pc.module_index,
+        thread_context.pc.function_internal_index,
+    );
+    let mut opt_index = function.get_instruction_index(thread_context.pc.instruction_address);
+
+    loop {
+        // Check the interrupt handle and consume the fuel before
+        // the instruction is executed, see `process_continuous_instructions()`.
+        if thread_context.is_interrupted() {
+            break Err(ProcessorErrorType::Interrupted);
+        }
+
+        if let Some(fuel) = thread_context.fuel.as_mut() {
+            if *fuel == 0 {
+                break Err(ProcessorErrorType::OutOfFuel);
+            }
+            *fuel -= 1;
+        }
+
+        let Some(instruction) = opt_index.and_then(|index| function.instructions.get(index)) else {
+            // The program counter does not point to the start of an instruction.
+            break Err(build_trap(thread_context, TrapKind::InvalidOpcode));
+        };
+        let instruction = *instruction;
+
+        let result = match instruction.handler {
+            PredecodedHandler::Plain(handler) => handler(thread_context),
+            PredecodedHandler::WithParams(handler) => handler(thread_context, &instruction.params),
+        };
+
+        match result {
+            HandleResult::Move(relate_offset_in_bytes) => {
+                let next_instruction_offset =
+                    thread_context.pc.instruction_address as isize + relate_offset_in_bytes;
+                thread_context.pc.instruction_address = next_instruction_offset as usize;
+
+                // Most instructions move to the next instruction,
+                // the address lookup is only required for the branches.
+                opt_index = if relate_offset_in_bytes == instruction.length_in_bytes as isize {
+                    opt_index.map(|index| index + 1)
+                } else {
+                    function.get_instruction_index(thread_context.pc.instruction_address)
+                };
+            }
+            HandleResult::Jump(next_pc) => {
+                if next_pc.module_index != thread_context.pc.module_index
+                    || next_pc.function_internal_index != thread_context.pc.function_internal_index
+                {
+                    function = get_or_create_predecoded_function(
+                        thread_context,
+                        next_pc.module_index,
+                        next_pc.function_internal_index,
+                    );
+                }
+
+                thread_context.pc.module_index = next_pc.module_index;
+                thread_context.pc.function_internal_index = next_pc.function_internal_index;
+                thread_context.pc.instruction_address = next_pc.instruction_address;
+
+                opt_index = function.get_instruction_index(next_pc.instruction_address);
+            }
+            HandleResult::End(original_pc) => {
+                thread_context.pc.module_index = original_pc.module_index;
+                thread_context.pc.function_internal_index = original_pc.function_internal_index;
+                thread_context.pc.instruction_address = original_pc.instruction_address;
+
+                // Break the instruction processing loop.
+                break Ok(());
+            }
+            HandleResult::Terminate(terminate_code) => {
+                break Err(ProcessorErrorType::Terminate(terminate_code));
+            }
+            HandleResult::Trap(kind) => {
+                break Err(build_trap(thread_context, kind));
+            }
+        }
+    }
+}
+
+fn build_trap(thread_context: &ThreadContext, kind: TrapKind) -> ProcessorErrorType {
+    ProcessorErrorType::Trap {
+        kind,
+        module_index: thread_context.pc.module_index,
+        function_internal_index: thread_context.pc.function_internal_index,
+        instruction_address: thread_context.pc.instruction_address,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_context::{
+        process_property::ProcessProperty, program_source::ProgramSource, thread_context::TrapKind,
+    };
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        utils::{
+            helper_build_module_binary_with_functions_and_blocks,
+            helper_build_module_binary_with_single_function,
+            helper_build_module_binary_with_single_function_and_blocks, HelperBlockEntry,
+            HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        in_memory_program_source::InMemoryProgramSource,
+        process::{process_function, resume_function},
+        ProcessorError, ProcessorErrorType,
+    };
+
+    use super::{get_opcode, PredecodedFunction, PredecodedHandler, OPCODES};
+
+    fn build_binary_with_accumulate_function() -> Vec<u8> {
+        // fn accu (sum/0:i32, n/1:i32) -> (i32)
+        //                              ;; sum = sum + n
+        //     local_load32(0, 0)       ;; 0x0000
+        //     local_load32(0, 1)       ;; 0x0008
+        //     add_i32                  ;; 0x0010
+        //     local_store_i32(0, 0)    ;; 0x0012
+        //                              ;; n = n - 1
+        //     local_load32(0, 1)       ;; 0x001a
+        //     sub_imm_i32(1)           ;; 0x0022
+        //     local_store_i32(0, 1)    ;; 0x0026
+        //                              ;; if n > 0 recur (sum,n)
+        //     local_load32(0, 1)       ;; 0x002e
+        //     imm_i32(0)               ;; 0x0036
+        //     gt_i32_u                 ;; 0x003e
+        //     block_nez () -> ()       ;; 0x0040
+        //         local_load32(0, 0)
+        //         local_load32(0, 1)
+        //         recur(1)
+        //     end
+        //     local_load32(0, 0)       ;; load sum
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode_i16_i32(Opcode::local_store_i32, 0, 0)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode_i16(Opcode::sub_imm_i32, 1)
+            .append_opcode_i16_i32(Opcode::local_store_i32, 0, 1)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode(Opcode::gt_i32_u)
+            .append_opcode_i32_i32(Opcode::block_nez, 1, 0x26)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode_i16_i32(Opcode::recur, 1, 0)
+            // block end
+            .append_opcode(Opcode::end)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_single_function_and_blocks(
+            vec![OperandDataType::I32, OperandDataType::I32], // params
+            vec![OperandDataType::I32],                       // results
+            vec![],                                           // local variables
+            code0,
+            vec![HelperBlockEntry {
+                params: vec![],
+                results: vec![],
+                local_variable_item_entries_without_args: vec![],
+            }], // blocks
+        )
+    }
+
+    #[test]
+    fn test_predecode_function() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11) // 0x0000
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 2, 3) // 0x0008
+            .append_opcode(Opcode::add_i32) // 0x0010
+            .append_opcode_i16(Opcode::add_imm_i32, 5) // 0x0012
+            .append_opcode_i64(Opcode::imm_i64, 0x1719_2329_3137_4143) // 0x0016
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let function0 = PredecodedFunction::new(&code0, 0, code0.len());
+        let instructions = &function0.instructions;
+        assert_eq!(instructions.len(), 6);
+
+        assert_eq!(
+            instructions
+                .iter()
+                .map(|instruction| instruction.length_in_bytes)
+                .collect::<Vec<_>>(),
+            vec![8, 8, 2, 4, 12, 2]
+        );
+
+        assert!(matches!(
+            instructions[0].handler,
+            PredecodedHandler::WithParams(_)
+        ));
+        assert_eq!(instructions[0].params, [11, 0, 0]);
+        assert_eq!(instructions[1].params, [2, 3, 0]);
+        assert!(matches!(
+            instructions[2].handler,
+            PredecodedHandler::Plain(_)
+        ));
+        assert_eq!(instructions[3].params, [5, 0, 0]);
+        assert_eq!(instructions[4].params, [0x3137_4143, 0x1719_2329, 0]);
+
+        // address -> index
+        assert_eq!(function0.get_instruction_index(0x00), Some(0));
+        assert_eq!(function0.get_instruction_index(0x08), Some(1));
+        assert_eq!(function0.get_instruction_index(0x10), Some(2));
+        assert_eq!(function0.get_instruction_index(0x12), Some(3));
+        assert_eq!(function0.get_instruction_index(0x16), Some(4));
+        assert_eq!(function0.get_instruction_index(0x22), Some(5));
+
+        // not the start of an instruction
+        assert_eq!(function0.get_instruction_index(0x04), None);
+        assert_eq!(function0.get_instruction_index(0x03), None);
+        assert_eq!(function0.get_instruction_index(0x24), None);
+    }
+
+    #[test]
+    fn test_get_opcode() {
+        for opcode in OPCODES {
+            let opcode_integer = opcode as u16;
+            assert_eq!(
+                get_opcode(opcode_integer).map(|item| item as u16),
+                Some(opcode_integer)
+            );
+        }
+
+        assert!(get_opcode(0xff00).is_none());
+        assert!(get_opcode(0xffff).is_none());
+    }
+
+    #[test]
+    fn test_predecode_invalid_opcode() {
+        // The opcode 0xff00 doesn't exist, it is decoded as an instruction
+        // without parameters, which raises the "invalid opcode" trap.
+        let code0 = [
+            vec![0x00, 0xff],
+            BytecodeWriterHelper::new()
+                .append_opcode(Opcode::end)
+                .to_bytes(),
+        ]
+        .concat();
+
+        let function0 = PredecodedFunction::new(&code0, 0, code0.len());
+        assert_eq!(function0.instructions.len(), 2);
+        assert_eq!(function0.instructions[0].length_in_bytes, 2);
+        assert!(matches!(
+            function0.instructions[0].handler,
+            PredecodedHandler::Plain(_)
+        ));
+    }
+
+    #[test]
+    fn test_process_predecoded_function() {
+        let binary0 = build_binary_with_accumulate_function();
+        let resource0 = InMemoryProgramSource::with_property(
+            vec![binary0],
+            ProcessProperty {
+                predecode: true,
+                ..ProcessProperty::default()
+            },
+        );
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        assert!(thread_context0.predecode);
+
+        let result0 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(0), ForeignValue::U32(100)],
+        );
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(5050)]);
+
+        // the function is cached after the first execution.
+        assert!(thread_context0.module_common_instances[0].predecoded_functions[0].is_some());
+
+        let result1 = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(10), ForeignValue::U32(5)],
+        );
+        assert_eq!(result1.unwrap(), vec![ForeignValue::U32(25)]);
+    }
+
+    #[test]
+    fn test_process_predecoded_function_call() {
+        // fn0 () -> (i32)
+        //     imm_i32(11)
+        //     imm_i32(13)
+        //     call(1)
+        //     imm_i32(17)
+        //     call(1)
+        //     end
+        //
+        // fn1 (a: i32, b: i32) -> (i32)
+        //     local_load_i32_u(0, 0)
+        //     local_load_i32_u(0, 1)
+        //     add_i32
+        //     end
+        //
+        // assert () -> (41)
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::imm_i32, 13)
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode_i32(Opcode::imm_i32, 17)
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+            ],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.predecode = true;
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(41)]);
+    }
+
+    #[test]
+    fn test_process_predecoded_function_with_fuel_slices() {
+        let binary0 = build_binary_with_accumulate_function();
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.predecode = true;
+
+        const FUEL_SLICE: u64 = 64;
+        thread_context0.set_fuel(Some(FUEL_SLICE));
+
+        let mut result = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(0), ForeignValue::U32(100)],
+        );
+
+        let mut slices = 1;
+        while let Err(ProcessorError {
+            error_type: ProcessorErrorType::OutOfFuel,
+            ..
+        }) = result
+        {
+            // switch between the two interpreter loops on each slice.
+            thread_context0.predecode = !thread_context0.predecode;
+            thread_context0.add_fuel(FUEL_SLICE);
+            result = resume_function(&mut thread_context0, 0, 0);
+            slices += 1;
+        }
+
+        assert_eq!(result.unwrap(), vec![ForeignValue::U32(5050)]);
+        assert!(slices > 1);
+    }
+
+    #[test]
+    fn test_process_predecoded_function_trap() {
+        // fn () -> (i32)
+        //     imm_i32(11)      ;; 0x0000
+        //     imm_i32(0)       ;; 0x0008
+        //     div_i32_u        ;; 0x0010
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode(Opcode::div_i32_u)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+        thread_context0.predecode = true;
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Trap {
+                    kind: TrapKind::IntegerDivideByZero,
+                    module_index: 0,
+                    function_internal_index: 0,
+                    instruction_address: 0x10,
+                },
+                ..
+            })
+        ));
+    }
+}
@@ -9,7 +9,9 @@ use anc_isa::{ForeignValue, OperandDataType, OPERAND_SIZE_IN_BYTES};
 use anc_stack::ProgramCounter;
 
 use crate::{
+    backtrace::capture_backtrace,
     instruction_handler::{get_instruction_handler, HandleResult},
+    predecode::process_continuous_predecoded_instructions,
     ProcessorError, ProcessorErrorType,
 };
 
@@ -27,11 +29,36 @@ pub fn process_function(
     function_public_index: usize,
     arguments: &[ForeignValue],
 ) -> Result<Vec<ForeignValue>, ProcessorError> {
+    let results = prepare_function(
+        thread_context,
+        module_index,
+        function_public_index,
+        arguments,
+    )?;
+
+    // Start processing instructions.
+    process_continuous_instructions(/* handler, */ thread_context)
+        .map_err(|error_type| build_processor_error(thread_context, error_type))?;
+
+    Ok(pop_results(thread_context, &results))
+}
+
+/// Pushes the arguments, creates the frame of the entry function and
+/// sets the program counter to the first instruction of the function.
+///
+/// Returns the data types of the results of the function.
+pub(crate) fn prepare_function(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_public_index: usize,
+    arguments: &[ForeignValue],
+) -> Result<Vec<OperandDataType>, ProcessorError> {
     // Reset the stack before function execution.
     thread_context.stack.reset();
 
-    let target_function_object =
-        thread_context.get_target_function_object(module_index, function_public_index);
+    let target_function_object = thread_context
+        .get_target_function_object(module_index, function_public_index)
+        .map_err(|_| ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
     let function_info = thread_context.get_function_info(
         target_function_object.module_index,
         target_function_object.function_internal_index,
@@ -97,15 +124,63 @@ pub fn process_function(
     thread_context.pc.function_internal_index = target_function_object.function_internal_index;
     thread_context.pc.instruction_address = function_info.code_offset;
 
-    // Start processing instructions.
-    if let Some(terminate_code) =
-        process_continuous_instructions(/* handler, */ thread_context)
-    {
-        return Err(ProcessorError::new(ProcessorErrorType::Terminate(
-            terminate_code,
-        )));
+    Ok(results)
+}
+
+/// Resumes a function call that was suspended because the instruction
+/// budget ("fuel") of the thread was exhausted, or the thread was interrupted.
+///
+/// The `module_index` and `function_public_index` must be the same as the
+/// ones passed to the `process_function()` which returned `OutOfFuel` or
+/// `Interrupted`, they are used to get the results of the function.
+///
+/// The execution continues from the current program counter, so the
+/// fuel should be topped up (via `ThreadContext::add_fuel()` or
+/// `ThreadContext::set_fuel()`), or the interrupt handle should be
+/// cleared, before calling this function.
+pub fn resume_function(
+    thread_context: &mut ThreadContext,
+    module_index: usize,
+    function_public_index: usize,
+) -> Result<Vec<ForeignValue>, ProcessorError> {
+    let target_function_object = thread_context
+        .get_target_function_object(module_index, function_public_index)
+        .map_err(|_| ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
+    let function_info = thread_context.get_function_info(
+        target_function_object.module_index,
+        target_function_object.function_internal_index,
+    );
+
+    let results = thread_context.module_common_instances[target_function_object.module_index]
+        .type_section
+        .get_item_params_and_results(function_info.type_index)
+        .1
+        .to_vec();
+
+    process_continuous_instructions(/* handler, */ thread_context)
+        .map_err(|error_type| build_processor_error(thread_context, error_type))?;
+
+    Ok(pop_results(thread_context, &results))
+}
+
+/// Converts the error raised by the instruction processing loop into `ProcessorError`,
+/// the VM backtrace is attached when the program terminated or trapped.
+pub(crate) fn build_processor_error(
+    thread_context: &ThreadContext,
+    error_type: ProcessorErrorType,
+) -> ProcessorError {
+    match error_type {
+        ProcessorErrorType::Terminate(_) | ProcessorErrorType::Trap { .. } => {
+            ProcessorError::with_backtrace(error_type, capture_backtrace(thread_context))
+        }
+        _ => ProcessorError::new(error_type),
     }
+}
 
+pub(crate) fn pop_results(
+    thread_context: &mut ThreadContext,
+    results: &[OperandDataType],
+) -> Vec<ForeignValue> {
     // Pop results from the stack.
     // ---------------------------
     // Results are popped from the top of the stack and become the last elements of the result array.
@@ -120,7 +195,7 @@ pub fn process_function(
     // Do not use the `pop_xxx` functions to pop results, as they require a stack frame.
     // After the entry function finishes, the stack has no frame.
     let result_operands = thread_context.stack.pop_last_operands(results.len());
-    let result_values = results
+    results
         .iter()
         .enumerate()
         .map(|(idx, dt)| match dt {
@@ -145,39 +220,97 @@ pub fn process_function(
                     .unwrap(),
             )),
         })
-        .collect::<Vec<_>>();
-
-    Ok(result_values)
+        .collect::<Vec<_>>()
 }
 
 pub fn process_continuous_instructions(
     thread_context: &mut ThreadContext,
-) -> Option<i32> /* terminate code */ {
+) -> Result<(), ProcessorErrorType> {
+    if thread_context.predecode {
+        return process_continuous_predecoded_instructions(thread_context);
+    }
+
     loop {
-        let result = process_instruction(/*handler, */ thread_context);
-        match result {
-            HandleResult::Move(relate_offset_in_bytes) => {
-                let next_instruction_offset =
-                    thread_context.pc.instruction_address as isize + relate_offset_in_bytes;
-                thread_context.pc.instruction_address = next_instruction_offset as usize;
-            }
-            HandleResult::Jump(return_pc) => {
-                thread_context.pc.module_index = return_pc.module_index;
-                thread_context.pc.function_internal_index = return_pc.function_internal_index;
-                thread_context.pc.instruction_address = return_pc.instruction_address;
-            }
-            HandleResult::End(original_pc) => {
-                thread_context.pc.module_index = original_pc.module_index;
-                thread_context.pc.function_internal_index = original_pc.function_internal_index;
-                thread_context.pc.instruction_address = original_pc.instruction_address;
-
-                // Break the instruction processing loop.
-                break None;
-            }
-            HandleResult::Terminate(terminate_code) => {
-                // Break the instruction processing loop with terminate code.
-                break Some(terminate_code);
-            }
+        if let Some(result) = process_next_metered_instruction(thread_context) {
+            break result;
+        }
+    }
+}
+
+/// Checks the interrupt handle and consumes one unit of fuel, and then executes
+/// the instruction at the current program counter.
+///
+/// The return value is the same as `process_next_instruction()`.
+#[inline]
+pub(crate) fn process_next_metered_instruction(
+    thread_context: &mut ThreadContext,
+) -> Option<Result<(), ProcessorErrorType>> {
+    // Check whether the host has requested to stop this thread (or the whole process).
+    // The execution is stopped before the current instruction is executed,
+    // so the execution can be resumed after the interrupt handle is cleared.
+    if thread_context.is_interrupted() {
+        return Some(Err(ProcessorErrorType::Interrupted));
+    }
+
+    // Consume one unit of fuel for each instruction.
+    // When the fuel is exhausted, the execution is stopped before the current
+    // instruction is executed, so the program counter and the stack
+    // remain valid and the execution can be resumed later.
+    if let Some(fuel) = thread_context.fuel.as_mut() {
+        if *fuel == 0 {
+            return Some(Err(ProcessorErrorType::OutOfFuel));
+        }
+        *fuel -= 1;
+    }
+
+    process_next_instruction(thread_context)
+}
+
+/// Executes the instruction at the current program counter and updates the program counter.
+///
+/// Returns:
+/// - `None`: the execution of the current "function calling path" continues.
+/// - `Some(Ok(()))`: the current "function calling path" is finished.
+/// - `Some(Err(...))`: the program is terminated or trapped.
+#[inline]
+pub(crate) fn process_next_instruction(
+    thread_context: &mut ThreadContext,
+) -> Option<Result<(), ProcessorErrorType>> {
+    let result = process_instruction(/*handler, */ thread_context);
+    match result {
+        HandleResult::Move(relate_offset_in_bytes) => {
+            let next_instruction_offset =
+                thread_context.pc.instruction_address as isize + relate_offset_in_bytes;
+            thread_context.pc.instruction_address = next_instruction_offset as usize;
+            None
+        }
+        HandleResult::Jump(return_pc) => {
+            thread_context.pc.module_index = return_pc.module_index;
+            thread_context.pc.function_internal_index = return_pc.function_internal_index;
+            thread_context.pc.instruction_address = return_pc.instruction_address;
+            None
+        }
+        HandleResult::End(original_pc) => {
+            thread_context.pc.module_index = original_pc.module_index;
+            thread_context.pc.function_internal_index = original_pc.function_internal_index;
+            thread_context.pc.instruction_address = original_pc.instruction_address;
+
+            // Break the instruction processing loop.
+            Some(Ok(()))
+        }
+        HandleResult::Terminate(terminate_code) => {
+            // Break the instruction processing loop with terminate code.
+            Some(Err(ProcessorErrorType::Terminate(terminate_code)))
+        }
+        HandleResult::Trap(kind) => {
+            // Break the instruction processing loop with the trap and its location.
+            // The program counter still points to the faulting instruction.
+            Some(Err(ProcessorErrorType::Trap {
+                kind,
+                module_index: thread_context.pc.module_index,
+                function_internal_index: thread_context.pc.function_internal_index,
+                instruction_address: thread_context.pc.instruction_address,
+            }))
         }
     }
 }
@@ -188,3 +321,452 @@ fn process_instruction(thread_context: &mut ThreadContext) -> HandleResult {
     let function = get_instruction_handler(opcode_num);
     function(/* handler, */ thread_context)
 }
+
+#[cfg(test)]
+mod tests {
+    use anc_context::{process_property::ProcessProperty, program_source::ProgramSource};
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadWriteDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_data_and_external_functions,
+            helper_build_module_binary_with_single_function,
+            helper_build_module_binary_with_single_function_and_blocks, HelperBlockEntry,
+            HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::EnvCallNum,
+        in_memory_program_source::InMemoryProgramSource,
+        process::{process_function, resume_function},
+        program::start_program,
+        ProcessorError, ProcessorErrorType,
+    };
+
+    #[test]
+    fn test_process_out_of_fuel() {
+        // fn () -> ()
+        //     recur(0)     ;; infinite loop
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::recur, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // unlimited by default
+        assert_eq!(thread_context0.get_fuel(), None);
+
+        thread_context0.set_fuel(Some(100));
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::OutOfFuel,
+                ..
+            })
+        ));
+        assert_eq!(thread_context0.get_fuel(), Some(0));
+
+        // resume with more fuel
+        thread_context0.add_fuel(50);
+        assert_eq!(thread_context0.get_fuel(), Some(50));
+
+        let result1 = resume_function(&mut thread_context0, 0, 0);
+        assert!(matches!(
+            result1,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::OutOfFuel,
+                ..
+            })
+        ));
+        assert_eq!(thread_context0.get_fuel(), Some(0));
+    }
+
+    #[test]
+    fn test_process_resume_with_fuel_slices() {
+        // fn accu (sum/0:i32, n/1:i32) -> (i32)
+        //                              ;; sum = sum + n
+        //     local_load32(0, 0)
+        //     local_load32(0, 1)
+        //     add_i32
+        //     local_store_i32(0, 0)
+        //                              ;; n = n - 1
+        //     local_load32(0, 1)
+        //     sub_imm_i32(1)
+        //     local_store_i32(0, 1)
+        //                              ;; if n > 0 recur (sum,n)
+        //     local_load32(0, 1)
+        //     imm_i32(0)
+        //     gt_i32_u
+        //     block_nez () -> ()
+        //         local_load32(0, 0)
+        //         local_load32(0, 1)
+        //         recur(1)
+        //     end
+        //     local_load32(0, 0)       ;; load sum
+        // end
+        //
+        // assert (0, 100) -> (5050)
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode_i16_i32(Opcode::local_store_i32, 0, 0)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode_i16(Opcode::sub_imm_i32, 1)
+            .append_opcode_i16_i32(Opcode::local_store_i32, 0, 1)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode(Opcode::gt_i32_u)
+            .append_opcode_i32_i32(Opcode::block_nez, 1, 0x26)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode_i16_i32(Opcode::recur, 1, 0)
+            // block end
+            .append_opcode(Opcode::end)
+            //
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_blocks(
+            vec![OperandDataType::I32, OperandDataType::I32], // params
+            vec![OperandDataType::I32],                       // results
+            vec![],                                           // local variables
+            code0,
+            vec![HelperBlockEntry {
+                params: vec![],
+                results: vec![],
+                local_variable_item_entries_without_args: vec![],
+            }], // blocks
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        const FUEL_SLICE: u64 = 64;
+        thread_context0.set_fuel(Some(FUEL_SLICE));
+
+        let mut result = process_function(
+            &mut thread_context0,
+            0,
+            0,
+            &[ForeignValue::U32(0), ForeignValue::U32(100)],
+        );
+
+        let mut slices = 1;
+        while let Err(ProcessorError {
+            error_type: ProcessorErrorType::OutOfFuel,
+            ..
+        }) = result
+        {
+            thread_context0.add_fuel(FUEL_SLICE);
+            result = resume_function(&mut thread_context0, 0, 0);
+            slices += 1;
+        }
+
+        assert_eq!(result.unwrap(), vec![ForeignValue::U32(5050)]);
+
+        // the function executes more than 100 instructions,
+        // so it must have been suspended several times.
+        assert!(slices > 1);
+    }
+
+    #[test]
+    fn test_process_start_program_out_of_fuel() {
+        // fn () -> (i32)
+        //     recur(0)     ;; infinite loop
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::recur, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let process_property = ProcessProperty {
+            fuel: Some(1000),
+            ..ProcessProperty::default()
+        };
+
+        let resource0 = InMemoryProgramSource::with_property(vec![binary0], process_property);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let result0 = start_program(&process_context0, "_start", vec![]);
+
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::OutOfFuel,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_process_interrupt_thread() {
+        // fn () -> ()
+        //     recur(0)     ;; infinite loop
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::recur, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        let interrupt_handle0 = thread_context0.get_interrupt_handle();
+        let watchdog = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            interrupt_handle0.interrupt();
+        });
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Interrupted,
+                ..
+            })
+        ));
+
+        watchdog.join().unwrap();
+
+        // the flag remains set until it is cleared.
+        let result1 = resume_function(&mut thread_context0, 0, 0);
+        assert!(matches!(
+            result1,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Interrupted,
+                ..
+            })
+        ));
+
+        // clear the flag and resume with limited fuel.
+        thread_context0.get_interrupt_handle().clear();
+        thread_context0.set_fuel(Some(100));
+
+        let result2 = resume_function(&mut thread_context0, 0, 0);
+        assert!(matches!(
+            result2,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::OutOfFuel,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_process_interrupt_process() {
+        // fn () -> (i32)
+        //     recur(0)     ;; infinite loop
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::recur, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+
+        let interrupt_handle0 = process_context0.get_interrupt_handle();
+        let watchdog = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            interrupt_handle0.interrupt();
+        });
+
+        let result0 = start_program(&process_context0, "_start", vec![]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Interrupted,
+                ..
+            })
+        ));
+
+        watchdog.join().unwrap();
+    }
+
+    #[test]
+    fn test_process_interrupt_child_thread() {
+        // fn _start () -> (i32)
+        //     (thread_create 1 0 0)
+        //     (thread_wait_and_collect)    ;; -> (exit_code, thread_error_number)
+        // end
+        //
+        // fn child () -> (i32)
+        //     recur(0)     ;; infinite loop
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 1) // function public index
+            .append_opcode_i64(Opcode::imm_i64, 0) // thread start data access index
+            .append_opcode_i64(Opcode::imm_i64, 0) // thread start data length
+            .append_opcode_i32(Opcode::envcall, EnvCallNum::thread_create as u32)
+            .append_opcode_i32(Opcode::envcall, EnvCallNum::thread_wait_and_collect as u32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::recur, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
+            &[
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+            ],
+            &[],
+            &[ReadWriteDataEntry::from_i64(0)],
+            &[],
+            &[],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+
+        let interrupt_handle0 = process_context0.get_interrupt_handle();
+        let watchdog = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            interrupt_handle0.interrupt();
+        });
+
+        // The main thread is blocked in `thread_wait_and_collect` until the child
+        // thread is stopped, and then it is stopped at the next instruction.
+        let result0 = start_program(&process_context0, "_start", vec![]);
+        assert!(matches!(
+            result0,
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::Interrupted,
+                ..
+            })
+        ));
+
+        watchdog.join().unwrap();
+    }
+
+    #[test]
+    fn test_process_child_thread_failure() {
+        // fn _start () -> (i32)
+        //     (thread_create 1 0 0)
+        //     (thread_wait_and_collect)    ;; -> (exit_code, thread_error_number)
+        //     add_i32
+        // end
+        //
+        // fn child () -> (i32)
+        //     ...                          ;; trap or terminate
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 1) // function public index
+            .append_opcode_i64(Opcode::imm_i64, 0) // thread start data access index
+            .append_opcode_i64(Opcode::imm_i64, 0) // thread start data length
+            .append_opcode_i32(Opcode::envcall, EnvCallNum::thread_create as u32)
+            .append_opcode_i32(Opcode::envcall, EnvCallNum::thread_wait_and_collect as u32)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        // trap: integer divide by zero
+        let code_trap = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 1)
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode(Opcode::div_i32_s)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        // terminate with code 0x11
+        let code_terminate = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::terminate, 0x11)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        for (code1, expected) in [
+            (code_trap, 3),             // thread_error_number: trapped
+            (code_terminate, 0x11 + 4), // exit_code + thread_error_number: terminated
+        ] {
+            let binary0 = helper_build_module_binary_with_functions_and_data_and_external_functions(
+                &[
+                    HelperFunctionEntry {
+                        params: vec![],
+                        results: vec![OperandDataType::I32],
+                        local_variable_item_entries_without_args: vec![],
+                        code: code0.clone(),
+                    },
+                    HelperFunctionEntry {
+                        params: vec![],
+                        results: vec![OperandDataType::I32],
+                        local_variable_item_entries_without_args: vec![],
+                        code: code1,
+                    },
+                ],
+                &[],
+                &[ReadWriteDataEntry::from_i64(0)],
+                &[],
+                &[],
+                &[],
+            );
+
+            let resource0 = InMemoryProgramSource::new(vec![binary0]);
+            let process_context0 = resource0.create_process_context().unwrap();
+
+            // The failure of the child thread does not stop the main thread.
+            let result0 = start_program(&process_context0, "_start", vec![]);
+            assert_eq!(result0.unwrap(), expected);
+        }
+    }
+}
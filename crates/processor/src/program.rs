@@ -8,8 +8,8 @@ use anc_context::process_context::ProcessContext;
 use anc_isa::ForeignValue;
 
 use crate::{
+    debug_adapter::process_function_or_debug,
     multithread_handler::{PROCESS_CONTEXT_ADDRESS, THREAD_START_DATA},
-    process::process_function,
     ProcessorError, ProcessorErrorType,
 };
 
@@ -65,11 +65,12 @@ pub fn start_program(
 
     const MAIN_MODULE_INDEX: usize = 0;
 
-    let result_foreign_values = process_function(
+    let result_foreign_values = process_function_or_debug(
         &mut thread_context,
         MAIN_MODULE_INDEX,
         function_public_index,
         &[],
+        "main",
     );
 
     match result_foreign_values {
@@ -9,148 +9,351 @@
 //
 // call external functon from Rust application example:
 //
+// ```rust
+// let fn_add = get_function::<extern "C" fn(i32, i32) -> i32>(
+//     &mut thread_context,
+//     "main",
+//     "add",
+// )
+// .unwrap();
+//
+// assert_eq!(fn_add(11, 13), 24);
+// ```
+//
+// access the exported data from Rust application example:
+//
+// ```rust
+// let mut config = get_data_view_mut(&mut thread_context, "main", "config").unwrap();
+// config.write_str("verbose=1").unwrap();
+// config.write_u32(12, 0x11).unwrap();
+// ```
+//
+// note that the bridge function holds the address of the thread context,
+// so the thread context must not be moved or dropped while the bridge function is in use.
+//
 // ref:
 // https://doc.rust-lang.org/nomicon/ffi.html
 // https://doc.rust-lang.org/book/ch19-01-unsafe-rust.html
 // https://doc.rust-lang.org/stable/reference/items/functions.html
 
 use anc_context::thread_context::ThreadContext;
+use anc_isa::DataSectionType;
 
-use crate::{
-    bridge_handler::{get_or_create_bridge_data, get_or_create_bridge_function},
-    ProcessorErrorType, ProcessorError,
-};
+use crate::{bridge_handler::get_or_create_bridge_function, ProcessorError, ProcessorErrorType};
 
-// create a new bridge function and map it to the specified VM function.
-//
-// return the existing one if the bridge function corresponding
-// to the specified function has already been created.
+/// Creates a new bridge function and maps it to the specified VM function,
+/// the type `T` should be an `extern "C" fn` which has the same signature
+/// as the VM function.
+///
+/// Returns the existing one if the bridge function corresponding
+/// to the specified function has already been created.
+///
+/// Returns `DataTypeMissmatch` if the size of type `T` is not the size of
+/// a function pointer. Note that the parameters of `T` can not be checked,
+/// calling the function with a mismatched signature is undefined behavior.
 pub fn get_function<T>(
     thread_context: &mut ThreadContext,
     module_name: &str,
-    function_name: &str,
+    function_full_name: &str,
 ) -> Result<T, ProcessorError> {
-    todo!(); // find_function_by_full_name returns target function internal index
-    let (module_index, function_public_index) = thread_context
-        .find_function_by_full_name(module_name, function_name)
+    if std::mem::size_of::<T>() != std::mem::size_of::<*const u8>() {
+        return Err(ProcessorError::new(ProcessorErrorType::DataTypeMissmatch));
+    }
+
+    let (target_module_index, function_internal_index) = thread_context
+        .find_function_by_full_name(module_name, function_full_name)
         .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
 
     let function_ptr = get_or_create_bridge_function(
-        handler,
         thread_context,
-        module_index,
-        function_public_index,
+        target_module_index,
+        function_internal_index,
     )?;
     let function = unsafe { std::mem::transmute_copy(&function_ptr) };
     Ok(function)
 }
 
+/// Returns the pointer to the specified data.
+///
+/// Returns `DataTypeMissmatch` if the size of type `T` exceeds the length of the data.
 pub fn get_data<T>(
     thread_context: &mut ThreadContext,
     module_name: &str,
-    data_name: &str,
+    data_full_name: &str,
 ) -> Result<*const T, ProcessorError>
 where
     T: Sized,
 {
-    let (module_index, data_public_index) = thread_context
-        .find_data_public_index_by_name_path(module_name, data_name)
-        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
-
-    let data_ptr = get_or_create_bridge_data(
+    let data_ptr = get_data_ptr(
         thread_context,
-        module_index,
-        data_public_index,
-        0,
+        module_name,
+        data_full_name,
         std::mem::size_of::<T>(),
     )?;
 
     Ok(data_ptr as *const T)
 }
 
+/// Returns the mutable pointer to the specified data.
+///
+/// Returns `DataTypeMissmatch` if the size of type `T` exceeds the length of the data.
 pub fn get_data_mut<T>(
     thread_context: &mut ThreadContext,
     module_name: &str,
-    data_name: &str,
+    data_full_name: &str,
 ) -> Result<*mut T, ProcessorError>
 where
     T: Sized,
 {
-    let (module_index, data_public_index) = thread_context
-        .find_data_public_index_by_name_path(module_name, data_name)
-        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
-
-    let data_ptr = get_or_create_bridge_data(
+    let data_ptr = get_data_ptr(
         thread_context,
-        module_index,
-        data_public_index,
-        0,
+        module_name,
+        data_full_name,
         std::mem::size_of::<T>(),
     )?;
 
     Ok(data_ptr as *mut T)
 }
 
+fn get_data_ptr(
+    thread_context: &ThreadContext,
+    module_name: &str,
+    data_full_name: &str,
+    data_length_in_bytes: usize,
+) -> Result<*const u8, ProcessorError> {
+    let (target_module_index, data_section_type, data_internal_index_in_section) = thread_context
+        .find_data_by_full_name(module_name, data_full_name)
+        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
+
+    let accessor = thread_context.module_common_instances[target_module_index].datas
+        [data_section_type as usize]
+        .as_ref();
 
-// //     pub fn find_bridge_function(
-// //         &self,
-// //         target_module_index: usize,
-// //         function_internal_index: usize,
-// //     ) -> Option<*const u8> {
-// //         find_delegate_function(
-// //             &self.bridge_function_module_items,
-// //             target_module_index,
-// //             function_internal_index,
-// //         )
-// //     }
-// //
-// //     pub fn find_bridge_callback_function(
-// //         &self,
-// //         target_module_index: usize,
-// //         function_internal_index: usize,
-// //     ) -> Option<*const u8> {
-// //         find_delegate_function(
-// //             &self.callback_function_entries,
-// //             target_module_index,
-// //             function_internal_index,
-// //         )
-// //     }
-// //
-// //     pub fn insert_bridge_function(
-// //         &mut self,
-// //         target_module_index: usize,
-// //         function_internal_index: usize,
-// //         bridge_function_ptr: *const u8,
-// //     ) {
-// //         insert_delegate_function(
-// //             &mut self.bridge_function_module_items,
-// //             target_module_index,
-// //             function_internal_index,
-// //             bridge_function_ptr,
-// //         );
-// //     }
-// //
-// // pub fn insert_callback_function(
-// //     &mut self,
-// //     target_module_index: usize,
-// //     function_internal_index: usize,
-// //     bridge_function_ptr: *const u8,
-// // ) {
-// //     insert_delegate_function(
-// //         &mut self.callback_function_entries,
-// //         target_module_index,
-// //         function_internal_index,
-// //         bridge_function_ptr,
-// //     );
-// // }
+    if data_length_in_bytes > accessor.get_data_length(data_internal_index_in_section) {
+        return Err(ProcessorError::new(ProcessorErrorType::DataTypeMissmatch));
+    }
+
+    let start_address = accessor.get_start_address_by_index(data_internal_index_in_section);
+    Ok(accessor.get_ptr(start_address, 0))
+}
 
+/// Returns a read-only view of the specified data, the view is bounded
+/// by the length of the data.
+pub fn get_data_view<'b>(
+    thread_context: &'b ThreadContext,
+    module_name: &str,
+    data_full_name: &str,
+) -> Result<DataView<'b>, ProcessorError> {
+    let (target_module_index, data_section_type, data_internal_index_in_section) = thread_context
+        .find_data_by_full_name(module_name, data_full_name)
+        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
+
+    let accessor = thread_context.module_common_instances[target_module_index].datas
+        [data_section_type as usize]
+        .as_ref();
+
+    let data_length = accessor.get_data_length(data_internal_index_in_section);
+    let start_address = accessor.get_start_address_by_index(data_internal_index_in_section);
+    let data_ptr = accessor.get_ptr(start_address, 0);
+
+    Ok(DataView {
+        bytes: unsafe { std::slice::from_raw_parts(data_ptr, data_length) },
+    })
+}
+
+/// Returns a writable view of the specified data, the view is bounded
+/// by the length of the data.
+///
+/// Returns `DataReadOnly` if the data is located in the read-only data section.
+pub fn get_data_view_mut<'b>(
+    thread_context: &'b mut ThreadContext,
+    module_name: &str,
+    data_full_name: &str,
+) -> Result<DataViewMut<'b>, ProcessorError> {
+    let (target_module_index, data_section_type, data_internal_index_in_section) = thread_context
+        .find_data_by_full_name(module_name, data_full_name)
+        .ok_or(ProcessorError::new(ProcessorErrorType::ItemNotFound))?;
+
+    if matches!(data_section_type, DataSectionType::ReadOnly) {
+        return Err(ProcessorError::new(ProcessorErrorType::DataReadOnly));
+    }
+
+    let accessor = thread_context.module_common_instances[target_module_index].datas
+        [data_section_type as usize]
+        .as_mut();
+
+    let data_length = accessor.get_data_length(data_internal_index_in_section);
+    let start_address = accessor.get_start_address_by_index(data_internal_index_in_section);
+    let data_ptr = accessor.get_mut_ptr(start_address, 0);
+
+    Ok(DataViewMut {
+        bytes: unsafe { std::slice::from_raw_parts_mut(data_ptr, data_length) },
+    })
+}
+
+/// A read-only view of a data item.
+pub struct DataView<'b> {
+    bytes: &'b [u8],
+}
+
+/// A writable view of a data item in the read-write or uninit data section.
+pub struct DataViewMut<'b> {
+    bytes: &'b mut [u8],
+}
+
+// generates the methods `read_*` for reading primitive values
+// at the specified offset (in bytes) of the data.
+macro_rules! impl_read_primitives {
+    ($(($method:ident, $type:ty)),*) => {
+        $(
+            pub fn $method(&self, offset_in_bytes: usize) -> Result<$type, ProcessorError> {
+                let bytes = self.read_bytes(offset_in_bytes, std::mem::size_of::<$type>())?;
+                Ok(<$type>::from_ne_bytes(bytes.try_into().unwrap()))
+            }
+        )*
+    };
+}
+
+// generates the methods `write_*` for writing primitive values
+// at the specified offset (in bytes) of the data.
+macro_rules! impl_write_primitives {
+    ($(($method:ident, $type:ty)),*) => {
+        $(
+            pub fn $method(&mut self, offset_in_bytes: usize, value: $type) -> Result<(), ProcessorError> {
+                self.write_bytes(offset_in_bytes, &value.to_ne_bytes())
+            }
+        )*
+    };
+}
+
+// generates the methods which are shared by `DataView` and `DataViewMut`.
+macro_rules! impl_data_view_common {
+    () => {
+        /// Returns the length of the data in bytes.
+        pub fn len(&self) -> usize {
+            self.bytes.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.bytes.is_empty()
+        }
+
+        pub fn as_bytes(&self) -> &[u8] {
+            self.bytes
+        }
+
+        /// Returns `DataOutOfBounds` if the range exceeds the length of the data.
+        pub fn read_bytes(
+            &self,
+            offset_in_bytes: usize,
+            length_in_bytes: usize,
+        ) -> Result<&[u8], ProcessorError> {
+            get_range(self.bytes.len(), offset_in_bytes, length_in_bytes)
+                .map(|range| &self.bytes[range])
+        }
+
+        /// Reads a UTF-8 string from the specified range.
+        ///
+        /// Returns `DataTypeMissmatch` if the bytes are not valid UTF-8.
+        pub fn read_str(
+            &self,
+            offset_in_bytes: usize,
+            length_in_bytes: usize,
+        ) -> Result<&str, ProcessorError> {
+            let bytes = self.read_bytes(offset_in_bytes, length_in_bytes)?;
+            std::str::from_utf8(bytes)
+                .map_err(|_| ProcessorError::new(ProcessorErrorType::DataTypeMissmatch))
+        }
+
+        /// Reads the whole data as a UTF-8 string, the string ends at
+        /// the first `\0` (if any), i.e. the padding zeros are excluded.
+        pub fn to_str(&self) -> Result<&str, ProcessorError> {
+            let length = self
+                .bytes
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(self.bytes.len());
+            self.read_str(0, length)
+        }
+
+        impl_read_primitives!(
+            (read_u8, u8),
+            (read_i8, i8),
+            (read_u16, u16),
+            (read_i16, i16),
+            (read_u32, u32),
+            (read_i32, i32),
+            (read_u64, u64),
+            (read_i64, i64),
+            (read_f32, f32),
+            (read_f64, f64)
+        );
+    };
+}
+
+impl DataView<'_> {
+    impl_data_view_common!();
+}
+
+impl DataViewMut<'_> {
+    impl_data_view_common!();
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.bytes
+    }
+
+    /// Returns `DataOutOfBounds` if the range exceeds the length of the data.
+    pub fn write_bytes(
+        &mut self,
+        offset_in_bytes: usize,
+        value: &[u8],
+    ) -> Result<(), ProcessorError> {
+        let range = get_range(self.bytes.len(), offset_in_bytes, value.len())?;
+        self.bytes[range].copy_from_slice(value);
+        Ok(())
+    }
+
+    /// Writes the string from the beginning of the data, and fills
+    /// the remaining bytes with `\0`.
+    ///
+    /// Returns `DataOutOfBounds` if the string is longer than the data.
+    pub fn write_str(&mut self, value: &str) -> Result<(), ProcessorError> {
+        self.write_bytes(0, value.as_bytes())?;
+        self.bytes[value.len()..].fill(0);
+        Ok(())
+    }
+
+    impl_write_primitives!(
+        (write_u8, u8),
+        (write_i8, i8),
+        (write_u16, u16),
+        (write_i16, i16),
+        (write_u32, u32),
+        (write_i32, i32),
+        (write_u64, u64),
+        (write_i64, i64),
+        (write_f32, f32),
+        (write_f64, f64)
+    );
+}
+
+fn get_range(
+    data_length: usize,
+    offset_in_bytes: usize,
+    length_in_bytes: usize,
+) -> Result<std::ops::Range<usize>, ProcessorError> {
+    match offset_in_bytes.checked_add(length_in_bytes) {
+        Some(end) if end <= data_length => Ok(offset_in_bytes..end),
+        _ => Err(ProcessorError::new(ProcessorErrorType::DataOutOfBounds)),
+    }
+}
 
 #[cfg(test)]
 mod tests {
-    use anc_context::process_resource::ProgramSource;
+    use anc_context::program_source::ProgramSource;
     use anc_image::{
         bytecode_writer::BytecodeWriterHelper,
-        entry::InitedDataEntry,
+        entry::{ReadOnlyDataEntry, ReadWriteDataEntry},
         utils::{
             helper_build_module_binary_with_single_function,
             helper_build_module_binary_with_single_function_and_data,
@@ -159,9 +362,10 @@ mod tests {
     use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
 
     use crate::{
-        bridge_process::{get_data, get_data_mut, get_function},
         in_memory_program_source::InMemoryProgramSource,
         process::process_function,
+        program_embed::{get_data, get_data_mut, get_data_view, get_data_view_mut, get_function},
+        ProcessorError, ProcessorErrorType,
     };
 
     #[test]
@@ -172,12 +376,13 @@ mod tests {
         //
         // bytecode
         //
-        // 0x0000 local_load32         0 0 0
-        // 0x0008 local_load32         0 0 1
+        // 0x0000 local_load_i32_u     0 0
+        // 0x0008 local_load_i32_u     0 1
         // 0x0010 add_i32
+        // 0x0012 end
         let code0 = BytecodeWriterHelper::new()
-            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0, 0)
-            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0, 1)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
             .append_opcode(Opcode::add_i32)
             .append_opcode(Opcode::end)
             .to_bytes();
@@ -189,27 +394,129 @@ mod tests {
             code0,
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
 
-        let fn_add = get_function::<extern "C" fn(i32, i32) -> i32>(
-            &handler,
-            &mut thread_context0,
-            "main",
-            "func0",
-        )
-        .unwrap();
+        let fn_add =
+            get_function::<extern "C" fn(i32, i32) -> i32>(&mut thread_context0, "main", "func0")
+                .unwrap();
 
         assert_eq!(fn_add(11, 13), 24);
         assert_eq!(fn_add(23, 29), 52);
+
+        // the existing bridge function is returned
+        let fn_add_again =
+            get_function::<extern "C" fn(i32, i32) -> i32>(&mut thread_context0, "main", "func0")
+                .unwrap();
+        assert_eq!(fn_add_again as *const u8, fn_add as *const u8);
+    }
+
+    #[test]
+    fn test_get_function_not_found() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // function not found
+        assert!(matches!(
+            get_function::<extern "C" fn()>(&mut thread_context0, "main", "foo"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ItemNotFound,
+                ..
+            })
+        ));
+
+        // module not found
+        assert!(matches!(
+            get_function::<extern "C" fn()>(&mut thread_context0, "foo", "func0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ItemNotFound,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_function_with_wrong_type() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // the type is not a function pointer
+        assert!(matches!(
+            get_function::<u8>(&mut thread_context0, "main", "func0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataTypeMissmatch,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            get_function::<[usize; 2]>(&mut thread_context0, "main", "func0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataTypeMissmatch,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_function_with_multiple_results() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i32(Opcode::imm_i32, 13)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],                                           // params
+            &[OperandDataType::I32, OperandDataType::I32], // results
+            &[],                                           // local variables
+            code0,
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // a C function can only return 0 or 1 value.
+        assert!(matches!(
+            get_function::<extern "C" fn() -> i32>(&mut thread_context0, "main", "func0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ResultsAmountMissmatch,
+                ..
+            })
+        ));
     }
 
     #[test]
     fn test_get_data() {
         let code0 = BytecodeWriterHelper::new()
-            // (param offset_bytes:i16 data_public_index:i32) -> i64
+            // (param offset_bytes:i16 data_public_index:i32) -> i32
             .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
             .append_opcode(Opcode::end)
             .to_bytes();
@@ -219,12 +526,11 @@ mod tests {
             &[OperandDataType::I32], // results
             &[],                     // local variables
             code0,
-            &[InitedDataEntry::from_i32(0x11)],
-            &[InitedDataEntry::from_i32(0x13)],
+            &[ReadOnlyDataEntry::from_i32(0x11)],
+            &[ReadWriteDataEntry::from_i32(0x13)],
             &[],
         );
 
-
         let resource0 = InMemoryProgramSource::new(vec![binary0]);
         let process_context0 = resource0.create_process_context().unwrap();
         let mut thread_context0 = process_context0.create_thread_context();
@@ -240,7 +546,103 @@ mod tests {
             *data1_ptr = 0x17;
         }
 
-        let result0 = process_function( &mut thread_context0, 0, 0, &[]);
-        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0x17),]);
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(result0.unwrap(), vec![ForeignValue::U32(0x17)]);
+
+        // the type is larger than the data
+        assert!(matches!(
+            get_data::<i64>(&mut thread_context0, "main", "data0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataTypeMissmatch,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_data_view() {
+        // fn () -> (i32, i32)
+        //     data_load_i32_u(0, 1)    ;; data1[0..4]
+        //     data_load_i32_u(4, 1)    ;; data1[4..8]
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 4, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_data(
+            &[],                                           // params
+            &[OperandDataType::I32, OperandDataType::I32], // results
+            &[],                                           // local variables
+            code0,
+            &[ReadOnlyDataEntry::from_bytes(b"hello\0\0\0".to_vec(), 1)],
+            &[ReadWriteDataEntry::from_bytes(vec![0u8; 16], 8)],
+            &[],
+        );
+
+        let resource0 = InMemoryProgramSource::new(vec![binary0]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        let mut thread_context0 = process_context0.create_thread_context();
+
+        // read-only data
+        let view0 = get_data_view(&thread_context0, "main", "data0").unwrap();
+        assert_eq!(view0.len(), 8);
+        assert_eq!(view0.to_str().unwrap(), "hello");
+        assert_eq!(view0.read_str(1, 3).unwrap(), "ell");
+        assert_eq!(view0.read_u8(4).unwrap(), b'o');
+        assert!(matches!(
+            view0.read_u64(1),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataOutOfBounds,
+                ..
+            })
+        ));
+
+        // writing to read-only data is refused
+        assert!(matches!(
+            get_data_view_mut(&mut thread_context0, "main", "data0"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataReadOnly,
+                ..
+            })
+        ));
+
+        // read-write data
+        let mut view1 = get_data_view_mut(&mut thread_context0, "main", "data1").unwrap();
+        assert_eq!(view1.as_bytes(), &[0u8; 16]);
+        view1.write_u32(0, 0x11).unwrap();
+        view1.write_i32(4, -13).unwrap();
+        view1.write_f64(8, 3.5).unwrap();
+        assert_eq!(view1.read_f64(8).unwrap(), 3.5);
+        assert!(matches!(
+            view1.write_u32(14, 0x17),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::DataOutOfBounds,
+                ..
+            })
+        ));
+
+        let result0 = process_function(&mut thread_context0, 0, 0, &[]);
+        assert_eq!(
+            result0.unwrap(),
+            vec![ForeignValue::U32(0x11), ForeignValue::U32(-13i32 as u32)]
+        );
+
+        // string helpers
+        let mut view1 = get_data_view_mut(&mut thread_context0, "main", "data1").unwrap();
+        view1.write_str("config=1").unwrap();
+        assert_eq!(view1.to_str().unwrap(), "config=1");
+        assert!(view1.write_str("the string is too long").is_err());
+
+        // not found
+        assert!(matches!(
+            get_data_view(&thread_context0, "main", "foo"),
+            Err(ProcessorError {
+                error_type: ProcessorErrorType::ItemNotFound,
+                ..
+            })
+        ));
     }
 }
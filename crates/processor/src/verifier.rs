@@ -0,0 +1,1622 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Bytecode verifier
+// -----------------
+//
+// The instruction handlers trust the bytecode, e.g. the parameters are read from the
+// bytecode without checking, and the jump instructions move the program counter to
+// whatever address the offsets specify. The verifier checks the functions of the module
+// images in a single linear pass before they are executed, so the malformed bytecode
+// is reported as a `VerificationError` instead of a crash (or a trap) at runtime.
+//
+// The following items are checked:
+//
+// - Each opcode is valid, and no instruction is truncated by the end of the function.
+// - The blocks are nested properly, and the function finishes at its last `end`.
+// - The jump targets of `block_alt`, `block_nez`, `break`, `break_alt` and `recur`
+//   are the addresses which the structure of the blocks implies.
+// - The layers of the local variable access, `break` and `recur` are in the range
+//   of the frames.
+// - The type, local variable list, local variable, data, function and external
+//   function indices are in bounds, and the access of the local variables and the
+//   data (with a constant offset) doesn't exceed their length.
+// - The height and the types of the operands of each block.
+//
+// The operand types are checked loosely, `i32` and `i64` are both treated as integers
+// because the bytecode uses them interchangeably, e.g. the `module_index:i32` and
+// `data_public_index:i32` pushed by `get_data` are consumed as `module_index:i32` and
+// `data_access_index:i64` by `memory_load_*`.
+//
+// Limitations
+// -----------
+//
+// The signatures of `envcall` and `call_dynamic`, and the number of arguments of `syscall`,
+// are unknown at verification time. The operands of the current block are treated as
+// unknown after these instructions, i.e. they can satisfy any operand type and height,
+// until the `end` of the block. The same applies to the unreachable instructions following
+// `break`, `break_alt`, `recur` and `terminate`.
+//
+// The verifier is not invoked automatically, call `verify_module_images()` with the
+// module images of a process context before executing it, e.g.
+//
+// ```rust
+// let process_context = program_source.create_process_context().unwrap();
+// verify_module_images(&process_context.module_images)?;
+// ```
+
+use std::fmt::Display;
+
+use anc_context::module_linking_instance::ModuleLinkingInstance;
+use anc_image::{
+    common_sections::{
+        function_section::FunctionSection, local_variable_section::LocalVariableSection,
+        type_section::TypeSection,
+    },
+    module_image::ModuleImage,
+};
+use anc_isa::{opcode::Opcode, OperandDataType};
+
+use crate::predecode::{get_instruction_param_layout, get_opcode};
+
+const DATA_LENGTH_IN_BYTES_64_BIT: usize = 8;
+const DATA_LENGTH_IN_BYTES_32_BIT: usize = 4;
+const DATA_LENGTH_IN_BYTES_16_BIT: usize = 2;
+const DATA_LENGTH_IN_BYTES_8_BIT: usize = 1;
+
+// The type of the operands which is tracked by the verifier.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OperandType {
+    Integer, // i32 or i64
+    F32,
+    F64,
+}
+
+// Shorthands of the operand types.
+const INT: OperandType = OperandType::Integer;
+const F32: OperandType = OperandType::F32;
+const F64: OperandType = OperandType::F64;
+
+impl From<OperandDataType> for OperandType {
+    fn from(value: OperandDataType) -> Self {
+        match value {
+            OperandDataType::I32 | OperandDataType::I64 => OperandType::Integer,
+            OperandDataType::F32 => OperandType::F32,
+            OperandDataType::F64 => OperandType::F64,
+        }
+    }
+}
+
+impl Display for OperandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperandType::Integer => f.write_str("integer"),
+            OperandType::F32 => f.write_str("f32"),
+            OperandType::F64 => f.write_str("f64"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct VerificationError {
+    pub error_type: VerificationErrorType,
+    pub module_index: usize,
+    pub function_internal_index: usize,
+
+    // The address of the instruction which fails the verification,
+    // it is the address of the function for the errors of the function itself.
+    pub instruction_address: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum VerificationErrorType {
+    CodeOutOfBounds,          // The code of the function exceeds the code section.
+    InvalidOpcode(u16),       // The opcode is invalid.
+    TruncatedInstruction,     // The instruction is truncated by the end of the function.
+    MissingEnd,               // The code of the function finishes before the `end` of the function.
+    CodeAfterEnd,             // There are instructions following the `end` of the function.
+    InvalidJumpTarget,        // The target address doesn't match the structure of the blocks.
+    LayersOutOfBounds(usize), // The layers exceed the number of the enclosing frames.
+    TypeIndexOutOfBounds(usize),
+    LocalVariableListIndexOutOfBounds(usize),
+    LocalVariableIndexOutOfBounds(usize),
+    LocalVariableAccessOutOfBounds(usize), // The access exceeds the length of the local variable.
+    DataIndexOutOfBounds(usize),
+    DataAccessOutOfBounds(usize), // The access exceeds the length of the data.
+    FunctionIndexOutOfBounds(usize),
+    ExternalFunctionIndexOutOfBounds(usize),
+    StackUnderflow, // There are not enough operands in the current block.
+    TypeMismatch {
+        expected: OperandType,
+        found: OperandType,
+    },
+}
+
+impl Display for VerificationErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationErrorType::CodeOutOfBounds => {
+                f.write_str("code of the function out of bounds")
+            }
+            VerificationErrorType::InvalidOpcode(opcode) => {
+                write!(f, "invalid opcode 0x{:04x}", opcode)
+            }
+            VerificationErrorType::TruncatedInstruction => f.write_str("truncated instruction"),
+            VerificationErrorType::MissingEnd => f.write_str("missing the end of the function"),
+            VerificationErrorType::CodeAfterEnd => {
+                f.write_str("instructions following the end of the function")
+            }
+            VerificationErrorType::InvalidJumpTarget => f.write_str("invalid jump target"),
+            VerificationErrorType::LayersOutOfBounds(layers) => {
+                write!(f, "layers {} out of bounds", layers)
+            }
+            VerificationErrorType::TypeIndexOutOfBounds(index) => {
+                write!(f, "type index {} out of bounds", index)
+            }
+            VerificationErrorType::LocalVariableListIndexOutOfBounds(index) => {
+                write!(f, "local variable list index {} out of bounds", index)
+            }
+            VerificationErrorType::LocalVariableIndexOutOfBounds(index) => {
+                write!(f, "local variable index {} out of bounds", index)
+            }
+            VerificationErrorType::LocalVariableAccessOutOfBounds(index) => {
+                write!(f, "access of local variable {} out of bounds", index)
+            }
+            VerificationErrorType::DataIndexOutOfBounds(index) => {
+                write!(f, "data index {} out of bounds", index)
+            }
+            VerificationErrorType::DataAccessOutOfBounds(index) => {
+                write!(f, "access of data {} out of bounds", index)
+            }
+            VerificationErrorType::FunctionIndexOutOfBounds(index) => {
+                write!(f, "function index {} out of bounds", index)
+            }
+            VerificationErrorType::ExternalFunctionIndexOutOfBounds(index) => {
+                write!(f, "external function index {} out of bounds", index)
+            }
+            VerificationErrorType::StackUnderflow => f.write_str("operand stack underflow"),
+            VerificationErrorType::TypeMismatch { expected, found } => {
+                write!(f, "type mismatch, expected {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Verification error: {}, module index: {}, function internal index: {}, instruction address: 0x{:04x}.",
+            self.error_type, self.module_index, self.function_internal_index, self.instruction_address
+        )
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Verifies the bytecode of all functions of the module images,
+/// returns the first error found.
+pub fn verify_module_images<'a>(
+    module_images: &'a [ModuleImage<'a>],
+) -> Result<(), VerificationError> {
+    if module_images.is_empty() {
+        return Ok(());
+    }
+
+    let program = Program {
+        module_linking_instance: ModuleLinkingInstance::new(module_images),
+        modules: module_images.iter().map(ModuleSections::new).collect(),
+    };
+
+    for (module_index, module_sections) in program.modules.iter().enumerate() {
+        for function_internal_index in 0..module_sections.function_section.items.len() {
+            FunctionVerifier::new(&program, module_index, function_internal_index).verify()?;
+        }
+    }
+
+    Ok(())
+}
+
+// The sections of a module which are used by the verification.
+struct ModuleSections<'a> {
+    type_section: TypeSection<'a>,
+    local_variable_section: LocalVariableSection<'a>,
+    function_section: FunctionSection<'a>,
+
+    // The length of each item of each data section, the order
+    // of the sections is the same as `DataSectionType`.
+    data_lengths: [Vec<usize>; 3],
+}
+
+impl<'a> ModuleSections<'a> {
+    fn new(module_image: &'a ModuleImage<'a>) -> Self {
+        let read_only_data_lengths = module_image
+            .get_optional_read_only_data_section()
+            .map(|section| {
+                section
+                    .items
+                    .iter()
+                    .map(|item| item.data_length as usize)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let read_write_data_lengths = module_image
+            .get_optional_read_write_data_section()
+            .map(|section| {
+                section
+                    .items
+                    .iter()
+                    .map(|item| item.data_length as usize)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let uninit_data_lengths = module_image
+            .get_optional_uninit_data_section()
+            .map(|section| {
+                section
+                    .items
+                    .iter()
+                    .map(|item| item.data_length as usize)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            type_section: module_image.get_type_section(),
+            local_variable_section: module_image.get_local_variable_section(),
+            function_section: module_image.get_function_section(),
+            data_lengths: [
+                read_only_data_lengths,
+                read_write_data_lengths,
+                uninit_data_lengths,
+            ],
+        }
+    }
+}
+
+struct Program<'a> {
+    module_linking_instance: ModuleLinkingInstance<'a>,
+    modules: Vec<ModuleSections<'a>>,
+}
+
+// The counterpart of the frame on the stack at verification time.
+struct ControlFrame {
+    params: Vec<OperandType>,
+    results: Vec<OperandType>,
+    local_variable_list_index: usize,
+
+    // The address of the first instruction of the body, i.e. the target of `recur`.
+    body_address: usize,
+
+    // The address following the instruction `end`, which is specified by the instructions
+    // `block_nez`, `break` and `break_alt`, it is checked when the `end` is reached.
+    // The second element is the address of the instruction which specifies it.
+    exit_address: Option<(usize, usize)>,
+
+    // The address of the alternative part of `block_alt`, and the address of the `block_alt`.
+    alternative_address: Option<(usize, usize)>,
+
+    // The operands which are pushed in this frame.
+    operands: Vec<OperandType>,
+
+    // The operands below the `operands` are unknown, see the "Limitations" section.
+    polymorphic: bool,
+}
+
+impl ControlFrame {
+    fn new(
+        params: Vec<OperandType>,
+        results: Vec<OperandType>,
+        local_variable_list_index: usize,
+        body_address: usize,
+    ) -> Self {
+        Self {
+            params,
+            results,
+            local_variable_list_index,
+            body_address,
+            exit_address: None,
+            alternative_address: None,
+            operands: vec![],
+            polymorphic: false,
+        }
+    }
+}
+
+struct FunctionVerifier<'a> {
+    program: &'a Program<'a>,
+    module_index: usize,
+    function_internal_index: usize,
+    frames: Vec<ControlFrame>,
+
+    // The address of the instruction which is being verified.
+    address: usize,
+}
+
+impl<'a> FunctionVerifier<'a> {
+    fn new(program: &'a Program<'a>, module_index: usize, function_internal_index: usize) -> Self {
+        Self {
+            program,
+            module_index,
+            function_internal_index,
+            frames: vec![],
+            address: 0,
+        }
+    }
+
+    fn verify(&mut self) -> Result<(), VerificationError> {
+        let program = self.program;
+        let module_sections = &program.modules[self.module_index];
+        let function_item = &module_sections.function_section.items[self.function_internal_index];
+        let code_offset = function_item.code_offset as usize;
+        let code_length = function_item.code_length as usize;
+        self.address = code_offset;
+
+        let codes = code_offset
+            .checked_add(code_length)
+            .and_then(|code_end| {
+                module_sections
+                    .function_section
+                    .codes_data
+                    .get(code_offset..code_end)
+            })
+            .ok_or_else(|| self.error(VerificationErrorType::CodeOutOfBounds))?;
+
+        let (params, results) =
+            self.get_type_params_and_results(self.module_index, function_item.type_index as usize)?;
+        let local_variable_list_index = function_item.local_variable_list_index as usize;
+        self.check_local_variable_list_index(local_variable_list_index)?;
+
+        // The arguments of the function are stored in the local variables,
+        // so the body starts with an empty operand stack.
+        self.frames.push(ControlFrame::new(
+            params,
+            results,
+            local_variable_list_index,
+            code_offset,
+        ));
+
+        let mut offset = 0;
+
+        // The verification is finished when the `end` of the function frame is reached.
+        while !self.frames.is_empty() {
+            self.address = code_offset + offset;
+
+            if offset + 2 > codes.len() {
+                return Err(self.error(VerificationErrorType::MissingEnd));
+            }
+
+            // Switch to the alternative part of `block_alt`, it starts with an empty operand stack.
+            let frame = self.frames.last_mut().unwrap();
+            if matches!(frame.alternative_address, Some((address, _)) if address == self.address) {
+                frame.alternative_address = None;
+                frame.operands.clear();
+                frame.polymorphic = false;
+            }
+
+            let opcode_integer =
+                u16::from_le_bytes(codes[offset..(offset + 2)].try_into().unwrap());
+            let Some(opcode) = get_opcode(opcode_integer) else {
+                return Err(self.error(VerificationErrorType::InvalidOpcode(opcode_integer)));
+            };
+
+            let layout = get_instruction_param_layout(opcode);
+            let length = layout.get_length_in_bytes();
+            if offset + length > codes.len() {
+                return Err(self.error(VerificationErrorType::TruncatedInstruction));
+            }
+
+            let params = layout.decode_params(&codes[offset..(offset + length)]);
+            self.verify_instruction(opcode, opcode_integer, params)?;
+
+            offset += length;
+        }
+
+        if offset != codes.len() {
+            self.address = code_offset + offset;
+            return Err(self.error(VerificationErrorType::CodeAfterEnd));
+        }
+
+        Ok(())
+    }
+
+    fn verify_instruction(
+        &mut self,
+        opcode: Opcode,
+        opcode_integer: u16,
+        params: [u32; 3],
+    ) -> Result<(), VerificationError> {
+        let address = self.address;
+
+        match opcode {
+            // fundamental
+            Opcode::nop => {}
+            Opcode::imm_i32 | Opcode::imm_i64 => self.push(INT),
+            Opcode::imm_f32 => self.push(F32),
+            Opcode::imm_f64 => self.push(F64),
+
+            // local
+            Opcode::local_load_i64
+            | Opcode::local_load_i32_s
+            | Opcode::local_load_i32_u
+            | Opcode::local_load_i16_s
+            | Opcode::local_load_i16_u
+            | Opcode::local_load_i8_s
+            | Opcode::local_load_i8_u
+            | Opcode::local_load_f32
+            | Opcode::local_load_f64 => {
+                let (data_length, operand_type) =
+                    get_data_length_and_type_of_load_and_store(opcode);
+                self.check_local_variable(params[0] as usize, params[1] as usize, data_length)?;
+                self.push(operand_type);
+            }
+            Opcode::local_store_i64
+            | Opcode::local_store_i32
+            | Opcode::local_store_i16
+            | Opcode::local_store_i8
+            | Opcode::local_store_f64
+            | Opcode::local_store_f32 => {
+                let (data_length, operand_type) =
+                    get_data_length_and_type_of_load_and_store(opcode);
+                self.check_local_variable(params[0] as usize, params[1] as usize, data_length)?;
+                self.pop_with_type(operand_type)?;
+            }
+
+            // data
+            Opcode::data_load_i64
+            | Opcode::data_load_i32_s
+            | Opcode::data_load_i32_u
+            | Opcode::data_load_i16_s
+            | Opcode::data_load_i16_u
+            | Opcode::data_load_i8_s
+            | Opcode::data_load_i8_u
+            | Opcode::data_load_f64
+            | Opcode::data_load_f32 => {
+                let (data_length, operand_type) =
+                    get_data_length_and_type_of_load_and_store(opcode);
+                self.check_data(params[1] as usize, Some((params[0] as usize, data_length)))?;
+                self.push(operand_type);
+            }
+            Opcode::data_store_i64
+            | Opcode::data_store_i32
+            | Opcode::data_store_i16
+            | Opcode::data_store_i8
+            | Opcode::data_store_f64
+            | Opcode::data_store_f32 => {
+                let (data_length, operand_type) =
+                    get_data_length_and_type_of_load_and_store(opcode);
+                self.check_data(params[1] as usize, Some((params[0] as usize, data_length)))?;
+                self.pop_with_type(operand_type)?;
+            }
+            Opcode::data_load_extend_i64
+            | Opcode::data_load_extend_i32_s
+            | Opcode::data_load_extend_i32_u
+            | Opcode::data_load_extend_i16_s
+            | Opcode::data_load_extend_i16_u
+            | Opcode::data_load_extend_i8_s
+            | Opcode::data_load_extend_i8_u
+            | Opcode::data_load_extend_f64
+            | Opcode::data_load_extend_f32 => {
+                let (_, operand_type) = get_data_length_and_type_of_load_and_store(opcode);
+                self.check_data(params[0] as usize, None)?;
+                self.apply(&[INT], &[operand_type])?;
+            }
+            Opcode::data_store_extend_i64
+            | Opcode::data_store_extend_i32
+            | Opcode::data_store_extend_i16
+            | Opcode::data_store_extend_i8
+            | Opcode::data_store_extend_f64
+            | Opcode::data_store_extend_f32 => {
+                let (_, operand_type) = get_data_length_and_type_of_load_and_store(opcode);
+                self.check_data(params[0] as usize, None)?;
+                self.apply(&[operand_type, INT], &[])?;
+            }
+
+            // arithmetic
+            Opcode::add_i32
+            | Opcode::sub_i32
+            | Opcode::mul_i32
+            | Opcode::div_i32_s
+            | Opcode::div_i32_u
+            | Opcode::rem_i32_s
+            | Opcode::rem_i32_u
+            | Opcode::add_i64
+            | Opcode::sub_i64
+            | Opcode::mul_i64
+            | Opcode::div_i64_s
+            | Opcode::div_i64_u
+            | Opcode::rem_i64_s
+            | Opcode::rem_i64_u => self.apply(&[INT, INT], &[INT])?,
+            Opcode::add_imm_i32
+            | Opcode::sub_imm_i32
+            | Opcode::add_imm_i64
+            | Opcode::sub_imm_i64 => self.apply(&[INT], &[INT])?,
+            Opcode::add_f32 | Opcode::sub_f32 | Opcode::mul_f32 | Opcode::div_f32 => {
+                self.apply(&[F32, F32], &[F32])?
+            }
+            Opcode::add_f64 | Opcode::sub_f64 | Opcode::mul_f64 | Opcode::div_f64 => {
+                self.apply(&[F64, F64], &[F64])?
+            }
+
+            // bitwise
+            Opcode::and
+            | Opcode::or
+            | Opcode::xor
+            | Opcode::shift_left_i32
+            | Opcode::shift_right_i32_s
+            | Opcode::shift_right_i32_u
+            | Opcode::rotate_left_i32
+            | Opcode::rotate_right_i32
+            | Opcode::shift_left_i64
+            | Opcode::shift_right_i64_s
+            | Opcode::shift_right_i64_u
+            | Opcode::rotate_left_i64
+            | Opcode::rotate_right_i64 => self.apply(&[INT, INT], &[INT])?,
+            Opcode::not
+            | Opcode::count_leading_zeros_i32
+            | Opcode::count_leading_ones_i32
+            | Opcode::count_trailing_zeros_i32
+            | Opcode::count_ones_i32
+            | Opcode::count_leading_zeros_i64
+            | Opcode::count_leading_ones_i64
+            | Opcode::count_trailing_zeros_i64
+            | Opcode::count_ones_i64 => self.apply(&[INT], &[INT])?,
+
+            // math
+            Opcode::abs_i32 | Opcode::neg_i32 | Opcode::abs_i64 | Opcode::neg_i64 => {
+                self.apply(&[INT], &[INT])?
+            }
+            Opcode::abs_f32
+            | Opcode::neg_f32
+            | Opcode::sqrt_f32
+            | Opcode::ceil_f32
+            | Opcode::floor_f32
+            | Opcode::round_half_away_from_zero_f32
+            | Opcode::round_half_to_even_f32
+            | Opcode::trunc_f32
+            | Opcode::fract_f32
+            | Opcode::cbrt_f32
+            | Opcode::exp_f32
+            | Opcode::exp2_f32
+            | Opcode::ln_f32
+            | Opcode::log2_f32
+            | Opcode::log10_f32
+            | Opcode::sin_f32
+            | Opcode::cos_f32
+            | Opcode::tan_f32
+            | Opcode::asin_f32
+            | Opcode::acos_f32
+            | Opcode::atan_f32 => self.apply(&[F32], &[F32])?,
+            Opcode::copysign_f32
+            | Opcode::min_f32
+            | Opcode::max_f32
+            | Opcode::pow_f32
+            | Opcode::log_f32 => self.apply(&[F32, F32], &[F32])?,
+            Opcode::abs_f64
+            | Opcode::neg_f64
+            | Opcode::sqrt_f64
+            | Opcode::ceil_f64
+            | Opcode::floor_f64
+            | Opcode::round_half_away_from_zero_f64
+            | Opcode::round_half_to_even_f64
+            | Opcode::trunc_f64
+            | Opcode::fract_f64
+            | Opcode::cbrt_f64
+            | Opcode::exp_f64
+            | Opcode::exp2_f64
+            | Opcode::ln_f64
+            | Opcode::log2_f64
+            | Opcode::log10_f64
+            | Opcode::sin_f64
+            | Opcode::cos_f64
+            | Opcode::tan_f64
+            | Opcode::asin_f64
+            | Opcode::acos_f64
+            | Opcode::atan_f64 => self.apply(&[F64], &[F64])?,
+            Opcode::copysign_f64
+            | Opcode::min_f64
+            | Opcode::max_f64
+            | Opcode::pow_f64
+            | Opcode::log_f64 => self.apply(&[F64, F64], &[F64])?,
+
+            // conversion
+            Opcode::truncate_i64_to_i32
+            | Opcode::extend_i32_s_to_i64
+            | Opcode::extend_i32_u_to_i64 => self.apply(&[INT], &[INT])?,
+            Opcode::demote_f64_to_f32 => self.apply(&[F64], &[F32])?,
+            Opcode::promote_f32_to_f64 => self.apply(&[F32], &[F64])?,
+            Opcode::convert_f32_to_i32_s
+            | Opcode::convert_f32_to_i32_u
+            | Opcode::convert_f32_to_i64_s
+            | Opcode::convert_f32_to_i64_u => self.apply(&[F32], &[INT])?,
+            Opcode::convert_f64_to_i32_s
+            | Opcode::convert_f64_to_i32_u
+            | Opcode::convert_f64_to_i64_s
+            | Opcode::convert_f64_to_i64_u => self.apply(&[F64], &[INT])?,
+            Opcode::convert_i32_s_to_f32
+            | Opcode::convert_i32_u_to_f32
+            | Opcode::convert_i64_s_to_f32
+            | Opcode::convert_i64_u_to_f32 => self.apply(&[INT], &[F32])?,
+            Opcode::convert_i32_s_to_f64
+            | Opcode::convert_i32_u_to_f64
+            | Opcode::convert_i64_s_to_f64
+            | Opcode::convert_i64_u_to_f64 => self.apply(&[INT], &[F64])?,
+
+            // comparison
+            Opcode::eqz_i32 | Opcode::nez_i32 | Opcode::eqz_i64 | Opcode::nez_i64 => {
+                self.apply(&[INT], &[INT])?
+            }
+            Opcode::eq_i32
+            | Opcode::ne_i32
+            | Opcode::lt_i32_s
+            | Opcode::lt_i32_u
+            | Opcode::gt_i32_s
+            | Opcode::gt_i32_u
+            | Opcode::le_i32_s
+            | Opcode::le_i32_u
+            | Opcode::ge_i32_s
+            | Opcode::ge_i32_u
+            | Opcode::eq_i64
+            | Opcode::ne_i64
+            | Opcode::lt_i64_s
+            | Opcode::lt_i64_u
+            | Opcode::gt_i64_s
+            | Opcode::gt_i64_u
+            | Opcode::le_i64_s
+            | Opcode::le_i64_u
+            | Opcode::ge_i64_s
+            | Opcode::ge_i64_u => self.apply(&[INT, INT], &[INT])?,
+            Opcode::eq_f32
+            | Opcode::ne_f32
+            | Opcode::lt_f32
+            | Opcode::gt_f32
+            | Opcode::le_f32
+            | Opcode::ge_f32 => self.apply(&[F32, F32], &[INT])?,
+            Opcode::eq_f64
+            | Opcode::ne_f64
+            | Opcode::lt_f64
+            | Opcode::gt_f64
+            | Opcode::le_f64
+            | Opcode::ge_f64 => self.apply(&[F64, F64], &[INT])?,
+
+            // control flow
+            Opcode::end => self.close_frame()?,
+            Opcode::block => {
+                // (param type_index:i32, local_variable_list_index:i32)
+                let (param_types, result_types) =
+                    self.get_type_params_and_results(self.module_index, params[0] as usize)?;
+                self.pop_with_types(&param_types)?;
+                self.open_frame(param_types, result_types, params[1] as usize, address + 12)?;
+            }
+            Opcode::block_alt => {
+                // (param type_index:i32, local_variable_list_index:i32, next_inst_offset:i32)
+                self.pop_with_type(INT)?;
+                let (param_types, result_types) =
+                    self.get_type_params_and_results(self.module_index, params[0] as usize)?;
+                self.pop_with_types(&param_types)?;
+                self.open_frame(param_types, result_types, params[1] as usize, address + 16)?;
+                self.frames.last_mut().unwrap().alternative_address =
+                    Some((address + params[2] as usize, address));
+            }
+            Opcode::block_nez => {
+                // (param local_variable_list_index:i32, next_inst_offset:i32)
+                self.pop_with_type(INT)?;
+                self.open_frame(vec![], vec![], params[0] as usize, address + 12)?;
+                self.frames.last_mut().unwrap().exit_address =
+                    Some((address + params[1] as usize, address));
+            }
+            Opcode::break_ => {
+                // (param layers:i16, next_inst_offset:i32)
+                self.break_frame(params[0] as usize, address + params[1] as usize)?;
+            }
+            Opcode::break_alt => {
+                // (param next_inst_offset:i32)
+                self.break_frame(0, address + params[0] as usize)?;
+            }
+            Opcode::recur => {
+                // (param layers:i16, start_inst_offset:i32)
+                self.recur_frame(params[0] as usize, params[1] as usize)?;
+            }
+
+            // calling
+            Opcode::call => {
+                let (param_types, result_types) =
+                    self.get_function_params_and_results(params[0] as usize)?;
+                self.apply(&param_types, &result_types)?;
+            }
+            Opcode::call_dynamic => {
+                // (operand args... module_index:i32 function_public_index:i32)
+                self.pop_with_types(&[INT, INT])?;
+                self.set_polymorphic();
+            }
+            Opcode::envcall => {
+                self.set_polymorphic();
+            }
+            Opcode::syscall => {
+                // (operand args... params_count:i32 syscall_num:i32)
+                self.pop_with_types(&[INT, INT])?;
+                self.set_polymorphic();
+            }
+            Opcode::extcall => {
+                let (param_types, result_types) =
+                    self.get_external_function_params_and_results(params[0] as usize)?;
+                self.apply(&param_types, &result_types)?;
+            }
+
+            // memory
+            Opcode::memory_load_i64
+            | Opcode::memory_load_i32_s
+            | Opcode::memory_load_i32_u
+            | Opcode::memory_load_i16_s
+            | Opcode::memory_load_i16_u
+            | Opcode::memory_load_i8_s
+            | Opcode::memory_load_i8_u
+            | Opcode::memory_load_f64
+            | Opcode::memory_load_f32 => {
+                // (operand module_index:i32 data_access_index:i64 offset_bytes:i64)
+                let (_, operand_type) = get_data_length_and_type_of_load_and_store(opcode);
+                self.apply(&[INT, INT, INT], &[operand_type])?;
+            }
+            Opcode::memory_store_i64
+            | Opcode::memory_store_i32
+            | Opcode::memory_store_i16
+            | Opcode::memory_store_i8
+            | Opcode::memory_store_f64
+            | Opcode::memory_store_f32 => {
+                // (operand value module_index:i32 data_access_index:i64 offset_bytes:i64)
+                let (_, operand_type) = get_data_length_and_type_of_load_and_store(opcode);
+                self.apply(&[operand_type, INT, INT, INT], &[])?;
+            }
+            Opcode::memory_allocate => self.apply(&[INT, INT], &[INT])?,
+            Opcode::memory_reallocate => self.apply(&[INT, INT, INT], &[INT])?,
+            Opcode::memory_free => self.apply(&[INT], &[])?,
+            Opcode::memory_fill => self.apply(&[INT; 5], &[])?,
+            Opcode::memory_copy => self.apply(&[INT; 7], &[])?,
+
+            // machine
+            Opcode::terminate => self.set_polymorphic(),
+            Opcode::get_function => {
+                self.get_function_params_and_results(params[0] as usize)?;
+                self.apply(&[], &[INT, INT])?;
+            }
+            Opcode::get_data => {
+                self.check_data(params[0] as usize, None)?;
+                self.apply(&[], &[INT, INT])?;
+            }
+            Opcode::host_addr_function => {
+                self.get_function_params_and_results(params[0] as usize)?;
+                self.push(INT);
+            }
+            Opcode::host_addr_function_dynamic => self.apply(&[INT, INT], &[INT])?,
+            Opcode::host_addr_data => {
+                self.check_data(params[1] as usize, None)?;
+                self.push(INT);
+            }
+            Opcode::host_addr_data_extend => {
+                self.check_data(params[0] as usize, None)?;
+                self.apply(&[INT], &[INT])?;
+            }
+            Opcode::host_addr_memory => self.apply(&[INT, INT, INT], &[INT])?,
+
+            _ => {
+                return Err(self.error(VerificationErrorType::InvalidOpcode(opcode_integer)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn error(&self, error_type: VerificationErrorType) -> VerificationError {
+        self.error_at(error_type, self.address)
+    }
+
+    fn error_at(
+        &self,
+        error_type: VerificationErrorType,
+        instruction_address: usize,
+    ) -> VerificationError {
+        VerificationError {
+            error_type,
+            module_index: self.module_index,
+            function_internal_index: self.function_internal_index,
+            instruction_address,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, operand_type: OperandType) {
+        self.frames.last_mut().unwrap().operands.push(operand_type);
+    }
+
+    fn pop_with_type(&mut self, expected: OperandType) -> Result<(), VerificationError> {
+        let frame = self.frames.last_mut().unwrap();
+        let opt_found = frame.operands.pop();
+        let polymorphic = frame.polymorphic;
+
+        match opt_found {
+            Some(found) if found != expected => {
+                Err(self.error(VerificationErrorType::TypeMismatch { expected, found }))
+            }
+            Some(_) => Ok(()),
+            None if polymorphic => Ok(()),
+            None => Err(self.error(VerificationErrorType::StackUnderflow)),
+        }
+    }
+
+    // Pops the operands, the first item is the bottom one.
+    fn pop_with_types(&mut self, expected_types: &[OperandType]) -> Result<(), VerificationError> {
+        for expected in expected_types.iter().rev() {
+            self.pop_with_type(*expected)?;
+        }
+        Ok(())
+    }
+
+    // Pops the operands of the instruction and pushes its results.
+    fn apply(
+        &mut self,
+        operand_types: &[OperandType],
+        result_types: &[OperandType],
+    ) -> Result<(), VerificationError> {
+        self.pop_with_types(operand_types)?;
+        self.frames
+            .last_mut()
+            .unwrap()
+            .operands
+            .extend_from_slice(result_types);
+        Ok(())
+    }
+
+    // The operands of the current block are unknown from now on, either the following
+    // instructions are unreachable, or the stack effect of the instruction is unknown.
+    fn set_polymorphic(&mut self) {
+        let frame = self.frames.last_mut().unwrap();
+        frame.operands.clear();
+        frame.polymorphic = true;
+    }
+
+    fn get_target_frame_index(&self, layers: usize) -> Result<usize, VerificationError> {
+        (self.frames.len() - 1)
+            .checked_sub(layers)
+            .ok_or_else(|| self.error(VerificationErrorType::LayersOutOfBounds(layers)))
+    }
+
+    fn open_frame(
+        &mut self,
+        params: Vec<OperandType>,
+        results: Vec<OperandType>,
+        local_variable_list_index: usize,
+        body_address: usize,
+    ) -> Result<(), VerificationError> {
+        self.check_local_variable_list_index(local_variable_list_index)?;
+        self.frames.push(ControlFrame::new(
+            params,
+            results,
+            local_variable_list_index,
+            body_address,
+        ));
+        Ok(())
+    }
+
+    // Handles the instruction `end`.
+    fn close_frame(&mut self) -> Result<(), VerificationError> {
+        let results = self.frames.last().unwrap().results.clone();
+        self.pop_with_types(&results)?;
+
+        let frame = self.frames.pop().unwrap();
+        if let Some((_, block_alt_address)) = frame.alternative_address {
+            // The alternative part is never reached.
+            return Err(self.error_at(VerificationErrorType::InvalidJumpTarget, block_alt_address));
+        }
+
+        if self.frames.is_empty() {
+            // The end of the function.
+            return Ok(());
+        }
+
+        if let Some((exit_address, jump_address)) = frame.exit_address {
+            if exit_address != self.address + 2 {
+                return Err(self.error_at(VerificationErrorType::InvalidJumpTarget, jump_address));
+            }
+        }
+
+        self.frames
+            .last_mut()
+            .unwrap()
+            .operands
+            .extend_from_slice(&results);
+        Ok(())
+    }
+
+    fn break_frame(&mut self, layers: usize, exit_address: usize) -> Result<(), VerificationError> {
+        let target_frame_index = self.get_target_frame_index(layers)?;
+        let results = self.frames[target_frame_index].results.clone();
+        self.pop_with_types(&results)?;
+
+        // Breaking the function frame is returning from the function,
+        // the `next_inst_offset` is ignored.
+        if target_frame_index > 0 {
+            let address = self.address;
+            let target_frame = &mut self.frames[target_frame_index];
+            match target_frame.exit_address {
+                Some((expected_address, _)) if expected_address != exit_address => {
+                    return Err(self.error(VerificationErrorType::InvalidJumpTarget));
+                }
+                Some(_) => {}
+                None => target_frame.exit_address = Some((exit_address, address)),
+            }
+        }
+
+        self.set_polymorphic();
+        Ok(())
+    }
+
+    fn recur_frame(
+        &mut self,
+        layers: usize,
+        start_inst_offset: usize,
+    ) -> Result<(), VerificationError> {
+        let target_frame_index = self.get_target_frame_index(layers)?;
+
+        // The `start_inst_offset` is ignored when the target frame is a function frame.
+        if target_frame_index > 0
+            && self.address.checked_sub(start_inst_offset)
+                != Some(self.frames[target_frame_index].body_address)
+        {
+            return Err(self.error(VerificationErrorType::InvalidJumpTarget));
+        }
+
+        let params = self.frames[target_frame_index].params.clone();
+        self.pop_with_types(&params)?;
+
+        self.set_polymorphic();
+        Ok(())
+    }
+
+    fn get_type_params_and_results(
+        &self,
+        module_index: usize,
+        type_index: usize,
+    ) -> Result<(Vec<OperandType>, Vec<OperandType>), VerificationError> {
+        let type_section = &self.program.modules[module_index].type_section;
+        if type_index >= type_section.items.len() {
+            return Err(self.error(VerificationErrorType::TypeIndexOutOfBounds(type_index)));
+        }
+
+        let (params, results) = type_section.get_item_params_and_results(type_index);
+        Ok((
+            params.iter().map(|dt| OperandType::from(*dt)).collect(),
+            results.iter().map(|dt| OperandType::from(*dt)).collect(),
+        ))
+    }
+
+    fn get_function_params_and_results(
+        &self,
+        function_public_index: usize,
+    ) -> Result<(Vec<OperandType>, Vec<OperandType>), VerificationError> {
+        let function_index_section = &self.program.module_linking_instance.function_index_section;
+        if function_public_index >= function_index_section.get_items_count(self.module_index) {
+            return Err(self.error(VerificationErrorType::FunctionIndexOutOfBounds(
+                function_public_index,
+            )));
+        }
+
+        let (target_module_index, target_function_internal_index) = function_index_section
+            .get_item_target_module_index_and_function_internal_index(
+                self.module_index,
+                function_public_index,
+            );
+
+        let type_index = self.program.modules[target_module_index]
+            .function_section
+            .items[target_function_internal_index]
+            .type_index as usize;
+        self.get_type_params_and_results(target_module_index, type_index)
+    }
+
+    fn get_external_function_params_and_results(
+        &self,
+        external_function_index: usize,
+    ) -> Result<(Vec<OperandType>, Vec<OperandType>), VerificationError> {
+        let module_linking_instance = &self.program.module_linking_instance;
+        if external_function_index
+            >= module_linking_instance
+                .external_function_index_section
+                .get_items_count(self.module_index)
+        {
+            return Err(
+                self.error(VerificationErrorType::ExternalFunctionIndexOutOfBounds(
+                    external_function_index,
+                )),
+            );
+        }
+
+        let unified_external_function_index = module_linking_instance
+            .external_function_index_section
+            .get_item_unified_external_function_index(self.module_index, external_function_index);
+
+        let (_, _, type_index) = module_linking_instance
+            .unified_external_function_section
+            .get_item_name_and_external_library_index_and_type_index(
+                unified_external_function_index,
+            );
+
+        let (params, results) = module_linking_instance
+            .unified_external_type_section
+            .get_item_params_and_results(type_index);
+        Ok((
+            params.iter().map(|dt| OperandType::from(*dt)).collect(),
+            results.iter().map(|dt| OperandType::from(*dt)).collect(),
+        ))
+    }
+
+    fn check_local_variable_list_index(
+        &self,
+        local_variable_list_index: usize,
+    ) -> Result<(), VerificationError> {
+        let local_variable_section =
+            &self.program.modules[self.module_index].local_variable_section;
+        if local_variable_list_index >= local_variable_section.lists.len() {
+            return Err(
+                self.error(VerificationErrorType::LocalVariableListIndexOutOfBounds(
+                    local_variable_list_index,
+                )),
+            );
+        }
+        Ok(())
+    }
+
+    fn check_local_variable(
+        &self,
+        layers: usize,
+        local_variable_index: usize,
+        data_length: usize,
+    ) -> Result<(), VerificationError> {
+        let frame_index = self.get_target_frame_index(layers)?;
+        let local_variable_list_index = self.frames[frame_index].local_variable_list_index;
+
+        let local_variable_section =
+            &self.program.modules[self.module_index].local_variable_section;
+        let variable_item = local_variable_section
+            .get_local_variable_list(local_variable_list_index)
+            .get(local_variable_index)
+            .ok_or_else(|| {
+                self.error(VerificationErrorType::LocalVariableIndexOutOfBounds(
+                    local_variable_index,
+                ))
+            })?;
+
+        if data_length > variable_item.variable_actual_size_in_bytes as usize {
+            return Err(
+                self.error(VerificationErrorType::LocalVariableAccessOutOfBounds(
+                    local_variable_index,
+                )),
+            );
+        }
+
+        Ok(())
+    }
+
+    // Checks the data public index, and the access (offset, length) if it is
+    // specified by the parameters of the instruction.
+    fn check_data(
+        &self,
+        data_public_index: usize,
+        opt_access: Option<(usize, usize)>,
+    ) -> Result<(), VerificationError> {
+        let data_index_section = &self.program.module_linking_instance.data_index_section;
+        if data_public_index >= data_index_section.get_items_count(self.module_index) {
+            return Err(self.error(VerificationErrorType::DataIndexOutOfBounds(
+                data_public_index,
+            )));
+        }
+
+        let Some((offset_bytes, data_length)) = opt_access else {
+            return Ok(());
+        };
+
+        let (target_module_index, target_data_section_type, data_internal_index_in_section) =
+            data_index_section
+                .get_item_target_module_index_and_data_section_type_and_data_internal_index_in_section(
+                    self.module_index,
+                    data_public_index,
+                );
+
+        let data_actual_length = self.program.modules[target_module_index].data_lengths
+            [target_data_section_type as usize]
+            .get(data_internal_index_in_section)
+            .ok_or_else(|| {
+                self.error(VerificationErrorType::DataIndexOutOfBounds(
+                    data_public_index,
+                ))
+            })?;
+
+        if offset_bytes + data_length > *data_actual_length {
+            return Err(self.error(VerificationErrorType::DataAccessOutOfBounds(
+                data_public_index,
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+// Returns the length of the data and the operand type of the load and store instructions.
+fn get_data_length_and_type_of_load_and_store(opcode: Opcode) -> (usize, OperandType) {
+    match opcode {
+        Opcode::local_load_i32_s
+        | Opcode::local_load_i32_u
+        | Opcode::local_store_i32
+        | Opcode::data_load_i32_s
+        | Opcode::data_load_i32_u
+        | Opcode::data_store_i32
+        | Opcode::data_load_extend_i32_s
+        | Opcode::data_load_extend_i32_u
+        | Opcode::data_store_extend_i32
+        | Opcode::memory_load_i32_s
+        | Opcode::memory_load_i32_u
+        | Opcode::memory_store_i32 => (DATA_LENGTH_IN_BYTES_32_BIT, INT),
+        Opcode::local_load_i16_s
+        | Opcode::local_load_i16_u
+        | Opcode::local_store_i16
+        | Opcode::data_load_i16_s
+        | Opcode::data_load_i16_u
+        | Opcode::data_store_i16
+        | Opcode::data_load_extend_i16_s
+        | Opcode::data_load_extend_i16_u
+        | Opcode::data_store_extend_i16
+        | Opcode::memory_load_i16_s
+        | Opcode::memory_load_i16_u
+        | Opcode::memory_store_i16 => (DATA_LENGTH_IN_BYTES_16_BIT, INT),
+        Opcode::local_load_i8_s
+        | Opcode::local_load_i8_u
+        | Opcode::local_store_i8
+        | Opcode::data_load_i8_s
+        | Opcode::data_load_i8_u
+        | Opcode::data_store_i8
+        | Opcode::data_load_extend_i8_s
+        | Opcode::data_load_extend_i8_u
+        | Opcode::data_store_extend_i8
+        | Opcode::memory_load_i8_s
+        | Opcode::memory_load_i8_u
+        | Opcode::memory_store_i8 => (DATA_LENGTH_IN_BYTES_8_BIT, INT),
+        Opcode::local_load_f32
+        | Opcode::local_store_f32
+        | Opcode::data_load_f32
+        | Opcode::data_store_f32
+        | Opcode::data_load_extend_f32
+        | Opcode::data_store_extend_f32
+        | Opcode::memory_load_f32
+        | Opcode::memory_store_f32 => (DATA_LENGTH_IN_BYTES_32_BIT, F32),
+        Opcode::local_load_f64
+        | Opcode::local_store_f64
+        | Opcode::data_load_f64
+        | Opcode::data_store_f64
+        | Opcode::data_load_extend_f64
+        | Opcode::data_store_extend_f64
+        | Opcode::memory_load_f64
+        | Opcode::memory_store_f64 => (DATA_LENGTH_IN_BYTES_64_BIT, F64),
+        _ => (DATA_LENGTH_IN_BYTES_64_BIT, INT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anc_context::program_source::ProgramSource;
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadOnlyDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_blocks,
+            helper_build_module_binary_with_single_function,
+            helper_build_module_binary_with_single_function_and_blocks,
+            helper_build_module_binary_with_single_function_and_data, HelperBlockEntry,
+            HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, OperandDataType};
+
+    use crate::in_memory_program_source::InMemoryProgramSource;
+
+    use super::{verify_module_images, OperandType, VerificationError, VerificationErrorType};
+
+    fn verify_binary(binary: Vec<u8>) -> Result<(), VerificationError> {
+        let resource0 = InMemoryProgramSource::new(vec![binary]);
+        let process_context0 = resource0.create_process_context().unwrap();
+        verify_module_images(&process_context0.module_images)
+    }
+
+    #[test]
+    fn test_verify_valid_functions() {
+        // fn test (num/0:i32) -> (i32)             ;; type 0
+        //     call(sum_square)
+        // end
+        //
+        // fn sum_square (count/0:i32) -> (i32)     ;; type 1
+        //     imm_i32(0)
+        //     local_load32(0, 0)
+        //     block (sum/0:i32, n/1:i32) -> (i32)  ;; type 3
+        //         local_load32(0, 1)
+        //         eqz_i32
+        //         block_alt () -> (i32)            ;; type 4
+        //             local_load32(1, 0)
+        //         break_alt()
+        //             local_load32(1, 1)
+        //             call(square)
+        //             local_load32(1, 0)
+        //             add_i32
+        //             local_load32(1, 1)
+        //             sub_imm_i32(1)
+        //             recur(1)
+        //         end
+        //     end
+        // end
+        //
+        // fn square (num/0:i32) -> (i32)           ;; type 2
+        //     local_load_i32s(0, 0)
+        //     local_load_i32s(0, 0)
+        //     mul_i32()
+        // end
+
+        let code_main = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code_sum_square = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i32_i32(Opcode::block, 3, 3)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::eqz_i32)
+            .append_opcode_i32_i32_i32(Opcode::block_alt, 4, 4, 0x20)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode_i32(Opcode::break_alt, 0x3a)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode_i32(Opcode::call, 2)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 1)
+            .append_opcode_i16(Opcode::sub_imm_i32, 1)
+            .append_opcode_i16_i32(Opcode::recur, 1, 0x54)
+            .append_opcode(Opcode::end)
+            .append_opcode(Opcode::end)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code_square = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode(Opcode::mul_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_main,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_sum_square,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code_square,
+                },
+            ],
+            &[
+                HelperBlockEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                },
+                HelperBlockEntry {
+                    params: vec![],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                },
+            ],
+        );
+
+        assert!(verify_binary(binary0).is_ok());
+    }
+
+    #[test]
+    fn test_verify_invalid_opcode() {
+        // The opcode 0xff00 doesn't exist.
+        let code0 = [
+            vec![0x00, 0xff],
+            BytecodeWriterHelper::new()
+                .append_opcode(Opcode::end)
+                .to_bytes(),
+        ]
+        .concat();
+
+        let binary0 = helper_build_module_binary_with_single_function(&[], &[], &[], code0);
+
+        assert_eq!(
+            verify_binary(binary0).unwrap_err(),
+            VerificationError {
+                error_type: VerificationErrorType::InvalidOpcode(0xff00),
+                module_index: 0,
+                function_internal_index: 0,
+                instruction_address: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_missing_end() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code0,
+        );
+
+        assert!(matches!(
+            verify_binary(binary0),
+            Err(VerificationError {
+                error_type: VerificationErrorType::MissingEnd,
+                instruction_address: 0x08,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_verify_invalid_jump_target() {
+        // fn () -> ()
+        //     imm_i32(1)
+        //     block_nez                ;; the `next_inst_offset` should be 0x10
+        //         nop
+        //     end
+        // end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 1)
+            .append_opcode_i32_i32(Opcode::block_nez, 1, 0x14)
+            .append_opcode(Opcode::nop)
+            .append_opcode(Opcode::end)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_blocks(
+            vec![],
+            vec![],
+            vec![],
+            code0.clone(),
+            vec![HelperBlockEntry {
+                params: vec![],
+                results: vec![],
+                local_variable_item_entries_without_args: vec![],
+            }],
+        );
+
+        // The error is reported at the `block_nez`.
+        assert_eq!(
+            verify_binary(binary0).unwrap_err().error_type,
+            VerificationErrorType::InvalidJumpTarget
+        );
+
+        // Fix the offset.
+        let mut code1 = code0;
+        code1[0x10..0x14].copy_from_slice(&0x10u32.to_le_bytes());
+
+        let binary1 = helper_build_module_binary_with_single_function_and_blocks(
+            vec![],
+            vec![],
+            vec![],
+            code1,
+            vec![HelperBlockEntry {
+                params: vec![],
+                results: vec![],
+                local_variable_item_entries_without_args: vec![],
+            }],
+        );
+
+        assert!(verify_binary(binary1).is_ok());
+    }
+
+    #[test]
+    fn test_verify_stack_underflow() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code0,
+        );
+
+        assert_eq!(
+            verify_binary(binary0).unwrap_err(),
+            VerificationError {
+                error_type: VerificationErrorType::StackUnderflow,
+                module_index: 0,
+                function_internal_index: 0,
+                instruction_address: 0x08,
+            }
+        );
+
+        // The function returns nothing.
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary1 = helper_build_module_binary_with_single_function(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code1,
+        );
+
+        assert_eq!(
+            verify_binary(binary1).unwrap_err().error_type,
+            VerificationErrorType::StackUnderflow
+        );
+    }
+
+    #[test]
+    fn test_verify_type_mismatch() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_f32(Opcode::imm_f32, 13.0)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code0,
+        );
+
+        assert_eq!(
+            verify_binary(binary0).unwrap_err(),
+            VerificationError {
+                error_type: VerificationErrorType::TypeMismatch {
+                    expected: OperandType::Integer,
+                    found: OperandType::F32
+                },
+                module_index: 0,
+                function_internal_index: 0,
+                instruction_address: 0x10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_local_variable_out_of_bounds() {
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::imm_i32, 11)
+            .append_opcode_i16_i32(Opcode::local_store_i32, 0, 2)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function(
+            &[],
+            &[],
+            &[OperandDataType::I32],
+            code0,
+        );
+
+        assert_eq!(
+            verify_binary(binary0).unwrap_err().error_type,
+            VerificationErrorType::LocalVariableIndexOutOfBounds(2)
+        );
+
+        // Load `i64` from an `i32` variable.
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i64, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary1 = helper_build_module_binary_with_single_function(
+            &[],
+            &[],
+            &[OperandDataType::I32],
+            code1,
+        );
+
+        assert_eq!(
+            verify_binary(binary1).unwrap_err().error_type,
+            VerificationErrorType::LocalVariableAccessOutOfBounds(0)
+        );
+
+        // The layers exceed the function frame.
+        let code2 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 1, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary2 = helper_build_module_binary_with_single_function(
+            &[],
+            &[],
+            &[OperandDataType::I32],
+            code2,
+        );
+
+        assert_eq!(
+            verify_binary(binary2).unwrap_err().error_type,
+            VerificationErrorType::LayersOutOfBounds(1)
+        );
+    }
+
+    #[test]
+    fn test_verify_data_and_function_out_of_bounds() {
+        // Load `i32` from an `i32` data with offset 2.
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 2, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_data(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code0,
+            &[ReadOnlyDataEntry::from_i32(11)],
+            &[],
+            &[],
+        );
+
+        assert_eq!(
+            verify_binary(binary0).unwrap_err().error_type,
+            VerificationErrorType::DataAccessOutOfBounds(0)
+        );
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary1 = helper_build_module_binary_with_single_function_and_data(
+            &[],
+            &[OperandDataType::I32],
+            &[],
+            code1,
+            &[ReadOnlyDataEntry::from_i32(11)],
+            &[],
+            &[],
+        );
+
+        assert_eq!(
+            verify_binary(binary1).unwrap_err().error_type,
+            VerificationErrorType::DataIndexOutOfBounds(1)
+        );
+
+        let code2 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::call, 1)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary2 = helper_build_module_binary_with_single_function(&[], &[], &[], code2);
+
+        assert_eq!(
+            verify_binary(binary2).unwrap_err().error_type,
+            VerificationErrorType::FunctionIndexOutOfBounds(1)
+        );
+    }
+}
@@ -0,0 +1,1130 @@
+// Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+//
+// This Source Code Form is subject to the terms of
+// the Mozilla Public License version 2.0 and additional exceptions.
+// For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+// Vm and Instance
+// ---------------
+//
+// A high-level facade for the applications which embed the XiaoXuan Core VM.
+// It wires up the module images, the process context, the thread context and
+// the instruction processor, so the application can call the exported functions
+// by their names instead of the public indices.
+//
+// Example:
+//
+// ```rust
+// let vm = VmBuilder::new()
+//     .module_binary(binary)
+//     .arguments(vec!["--verbose".to_owned()])
+//     .capability(Capability::default())
+//
+//     .host_function("config::version", &[], &[OperandDataType::I32], |_| {
+//         Ok(vec![ForeignValue::U32(1)])
+//     })
+//     .build()?;
+//
+// let process_context = vm.create_process_context()?;
+// let mut instance = Instance::new(&process_context);
+//
+// let results = instance.call("main::add", &[ForeignValue::U32(11), ForeignValue::U32(13)])?;
+// let sum = instance.call_typed::<u32>("main::add", &[ForeignValue::U32(11), ForeignValue::U32(13)])?;
+// ```
+//
+// The process context borrows the module binaries owned by the `Vm`, and the
+// instance borrows the process context, so they are created in 3 steps.
+
+use std::{
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anc_context::{
+    call_audit_log::CallAuditLog,
+    capability::Capability,
+    capability_prompter::CapabilityGrantor,
+    host_function_table::{HostFunctionResult, HostFunctionTable, HostFunctionTableError},
+    process_context::ProcessContext,
+    process_property::ProcessProperty,
+    standard_streams::StandardOutput,
+    thread_context::{ThreadContext, ThreadContextSnapshot},
+};
+use anc_image::{utils::helper_load_modules_from_binaries, ImageError};
+use anc_isa::{ForeignValue, OperandDataType};
+
+use crate::{
+    multithread_handler::PROCESS_CONTEXT_ADDRESS,
+    process::process_function,
+    verifier::{verify_module_images, VerificationError},
+    ProcessorError,
+};
+
+#[derive(Debug)]
+pub enum VmError {
+    // Failed to read the module image file.
+    Io(PathBuf, std::io::Error),
+
+    // No module is specified, at least the main module is required.
+    NoModules,
+
+    // Failed to register the host function, e.g. the name has already been registered.
+    HostFunction(HostFunctionTableError),
+
+    // Failed to load the module images.
+    Image(ImageError),
+
+    // The bytecode of the module images is invalid.
+    Verification(VerificationError),
+
+    // The function name is not in the form of "module::name".
+    InvalidFunctionName(String),
+
+    // The function does not exist or it is not public.
+    FunctionNotFound(String),
+
+    ArgumentsAmountMismatch {
+        function_name: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    ArgumentTypeMismatch {
+        function_name: String,
+        argument_index: usize,
+        expected: OperandDataType,
+        actual: OperandDataType,
+    },
+
+    // The results of the function do not match the requested Rust type.
+    ResultsMismatch {
+        function_name: String,
+        results: Vec<ForeignValue>,
+    },
+
+    // The function terminated or trapped.
+    Processor(ProcessorError),
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::Io(path, error) => {
+                write!(
+                    f,
+                    "Failed to read module image file \"{}\": {}",
+                    path.display(),
+                    error
+                )
+            }
+            VmError::NoModules => f.write_str("No module is specified."),
+            VmError::HostFunction(error) => write!(f, "{}", error),
+            VmError::Image(error) => write!(f, "Failed to load module images: {}", error),
+            VmError::Verification(error) => write!(f, "Verification failed: {}", error),
+            VmError::InvalidFunctionName(function_name) => write!(
+                f,
+                "Invalid function name \"{}\", the name should be in the form of \"module::name\".",
+                function_name
+            ),
+            VmError::FunctionNotFound(function_name) => {
+                write!(f, "Public function \"{}\" not found.", function_name)
+            }
+            VmError::ArgumentsAmountMismatch {
+                function_name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Function \"{}\" expects {} argument(s), but {} given.",
+                function_name, expected, actual
+            ),
+            VmError::ArgumentTypeMismatch {
+                function_name,
+                argument_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The argument {} of function \"{}\" expects type {}, but {} given.",
+                argument_index,
+                function_name,
+                get_data_type_name(*expected),
+                get_data_type_name(*actual)
+            ),
+            VmError::ResultsMismatch {
+                function_name,
+                results,
+            } => write!(
+                f,
+                "The results of function \"{}\" do not match the expected type, actual: {:?}.",
+                function_name, results
+            ),
+            VmError::Processor(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+impl From<ProcessorError> for VmError {
+    fn from(value: ProcessorError) -> Self {
+        VmError::Processor(value)
+    }
+}
+
+/// Builds a `Vm` with the module images and the process properties.
+pub struct VmBuilder {
+    module_binaries: Vec<Vec<u8>>,
+    module_files: Vec<PathBuf>,
+    process_property: ProcessProperty,
+    host_function_table: HostFunctionTable,
+    host_function_error: Option<HostFunctionTableError>,
+    verify: bool,
+}
+
+impl VmBuilder {
+    pub fn new() -> Self {
+        Self {
+            module_binaries: vec![],
+            module_files: vec![],
+            process_property: ProcessProperty::default(),
+            host_function_table: HostFunctionTable::new(),
+            host_function_error: None,
+            verify: true,
+        }
+    }
+
+    /// Adds a module image binary, the first module is the main module.
+    pub fn module_binary(mut self, module_binary: Vec<u8>) -> Self {
+        self.module_binaries.push(module_binary);
+        self
+    }
+
+    /// Adds module image binaries.
+    pub fn module_binaries(mut self, module_binaries: Vec<Vec<u8>>) -> Self {
+        self.module_binaries.extend(module_binaries);
+        self
+    }
+
+    /// Adds a module image file, it is read when the `Vm` is built.
+    ///
+    /// The module image files are appended after the module image binaries.
+    pub fn module_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.module_files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the path of the program, which is used to resolve
+    /// the relative paths of the external libraries.
+    pub fn program_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.process_property.program_path = path.as_ref().to_path_buf();
+        self
+    }
+
+    pub fn arguments(mut self, arguments: Vec<String>) -> Self {
+        self.process_property.arguments = arguments;
+        self
+    }
+
+    pub fn environments(mut self, environments: Vec<(String, String)>) -> Self {
+        self.process_property.environments = environments;
+        self
+    }
+
+    pub fn capability(mut self, capability: Capability) -> Self {
+        self.process_property.capability = capability;
+        self
+    }
+
+    /// Sets how the capabilities requested at runtime are decided,
+    /// see `ProcessProperty::capability_grantor`.
+    pub fn capability_grantor(mut self, capability_grantor: CapabilityGrantor) -> Self {
+        self.process_property.capability_grantor = capability_grantor;
+        self
+    }
+
+    /// See `ProcessProperty::call_audit_log`.
+    pub fn call_audit_log(mut self, call_audit_log: CallAuditLog) -> Self {
+        self.process_property.call_audit_log = Some(call_audit_log);
+        self
+    }
+
+    /// See `ProcessProperty::fuel`.
+    pub fn fuel(mut self, fuel: u64) -> Self {
+        self.process_property.fuel = Some(fuel);
+        self
+    }
+
+    /// See `ProcessProperty::predecode`.
+    pub fn predecode(mut self, predecode: bool) -> Self {
+        self.process_property.predecode = predecode;
+        self
+    }
+
+    /// See `ProcessProperty::jit_threshold`.
+    pub fn jit_threshold(mut self, jit_threshold: u32) -> Self {
+        self.process_property.jit_threshold = Some(jit_threshold);
+        self
+    }
+
+    /// Redirects the standard input of the program.
+    pub fn stdin<R: Read + Send + 'static>(mut self, reader: R) -> Self {
+        let standard_streams = std::mem::take(&mut self.process_property.standard_streams);
+        self.process_property.standard_streams = standard_streams.with_stdin(reader);
+        self
+    }
+
+    /// Redirects the standard output of the program, see `StandardStreams::with_stdout`.
+    pub fn stdout(mut self, writer: StandardOutput) -> Self {
+        self.process_property.standard_streams.stdout = Some(writer);
+        self
+    }
+
+    /// Redirects the standard error of the program, see `StandardStreams::with_stderr`.
+    pub fn stderr(mut self, writer: StandardOutput) -> Self {
+        self.process_property.standard_streams.stderr = Some(writer);
+        self
+    }
+
+    /// Registers a Rust closure as a host function, the bytecode calls it through
+    /// the instruction `envcall` with the number `get_host_function_envcall_num(index)`,
+    /// where `index` is the order of registration (starting from 0).
+    ///
+    /// Building fails with `HostFunction` if the name has already been registered
+    /// or there are too many host functions.
+    pub fn host_function<F>(
+        mut self,
+        name: &str,
+        params: &[OperandDataType],
+        results: &[OperandDataType],
+        function: F,
+    ) -> Self
+    where
+        F: Fn(&[ForeignValue]) -> HostFunctionResult + Send + Sync + 'static,
+    {
+        if let Err(error) = self
+            .host_function_table
+            .register(name, params, results, function)
+        {
+            // Only the first error is reported.
+            self.host_function_error.get_or_insert(error);
+        }
+        self
+    }
+
+    /// Whether to verify the bytecode before executing, it is enabled by default.
+    ///
+    /// Disable it only when the module images come from a trusted source.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn build(self) -> Result<Vm, VmError> {
+        if let Some(error) = self.host_function_error {
+            return Err(VmError::HostFunction(error));
+        }
+
+        let mut module_binaries = self.module_binaries;
+
+        for path in self.module_files {
+            let module_binary = std::fs::read(&path).map_err(|error| VmError::Io(path, error))?;
+            module_binaries.push(module_binary);
+        }
+
+        if module_binaries.is_empty() {
+            return Err(VmError::NoModules);
+        }
+
+        Ok(Vm {
+            module_binaries,
+            process_property: self.process_property,
+            host_function_table: self.host_function_table,
+            verify: self.verify,
+        })
+    }
+}
+
+impl Default for VmBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Owns the module image binaries and the process properties.
+pub struct Vm {
+    module_binaries: Vec<Vec<u8>>,
+    process_property: ProcessProperty,
+    host_function_table: HostFunctionTable,
+    verify: bool,
+}
+
+impl Vm {
+    pub fn builder() -> VmBuilder {
+        VmBuilder::new()
+    }
+
+    /// Loads (and verifies) the module images and creates a new `ProcessContext`.
+    pub fn create_process_context(&self) -> Result<ProcessContext<'_>, VmError> {
+        let binaries_ref = self
+            .module_binaries
+            .iter()
+            .map(|e| &e[..])
+            .collect::<Vec<_>>();
+
+        let module_images =
+            helper_load_modules_from_binaries(&binaries_ref).map_err(VmError::Image)?;
+
+        if self.verify {
+            verify_module_images(&module_images).map_err(VmError::Verification)?;
+        }
+
+        let mut process_context = ProcessContext::new(self.process_property.clone(), module_images);
+        process_context.host_function_table = self.host_function_table.clone();
+
+        Ok(process_context)
+    }
+}
+
+/// An instance of the program, it holds a thread context, so the data
+/// of the program is kept between function calls.
+pub struct Instance<'a> {
+    process_context: &'a ProcessContext<'a>,
+
+    // The bridge functions and the callback functions hold the address
+    // of the thread context, so it is boxed to keep the address unchanged
+    // when the instance is moved.
+    thread_context: Box<ThreadContext<'a>>,
+
+    // The initial state of the thread context, see `reset()`.
+    snapshot: ThreadContextSnapshot,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new(process_context: &'a ProcessContext<'a>) -> Self {
+        let thread_context = Box::new(process_context.create_thread_context());
+        let snapshot = thread_context.take_snapshot();
+
+        Self {
+            process_context,
+            thread_context,
+            snapshot,
+        }
+    }
+
+    /// Restores the instance to the initial state, i.e. the data, the allocator
+    /// and the thread resources are reset, which is much cheaper than creating
+    /// a new instance.
+    pub fn reset(&mut self) {
+        self.thread_context.restore_snapshot(&self.snapshot);
+    }
+
+    /// Returns the underlying thread context, e.g. for
+    /// obtaining the bridge functions through `program_embed`.
+    pub fn thread_context(&mut self) -> &mut ThreadContext<'a> {
+        &mut self.thread_context
+    }
+
+    /// Calls the specified public function, the function name should
+    /// be in the form of "module::name", e.g. "main::add", "network::http::get".
+    ///
+    /// The number and the data types of the arguments are checked against
+    /// the function type before the function is executed.
+    pub fn call(
+        &mut self,
+        function_name: &str,
+        arguments: &[ForeignValue],
+    ) -> Result<Vec<ForeignValue>, VmError> {
+        let (module_name, function_full_name) = function_name
+            .split_once("::")
+            .ok_or_else(|| VmError::InvalidFunctionName(function_name.to_owned()))?;
+
+        let (target_module_index, function_internal_index) = self
+            .thread_context
+            .find_function_by_full_name(module_name, function_full_name)
+            .ok_or_else(|| VmError::FunctionNotFound(function_name.to_owned()))?;
+
+        let function_info = self
+            .thread_context
+            .get_function_info(target_module_index, function_internal_index);
+        let (params, _) = self.thread_context.module_common_instances[target_module_index]
+            .type_section
+            .get_item_params_and_results(function_info.type_index);
+
+        check_arguments(function_name, params, arguments)?;
+
+        // `find_function_by_full_name()` returns the internal index, so converts
+        // it to the public index which `process_function()` accepts.
+        let function_public_index = get_function_public_index(
+            &self.thread_context,
+            target_module_index,
+            function_internal_index,
+        )
+        .ok_or_else(|| VmError::FunctionNotFound(function_name.to_owned()))?;
+
+        // The multithread envcalls access the process context through this thread-local
+        // address, the previous value is restored when the calling is finished, since
+        // the instances of different processes may be called by the same host thread.
+        let _process_context_address_guard = ProcessContextAddressGuard::new(self.process_context);
+
+        let results = process_function(
+            &mut self.thread_context,
+            target_module_index,
+            function_public_index,
+            arguments,
+        )?;
+
+        Ok(results)
+    }
+
+    /// Calls the specified public function and converts the results into the Rust type `T`.
+    pub fn call_typed<T: FromForeignValues>(
+        &mut self,
+        function_name: &str,
+        arguments: &[ForeignValue],
+    ) -> Result<T, VmError> {
+        let results = self.call(function_name, arguments)?;
+        T::from_foreign_values(&results).ok_or_else(|| VmError::ResultsMismatch {
+            function_name: function_name.to_owned(),
+            results,
+        })
+    }
+}
+
+// Sets `PROCESS_CONTEXT_ADDRESS` and restores the previous value when dropped.
+struct ProcessContextAddressGuard {
+    previous_address: usize,
+}
+
+impl ProcessContextAddressGuard {
+    fn new(process_context: &ProcessContext) -> Self {
+        let address = process_context as *const ProcessContext as *const u8 as usize;
+        let previous_address = PROCESS_CONTEXT_ADDRESS.with(|data| data.replace(address));
+        Self { previous_address }
+    }
+}
+
+impl Drop for ProcessContextAddressGuard {
+    fn drop(&mut self) {
+        PROCESS_CONTEXT_ADDRESS.with(|data| {
+            *data.borrow_mut() = self.previous_address;
+        });
+    }
+}
+
+fn get_function_public_index(
+    thread_context: &ThreadContext,
+    module_index: usize,
+    function_internal_index: usize,
+) -> Option<usize> {
+    let function_index_section = &thread_context
+        .module_linking_instance
+        .function_index_section;
+
+    (0..function_index_section.get_items_count(module_index)).find(|function_public_index| {
+        function_index_section.get_item_target_module_index_and_function_internal_index(
+            module_index,
+            *function_public_index,
+        ) == (module_index, function_internal_index)
+    })
+}
+
+fn check_arguments(
+    function_name: &str,
+    params: &[OperandDataType],
+    arguments: &[ForeignValue],
+) -> Result<(), VmError> {
+    if params.len() != arguments.len() {
+        return Err(VmError::ArgumentsAmountMismatch {
+            function_name: function_name.to_owned(),
+            expected: params.len(),
+            actual: arguments.len(),
+        });
+    }
+
+    for (argument_index, (expected, argument)) in params.iter().zip(arguments).enumerate() {
+        let actual = get_foreign_value_data_type(argument);
+        if *expected != actual {
+            return Err(VmError::ArgumentTypeMismatch {
+                function_name: function_name.to_owned(),
+                argument_index,
+                expected: *expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn get_foreign_value_data_type(value: &ForeignValue) -> OperandDataType {
+    match value {
+        ForeignValue::U32(_) => OperandDataType::I32,
+        ForeignValue::U64(_) => OperandDataType::I64,
+        ForeignValue::F32(_) => OperandDataType::F32,
+        ForeignValue::F64(_) => OperandDataType::F64,
+    }
+}
+
+fn get_data_type_name(data_type: OperandDataType) -> &'static str {
+    match data_type {
+        OperandDataType::I32 => "i32",
+        OperandDataType::I64 => "i64",
+        OperandDataType::F32 => "f32",
+        OperandDataType::F64 => "f64",
+    }
+}
+
+/// Converts the results of a function into a Rust type.
+pub trait FromForeignValues: Sized {
+    fn from_foreign_values(values: &[ForeignValue]) -> Option<Self>;
+}
+
+/// Converts a single result of a function into a Rust type.
+pub trait FromForeignValue: Sized {
+    fn from_foreign_value(value: &ForeignValue) -> Option<Self>;
+}
+
+macro_rules! impl_from_foreign_value {
+    ($type:ty, $variant:ident) => {
+        impl FromForeignValue for $type {
+            fn from_foreign_value(value: &ForeignValue) -> Option<Self> {
+                match value {
+                    ForeignValue::$variant(v) => Some(<$type>::from_ne_bytes(v.to_ne_bytes())),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_from_foreign_value!(u32, U32);
+impl_from_foreign_value!(i32, U32);
+impl_from_foreign_value!(u64, U64);
+impl_from_foreign_value!(i64, U64);
+impl_from_foreign_value!(f32, F32);
+impl_from_foreign_value!(f64, F64);
+
+impl FromForeignValues for () {
+    fn from_foreign_values(values: &[ForeignValue]) -> Option<Self> {
+        values.is_empty().then_some(())
+    }
+}
+
+impl<T: FromForeignValue> FromForeignValues for T {
+    fn from_foreign_values(values: &[ForeignValue]) -> Option<Self> {
+        match values {
+            [value] => T::from_foreign_value(value),
+            _ => None,
+        }
+    }
+}
+
+impl<A: FromForeignValue, B: FromForeignValue> FromForeignValues for (A, B) {
+    fn from_foreign_values(values: &[ForeignValue]) -> Option<Self> {
+        match values {
+            [a, b] => Some((A::from_foreign_value(a)?, B::from_foreign_value(b)?)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        sync::{Arc, Mutex},
+    };
+
+    use anc_context::host_function_table::HostFunctionTableError;
+    use anc_image::{
+        bytecode_writer::BytecodeWriterHelper,
+        entry::ReadWriteDataEntry,
+        utils::{
+            helper_build_module_binary_with_functions_and_blocks,
+            helper_build_module_binary_with_single_function_and_data, HelperFunctionEntry,
+        },
+    };
+    use anc_isa::{opcode::Opcode, ForeignValue, OperandDataType};
+
+    use crate::{
+        envcall_num::get_host_function_envcall_num,
+        multithread_handler::PROCESS_CONTEXT_ADDRESS,
+        program_embed::get_function,
+        verifier::{VerificationError, VerificationErrorType},
+        ProcessorError, ProcessorErrorType,
+    };
+
+    use super::{Instance, VmBuilder, VmError};
+
+    fn build_binary() -> Vec<u8> {
+        // fn0 (a: i32, b: i32) -> (i32)
+        //     local_load_i32_u(0, 0)
+        //     local_load_i32_u(0, 1)
+        //     add_i32
+        //     end
+        //
+        // fn1 (a: f64) -> (f64, i64)
+        //     local_load_f64(0, 0)
+        //     imm_i64(17)
+        //     end
+        //
+        // fn2 () -> ()
+        //     terminate(0x11)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 1)
+            .append_opcode(Opcode::add_i32)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code1 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_f64, 0, 0)
+            .append_opcode_i64(Opcode::imm_i64, 17)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let code2 = BytecodeWriterHelper::new()
+            .append_opcode_i32(Opcode::terminate, 0x11)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        helper_build_module_binary_with_functions_and_blocks(
+            &[
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::I32, OperandDataType::I32],
+                    results: vec![OperandDataType::I32],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code0,
+                },
+                HelperFunctionEntry {
+                    params: vec![OperandDataType::F64],
+                    results: vec![OperandDataType::F64, OperandDataType::I64],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code1,
+                },
+                HelperFunctionEntry {
+                    params: vec![],
+                    results: vec![],
+                    local_variable_item_entries_without_args: vec![],
+                    code: code2,
+                },
+            ],
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_vm_call_function() {
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        assert_eq!(
+            instance
+                .call(
+                    "main::func0",
+                    &[ForeignValue::U32(11), ForeignValue::U32(13)]
+                )
+                .unwrap(),
+            vec![ForeignValue::U32(24)]
+        );
+
+        assert_eq!(
+            instance
+                .call("main::func1", &[ForeignValue::F64(3.5)])
+                .unwrap(),
+            vec![ForeignValue::F64(3.5), ForeignValue::U64(17)]
+        );
+
+        assert!(matches!(
+            instance.call("main::func2", &[]),
+            Err(VmError::Processor(ProcessorError {
+                error_type: ProcessorErrorType::Terminate(0x11),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_vm_call_restore_process_context_address() {
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        PROCESS_CONTEXT_ADDRESS.with(|data| data.replace(0x1234));
+
+        assert!(instance
+            .call(
+                "main::func0",
+                &[ForeignValue::U32(11), ForeignValue::U32(13)]
+            )
+            .is_ok());
+        assert_eq!(PROCESS_CONTEXT_ADDRESS.with(|data| *data.borrow()), 0x1234);
+
+        // also restored when the function is terminated
+        assert!(instance.call("main::func2", &[]).is_err());
+        assert_eq!(PROCESS_CONTEXT_ADDRESS.with(|data| *data.borrow()), 0x1234);
+    }
+
+    #[test]
+    fn test_vm_instance_moved() {
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        let fn_add = get_function::<extern "C" fn(i32, i32) -> i32>(
+            instance.thread_context(),
+            "main",
+            "func0",
+        )
+        .unwrap();
+
+        // the bridge function holds the address of the thread context,
+        // it should still work after the instance is moved.
+        let mut instances = vec![instance];
+        assert_eq!(fn_add(11, 13), 24);
+
+        assert_eq!(
+            instances[0]
+                .call_typed::<u32>(
+                    "main::func0",
+                    &[ForeignValue::U32(23), ForeignValue::U32(29)]
+                )
+                .unwrap(),
+            52
+        );
+        assert_eq!(fn_add(31, 37), 68);
+    }
+
+    #[test]
+    fn test_vm_call_typed() {
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        assert_eq!(
+            instance
+                .call_typed::<u32>(
+                    "main::func0",
+                    &[ForeignValue::U32(11), ForeignValue::U32(13)]
+                )
+                .unwrap(),
+            24
+        );
+
+        assert_eq!(
+            instance
+                .call_typed::<(f64, i64)>("main::func1", &[ForeignValue::F64(3.5)])
+                .unwrap(),
+            (3.5, 17)
+        );
+
+        assert!(matches!(
+            instance.call_typed::<u64>(
+                "main::func0",
+                &[ForeignValue::U32(11), ForeignValue::U32(13)]
+            ),
+            Err(VmError::ResultsMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_vm_check_function_and_arguments() {
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        assert!(matches!(
+            instance.call("func0", &[]),
+            Err(VmError::InvalidFunctionName(_))
+        ));
+
+        assert!(matches!(
+            instance.call("main::foo", &[]),
+            Err(VmError::FunctionNotFound(_))
+        ));
+
+        assert!(matches!(
+            instance.call("main::func0", &[ForeignValue::U32(11)]),
+            Err(VmError::ArgumentsAmountMismatch {
+                expected: 2,
+                actual: 1,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            instance.call(
+                "main::func0",
+                &[ForeignValue::U32(11), ForeignValue::U64(13)]
+            ),
+            Err(VmError::ArgumentTypeMismatch {
+                argument_index: 1,
+                expected: OperandDataType::I32,
+                actual: OperandDataType::I64,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_vm_host_function() {
+        // fn0 (a: i32) -> (i32)
+        //     local_load_i32_u(0, 0)
+        //     envcall(host function 1)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::local_load_i32_u, 0, 0)
+            .append_opcode_i32(Opcode::envcall, get_host_function_envcall_num(1))
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_functions_and_blocks(
+            &[HelperFunctionEntry {
+                params: vec![OperandDataType::I32],
+                results: vec![OperandDataType::I32],
+                local_variable_item_entries_without_args: vec![],
+                code: code0,
+            }],
+            &[],
+        );
+
+        let vm = VmBuilder::new()
+            .module_binary(binary0)
+            .host_function("config::version", &[], &[OperandDataType::I32], |_| {
+                Ok(vec![ForeignValue::U32(1)])
+            })
+            .host_function(
+                "math::square",
+                &[OperandDataType::I32],
+                &[OperandDataType::I32],
+                |args| match args {
+                    [ForeignValue::U32(value)] => Ok(vec![ForeignValue::U32(value * value)]),
+                    _ => Err("invalid arguments".into()),
+                },
+            )
+            .build()
+            .unwrap();
+
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        assert_eq!(
+            instance
+                .call_typed::<u32>("main::func0", &[ForeignValue::U32(7)])
+                .unwrap(),
+            49
+        );
+
+        assert!(matches!(
+            VmBuilder::new()
+                .module_binary(build_binary())
+                .host_function("math::square", &[], &[], |_| Ok(vec![]))
+                .host_function("math::square", &[], &[], |_| Ok(vec![]))
+                .build(),
+            Err(VmError::HostFunction(HostFunctionTableError::DuplicateName(name)))
+                if name == "math::square"
+        ));
+    }
+
+    #[test]
+    fn test_vm_standard_streams() {
+        let output = Arc::new(Mutex::new(Vec::<u8>::new()));
+        let error = Arc::new(Mutex::new(Vec::<u8>::new()));
+
+        let vm = VmBuilder::new()
+            .module_binary(build_binary())
+            .stdin(Cursor::new(b"hello".to_vec()))
+            .stdout(output.clone())
+            .stderr(error.clone())
+            .build()
+            .unwrap();
+        let process_context = vm.create_process_context().unwrap();
+
+        // the streams are shared by all threads of the process
+        let mut instance0 = Instance::new(&process_context);
+        let mut instance1 = Instance::new(&process_context);
+
+        let resources0 = &instance0.thread_context().thread_resources;
+        let mut buf = [0u8; 3];
+        assert_eq!(resources0.read_file(0, &mut buf).unwrap(), 3);
+        assert_eq!(&buf, b"hel");
+        resources0.write_file(1, b"foo").unwrap();
+        resources0.write_file(2, b"bar").unwrap();
+
+        let resources1 = &instance1.thread_context().thread_resources;
+        assert_eq!(resources1.read_file(0, &mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"lo");
+        resources1.write_file(1, b"baz").unwrap();
+        resources1.flush_file(1).unwrap();
+
+        // writing to stdin and accessing a file that doesn't exist fail
+        assert!(resources1.write_file(0, b"foo").is_err());
+        assert!(resources1.read_file(3, &mut buf).is_err());
+
+        assert_eq!(output.lock().unwrap().as_slice(), b"foobaz");
+        assert_eq!(error.lock().unwrap().as_slice(), b"bar");
+    }
+
+    #[test]
+    fn test_vm_instance_reset() {
+        // fn0 () -> (i32)
+        //     data_load_i32_u(0, 0)    ;; counter = counter + 1
+        //     add_imm_i32(1)
+        //     data_store_i32(0, 0)
+        //     data_load_i32_u(0, 0)
+        //     end
+
+        let code0 = BytecodeWriterHelper::new()
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 0)
+            .append_opcode_i16(Opcode::add_imm_i32, 1)
+            .append_opcode_i16_i32(Opcode::data_store_i32, 0, 0)
+            .append_opcode_i16_i32(Opcode::data_load_i32_u, 0, 0)
+            .append_opcode(Opcode::end)
+            .to_bytes();
+
+        let binary0 = helper_build_module_binary_with_single_function_and_data(
+            &[],                     // params
+            &[OperandDataType::I32], // results
+            &[],                     // local variables
+            code0,
+            &[],
+            &[ReadWriteDataEntry::from_i32(10)],
+            &[],
+        );
+
+        let vm = VmBuilder::new().module_binary(binary0).build().unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+
+        assert_eq!(instance.call_typed::<u32>("main::func0", &[]).unwrap(), 11);
+        assert_eq!(instance.call_typed::<u32>("main::func0", &[]).unwrap(), 12);
+
+        instance.reset();
+        assert_eq!(instance.call_typed::<u32>("main::func0", &[]).unwrap(), 11);
+
+        // the data is restored to the state when the snapshot was taken
+        let snapshot = instance.thread_context().take_snapshot();
+        assert_eq!(instance.call_typed::<u32>("main::func0", &[]).unwrap(), 12);
+        instance.thread_context().restore_snapshot(&snapshot);
+        assert_eq!(instance.call_typed::<u32>("main::func0", &[]).unwrap(), 12);
+
+        // the fuel is restored as well
+        instance.thread_context().set_fuel(Some(100));
+        let snapshot = instance.thread_context().take_snapshot();
+        instance.thread_context().set_fuel(Some(0));
+        instance.thread_context().restore_snapshot(&snapshot);
+        assert_eq!(instance.thread_context().get_fuel(), Some(100));
+    }
+
+    #[test]
+    fn test_vm_restore_snapshot_changed_pages() {
+        // a read-write data which spans several pages
+        let binary0 = helper_build_module_binary_with_single_function_and_data(
+            &[], // params
+            &[], // results
+            &[], // local variables
+            BytecodeWriterHelper::new()
+                .append_opcode(Opcode::end)
+                .to_bytes(),
+            &[],
+            &[ReadWriteDataEntry::from_bytes(vec![0x11u8; 10000], 8)],
+            &[],
+        );
+
+        let vm = VmBuilder::new().module_binary(binary0).build().unwrap();
+        let process_context = vm.create_process_context().unwrap();
+        let mut instance = Instance::new(&process_context);
+        let thread_context = instance.thread_context();
+
+        let snapshot = thread_context.take_snapshot();
+
+        // write to the first and the third pages after the snapshot is taken
+        for offset in [0, 9000] {
+            let data = [0x22u8; 16];
+            let target_data_object = thread_context
+                .get_target_data_object(0, 0, offset, data.len())
+                .unwrap();
+            target_data_object.accessor.write_idx(
+                data.as_ptr(),
+                target_data_object.data_internal_index_in_section,
+                offset,
+                data.len(),
+            );
+        }
+
+        thread_context.restore_snapshot(&snapshot);
+
+        let mut buf = vec![0u8; 10000];
+        let target_data_object = thread_context
+            .get_target_data_object(0, 0, 0, buf.len())
+            .unwrap();
+        target_data_object.accessor.read_idx(
+            target_data_object.data_internal_index_in_section,
+            0,
+            buf.len(),
+            buf.as_mut_ptr(),
+        );
+        assert!(buf.iter().all(|value| *value == 0x11));
+    }
+
+    #[test]
+    fn test_vm_build_errors() {
+        assert!(matches!(VmBuilder::new().build(), Err(VmError::NoModules)));
+
+        assert!(matches!(
+            VmBuilder::new()
+                .module_file("/path/to/nonexistent.ancm")
+                .build(),
+            Err(VmError::Io(..))
+        ));
+
+        // The opcode 0xff00 doesn't exist.
+        let code0 = [
+            vec![0x00, 0xff],
+            BytecodeWriterHelper::new()
+                .append_opcode(Opcode::end)
+                .to_bytes(),
+        ]
+        .concat();
+
+        let binary0 = helper_build_module_binary_with_functions_and_blocks(
+            &[HelperFunctionEntry {
+                params: vec![],
+                results: vec![],
+                local_variable_item_entries_without_args: vec![],
+                code: code0,
+            }],
+            &[],
+        );
+
+        let vm = VmBuilder::new().module_binary(binary0).build().unwrap();
+        assert!(matches!(
+            vm.create_process_context(),
+            Err(VmError::Verification(VerificationError {
+                error_type: VerificationErrorType::InvalidOpcode(0xff00),
+                ..
+            }))
+        ));
+    }
+}
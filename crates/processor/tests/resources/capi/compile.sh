@@ -0,0 +1,12 @@
+# Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+#
+# This Source Code Form is subject to the terms of
+# the Mozilla Public License version 2.0 and additional exceptions.
+# For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+
+#!/bin/bash
+# Build the static library first:
+#   cargo build -p anc-processor
+gcc -Wall -g -I../../../include -o capi_test test.c \
+    ../../../../../target/debug/libanc_processor.a \
+    -lpthread -ldl -lm
@@ -0,0 +1,112 @@
+/**
+ * Copyright (c) 2025 Hemashushu <hippospark@gmail.com>, All rights reserved.
+ *
+ * This Source Code Form is subject to the terms of
+ * the Mozilla Public License version 2.0 and additional exceptions.
+ * For more details, see the LICENSE, LICENSE.additional, and CONTRIBUTING files.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "anc_processor.h"
+
+static unsigned char *read_file(const char *path, size_t *length)
+{
+    FILE *file = fopen(path, "rb");
+    if (file == NULL)
+    {
+        return NULL;
+    }
+
+    fseek(file, 0, SEEK_END);
+    *length = (size_t)ftell(file);
+    fseek(file, 0, SEEK_SET);
+
+    unsigned char *data = malloc(*length);
+    if (fread(data, 1, *length, file) != *length)
+    {
+        free(data);
+        data = NULL;
+    }
+
+    fclose(file);
+    return data;
+}
+
+#define CHECK(condition, message)                 \
+    if (!(condition))                             \
+    {                                             \
+        fprintf(stderr, "failed: %s\n", message); \
+        return 1;                                 \
+    }
+
+/**
+ * Loads the module image specified by the first argument, the module should
+ * export the function "main::func0" with the signature `fn (i32, i32) -> i32`
+ * which returns the sum of the arguments.
+ *
+ * Returns 0 if all checks pass.
+ */
+int main(int argc, char **argv)
+{
+    CHECK(argc == 2, "usage: capi_test /path/to/module.ancm");
+
+    size_t length = 0;
+    unsigned char *data = read_file(argv[1], &length);
+    CHECK(data != NULL, "failed to read the module image file");
+
+    // create process from bytes
+    AncVmBuilder *builder = anc_vm_builder_new();
+    anc_vm_builder_add_module_bytes(builder, data, length);
+    free(data);
+
+    AncError *error = NULL;
+    AncProcess *process = anc_process_create(builder, &error);
+    CHECK(process != NULL, "failed to create the process");
+
+    AncThread *thread = anc_thread_create(process);
+
+    // call function
+    AncValue args[2] = {
+        {.value_type = ANC_VALUE_TYPE_I32, .payload = {.i32 = 11}},
+        {.value_type = ANC_VALUE_TYPE_I32, .payload = {.i32 = -13}},
+    };
+    AncValue results[1];
+    size_t results_count = 0;
+
+    int32_t ret = anc_thread_call(thread, "main::func0", args, 2, results, 1, &results_count, &error);
+    CHECK(ret == 0, "failed to call the function");
+    CHECK(results_count == 1, "the number of results mismatch");
+    CHECK(results[0].value_type == ANC_VALUE_TYPE_I32, "the result type mismatch");
+    CHECK(results[0].payload.i32 == -2, "the result value mismatch");
+
+    // function not found
+    ret = anc_thread_call(thread, "main::foo", args, 2, results, 1, &results_count, &error);
+    CHECK(ret == -1, "calling a nonexistent function should fail");
+    CHECK(strstr(anc_error_message(error), "main::foo") != NULL, "the error message mismatch");
+    anc_error_free(error);
+
+    anc_thread_free(thread);
+    anc_process_free(process);
+
+    // create process from a nonexistent file
+    builder = anc_vm_builder_new();
+    anc_vm_builder_add_module_file(builder, "/path/to/nonexistent.ancm");
+
+    error = NULL;
+    process = anc_process_create(builder, &error);
+    CHECK(process == NULL, "creating process from a nonexistent file should fail");
+    CHECK(error != NULL, "the error should be set");
+    anc_error_free(error);
+
+    printf("ok\n");
+    return 0;
+}
+
+/**
+ * Compile this file using the script `compile.sh`, which links the static library
+ * `libanc_processor.a`, so build the crate first:
+ *   cargo build -p anc-processor
+ */
@@ -105,6 +105,13 @@ pub struct NostdStack {
     // The start position of the current frame (frame pointer).
     pub fp: usize,
 
+    // The number of frames (both function frames and block frames) on the stack.
+    //
+    // Both the FP of an empty stack and the FP of the first frame are 0, so
+    // whether a frame is present can not be determined by the FP, this counter
+    // is used for walking through the frame chain instead.
+    pub frames_count: usize,
+
     // A temporary memory area used for swapping operands.
     //
     // When a new stack frame is created:
@@ -316,6 +323,21 @@ impl OperandStack for NostdStack {
         self.sp -= length;
         &self.data[self.sp..]
     }
+
+    fn get_operands(&self) -> &[u8] {
+        // the operands are located after the local variables area of the current frame,
+        // or at the start of the stack when there is no frame.
+        let start = if self.frames_count == 0 {
+            0
+        } else {
+            let frame_info_data = self.get_frame_info_data(self.fp);
+            self.fp
+                + size_of::<FrameInfoData>()
+                + frame_info_data.local_variables_with_arguments_allocated_bytes as usize
+        };
+
+        &self.data[start..self.sp]
+    }
 }
 
 impl CallingStack for NostdStack {
@@ -375,6 +397,7 @@ impl CallingStack for NostdStack {
         // update sp and fp
         self.sp += size_of::<FrameInfoData>();
         self.fp = next_fp;
+        self.frames_count += 1;
 
         // restore the arguments from swap
         self.restore_operands_from_swap(params_count as usize);
@@ -436,6 +459,7 @@ impl CallingStack for NostdStack {
 
         self.sp = sp;
         self.fp = fp;
+        self.frames_count -= layers as usize + 1;
 
         // restore parameters from swap
         self.restore_operands_from_swap(results_count as usize);
@@ -619,6 +643,68 @@ impl CallingStack for NostdStack {
         self.swap = [0u8; SWAP_SIZE_IN_BYTES];
         self.fp = 0;
         self.sp = 0;
+        self.frames_count = 0;
+    }
+
+    fn get_return_program_counters(&self) -> Vec<ProgramCounter> {
+        let mut program_counters = vec![];
+        let mut fp = self.fp;
+
+        for _ in 0..self.frames_count {
+            let frame_info_data = self.get_frame_info_data(fp);
+
+            // Block frames do not carry the return PC.
+            if fp == frame_info_data.function_frame_address as usize {
+                program_counters.push(ProgramCounter {
+                    instruction_address: frame_info_data.return_instruction_address as usize,
+                    function_internal_index: frame_info_data.return_function_internal_index
+                        as usize,
+                    module_index: frame_info_data.return_module_index as usize,
+                });
+            }
+
+            fp = frame_info_data.previous_frame_address as usize;
+        }
+
+        program_counters
+    }
+
+    fn get_function_frames_local_variable_list_index_and_start_address(
+        &self,
+    ) -> Vec<(usize, usize)> {
+        let mut items = vec![];
+        let mut fp = self.fp;
+
+        for _ in 0..self.frames_count {
+            let frame_info_data = self.get_frame_info_data(fp);
+
+            if fp == frame_info_data.function_frame_address as usize {
+                items.push((
+                    frame_info_data.local_variable_list_index as usize,
+                    fp + size_of::<FrameInfoData>(),
+                ));
+            }
+
+            fp = frame_info_data.previous_frame_address as usize;
+        }
+
+        items
+    }
+
+    fn get_current_function_frames_count(&self) -> usize {
+        let mut fp = self.fp;
+
+        for count in 1..=self.frames_count {
+            let frame_info_data = self.get_frame_info_data(fp);
+
+            if fp == frame_info_data.function_frame_address as usize {
+                return count;
+            }
+
+            fp = frame_info_data.previous_frame_address as usize;
+        }
+
+        0
     }
 }
 
@@ -634,6 +720,7 @@ impl NostdStack {
             swap,
             sp: 0,
             fp: 0,
+            frames_count: 0,
         }
     }
 
@@ -1906,6 +1993,111 @@ mod tests {
         assert_eq!(stack.fp, 0);
     }
 
+    #[test]
+    fn test_return_program_counters_and_operands() {
+        let mut stack = NostdStack::new();
+        assert!(stack.get_return_program_counters().is_empty());
+
+        // the arguments for the first functon call.
+        stack.push_i32_u(11);
+
+        // create frames:
+        //
+        // ```diagram
+        // | block frame 1 |
+        // | func frame 1  | return to (3, 5, 7)
+        // | block frame 0 |
+        // | func frame 0  | return to (0x8000_0000, 0, 0)
+        // \---------------/
+        // ```
+
+        stack
+            .create_frame(
+                1,
+                0,
+                0,
+                8,
+                Some(ProgramCounter {
+                    instruction_address: 0,
+                    module_index: 0x8000_0000,
+                    function_internal_index: 0,
+                }),
+            )
+            .unwrap();
+        stack.create_empty_frame();
+        stack
+            .create_frame(
+                0,
+                0,
+                0,
+                0,
+                Some(ProgramCounter {
+                    instruction_address: 7,
+                    module_index: 3,
+                    function_internal_index: 5,
+                }),
+            )
+            .unwrap();
+        stack.create_empty_frame();
+
+        stack.push_i32_u(13);
+        stack.push_i32_u(17);
+        assert_eq!(
+            stack.get_operands(),
+            [13u64.to_le_bytes(), 17u64.to_le_bytes()].concat()
+        );
+
+        assert_eq!(stack.frames_count, 4);
+        assert_eq!(
+            stack.get_return_program_counters(),
+            vec![
+                ProgramCounter {
+                    instruction_address: 7,
+                    module_index: 3,
+                    function_internal_index: 5,
+                },
+                ProgramCounter {
+                    instruction_address: 0,
+                    module_index: 0x8000_0000,
+                    function_internal_index: 0,
+                }
+            ]
+        );
+
+        // the "func frame 0" is located at 0, and the "func frame 1" is located
+        // after the "func frame 0" (with 1 argument) and the "block frame 0".
+        let fp_func0 = 0;
+        let fp_func1 = size_of::<FrameInfoData>() * 2 + OPERAND_SIZE_IN_BYTES;
+        assert_eq!(
+            stack.get_function_frames_local_variable_list_index_and_start_address(),
+            vec![
+                (0, fp_func1 + size_of::<FrameInfoData>()),
+                (0, fp_func0 + size_of::<FrameInfoData>()),
+            ]
+        );
+
+        // "block frame 1" and "func frame 1"
+        assert_eq!(stack.get_current_function_frames_count(), 2);
+
+        // remove "block frame 1" and "func frame 1"
+        stack.remove_frames(1);
+        assert_eq!(stack.frames_count, 2);
+        assert_eq!(stack.get_current_function_frames_count(), 2);
+        assert_eq!(
+            stack.get_return_program_counters(),
+            vec![ProgramCounter {
+                instruction_address: 0,
+                module_index: 0x8000_0000,
+                function_internal_index: 0,
+            }]
+        );
+
+        stack.reset();
+        assert_eq!(stack.frames_count, 0);
+        assert_eq!(stack.get_current_function_frames_count(), 0);
+        assert!(stack.get_return_program_counters().is_empty());
+    }
+
     #[test]
     fn test_reset_frame() {
         // tasks:
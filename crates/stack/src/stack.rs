@@ -226,6 +226,12 @@ pub trait OperandStack: PrimitiveMemoryAccess {
     ///
     /// This method is used for returning values from the "entry" function.
     fn pop_last_operands(&mut self, count: usize) -> &[u8];
+
+    /// Retrieves all operands of the current frame without popping them,
+    /// the first operand (the bottom one) is at the beginning of the slice.
+    ///
+    /// This method is used for inspecting the stack, e.g., by the debugger.
+    fn get_operands(&self) -> &[u8];
 }
 
 pub trait CallingStack {
@@ -271,6 +277,30 @@ pub trait CallingStack {
     /// Resets the stack to its initial state by clearing all data and resetting pointers.
     fn reset(&mut self);
 
+    // Retrieves the return program counters of all function frames on the stack.
+    //
+    // The program counters are ordered from the current (innermost) function frame
+    // to the first (outermost) one, which is the chain used for building backtraces.
+    // Note that the MSB of `module_index` is set in the return program counter of
+    // the first frame of each "function call path".
+    fn get_return_program_counters(&self) -> Vec<ProgramCounter>;
+
+    // Retrieves the local variable list index and the start address of the local variables
+    // of all function frames on the stack, in the same order as `get_return_program_counters()`.
+    //
+    // Unlike `get_local_variable_list_index_and_start_address_by_layers()`, this method
+    // crosses the function frames, it is used for inspecting the stack, e.g. by the debugger.
+    fn get_function_frames_local_variable_list_index_and_start_address(
+        &self,
+    ) -> Vec<(usize, usize)>;
+
+    // Retrieves the number of frames of the current function, i.e., the function frame
+    // and the block frames within it, returns 0 if there is no frame.
+    //
+    // The `layers` of `get_local_variable_list_index_and_start_address_by_layers()`
+    // must be less than this number.
+    fn get_current_function_frames_count(&self) -> usize;
+
     fn get_local_variable_list_index_and_start_address_by_layers(
         &self,
         layers: u16,